@@ -0,0 +1,82 @@
+//! Exposes the locked folding-schemes version and git revision to
+//! `build_info`, so results stay attributable to a prover build, and the
+//! toolchain, profile, features and locked packages to `manifest`.
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    let lock = Path::new(&std::env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock.display());
+    // without a lockfile, e.g. as a dependency of another workspace
+    let (version, revision) = fs::read_to_string(&lock)
+        .ok()
+        .and_then(|lock| folding_schemes_package(&lock))
+        .unwrap_or_else(|| ("unknown".to_string(), "unknown".to_string()));
+    println!("cargo:rustc-env=FOLDING_SCHEMES_VERSION={}", version);
+    println!("cargo:rustc-env=FOLDING_SCHEMES_REVISION={}", revision);
+
+    let env = |key: &str| std::env::var(key).unwrap_or_else(|_| "unknown".to_string());
+    let rustc = Command::new(env("RUSTC"))
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .map_or_else(|| "unknown".to_string(), |v| v.trim().to_string());
+    println!("cargo:rustc-env=BUILD_RUSTC_VERSION={}", rustc);
+    println!("cargo:rustc-env=BUILD_PROFILE={}", env("PROFILE"));
+    println!("cargo:rustc-env=BUILD_OPT_LEVEL={}", env("OPT_LEVEL"));
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_").map(|f| f.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=BUILD_FEATURES={}", features.join(","));
+
+    let packages = fs::read_to_string(&lock).map(|lock| locked_packages(&lock)).unwrap_or_default();
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("locked_packages.txt");
+    fs::write(out, packages).unwrap();
+}
+
+/// One `name version source` line per package of `lock`, the source being
+/// empty for path dependencies such as this crate.
+fn locked_packages(lock: &str) -> String {
+    let mut lines = String::new();
+    for package in lock.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            package.lines().find_map(|l| {
+                l.trim()
+                    .strip_prefix(key)
+                    .and_then(|v| v.trim().strip_prefix("= "))
+                    .map(|v| v.trim_matches('"').to_string())
+            })
+        };
+        if let (Some(name), Some(version)) = (field("name"), field("version")) {
+            let source = field("source").unwrap_or_default();
+            lines.push_str(format!("{} {} {}", name, version, source).trim_end());
+            lines.push('\n');
+        }
+    }
+    lines
+}
+
+/// Version and git revision of the `folding-schemes` entry of `lock`.
+fn folding_schemes_package(lock: &str) -> Option<(String, String)> {
+    let package = lock
+        .split("[[package]]")
+        .find(|p| p.lines().any(|l| l.trim() == "name = \"folding-schemes\""))?;
+    let field = |key: &str| {
+        package.lines().find_map(|l| {
+            l.trim()
+                .strip_prefix(key)
+                .and_then(|v| v.trim().strip_prefix("= "))
+                .map(|v| v.trim_matches('"').to_string())
+        })
+    };
+    let version = field("version")?;
+    let revision = field("source")
+        .and_then(|source| source.rsplit_once('#').map(|(_, rev)| rev.to_string()))
+        .unwrap_or_else(|| "unknown".to_string());
+    Some((version, revision))
+}
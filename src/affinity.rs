@@ -0,0 +1,126 @@
+//! Core pinning and isolation of the measured work, for small differences
+//! to stand out of the run-to-run noise of shared machines.
+//!
+//! With `BenchConfig::pin_cores`, every benchmark runs in its own rayon pool
+//! of one worker per listed core, each pinned to its core, so the scheduler
+//! neither migrates the workers nor spreads them over busier cores. Rayon
+//! has no switch for work-stealing itself: `no_work_stealing` runs the
+//! benchmark on a single worker instead (pinned to the first listed core, if
+//! any), so that how parallel work was split and stolen no longer varies
+//! from run to run, at the cost of the parallel speedup.
+use std::str::FromStr;
+
+use crate::config::BenchConfig;
+use crate::error::BenchError;
+
+/// Core ids as a comma-separated list of ids and inclusive ranges, e.g.
+/// `0-3,6`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoreList(pub Vec<usize>);
+
+impl FromStr for CoreList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cores = Vec::new();
+        for part in s.split(',').map(str::trim) {
+            let parse = |id: &str| {
+                id.trim()
+                    .parse::<usize>()
+                    .map_err(|e| format!("invalid core {:?} in {:?}: {}", id, s, e))
+            };
+            match part.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (parse(first)?, parse(last)?);
+                    if first > last {
+                        return Err(format!("empty core range {:?} in {:?}", part, s));
+                    }
+                    cores.extend(first..=last);
+                }
+                None => cores.push(parse(part)?),
+            }
+        }
+        cores.sort_unstable();
+        cores.dedup();
+        Ok(Self(cores))
+    }
+}
+
+/// Checks that the process may run on every core of `cores`.
+pub fn check_cores(cores: &[usize]) -> Result<(), BenchError> {
+    if cores.is_empty() {
+        return Ok(());
+    }
+    let Some(available) = core_affinity::get_core_ids() else {
+        return Err(BenchError::InvalidInput(
+            "threads cannot be pinned on this platform".into(),
+        ));
+    };
+    match cores
+        .iter()
+        .find(|&&id| !available.iter().any(|core| core.id == id))
+    {
+        Some(id) => Err(BenchError::InvalidInput(format!(
+            "cannot pin to core {}, the process may run on cores {:?}",
+            id,
+            available.iter().map(|core| core.id).collect::<Vec<_>>()
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Runs `f` in a rayon pool pinned and sized as set by `config`, or as it is
+/// in the global pool when it neither pins nor disables work-stealing.
+pub fn isolated<T: Send>(
+    label: &str,
+    config: &BenchConfig,
+    f: impl FnOnce() -> T + Send,
+) -> Result<T, BenchError> {
+    if config.pin_cores.is_empty() && !config.no_work_stealing {
+        return Ok(f());
+    }
+    let workers = if config.no_work_stealing {
+        1
+    } else {
+        config.pin_cores.len()
+    };
+    let cores = config.pin_cores.clone();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers)
+        .start_handler(move |i| {
+            // the cores were checked by `BenchConfigBuilder::build`
+            if !cores.is_empty() {
+                core_affinity::set_for_current(core_affinity::CoreId {
+                    id: cores[i % cores.len()],
+                });
+            }
+        })
+        .build()
+        .map_err(|e| BenchError::InvalidInput(format!("{} thread pool: {}", label, e)))?;
+    Ok(pool.install(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_list() {
+        assert_eq!("0-3,6".parse(), Ok(CoreList(vec![0, 1, 2, 3, 6])));
+        assert_eq!("5, 2,2-3".parse(), Ok(CoreList(vec![2, 3, 5])));
+        assert!("3-1".parse::<CoreList>().is_err());
+        assert!("".parse::<CoreList>().is_err());
+        assert!("0,x".parse::<CoreList>().is_err());
+    }
+
+    #[test]
+    fn test_no_work_stealing() {
+        let config = BenchConfig::builder()
+            .no_work_stealing(true)
+            .build()
+            .unwrap();
+        let workers = isolated("isolated", &config, rayon::current_num_threads).unwrap();
+        assert_eq!(workers, 1);
+        assert!(check_cores(&[usize::MAX]).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Preprocess amortized over many chains, as a prover service that runs the
+//! setup once and folds the chains of many customers with it.
+//!
+//! `bench_amortized` preprocesses the folding scheme and the decider once,
+//! then folds `chains` chains of the same circuit from different initial
+//! states and proves each with the decider. The marginal cost of a chain is
+//! its folding and decider proof; its amortized cost adds an equal share of
+//! the preprocess, which vanishes as the chains grow in number.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::time::Duration;
+
+use folding_schemes::{frontend::FCircuit, Decider, FoldingScheme};
+
+use crate::bench::{
+    preprocess_into, prove_and_verify_into, DeciderVerifierInputs, PpHash, PreprocessStages,
+};
+use crate::error::BenchError;
+use crate::result::BenchResult;
+
+/// What one chain costs on top of the shared preprocess.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChainCost {
+    /// All the folding steps of the chain.
+    pub fold: Duration,
+    pub decider_prove: Duration,
+}
+
+impl ChainCost {
+    pub fn marginal(&self) -> Duration {
+        self.fold + self.decider_prove
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Amortized {
+    /// Folding scheme and decider preprocess, run once.
+    pub preprocess: Duration,
+    pub chains: Vec<ChainCost>,
+}
+
+impl Amortized {
+    /// Mean marginal cost of a chain.
+    pub fn marginal(&self) -> Duration {
+        let total: Duration = self.chains.iter().map(ChainCost::marginal).sum();
+        total / self.chains.len().max(1) as u32
+    }
+
+    /// Cost per chain when `chains` chains share the preprocess.
+    pub fn per_chain(&self, chains: usize) -> Duration {
+        self.preprocess / chains.max(1) as u32 + self.marginal()
+    }
+
+    pub fn print(&self, label: &str) {
+        let n = self.chains.len();
+        println!(
+            "{} Preprocess {:?} shared by {} chains, marginal {:?} per chain",
+            label,
+            self.preprocess,
+            n,
+            self.marginal()
+        );
+        let mut counts: Vec<usize> = std::iter::successors(Some(1), |k| Some(k * 2))
+            .take_while(|&k| k < n)
+            .collect();
+        counts.push(n.max(1));
+        for k in counts {
+            let per_chain = self.per_chain(k);
+            println!(
+                "{}   {:>6} chains: {:?} per chain, {:.1}% preprocess",
+                label,
+                k,
+                per_chain,
+                100.0 * (per_chain - self.marginal()).as_secs_f64()
+                    / per_chain.as_secs_f64().max(f64::MIN_POSITIVE)
+            );
+        }
+    }
+}
+
+/// Preprocesses once, then folds `chains` chains of `n_steps` from
+/// `z_0(chain)`, fed by `external_inputs(chain, step)`, and proves and
+/// verifies a decider proof for each.
+pub fn bench_amortized<C1, C2, FC, FS, D>(
+    label: &str,
+    prep_param: &FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: impl Fn(usize) -> Vec<C1::ScalarField>,
+    chains: usize,
+    n_steps: usize,
+    external_inputs: impl Fn(usize, usize) -> Vec<C1::ScalarField>,
+) -> Result<Amortized, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    println!(
+        "\nRunning {} amortized benchmark: {} chains x {} steps",
+        label, chains, n_steps
+    );
+    let mut shared = BenchResult::new(label);
+    let params = preprocess_into::<C1, C2, FC, FS, D>(
+        label,
+        prep_param,
+        f_circuit.clone(),
+        z_0(0),
+        &mut shared,
+    )?;
+
+    let mut amortized = Amortized {
+        preprocess: shared.preprocess + shared.decider_preprocess,
+        chains: Vec::with_capacity(chains),
+    };
+    for chain in 0..chains {
+        let chain_label = format!("{} chain {}", label, chain);
+        let mut result = BenchResult::new(&chain_label);
+        prove_and_verify_into::<C1, C2, FC, FS, D>(
+            &chain_label,
+            params.clone(),
+            f_circuit.clone(),
+            z_0(chain),
+            n_steps,
+            |i| external_inputs(chain, i),
+            true,
+            &mut result,
+        )?;
+        amortized.chains.push(ChainCost {
+            fold: result.steps.iter().sum(),
+            decider_prove: result.decider_prove,
+        });
+    }
+    Ok(amortized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amortized() {
+        let ms = Duration::from_millis;
+        let amortized = Amortized {
+            preprocess: ms(1200),
+            chains: vec![
+                ChainCost {
+                    fold: ms(50),
+                    decider_prove: ms(150),
+                },
+                ChainCost {
+                    fold: ms(70),
+                    decider_prove: ms(130),
+                },
+                ChainCost {
+                    fold: ms(60),
+                    decider_prove: ms(140),
+                },
+            ],
+        };
+        assert_eq!(amortized.marginal(), ms(200));
+        assert_eq!(amortized.per_chain(1), ms(1400));
+        assert_eq!(amortized.per_chain(3), ms(600));
+        assert_eq!(amortized.per_chain(1200), ms(201));
+        assert_eq!(Amortized::default().per_chain(0), Duration::ZERO);
+    }
+}
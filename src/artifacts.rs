@@ -0,0 +1,212 @@
+//! Decider artifacts saved to disk and verified on their own, to split the
+//! prover and verifier across machines or to benchmark verification alone on
+//! weak hardware.
+//!
+//! `save_artifacts` writes what `Decider::verify` takes into a directory: the
+//! verifier params (`vp.bin`), the decider proof (`proof.bin`) and the public
+//! state (`state.bin`: the step count, the initial and final IVC states and
+//! the last running and incoming instances), all in arkworks' compressed
+//! encoding. `verify_artifacts` reads them back and times the verification,
+//! without folding or proving anything. `sonobe-bench --save-artifacts`
+//! saves the BN254 `DeciderEth` proofs and `sonobe-bench verify` checks
+//! them, see `verify_artifacts_bn254`.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use folding_schemes::{
+    folding::nova::CommittedInstance, frontend::FCircuit, Decider, FoldingScheme,
+};
+
+use crate::bench::DeciderVerifierInputs;
+use crate::error::BenchError;
+
+/// The inputs of `Decider::verify` besides the params and the proof.
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PublicState<C1: CurveGroup> {
+    pub i: C1::ScalarField,
+    pub z_0: Vec<C1::ScalarField>,
+    pub z_i: Vec<C1::ScalarField>,
+    pub U_i: CommittedInstance<C1>,
+    pub u_i: CommittedInstance<C1>,
+}
+
+/// Files written by `save_artifacts`, and their sizes in bytes.
+#[derive(Clone, Debug)]
+pub struct DeciderArtifacts {
+    pub vp_path: PathBuf,
+    pub proof_path: PathBuf,
+    pub state_path: PathBuf,
+    pub vp_bytes: usize,
+    pub proof_bytes: usize,
+    pub state_bytes: usize,
+}
+
+impl DeciderArtifacts {
+    /// The files of the artifacts in `dir`.
+    pub fn in_dir(dir: impl AsRef<Path>) -> Self {
+        let dir = dir.as_ref();
+        Self {
+            vp_path: dir.join("vp.bin"),
+            proof_path: dir.join("proof.bin"),
+            state_path: dir.join("state.bin"),
+            vp_bytes: 0,
+            proof_bytes: 0,
+            state_bytes: 0,
+        }
+    }
+}
+
+/// Verification of saved artifacts, see `verify_artifacts`.
+#[derive(Clone, Copy, Debug)]
+pub struct ArtifactVerification {
+    pub vp_bytes: usize,
+    pub proof_bytes: usize,
+    pub state_bytes: usize,
+    /// Reading and deserializing the three files, once.
+    pub load: Duration,
+    /// Mean `Decider::verify`.
+    pub verify: Duration,
+    pub iterations: usize,
+}
+
+fn write(path: &Path, value: &impl CanonicalSerialize) -> Result<usize, BenchError> {
+    let mut bytes = Vec::with_capacity(value.compressed_size());
+    value.serialize_compressed(&mut bytes)?;
+    fs::write(path, &bytes)?;
+    Ok(bytes.len())
+}
+
+fn read<T: CanonicalDeserialize>(path: &Path) -> Result<(T, usize), BenchError> {
+    let bytes = fs::read(path)?;
+    Ok((T::deserialize_compressed(&bytes[..])?, bytes.len()))
+}
+
+/// Writes `decider_vp`, `proof` and the public state of `nova` into `dir`.
+pub fn save_artifacts<C1, C2, FC, FS, D>(
+    label: &str,
+    dir: impl AsRef<Path>,
+    nova: &FS,
+    decider_vp: &D::VerifierParam,
+    proof: &D::Proof,
+) -> Result<DeciderArtifacts, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = CommittedInstance<C1>>,
+    D: Decider<C1, C2, FC, FS, CommittedInstance = CommittedInstance<C1>>,
+    D::VerifierParam: CanonicalSerialize,
+    D::Proof: CanonicalSerialize,
+{
+    fs::create_dir_all(dir.as_ref())?;
+    let mut artifacts = DeciderArtifacts::in_dir(dir);
+    let state = PublicState::<C1> {
+        i: nova.num_steps(),
+        z_0: nova.z_0(),
+        z_i: nova.z_i(),
+        U_i: nova.running_instance().clone(),
+        u_i: nova.incoming_instance().clone(),
+    };
+    artifacts.vp_bytes = write(&artifacts.vp_path, decider_vp)?;
+    artifacts.proof_bytes = write(&artifacts.proof_path, proof)?;
+    artifacts.state_bytes = write(&artifacts.state_path, &state)?;
+    println!(
+        "{} Wrote decider artifacts to {:?}: verifier params {} bytes, proof {} bytes, \
+         state {} bytes",
+        label,
+        dir.as_ref(),
+        artifacts.vp_bytes,
+        artifacts.proof_bytes,
+        artifacts.state_bytes
+    );
+    Ok(artifacts)
+}
+
+/// Reads the artifacts saved in `dir` by `save_artifacts` and times
+/// `iterations` verifications of them. Errs if the proof is rejected.
+pub fn verify_artifacts<C1, C2, FC, FS, D>(
+    label: &str,
+    dir: impl AsRef<Path>,
+    iterations: usize,
+) -> Result<ArtifactVerification, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = CommittedInstance<C1>>,
+    D: Decider<C1, C2, FC, FS, CommittedInstance = CommittedInstance<C1>>,
+    D::VerifierParam: Clone + CanonicalDeserialize,
+    D::Proof: CanonicalDeserialize,
+{
+    let paths = DeciderArtifacts::in_dir(dir);
+    let start = Instant::now();
+    let (decider_vp, vp_bytes) = read::<D::VerifierParam>(&paths.vp_path)?;
+    let (proof, proof_bytes) = read::<D::Proof>(&paths.proof_path)?;
+    let (state, state_bytes) = read::<PublicState<C1>>(&paths.state_path)?;
+    let load = start.elapsed();
+
+    let iterations = iterations.max(1);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let verified = D::verify(
+            decider_vp.clone(),
+            state.i,
+            state.z_0.clone(),
+            state.z_i.clone(),
+            &state.U_i,
+            &state.u_i,
+            &proof,
+        )?;
+        if !verified {
+            return Err(BenchError::Verification(format!(
+                "{} decider proof rejected",
+                label
+            )));
+        }
+    }
+    let verification = ArtifactVerification {
+        vp_bytes,
+        proof_bytes,
+        state_bytes,
+        load,
+        verify: start.elapsed() / iterations as u32,
+        iterations,
+    };
+    println!(
+        "{} Verified the decider proof of {} steps: loaded {} bytes in {:?}, verify {:?} \
+         (mean of {})",
+        label,
+        state.i,
+        vp_bytes + proof_bytes + state_bytes,
+        verification.load,
+        verification.verify,
+        iterations
+    );
+    Ok(verification)
+}
+
+/// `verify_artifacts` for the BN254 `DeciderEth` proofs that `sonobe-bench
+/// --save-artifacts` saves. The verifier types do not depend on the step
+/// circuit, so the artifacts of every circuit are read as the cubic one's.
+#[cfg(feature = "bn254")]
+pub fn verify_artifacts_bn254(
+    label: &str,
+    dir: impl AsRef<Path>,
+    iterations: usize,
+) -> Result<ArtifactVerification, BenchError> {
+    use crate::solidity::{DeciderBn, NovaBn};
+    use crate::CubicFCircuit;
+    use ark_bn254::{Fr, G1Projective as G1};
+    use ark_grumpkin::Projective as G2;
+
+    type Any = CubicFCircuit<Fr>;
+    verify_artifacts::<G1, G2, Any, NovaBn<Any>, DeciderBn<Any>>(label, dir, iterations)
+}
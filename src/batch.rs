@@ -0,0 +1,251 @@
+//! Batched verification of BN254 decider proofs, for aggregators and bridges
+//! that settle many chains at once.
+//!
+//! K chains are folded and decided with `DeciderBn` under the same params.
+//! Their Groth16 proofs share one verifying key, so the K checks
+//! `e(A, B) = e(α, β) e(acc(x), γ) e(C, δ)` fold into one multi-pairing of
+//! K + 3 pairs under random 128-bit weights, instead of K pairing checks of 4.
+//! The KZG openings of each decider proof are still checked one by one by
+//! `DeciderBn::verify`, which is timed alongside for context.
+use ark_bn254::{Bn254, Fr, G1Projective as G1};
+use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_grumpkin::Projective as G2;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::RngCore;
+use std::time::{Duration, Instant};
+
+use folding_schemes::{
+    folding::nova::{decider_eth_circuit::DeciderEthCircuit, PreprocessorParam},
+    frontend::FCircuit,
+    Decider, FoldingScheme,
+};
+
+use crate::bench::{fold_steps, no_external_inputs, preprocess};
+use crate::config::{poseidon_config, rng};
+use crate::error::BenchError;
+use crate::solidity::{DeciderBn, NovaBn};
+
+/// Verification times of K decider proofs.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchVerification {
+    pub proofs: usize,
+    /// `DeciderBn::verify` of every proof: Groth16 and the KZG openings.
+    pub decider_one_by_one: Duration,
+    /// The Groth16 part of every proof checked on its own.
+    pub groth16_one_by_one: Duration,
+    /// The Groth16 parts checked together by `batch_verify_groth16`.
+    pub groth16_batched: Duration,
+}
+
+impl BatchVerification {
+    pub fn print(&self, label: &str) {
+        let per_proof = |d: Duration| d / self.proofs.max(1) as u32;
+        println!(
+            "{} {} decider proofs, one by one: {:?} ({:?} per proof)",
+            label,
+            self.proofs,
+            self.decider_one_by_one,
+            per_proof(self.decider_one_by_one)
+        );
+        println!(
+            "{} Groth16 one by one: {:?} ({:?} per proof)",
+            label,
+            self.groth16_one_by_one,
+            per_proof(self.groth16_one_by_one)
+        );
+        println!(
+            "{} Groth16 batched: {:?} ({:?} per proof, {:.2}x faster)",
+            label,
+            self.groth16_batched,
+            per_proof(self.groth16_batched),
+            self.groth16_one_by_one.as_secs_f64() / self.groth16_batched.as_secs_f64().max(1e-12)
+        );
+    }
+}
+
+/// Checks every `(public inputs, proof)` of `instances` against `vk` with one
+/// multi-pairing. Accepts only if all of them verify, except with probability
+/// about `2^-128`.
+pub fn batch_verify_groth16(
+    vk: &VerifyingKey<Bn254>,
+    instances: &[(Vec<Fr>, Proof<Bn254>)],
+    rng: &mut impl RngCore,
+) -> Result<bool, BenchError> {
+    let mut input_coeffs = vec![Fr::zero(); vk.gamma_abc_g1.len()];
+    let mut c = G1::zero();
+    let mut g1 = Vec::with_capacity(instances.len() + 3);
+    let mut g2 = Vec::with_capacity(instances.len() + 3);
+    for (inputs, proof) in instances {
+        if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+            return Err(BenchError::InvalidInput(format!(
+                "{} public inputs for a key taking {}",
+                inputs.len(),
+                vk.gamma_abc_g1.len() - 1
+            )));
+        }
+        let r = Fr::from(((rng.next_u64() as u128) << 64) | rng.next_u64() as u128);
+        input_coeffs[0] += r;
+        for (coeff, x) in input_coeffs[1..].iter_mut().zip(inputs) {
+            *coeff += r * x;
+        }
+        c += proof.c * r;
+        g1.push((proof.a * r).into_affine());
+        g2.push(proof.b);
+    }
+    let r_sum = input_coeffs[0];
+    let acc = G1::msm(&vk.gamma_abc_g1, &input_coeffs)
+        .map_err(|_| BenchError::InvalidInput("mismatched MSM lengths".into()))?;
+    g1.extend([
+        (-acc).into_affine(),
+        (-c).into_affine(),
+        (vk.alpha_g1 * -r_sum).into_affine(),
+    ]);
+    g2.extend([vk.gamma_g2, vk.delta_g2, vk.beta_g2]);
+    Ok(Bn254::multi_pairing(g1, g2).is_zero())
+}
+
+/// The Groth16 proof of a `DeciderBn` proof and its public inputs. The
+/// inputs are read from the decider circuit synthesized for `nova`; the proof
+/// is the leading field of the decider proof's serialization.
+pub(crate) fn groth16_part<FC: FCircuit<Fr>>(
+    nova: &NovaBn<FC>,
+    proof: &<DeciderBn<FC> as Decider<G1, G2, FC, NovaBn<FC>>>::Proof,
+) -> Result<(Vec<Fr>, Proof<Bn254>), BenchError> {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    DeciderEthCircuit::from_nova(nova.clone())?
+        .generate_constraints(cs.clone())
+        .map_err(folding_schemes::Error::from)?;
+    let inputs = cs.borrow().map_or_else(Vec::new, |cs| cs.instance_assignment[1..].to_vec());
+
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes)?;
+    let snark_proof = Proof::<Bn254>::deserialize_compressed(&*bytes)?;
+    Ok((inputs, snark_proof))
+}
+
+/// Folds `proofs` chains of `n_steps` of `f_circuit` from different initial
+/// states, proves each with `DeciderBn`, then times verifying them one by one
+/// and batched.
+pub fn bench_batch_verification<FC: FCircuit<Fr>>(
+    label: &str,
+    f_circuit: FC,
+    proofs: usize,
+    n_steps: usize,
+) -> Result<BatchVerification, BenchError> {
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+    let z_0 = |k: usize| vec![Fr::from(3 + k as u64); f_circuit.state_len()];
+    let (fs_pp, fs_vp, decider_pp, decider_vp) = preprocess::<G1, G2, FC, NovaBn<FC>, DeciderBn<FC>>(
+        label,
+        &prep_param,
+        f_circuit.clone(),
+        z_0(0),
+    )?;
+
+    let mut rng = rng();
+    let mut chains = Vec::with_capacity(proofs);
+    for k in 0..proofs {
+        let chain_label = format!("{} chain {}", label, k);
+        let mut nova =
+            NovaBn::<FC>::init(&(fs_pp.clone(), fs_vp.clone()), f_circuit.clone(), z_0(k))?;
+        fold_steps::<G1, G2, FC, _>(&chain_label, &mut nova, n_steps, no_external_inputs)?;
+        let proof = DeciderBn::<FC>::prove(&mut rng, decider_pp.clone(), nova.clone())?;
+        let groth16 = groth16_part(&nova, &proof)?;
+        chains.push((nova, proof, groth16));
+    }
+
+    let start = Instant::now();
+    for (nova, proof, _) in &chains {
+        let verified = DeciderBn::<FC>::verify(
+            decider_vp.clone(),
+            nova.num_steps(),
+            nova.z_0(),
+            nova.z_i(),
+            nova.running_instance(),
+            nova.incoming_instance(),
+            proof,
+        )?;
+        if !verified {
+            return Err(BenchError::Verification(format!("{} decider proof rejected", label)));
+        }
+    }
+    let decider_one_by_one = start.elapsed();
+
+    let vk = &decider_vp.1;
+    let pvk = Groth16::<Bn254>::process_vk(vk).map_err(folding_schemes::Error::from)?;
+    let start = Instant::now();
+    for (_, _, (inputs, proof)) in &chains {
+        let verified = Groth16::<Bn254>::verify_proof(&pvk, proof, inputs)
+            .map_err(folding_schemes::Error::from)?;
+        if !verified {
+            return Err(BenchError::Verification(format!(
+                "{} Groth16 proof rejected; its public inputs do not match the decider's",
+                label
+            )));
+        }
+    }
+    let groth16_one_by_one = start.elapsed();
+
+    let instances: Vec<_> = chains.into_iter().map(|(_, _, groth16)| groth16).collect();
+    let start = Instant::now();
+    let verified = batch_verify_groth16(vk, &instances, &mut rng)?;
+    let groth16_batched = start.elapsed();
+    if !verified {
+        return Err(BenchError::Verification(format!("{} batch rejected", label)));
+    }
+
+    Ok(BatchVerification {
+        proofs,
+        decider_one_by_one,
+        groth16_one_by_one,
+        groth16_batched,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+    /// `x^3 = y` with `y` public.
+    #[derive(Clone)]
+    struct Cube(Fr);
+
+    impl ConstraintSynthesizer<Fr> for Cube {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let y = FpVar::new_input(cs.clone(), || Ok(self.0.pow([3])))?;
+            let x = FpVar::new_witness(cs, || Ok(self.0))?;
+            (&x * &x * &x).enforce_equal(&y)
+        }
+    }
+
+    #[test]
+    fn test_batch_verify_groth16() {
+        let mut rng = rng();
+        let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(
+            Cube(Fr::from(2_u32)),
+            &mut rng,
+        )
+        .unwrap();
+        let vk = pk.vk.clone();
+        let instances: Vec<(Vec<Fr>, Proof<Bn254>)> = (1..=4_u32)
+            .map(|x| {
+                let x = Fr::from(x);
+                let proof =
+                    Groth16::<Bn254>::create_random_proof_with_reduction(Cube(x), &pk, &mut rng)
+                        .unwrap();
+                (vec![x.pow([3])], proof)
+            })
+            .collect();
+        assert!(batch_verify_groth16(&vk, &instances, &mut rng).unwrap());
+        assert!(batch_verify_groth16(&vk, &[], &mut rng).unwrap());
+
+        let mut wrong = instances.clone();
+        wrong[2].0[0] += Fr::from(1_u32);
+        assert!(!batch_verify_groth16(&vk, &wrong, &mut rng).unwrap());
+        assert!(batch_verify_groth16(&vk, &[(vec![], instances[0].1.clone())], &mut rng).is_err());
+    }
+}
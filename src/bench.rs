@@ -0,0 +1,1598 @@
+//! Generic folding benchmark pipeline.
+//!
+//! `run_folding_bench` runs preprocess, the folding loop, and the decider for any
+//! `FoldingScheme`/`Decider` pair, so a new curve cycle only needs its `Nova`
+//! and `Decider` type aliases. `run_nova_bench` runs the same pipeline on a
+//! `BenchCase` and returns the measurements as a `BenchResult` instead.
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{groups::GroupOpsBounds, prelude::CurveVar, ToConstraintFieldGadget};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use ark_std::{rand::RngCore, UniformRand};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+use crate::build_info::BuildInfo;
+use crate::ccs::CcsStats;
+use crate::circuits::conformance::synthesize_step;
+use crate::circuits::timed::{take_step_span, take_times, StepCircuitTimes, TimedFCircuit};
+use crate::clock::{report_cycles, Stopwatch};
+use crate::config::{
+    check_sat, negative_tests, reseed, rng, verbosity, verify_every, vp_round_trip, BenchConfig,
+    Phase, Verbosity,
+};
+use crate::dry_run::{dry_run, CircuitShapes, R1csShape};
+use crate::energy::{report_energy, EnergySample};
+use crate::error::BenchError;
+use crate::perf::{report_perf, PerfSample};
+use crate::report::{phase_end, phase_start};
+use crate::result::{
+    median_trial, AugmentedBreakdown, BenchResult, ParamSizes, PreprocessBreakdown, VpRoundTrip,
+};
+use folding_schemes::{
+    commitment::CommitmentScheme,
+    folding::{
+        circuits::CF2,
+        hypernova::HyperNova,
+        nova::{get_r1cs, Nova},
+    },
+    frontend::FCircuit,
+    Decider, FoldingScheme,
+};
+
+/// The IVC state of a folding scheme: params hash, step count and states.
+pub trait IvcState<C1: CurveGroup> {
+    /// Hash of the folding scheme params the instances are bound to.
+    fn pp_hash(&self) -> C1::ScalarField;
+    fn num_steps(&self) -> C1::ScalarField;
+    fn z_0(&self) -> Vec<C1::ScalarField>;
+    fn z_i(&self) -> Vec<C1::ScalarField>;
+}
+
+/// Public data that `Decider::verify` takes from the folding scheme state.
+pub trait DeciderVerifierInputs<C1: CurveGroup>: IvcState<C1> {
+    type CommittedInstance;
+
+    fn running_instance(&self) -> &Self::CommittedInstance;
+    fn incoming_instance(&self) -> &Self::CommittedInstance;
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> IvcState<C1>
+    for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn pp_hash(&self) -> C1::ScalarField {
+        self.pp_hash
+    }
+    fn num_steps(&self) -> C1::ScalarField {
+        self.i
+    }
+    fn z_0(&self) -> Vec<C1::ScalarField> {
+        self.z_0.clone()
+    }
+    fn z_i(&self) -> Vec<C1::ScalarField> {
+        self.z_i.clone()
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> DeciderVerifierInputs<C1>
+    for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    type CommittedInstance = folding_schemes::folding::nova::CommittedInstance<C1>;
+
+    fn running_instance(&self) -> &Self::CommittedInstance {
+        &self.U_i
+    }
+    fn incoming_instance(&self) -> &Self::CommittedInstance {
+        &self.u_i
+    }
+}
+
+/// Constraint counts of the circuits a folding scheme proves at each step.
+pub trait CircuitSize {
+    /// Constraints of the augmented circuit, i.e. the step circuit plus the
+    /// folding verifier.
+    fn augmented_constraints(&self) -> usize;
+    /// Constraints of the CycleFold circuit on the secondary curve.
+    fn cyclefold_constraints(&self) -> usize;
+    /// Shape of the augmented circuit for schemes folding CCS.
+    fn ccs_stats(&self) -> Option<CcsStats> {
+        None
+    }
+    /// `augmented_constraints` per component, for schemes whose augmented
+    /// circuit can be resynthesized to tell them apart.
+    fn augmented_breakdown(&self) -> Result<Option<AugmentedBreakdown>, BenchError> {
+        Ok(None)
+    }
+
+    /// Sizes of the commitment keys in `params`, for schemes that can tell
+    /// them apart.
+    fn param_sizes(_params: &(Self::ProverParam, Self::VerifierParam)) -> Option<ParamSizes> {
+        None
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> CircuitSize
+    for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn augmented_constraints(&self) -> usize {
+        self.r1cs.A.n_rows
+    }
+    fn cyclefold_constraints(&self) -> usize {
+        self.cf_r1cs.A.n_rows
+    }
+    /// The augmented circuit is synthesized with the step circuit wrapped in
+    /// a `TimedFCircuit`, which splits it into the constraints before the
+    /// step circuit (the NIFS verifier), the step circuit and those after it
+    /// (the CycleFold checks). Poseidon runs in both parts; its share of each
+    /// is found by synthesizing again with two full rounds fewer, which
+    /// removes `2 * width` of the `full_rounds * width + partial_rounds`
+    /// S-boxes of every permutation.
+    fn augmented_breakdown(&self) -> Result<Option<AugmentedBreakdown>, BenchError> {
+        let poseidon = &self.poseidon_config;
+        if poseidon.full_rounds < 2 {
+            return Ok(None);
+        }
+        let segments = |poseidon: &PoseidonConfig<C1::ScalarField>| {
+            let f_circuit = TimedFCircuit::<C1::ScalarField, FC>::wrap(self.F.clone());
+            take_step_span();
+            let (r1cs, _) = get_r1cs::<C1, GC1, C2, GC2, _>(poseidon, f_circuit)?;
+            let (before, after) = take_step_span().ok_or_else(|| {
+                BenchError::InvalidInput("the augmented circuit has no step circuit".into())
+            })?;
+            Ok::<_, BenchError>([before, after - before, r1cs.A.n_rows - after])
+        };
+        let full = segments(poseidon)?;
+        let rounds = poseidon.full_rounds - 2 + poseidon.partial_rounds;
+        let reduced = segments(&PoseidonConfig::new(
+            poseidon.full_rounds - 2,
+            poseidon.partial_rounds,
+            poseidon.alpha,
+            poseidon.mds.clone(),
+            poseidon.ark[..rounds].to_vec(),
+            poseidon.rate,
+            poseidon.capacity,
+        ))?;
+
+        let width = poseidon.rate + poseidon.capacity;
+        let sboxes = poseidon.full_rounds * width + poseidon.partial_rounds;
+        let poseidon_in = |i: usize| (full[i] - reduced[i]) * sboxes / (2 * width);
+        let (before, after) = (poseidon_in(0), poseidon_in(2));
+        Ok(Some(AugmentedBreakdown {
+            step: full[1],
+            folding_verifier: full[0].saturating_sub(before),
+            transcript: before + after,
+            cyclefold: full[2].saturating_sub(after),
+        }))
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const MU: usize, const NU: usize, const H: bool>
+    IvcState<C1> for HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, H>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn pp_hash(&self) -> C1::ScalarField {
+        self.pp_hash
+    }
+    fn num_steps(&self) -> C1::ScalarField {
+        self.i
+    }
+    fn z_0(&self) -> Vec<C1::ScalarField> {
+        self.z_0.clone()
+    }
+    fn z_i(&self) -> Vec<C1::ScalarField> {
+        self.z_i.clone()
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const MU: usize, const NU: usize, const H: bool>
+    CircuitSize for HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, H>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn augmented_constraints(&self) -> usize {
+        self.ccs.m
+    }
+    fn cyclefold_constraints(&self) -> usize {
+        self.cf_r1cs.A.n_rows
+    }
+    fn ccs_stats(&self) -> Option<CcsStats> {
+        Some(CcsStats::new(&self.ccs))
+    }
+}
+
+/// Folding scheme preprocess timed per stage, to tell whether loading an SRS
+/// (`srs`) or caching the whole params (`cache`) saves more.
+pub trait PreprocessStages<C1, C2, FC>: FoldingScheme<C1, C2, FC>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+{
+    /// `FoldingScheme::preprocess`, with the stage times for schemes whose
+    /// preprocess can be split.
+    #[allow(clippy::type_complexity)]
+    fn preprocess_stages(
+        rng: impl RngCore,
+        prep_param: &Self::PreprocessorParam,
+    ) -> Result<
+        (
+            (Self::ProverParam, Self::VerifierParam),
+            Option<PreprocessBreakdown>,
+        ),
+        BenchError,
+    >;
+
+    /// The circuits `preprocess` would synthesize, without generating any
+    /// key, for schemes that can tell them apart (see `dry_run`).
+    fn circuit_shapes(
+        _prep_param: &Self::PreprocessorParam,
+    ) -> Result<Option<CircuitShapes>, BenchError> {
+        Ok(None)
+    }
+}
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> PreprocessStages<C1, C2, FC>
+    for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn preprocess_stages(
+        mut rng: impl RngCore,
+        prep_param: &Self::PreprocessorParam,
+    ) -> Result<
+        (
+            (Self::ProverParam, Self::VerifierParam),
+            Option<PreprocessBreakdown>,
+        ),
+        BenchError,
+    > {
+        let start = Stopwatch::start();
+        let (r1cs, cf_r1cs) =
+            get_r1cs::<C1, GC1, C2, GC2, FC>(&prep_param.poseidon_config, prep_param.F.clone())?;
+        let synthesis = start.elapsed();
+
+        // same key lengths as `Nova::preprocess`; keys already given (e.g. a
+        // loaded SRS) are kept
+        let start = Stopwatch::start();
+        let mut prep_param = prep_param.clone();
+        if prep_param.cs_pp.is_none() || prep_param.cs_vp.is_none() {
+            let (cs_pp, cs_vp) = CS1::setup(&mut rng, r1cs.A.n_rows)?;
+            prep_param.cs_pp = Some(cs_pp);
+            prep_param.cs_vp = Some(cs_vp);
+        }
+        if prep_param.cf_cs_pp.is_none() || prep_param.cf_cs_vp.is_none() {
+            let len = cf_r1cs.A.n_rows.max(cf_r1cs.A.n_cols - cf_r1cs.l - 1);
+            let (cf_cs_pp, cf_cs_vp) = CS2::setup(&mut rng, len)?;
+            prep_param.cf_cs_pp = Some(cf_cs_pp);
+            prep_param.cf_cs_vp = Some(cf_cs_vp);
+        }
+        let commitment_keys = start.elapsed();
+
+        // `preprocess` synthesizes the circuits again before assembling the
+        // params, which is not counted twice
+        let start = Stopwatch::start();
+        let params = Self::preprocess(&mut rng, &prep_param)?;
+        let assembly = start.elapsed().saturating_sub(synthesis);
+        Ok((
+            params,
+            Some(PreprocessBreakdown {
+                synthesis,
+                commitment_keys,
+                assembly,
+            }),
+        ))
+    }
+
+    fn circuit_shapes(
+        prep_param: &Self::PreprocessorParam,
+    ) -> Result<Option<CircuitShapes>, BenchError> {
+        let (r1cs, cf_r1cs) =
+            get_r1cs::<C1, GC1, C2, GC2, FC>(&prep_param.poseidon_config, prep_param.F.clone())?;
+        let shape = |rows: usize, cols: usize, l: usize| R1csShape {
+            constraints: rows,
+            witness: cols - l - 1,
+            public_inputs: l,
+        };
+        Ok(Some(CircuitShapes {
+            augmented: shape(r1cs.A.n_rows, r1cs.A.n_cols, r1cs.l),
+            cyclefold: shape(cf_r1cs.A.n_rows, cf_r1cs.A.n_cols, cf_r1cs.l),
+        }))
+    }
+
+    fn param_sizes(params: &(Self::ProverParam, Self::VerifierParam)) -> Option<ParamSizes> {
+        Some(ParamSizes {
+            commitment_pk: params.0.cs_pp.compressed_size(),
+            commitment_vk: params.1.cs_vp.compressed_size(),
+            cyclefold_pk: params.0.cf_cs_pp.compressed_size(),
+            cyclefold_vk: params.1.cf_cs_vp.compressed_size(),
+        })
+    }
+}
+
+/// Not split: the augmented circuit behind the CCS is internal to sonobe.
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const MU: usize, const NU: usize, const H: bool>
+    PreprocessStages<C1, C2, FC> for HyperNova<C1, GC1, C2, GC2, FC, CS1, CS2, MU, NU, H>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn preprocess_stages(
+        rng: impl RngCore,
+        prep_param: &Self::PreprocessorParam,
+    ) -> Result<
+        (
+            (Self::ProverParam, Self::VerifierParam),
+            Option<PreprocessBreakdown>,
+        ),
+        BenchError,
+    > {
+        Ok((Self::preprocess(rng, prep_param)?, None))
+    }
+
+    fn param_sizes(params: &(Self::ProverParam, Self::VerifierParam)) -> Option<ParamSizes> {
+        Some(ParamSizes {
+            commitment_pk: params.0.cs_pp.compressed_size(),
+            commitment_vk: params.1.cs_vp.compressed_size(),
+            cyclefold_pk: params.0.cf_cs_pp.compressed_size(),
+            cyclefold_vk: params.1.cf_cs_vp.compressed_size(),
+        })
+    }
+}
+
+/// Decider verifier params that carry the `pp_hash` they were derived for.
+pub trait PpHash<F> {
+    fn pp_hash(&self) -> F;
+    /// Copy of the params bound to another `pp_hash`.
+    fn with_pp_hash(&self, pp_hash: F) -> Self;
+}
+
+/// `DeciderEth` verifier params, `(pp_hash, snark_vk, cs_vk)`.
+impl<F: Copy, SV: Clone, CV: Clone> PpHash<F> for (F, SV, CV) {
+    fn pp_hash(&self) -> F {
+        self.0
+    }
+    fn with_pp_hash(&self, pp_hash: F) -> Self {
+        (pp_hash, self.1.clone(), self.2.clone())
+    }
+}
+
+/// External inputs for circuits that take none.
+pub fn no_external_inputs<F>(_i: usize) -> Vec<F> {
+    vec![]
+}
+
+/// Uniformly random external inputs of the given width.
+pub fn random_external_inputs<F: PrimeField>(width: usize) -> impl FnMut(usize) -> Vec<F> {
+    let mut rng = rng();
+    move |_| (0..width).map(|_| F::rand(&mut rng)).collect()
+}
+
+/// Runs `n_steps` of `prove_step` on `fs`, feeding step `i` with
+/// `external_inputs(i)` and printing the average step time. Progress is shown
+/// as a bar, or with `-v` as one line per step (plus the state with `-vv`).
+/// Returns the time of every step.
+pub fn fold_steps<C1, C2, FC, FS>(
+    label: &str,
+    fs: &mut FS,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<Vec<Duration>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+{
+    Ok(fold_steps_with(label, fs, n_steps, external_inputs, |_, _| Ok(()))?.0)
+}
+
+/// IVC verification latency after a folding step.
+#[derive(Clone, Copy, Debug)]
+pub struct IvcVerification {
+    /// Number of steps folded when the IVC was verified.
+    pub step: usize,
+    pub time: Duration,
+}
+
+/// `fold_steps`, also running `FoldingScheme::verify` on the IVC after every
+/// `every` steps (outside the step timings), for the verifier-side latency of
+/// an incrementally verified chain. Returns the step times and the
+/// verifications.
+pub fn fold_and_verify_steps<C1, C2, FC, FS>(
+    label: &str,
+    fs: &mut FS,
+    fs_vp: &FS::VerifierParam,
+    n_steps: usize,
+    every: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<(Vec<Duration>, Vec<IvcVerification>), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + IvcState<C1>,
+{
+    let (steps, _, verifications) =
+        fold_verifying(label, fs, fs_vp, n_steps, every, external_inputs)?;
+    Ok((steps, verifications))
+}
+
+/// `fold_and_verify_steps`, also returning the step circuit times.
+#[allow(clippy::type_complexity)]
+fn fold_verifying<C1, C2, FC, FS>(
+    label: &str,
+    fs: &mut FS,
+    fs_vp: &FS::VerifierParam,
+    n_steps: usize,
+    every: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<(Vec<Duration>, Vec<StepCircuitTimes>, Vec<IvcVerification>), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + IvcState<C1>,
+{
+    let mut verifications = Vec::new();
+    let (steps, circuit_times) = fold_steps_with(label, fs, n_steps, external_inputs, |i, fs| {
+        if (i + 1) % every != 0 {
+            return Ok(());
+        }
+        let (running, incoming, cyclefold) = fs.instances();
+        let start = Stopwatch::start();
+        FS::verify(
+            fs_vp.clone(),
+            fs.z_0(),
+            fs.z_i(),
+            fs.num_steps(),
+            running,
+            incoming,
+            cyclefold,
+        )?;
+        let time = start.elapsed();
+        if verbosity() >= Verbosity::Verbose {
+            println!("{} IVC verify after step {}: {:?}", label, i + 1, time);
+        }
+        verifications.push(IvcVerification { step: i + 1, time });
+        Ok(())
+    })?;
+
+    if let (Some(first), Some(last)) = (verifications.first(), verifications.last()) {
+        let total: Duration = verifications.iter().map(|v| v.time).sum();
+        println!(
+            "{} IVC verification: {} checks, mean {:?}, first {:?}, last {:?}",
+            label,
+            verifications.len(),
+            total / verifications.len() as u32,
+            first.time,
+            last.time
+        );
+    }
+    Ok((steps, circuit_times, verifications))
+}
+
+/// Folds like `fold_steps`, or like `fold_and_verify_steps` when
+/// `config::verify_every` is set, recording the timings into `result`.
+fn fold_configured<C1, C2, FC, FS>(
+    label: &str,
+    fs: &mut FS,
+    fs_vp: &FS::VerifierParam,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+    result: &mut BenchResult,
+) -> Result<(), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + IvcState<C1>,
+{
+    result.n_steps = n_steps;
+    match verify_every() {
+        Some(every) => {
+            (result.steps, result.step_circuit, result.ivc_verifications) =
+                fold_verifying(label, fs, fs_vp, n_steps, every, external_inputs)?;
+        }
+        None => {
+            (result.steps, result.step_circuit) =
+                fold_steps_with(label, fs, n_steps, external_inputs, |_, _| Ok(()))?;
+        }
+    }
+    Ok(())
+}
+
+/// `fold_configured`, returning the external inputs fed to every step.
+fn fold_recording_inputs<C1, C2, FC, FS>(
+    label: &str,
+    fs: &mut FS,
+    fs_vp: &FS::VerifierParam,
+    n_steps: usize,
+    mut external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+    result: &mut BenchResult,
+) -> Result<Vec<Vec<C1::ScalarField>>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + IvcState<C1>,
+{
+    let mut inputs = Vec::with_capacity(n_steps);
+    let record = |i| {
+        let step_inputs = external_inputs(i);
+        inputs.push(step_inputs.clone());
+        step_inputs
+    };
+    fold_configured(label, fs, fs_vp, n_steps, record, result)?;
+    Ok(inputs)
+}
+
+/// Recomputes the final state by iterating `step_native` from `z_0` over
+/// `inputs` and checks that it matches the folded `z_i`, catching circuits
+/// whose gadget disagrees with their native step before their timings are
+/// trusted.
+pub fn check_native_state<C1, FC, FS>(
+    label: &str,
+    f_circuit: &FC,
+    fs: &FS,
+    inputs: &[Vec<C1::ScalarField>],
+) -> Result<(), BenchError>
+where
+    C1: CurveGroup,
+    FC: FCircuit<C1::ScalarField>,
+    FS: IvcState<C1>,
+{
+    let native = inputs
+        .iter()
+        .enumerate()
+        .try_fold(fs.z_0(), |z, (i, step_inputs)| {
+            f_circuit.step_native(i, z, step_inputs.clone())
+        })?;
+    if native != fs.z_i() {
+        return Err(BenchError::NativeMismatch(format!(
+            "{} after {} steps: folded {:?}, native {:?}",
+            label,
+            inputs.len(),
+            fs.z_i(),
+            native
+        )));
+    }
+    Ok(())
+}
+
+/// Re-synthesizes every folded step of `f_circuit` on a fresh
+/// `ConstraintSystem`, from `z_0` and the external `inputs` of every step,
+/// and checks that the step's witness satisfies its constraints and that its
+/// output matches `step_native`. A new circuit that folds but whose
+/// constraints its own witness breaks otherwise only shows up as a rejected
+/// proof, without the step or the constraint at fault.
+pub fn check_step_satisfaction<F, FC>(
+    label: &str,
+    f_circuit: &FC,
+    z_0: Vec<F>,
+    inputs: &[Vec<F>],
+) -> Result<(), BenchError>
+where
+    F: PrimeField,
+    FC: FCircuit<F>,
+{
+    let mut z_i = z_0;
+    for (i, step_inputs) in inputs.iter().enumerate() {
+        let (next, unsatisfied) = synthesize_step(f_circuit, i, z_i.clone(), step_inputs.clone())
+            .map_err(folding_schemes::Error::from)?;
+        if let Some(constraint) = unsatisfied {
+            return Err(BenchError::Unsatisfied(format!(
+                "{} step {}: constraint {}",
+                label, i, constraint
+            )));
+        }
+        let native = f_circuit.step_native(i, z_i, step_inputs.clone())?;
+        if next != native {
+            return Err(BenchError::NativeMismatch(format!(
+                "{} step {}: gadget {:?}, native {:?}",
+                label, i, next, native
+            )));
+        }
+        z_i = next;
+    }
+    Ok(())
+}
+
+/// `check_step_satisfaction` when `config::check_sat` is set, timed into
+/// `result.sat_check`.
+fn check_sat_configured<F, FC>(
+    label: &str,
+    f_circuit: &FC,
+    z_0: Vec<F>,
+    inputs: &[Vec<F>],
+    result: &mut BenchResult,
+) -> Result<(), BenchError>
+where
+    F: PrimeField,
+    FC: FCircuit<F>,
+{
+    if !check_sat() {
+        return Ok(());
+    }
+    let start = Stopwatch::start();
+    check_step_satisfaction(label, f_circuit, z_0, inputs)?;
+    result.sat_check = start.elapsed();
+    println!(
+        "{} Constraint satisfaction of {} steps: {:?}",
+        label,
+        inputs.len(),
+        result.sat_check
+    );
+    Ok(())
+}
+
+/// The folding loop of `fold_steps`, calling `after_step(i, fs)` once step
+/// `i` is folded and timed. Also returns the time spent in the step circuit
+/// at every step, empty unless it is wrapped in a `TimedFCircuit`.
+fn fold_steps_with<C1, C2, FC, FS>(
+    label: &str,
+    fs: &mut FS,
+    n_steps: usize,
+    mut external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+    mut after_step: impl FnMut(usize, &FS) -> Result<(), BenchError>,
+) -> Result<(Vec<Duration>, Vec<StepCircuitTimes>), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>,
+{
+    let verbosity = verbosity();
+    let progress = if verbosity == Verbosity::Normal {
+        let bar = ProgressBar::new(n_steps as u64).with_message(label.to_string());
+        bar.set_style(
+            ProgressStyle::with_template("{msg} [{elapsed_precise}] {bar:40} {pos}/{len} ETA {eta}")
+                .unwrap(),
+        );
+        bar
+    } else {
+        ProgressBar::hidden()
+    };
+
+    let mut steps = Vec::with_capacity(n_steps);
+    let mut circuit_times = Vec::with_capacity(n_steps);
+    let mut total_energy = None;
+    // drop what preprocess and earlier checks spent in the step circuit
+    take_times();
+    phase_start(label, "fold");
+    for i in 0..n_steps {
+        let inputs = external_inputs(i);
+        let energy = EnergySample::start();
+        let perf = PerfSample::start();
+        let start = Stopwatch::start();
+        fs.prove_step(rng(), inputs, None)?;
+        let (duration, cycles) = start.stop();
+        steps.push(duration);
+        let times = take_times();
+        circuit_times.push(times);
+        progress.inc(1);
+        if verbosity >= Verbosity::Verbose {
+            println!("{} prove_step {}: {:?}", label, i, duration);
+            if times != StepCircuitTimes::default() {
+                println!(
+                    "{} prove_step {}: witness {:?}, constraints {:?}",
+                    label, i, times.native, times.constraints
+                );
+            }
+        }
+        if verbosity >= Verbosity::Trace {
+            println!("{} z_{}: {:?}", label, i + 1, fs.state());
+        }
+        report_perf(label, &format!("prove_step {}", i), perf);
+        report_cycles(label, &format!("prove_step {}", i), cycles);
+        if let Some(joules) = report_energy(label, &format!("prove_step {}", i), energy) {
+            *total_energy.get_or_insert(0.0) += joules;
+        }
+        after_step(i, fs)?;
+    }
+    progress.finish_and_clear();
+    phase_end(label, "fold", steps.iter().sum());
+    println!(
+        "{} Average proving time: {:?}µs",
+        label,
+        steps.iter().sum::<Duration>().as_micros() / n_steps as u128
+    );
+    if let Some(joules) = total_energy {
+        println!("{} Average energy per step: {:.3} J", label, joules / n_steps as f64);
+    }
+    if circuit_times.iter().all(|t| *t == StepCircuitTimes::default()) {
+        circuit_times.clear();
+    }
+    Ok((steps, circuit_times))
+}
+
+/// Benchmarks the folding loop alone, for cycles without a decider.
+pub fn run_folding_only_bench<C1, C2, FC, FS>(
+    label: &str,
+    prep_param: FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<FS, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + PreprocessStages<C1, C2, FC> + IvcState<C1>,
+{
+    run_folding_only_bench_into(
+        label,
+        prep_param,
+        f_circuit,
+        z_0,
+        n_steps,
+        external_inputs,
+        &mut BenchResult::new(label),
+    )
+}
+
+fn run_folding_only_bench_into<C1, C2, FC, FS>(
+    label: &str,
+    prep_param: FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+    result: &mut BenchResult,
+) -> Result<FS, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + PreprocessStages<C1, C2, FC> + IvcState<C1>,
+{
+    println!("\nRunning {} benchmark ({}):", label, BuildInfo::current());
+    let total_start = Stopwatch::start();
+    let mut rng = rng();
+
+    let fs_params =
+        preprocess_folding_scheme::<C1, C2, FC, FS>(label, &mut rng, &prep_param, result)?;
+    let mut fs = FS::init(&fs_params, f_circuit.clone(), z_0)?;
+
+    let inputs =
+        fold_recording_inputs(label, &mut fs, &fs_params.1, n_steps, external_inputs, result)?;
+    check_native_state(label, &f_circuit, &fs, &inputs)?;
+    check_sat_configured(label, &f_circuit, fs.z_0(), &inputs, result)?;
+    result.total = total_start.elapsed();
+    println!("{} Total time: {:?}", label, result.total);
+    Ok(fs)
+}
+
+/// `FoldingScheme::preprocess`, recording its time and stages in `result`.
+#[allow(clippy::type_complexity)]
+fn preprocess_folding_scheme<C1, C2, FC, FS>(
+    label: &str,
+    rng: impl RngCore,
+    prep_param: &FS::PreprocessorParam,
+    result: &mut BenchResult,
+) -> Result<(FS::ProverParam, FS::VerifierParam), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + PreprocessStages<C1, C2, FC>,
+{
+    phase_start(label, "preprocess");
+    let start = Stopwatch::start();
+    let (fs_params, breakdown) = FS::preprocess_stages(rng, prep_param)?;
+    // splitting re-synthesizes the circuits, so the stages are what counts
+    result.preprocess = breakdown.as_ref().map_or(start.elapsed(), PreprocessBreakdown::total);
+    result.preprocess_breakdown = breakdown;
+    phase_end(label, "preprocess", result.preprocess);
+    println!("{} Folding scheme preprocess: {:?}", label, result.preprocess);
+    if let Some(breakdown) = &result.preprocess_breakdown {
+        println!("{}   {}", label, breakdown);
+    }
+    result.param_sizes = FS::param_sizes(&fs_params);
+    if let Some(sizes) = &result.param_sizes {
+        println!("{} Folding scheme {}", label, sizes);
+    }
+    Ok(fs_params)
+}
+
+/// Folding scheme and decider preprocess, timed separately.
+#[allow(clippy::type_complexity)]
+pub fn preprocess<C1, C2, FC, FS, D>(
+    label: &str,
+    prep_param: &FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+) -> Result<
+    (FS::ProverParam, FS::VerifierParam, D::ProverParam, D::VerifierParam),
+    BenchError,
+>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + PreprocessStages<C1, C2, FC>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: CanonicalSerialize,
+{
+    let mut result = BenchResult::new(label);
+    preprocess_into::<C1, C2, FC, FS, D>(label, prep_param, f_circuit, z_0, &mut result)
+}
+
+#[allow(clippy::type_complexity)]
+pub(crate) fn preprocess_into<C1, C2, FC, FS, D>(
+    label: &str,
+    prep_param: &FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    result: &mut BenchResult,
+) -> Result<
+    (FS::ProverParam, FS::VerifierParam, D::ProverParam, D::VerifierParam),
+    BenchError,
+>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + PreprocessStages<C1, C2, FC>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: CanonicalSerialize,
+{
+    let mut rng = rng();
+
+    let fs_params =
+        preprocess_folding_scheme::<C1, C2, FC, FS>(label, &mut rng, prep_param, result)?;
+
+    let fs = FS::init(&fs_params, f_circuit, z_0)?;
+    phase_start(label, "decider preprocess");
+    let start = Stopwatch::start();
+    let (decider_pp, decider_vp) = D::preprocess(&mut rng, fs_params.clone(), fs)?;
+    result.decider_preprocess = start.elapsed();
+    phase_end(label, "decider preprocess", result.decider_preprocess);
+    println!("{} Decider preprocess: {:?}", label, result.decider_preprocess);
+    result.decider_pk_size = decider_pp.compressed_size();
+    result.decider_vk_size = decider_vp.compressed_size();
+    println!(
+        "{} Decider proving key: {} bytes, verifying key: {} bytes",
+        label, result.decider_pk_size, result.decider_vk_size
+    );
+
+    Ok((fs_params.0, fs_params.1, decider_pp, decider_vp))
+}
+
+/// Benchmarks the whole pipeline: folding scheme and decider preprocess,
+/// `n_steps` folding steps, decider proving and verification.
+///
+/// Returns the folded scheme together with the decider verifier params and
+/// proof, for the stages that run after verification (e.g. `solidity`).
+pub fn run_folding_bench<C1, C2, FC, FS, D>(
+    label: &str,
+    prep_param: FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<(FS, D::VerifierParam, D::Proof), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    run_folding_bench_into::<C1, C2, FC, FS, D>(
+        label,
+        prep_param,
+        f_circuit,
+        z_0,
+        n_steps,
+        external_inputs,
+        true,
+        &mut BenchResult::new(label),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_folding_bench_into<C1, C2, FC, FS, D>(
+    label: &str,
+    prep_param: FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+    verify: bool,
+    result: &mut BenchResult,
+) -> Result<(FS, D::VerifierParam, D::Proof), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    println!("\nRunning {} benchmark ({}):", label, BuildInfo::current());
+    let total_start = Stopwatch::start();
+    let params = preprocess_into::<C1, C2, FC, FS, D>(
+        label,
+        &prep_param,
+        f_circuit.clone(),
+        z_0.clone(),
+        result,
+    )?;
+    let folded = prove_and_verify_into::<C1, C2, FC, FS, D>(
+        label,
+        params,
+        f_circuit,
+        z_0,
+        n_steps,
+        external_inputs,
+        verify,
+        result,
+    )?;
+    result.total = total_start.elapsed();
+    println!("{} Total time: {:?}", label, result.total);
+    Ok(folded)
+}
+
+/// What `run_nova_bench` runs: a step circuit with its preprocessor params,
+/// initial state and per-step external inputs. The chain length comes from
+/// the `BenchConfig`.
+pub struct BenchCase<F: PrimeField, FC, P> {
+    pub label: String,
+    pub f_circuit: FC,
+    pub prep_param: P,
+    pub z_0: Vec<F>,
+    pub external_inputs: Box<dyn FnMut(usize) -> Vec<F> + Send>,
+}
+
+impl<F: PrimeField, FC: FCircuit<F>, P> BenchCase<F, FC, P> {
+    /// Folds from `z_0 = (3, ..., 3)`, feeding random external inputs of the
+    /// width the circuit takes.
+    pub fn new(label: &str, f_circuit: FC, prep_param: P) -> Self {
+        Self {
+            label: label.to_string(),
+            z_0: vec![F::from(3_u32); f_circuit.state_len()],
+            external_inputs: Box::new(random_external_inputs(f_circuit.external_inputs_len())),
+            f_circuit,
+            prep_param,
+        }
+    }
+
+    pub fn with_z_0(mut self, z_0: Vec<F>) -> Self {
+        self.z_0 = z_0;
+        self
+    }
+
+    pub fn with_external_inputs(
+        mut self,
+        external_inputs: impl FnMut(usize) -> Vec<F> + Send + 'static,
+    ) -> Self {
+        self.external_inputs = Box::new(external_inputs);
+        self
+    }
+}
+
+/// Runs `trial` `config.trials` times, each from `config.seed` when set.
+pub(crate) fn run_trials(
+    label: &str,
+    config: &BenchConfig,
+    mut trial: impl FnMut(&mut BenchResult) -> Result<(), BenchError>,
+) -> Result<Vec<BenchResult>, BenchError> {
+    let results = (1..=config.trials)
+        .map(|i| {
+            if config.trials > 1 {
+                println!("\n{} Trial {}/{}", label, i, config.trials);
+            }
+            if let Some(seed) = config.seed {
+                reseed(seed);
+            }
+            let mut result = BenchResult::new(label);
+            trial(&mut result)?;
+            result.print_metrics();
+            Ok(result)
+        })
+        .collect::<Result<Vec<_>, BenchError>>()?;
+    if !config.median || results.len() < 2 {
+        return Ok(results);
+    }
+    let (i, median) = median_trial(results).expect("at least two trials");
+    println!(
+        "{} Median of {} trials: trial {} (total {:?})",
+        label,
+        config.trials,
+        i + 1,
+        median.total
+    );
+    Ok(vec![median])
+}
+
+/// Runs the phases of `config` on `case`, once per trial, and returns the
+/// measurements of every trial (only the median one with `config.median`),
+/// for embedding the benchmarks in other tools.
+/// Without the decider phases only the folding scheme is preprocessed.
+pub fn run_nova_bench<C1, C2, FC, FS, D>(
+    case: BenchCase<C1::ScalarField, FC, FS::PreprocessorParam>,
+    config: &BenchConfig,
+) -> Result<Vec<BenchResult>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>
+        + CircuitSize,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    run_nova_bench_with::<C1, C2, FC, FS, D>(case, config, |_, _, _, _| Ok(()))
+}
+
+/// `run_nova_bench`, calling `after_decider` on the folded scheme, decider
+/// verifier params and proof of every trial, for stages that measure them
+/// further (e.g. the EVM gas of `solidity`).
+pub fn run_nova_bench_with<C1, C2, FC, FS, D>(
+    case: BenchCase<C1::ScalarField, FC, FS::PreprocessorParam>,
+    config: &BenchConfig,
+    mut after_decider: impl FnMut(
+        &FS,
+        D::VerifierParam,
+        D::Proof,
+        &mut BenchResult,
+    ) -> Result<(), BenchError>,
+) -> Result<Vec<BenchResult>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>
+        + CircuitSize,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    if config.dry_run || !config.runs(Phase::DeciderProve) {
+        return run_nova_folding_only_bench::<C1, C2, FC, FS>(case, config);
+    }
+    let BenchCase {
+        label,
+        f_circuit,
+        prep_param,
+        z_0,
+        mut external_inputs,
+    } = case.with_config_inputs(config)?;
+    run_trials(&label, config, |result| {
+        let (fs, decider_vp, proof) = run_folding_bench_into::<C1, C2, FC, FS, D>(
+            &label,
+            prep_param.clone(),
+            f_circuit.clone(),
+            z_0.clone(),
+            config.steps,
+            &mut external_inputs,
+            config.runs(Phase::DeciderVerify),
+            result,
+        )?;
+        record_circuit_size(&fs, result)?;
+        after_decider(&fs, decider_vp, proof, result)
+    })
+}
+
+/// `run_nova_bench_with`, with the preprocessed params reused from
+/// `config.cache_dir` under `key`, for schemes whose params are serializable.
+/// On a hit `preprocess` reports the time to load the params and the decider
+/// preprocess is skipped; the rest of the run is unchanged.
+pub fn run_nova_bench_cached<C1, C2, FC, FS, D>(
+    case: BenchCase<C1::ScalarField, FC, FS::PreprocessorParam>,
+    key: &str,
+    config: &BenchConfig,
+    mut after_decider: impl FnMut(
+        &FS,
+        D::VerifierParam,
+        D::Proof,
+        &mut BenchResult,
+    ) -> Result<(), BenchError>,
+) -> Result<Vec<BenchResult>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>
+        + CircuitSize,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+    (FS::ProverParam, FS::VerifierParam, D::ProverParam, D::VerifierParam):
+        CanonicalSerialize + CanonicalDeserialize,
+{
+    if config.dry_run || !config.runs(Phase::DeciderProve) {
+        return run_nova_folding_only_bench::<C1, C2, FC, FS>(case, config);
+    }
+    let cache = config.param_cache();
+    let BenchCase {
+        label,
+        f_circuit,
+        prep_param,
+        z_0,
+        mut external_inputs,
+    } = case.with_config_inputs(config)?;
+    run_trials(&label, config, |result| {
+        println!("\nRunning {} benchmark ({}):", label, BuildInfo::current());
+        let total_start = Stopwatch::start();
+        let (params, load) = cache.get_or_insert_with(&label, key, || {
+            preprocess_into::<C1, C2, FC, FS, D>(
+                &label,
+                &prep_param,
+                f_circuit.clone(),
+                z_0.clone(),
+                result,
+            )
+        })?;
+        if let Some(load) = load {
+            result.preprocess = load;
+            result.params_cached = true;
+            result.decider_pk_size = params.2.compressed_size();
+            result.decider_vk_size = params.3.compressed_size();
+        }
+        let (fs, decider_vp, proof) = prove_and_verify_into::<C1, C2, FC, FS, D>(
+            &label,
+            params,
+            f_circuit.clone(),
+            z_0.clone(),
+            config.steps,
+            &mut external_inputs,
+            config.runs(Phase::DeciderVerify),
+            result,
+        )?;
+        result.total = total_start.elapsed();
+        println!("{} Total time: {:?}", label, result.total);
+        record_circuit_size(&fs, result)?;
+        after_decider(&fs, decider_vp, proof, result)
+    })
+}
+
+/// `run_nova_bench` for cycles without a decider, see `run_folding_only_bench`.
+pub fn run_nova_folding_only_bench<C1, C2, FC, FS>(
+    case: BenchCase<C1::ScalarField, FC, FS::PreprocessorParam>,
+    config: &BenchConfig,
+) -> Result<Vec<BenchResult>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + PreprocessStages<C1, C2, FC> + IvcState<C1> + CircuitSize,
+{
+    if config.dry_run {
+        return dry_run::<C1, C2, FC, FS>(case, config.steps);
+    }
+    let BenchCase {
+        label,
+        f_circuit,
+        prep_param,
+        z_0,
+        mut external_inputs,
+    } = case.with_config_inputs(config)?;
+    run_trials(&label, config, |result| {
+        let fs = run_folding_only_bench_into::<C1, C2, FC, FS>(
+            &label,
+            prep_param.clone(),
+            f_circuit.clone(),
+            z_0.clone(),
+            config.steps,
+            &mut external_inputs,
+            result,
+        )?;
+        record_circuit_size(&fs, result)
+    })
+}
+
+fn record_circuit_size(
+    fs: &impl CircuitSize,
+    result: &mut BenchResult,
+) -> Result<(), BenchError> {
+    result.augmented_constraints = fs.augmented_constraints();
+    result.cyclefold_constraints = fs.cyclefold_constraints();
+    result.ccs = fs.ccs_stats();
+    if let Some(ccs) = &result.ccs {
+        println!("{} CCS: {}", result.label, ccs);
+    }
+    result.augmented_breakdown = fs.augmented_breakdown()?;
+    if let Some(breakdown) = &result.augmented_breakdown {
+        println!("{} Augmented circuit: {}", result.label, breakdown);
+        if let Some(tax) = breakdown.tax() {
+            println!("{} Folding tax: {:.2}x the step circuit", result.label, tax);
+        }
+    }
+    Ok(())
+}
+
+/// Folds `n_steps` from `z_0` with already preprocessed params, then proves
+/// and verifies the decider proof.
+pub fn prove_and_verify<C1, C2, FC, FS, D>(
+    label: &str,
+    params: (FS::ProverParam, FS::VerifierParam, D::ProverParam, D::VerifierParam),
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<(FS, D::VerifierParam, D::Proof), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS>,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    prove_and_verify_into::<C1, C2, FC, FS, D>(
+        label,
+        params,
+        f_circuit,
+        z_0,
+        n_steps,
+        external_inputs,
+        true,
+        &mut BenchResult::new(label),
+    )
+}
+
+/// `prove_and_verify`, stopping after the decider proof unless `verify`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prove_and_verify_into<C1, C2, FC, FS, D>(
+    label: &str,
+    params: (FS::ProverParam, FS::VerifierParam, D::ProverParam, D::VerifierParam),
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+    verify: bool,
+    result: &mut BenchResult,
+) -> Result<(FS, D::VerifierParam, D::Proof), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS>,
+    D::VerifierParam: Clone + PpHash<C1::ScalarField> + CanonicalSerialize + CanonicalDeserialize,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    let mut rng = rng();
+    let (fs_pp, fs_vp, decider_pp, decider_vp) = params;
+    let fs_params = (fs_pp, fs_vp);
+    let mut fs = FS::init(&fs_params, f_circuit.clone(), z_0)?;
+
+    let inputs =
+        fold_recording_inputs(label, &mut fs, &fs_params.1, n_steps, external_inputs, result)?;
+    check_native_state(label, &f_circuit, &fs, &inputs)?;
+    check_sat_configured(label, &f_circuit, fs.z_0(), &inputs, result)?;
+
+    let energy = EnergySample::start();
+    let perf = PerfSample::start();
+    phase_start(label, "prove");
+    let start = Stopwatch::start();
+    let proof = D::prove(&mut rng, decider_pp, fs.clone())?;
+    let (decider_prove, cycles) = start.stop();
+    result.decider_prove = decider_prove;
+    phase_end(label, "prove", result.decider_prove);
+    result.proof_size = proof.compressed_size();
+    println!("{} Generated Decider proof: {:?}", label, result.decider_prove);
+    report_energy(label, "Decider proof", energy);
+    report_perf(label, "Decider proof", perf);
+    report_cycles(label, "Decider proof", cycles);
+    if !verify {
+        return Ok((fs, decider_vp, proof));
+    }
+
+    let perf = PerfSample::start();
+    phase_start(label, "verify");
+    let start = Stopwatch::start();
+    let verified = D::verify(
+        decider_vp.clone(),
+        fs.num_steps(),
+        fs.z_0(),
+        fs.z_i(),
+        fs.running_instance(),
+        fs.incoming_instance(),
+        &proof,
+    )?;
+    let (decider_verify, cycles) = start.stop();
+    result.decider_verify = decider_verify;
+    phase_end(label, "verify", result.decider_verify);
+    println!("{} Verification time: {:?}", label, result.decider_verify);
+    report_perf(label, "Verification", perf);
+    report_cycles(label, "Verification", cycles);
+    if !verified {
+        return Err(BenchError::Verification(format!("{} decider proof rejected", label)));
+    }
+    check_pp_hash::<C1, C2, FC, FS, D>(label, &fs, &decider_vp, &proof)?;
+    if vp_round_trip() {
+        let round_trip = check_vp_round_trip::<C1, C2, FC, FS, D>(label, &fs, &decider_vp, &proof)?;
+        println!("{} Verifier params round trip: {}", label, round_trip);
+        result.vp_round_trip = Some(round_trip);
+    }
+    if negative_tests() {
+        check_rejections::<C1, C2, FC, FS, D>(label, &fs, &decider_vp, &proof)?;
+    }
+    Ok((fs, decider_vp, proof))
+}
+
+/// Checks that the decider verifier params are bound to the same `pp_hash` as
+/// the folded instances, and that params tampered to another `pp_hash` do not
+/// verify the proof.
+pub fn check_pp_hash<C1, C2, FC, FS, D>(
+    label: &str,
+    fs: &FS,
+    decider_vp: &D::VerifierParam,
+    proof: &D::Proof,
+) -> Result<(), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS>,
+    D::VerifierParam: PpHash<C1::ScalarField>,
+{
+    let pp_hash = fs.pp_hash();
+    println!("{} pp_hash: {}", label, pp_hash);
+    if decider_vp.pp_hash() != pp_hash {
+        return Err(BenchError::Verification(format!(
+            "{} decider params were derived for other folding params",
+            label
+        )));
+    }
+
+    let tampered = decider_vp.with_pp_hash(pp_hash + C1::ScalarField::from(1_u32));
+    let verified = D::verify(
+        tampered,
+        fs.num_steps(),
+        fs.z_0(),
+        fs.z_i(),
+        fs.running_instance(),
+        fs.incoming_instance(),
+        proof,
+    );
+    if matches!(verified, Ok(true)) {
+        return Err(BenchError::Verification(format!(
+            "{} proof verified under a tampered pp_hash",
+            label
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the decider verifier params survive serialization, which a
+/// verifier on another machine depends on: reads them back from their
+/// compressed bytes alone, with validation, checks that they serialize to the
+/// same bytes, and verifies `proof` with them.
+pub fn check_vp_round_trip<C1, C2, FC, FS, D>(
+    label: &str,
+    fs: &FS,
+    decider_vp: &D::VerifierParam,
+    proof: &D::Proof,
+) -> Result<VpRoundTrip, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS>,
+    D::VerifierParam: CanonicalSerialize + CanonicalDeserialize,
+{
+    let start = Stopwatch::start();
+    let mut bytes = Vec::new();
+    decider_vp.serialize_compressed(&mut bytes)?;
+    let serialize = start.elapsed();
+
+    let start = Stopwatch::start();
+    let read_back = D::VerifierParam::deserialize_compressed(&bytes[..])?;
+    let deserialize = start.elapsed();
+    let mut reserialized = Vec::new();
+    read_back.serialize_compressed(&mut reserialized)?;
+    if reserialized != bytes {
+        return Err(BenchError::Verification(format!(
+            "{} decider verifier params serialize differently once read back",
+            label
+        )));
+    }
+
+    let start = Stopwatch::start();
+    let verified = D::verify(
+        read_back,
+        fs.num_steps(),
+        fs.z_0(),
+        fs.z_i(),
+        fs.running_instance(),
+        fs.incoming_instance(),
+        proof,
+    )?;
+    let verify = start.elapsed();
+    if !verified {
+        return Err(BenchError::Verification(format!(
+            "{} decider proof rejected by the verifier params read back",
+            label
+        )));
+    }
+    Ok(VpRoundTrip {
+        bytes: bytes.len(),
+        serialize,
+        deserialize,
+        verify,
+    })
+}
+
+/// `value` with the lowest bit of its first serialized byte flipped, read back
+/// without validation. `None` if the mutated bytes do not deserialize.
+fn flip_first_bit<T: CanonicalSerialize + CanonicalDeserialize>(value: &T) -> Option<T> {
+    let mut bytes = Vec::new();
+    value.serialize_uncompressed(&mut bytes).ok()?;
+    *bytes.first_mut()? ^= 1;
+    T::deserialize_with_mode(&bytes[..], Compress::No, Validate::No).ok()
+}
+
+/// Negative tests: checks that `D::verify` rejects `proof` once the proof
+/// itself, the final state `z_i` or the step count is mutated, so a pipeline
+/// that accepts anything cannot pass for a working one.
+pub fn check_rejections<C1, C2, FC, FS, D>(
+    label: &str,
+    fs: &FS,
+    decider_vp: &D::VerifierParam,
+    proof: &D::Proof,
+) -> Result<(), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS>,
+    D::VerifierParam: Clone,
+    D::Proof: CanonicalSerialize + CanonicalDeserialize,
+{
+    let one = C1::ScalarField::from(1_u32);
+    let verify = |num_steps, z_i, proof: &D::Proof| {
+        D::verify(
+            decider_vp.clone(),
+            num_steps,
+            fs.z_0(),
+            z_i,
+            fs.running_instance(),
+            fs.incoming_instance(),
+            proof,
+        )
+    };
+    let mut z_i = fs.z_i();
+    z_i[0] += one;
+
+    let mut mutations = vec![
+        ("z_i", verify(fs.num_steps(), z_i, proof)),
+        ("step count", verify(fs.num_steps() + one, fs.z_i(), proof)),
+    ];
+    match flip_first_bit(proof) {
+        Some(tampered) => mutations.push(("proof", verify(fs.num_steps(), fs.z_i(), &tampered))),
+        None => println!("{} Negative test: mutated proof does not deserialize, skipped", label),
+    }
+
+    for (mutated, verified) in mutations {
+        if matches!(verified, Ok(true)) {
+            return Err(BenchError::Verification(format!(
+                "{} proof verified with a mutated {}",
+                label, mutated
+            )));
+        }
+        println!("{} Negative test: mutated {} rejected", label, mutated);
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Build variant of the harness, printed with every run so results from
+//! differently built binaries are not compared by accident.
+//!
+//! The arkworks `asm` and `parallel` features are toggled through this
+//! crate's features of the same name (both on by default). To measure their
+//! impact, run the same benchmark under each variant and compare the
+//! per-phase times, e.g.
+//!
+//! ```text
+//! cargo test --release --no-default-features --features bn254,asm,parallel -- bench_bn254
+//! cargo test --release --no-default-features --features bn254,parallel -- bench_bn254
+//! cargo test --release --no-default-features --features bn254,asm -- bench_bn254
+//! ```
+//!
+//! sonobe enables `parallel` on some arkworks crates itself, so turning it off
+//! here mostly affects the arithmetic in this crate and in ark-ff/ark-ec.
+//!
+//! The folding-schemes version and git revision come from `Cargo.lock` (see
+//! `build.rs`) and are written into every report next to the features. The
+//! toolchain and profile are only in the `manifest`.
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// folding-schemes crate version and locked git revision.
+    pub sonobe_version: &'static str,
+    pub sonobe_revision: &'static str,
+    pub asm: bool,
+    pub parallel: bool,
+    /// sonobe's `light-test` feature.
+    pub light_test: bool,
+    pub debug_assertions: bool,
+    /// `rustc --version` of the compiler that built the harness.
+    pub rustc: &'static str,
+    /// Cargo profile (`debug` or `release`) and its opt-level.
+    pub profile: &'static str,
+    pub opt_level: &'static str,
+    /// All enabled features of this crate, comma-separated.
+    pub cargo_features: &'static str,
+}
+
+impl BuildInfo {
+    pub const fn current() -> Self {
+        Self {
+            sonobe_version: env!("FOLDING_SCHEMES_VERSION"),
+            sonobe_revision: env!("FOLDING_SCHEMES_REVISION"),
+            asm: cfg!(feature = "asm"),
+            parallel: cfg!(feature = "parallel"),
+            light_test: cfg!(feature = "light-test"),
+            debug_assertions: cfg!(debug_assertions),
+            rustc: env!("BUILD_RUSTC_VERSION"),
+            profile: env!("BUILD_PROFILE"),
+            opt_level: env!("BUILD_OPT_LEVEL"),
+            cargo_features: env!("BUILD_FEATURES"),
+        }
+    }
+
+    /// Names of the enabled features, e.g. `["asm", "parallel"]`.
+    pub fn features(&self) -> Vec<&'static str> {
+        [
+            ("asm", self.asm),
+            ("parallel", self.parallel),
+            ("light-test", self.light_test),
+        ]
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+    }
+
+    /// The revision abbreviated like `git log --oneline` does.
+    pub fn short_revision(&self) -> &'static str {
+        self.sonobe_revision.get(..8).unwrap_or(self.sonobe_revision)
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_off = |b: bool| if b { "on" } else { "off" };
+        write!(
+            f,
+            "sonobe {}@{}, asm {}, parallel {}, light-test {}, {}",
+            self.sonobe_version,
+            self.short_revision(),
+            on_off(self.asm),
+            on_off(self.parallel),
+            on_off(self.light_test),
+            if self.debug_assertions { "debug" } else { "release" }
+        )
+    }
+}
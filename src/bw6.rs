@@ -0,0 +1,521 @@
+//! BLS12-377/BW6-761 decider configuration.
+//!
+//! sonobe's Nova needs a proper 2-cycle (C1's base field is C2's scalar field
+//! and vice versa) for its CycleFold circuit, which BLS12-377/BW6-761 is not:
+//! it is a 2-chain. So the steps of the cubic circuit are folded here with
+//! Nova's folding scheme (NIFS) alone: every step is a fresh R1CS instance
+//! over the BLS12-377 scalar field with a Pedersen commitment on BLS12-377
+//! G1, folded natively into a running relaxed instance. There is no augmented
+//! circuit checking the folding, so the verifier takes the folded instance as
+//! given, and the decider does the one-layer recursion the chain is used for
+//! in practice: a Groth16 proof over BLS12-377 that the folded witness
+//! satisfies the relaxed R1CS, verified inside a Groth16 circuit over BW6-761
+//! which also opens the commitments, natively since BLS12-377 G1 lives over
+//! the BW6-761 scalar field.
+use ark_bls12_377::{
+    constraints::{G1Var, PairingVar},
+    Bls12_377, Fq as Fq377, Fr as Fr377, G1Affine, G1Projective as G1,
+};
+use ark_bw6_761::BW6_761;
+use ark_crypto_primitives::snark::{
+    BooleanInputVar, CircuitSpecificSetupSNARK, SNARKGadget, SNARK,
+};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_groth16::{
+    constraints::{Groth16VerifierGadget, ProofVar, VerifyingKeyVar},
+    Groth16, Proof, VerifyingKey,
+};
+use ark_r1cs_std::{
+    alloc::{AllocVar, AllocationMode},
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    groups::CurveVar,
+};
+use ark_relations::r1cs::{
+    ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef,
+    SynthesisError,
+};
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use std::iter;
+
+use crate::bench::run_trials;
+use crate::build_info::BuildInfo;
+use crate::clock::Stopwatch;
+use crate::config::{rng, BenchConfig, Phase};
+use crate::error::BenchError;
+use crate::report::{phase_end, phase_start};
+use crate::result::BenchResult;
+
+/// One step of `CubicFCircuit`, `z_{i+1} = z_i^3 + z_i + 5`, with both states
+/// public.
+#[derive(Clone, Copy, Debug)]
+pub struct CubicStepCircuit<F: PrimeField> {
+    pub z_i: F,
+}
+
+impl<F: PrimeField> CubicStepCircuit<F> {
+    pub fn next(z_i: F) -> F {
+        z_i * z_i * z_i + z_i + F::from(5_u32)
+    }
+
+    /// Public inputs and witness of the step, without the constant one.
+    fn assignment(self) -> Result<(Vec<F>, Vec<F>), SynthesisError> {
+        let cs = ConstraintSystem::<F>::new_ref();
+        self.generate_constraints(cs.clone())?;
+        cs.borrow()
+            .map(|cs| {
+                (
+                    cs.instance_assignment[1..].to_vec(),
+                    cs.witness_assignment.clone(),
+                )
+            })
+            .ok_or(SynthesisError::MissingCS)
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CubicStepCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let z_i = FpVar::<F>::new_input(cs.clone(), || Ok(self.z_i))?;
+        let z_next = FpVar::<F>::new_input(cs.clone(), || Ok(Self::next(self.z_i)))?;
+        let five = FpVar::<F>::new_constant(cs, F::from(5u32))?;
+        (&z_i * &z_i * &z_i + &z_i + five).enforce_equal(&z_next)
+    }
+}
+
+/// Instance of the relaxed R1CS `Az ∘ Bz = u·Cz + E` with `z = (u, x, w)`,
+/// committing to `w` and `E`.
+#[derive(Clone, Debug)]
+pub struct RelaxedInstance {
+    pub u: Fr377,
+    pub x: Vec<Fr377>,
+    pub cm_w: G1,
+    pub cm_e: G1,
+}
+
+#[derive(Clone, Debug)]
+pub struct RelaxedWitness {
+    pub w: Vec<Fr377>,
+    pub e: Vec<Fr377>,
+}
+
+/// Nova's NIFS over the R1CS of `CubicStepCircuit`, with Pedersen
+/// commitments on BLS12-377 G1.
+#[derive(Clone, Debug)]
+pub struct CubicFolding {
+    pub matrices: ConstraintMatrices<Fr377>,
+    pub generators: Vec<G1Affine>,
+}
+
+impl CubicFolding {
+    /// Synthesizes the step circuit and samples the commitment generators.
+    pub fn setup(rng: &mut impl ark_std::rand::RngCore) -> Result<Self, SynthesisError> {
+        let cs = ConstraintSystem::<Fr377>::new_ref();
+        CubicStepCircuit { z_i: Fr377::zero() }.generate_constraints(cs.clone())?;
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+        let len = matrices.num_witness_variables.max(matrices.num_constraints);
+        let generators: Vec<G1> = (0..len).map(|_| G1::rand(rng)).collect();
+        Ok(Self {
+            matrices,
+            generators: G1::normalize_batch(&generators),
+        })
+    }
+
+    fn commit(&self, v: &[Fr377]) -> G1 {
+        G1::msm_unchecked(&self.generators[..v.len()], v)
+    }
+
+    /// An instance and witness of the right lengths, for the decider setup.
+    pub fn dummy(&self) -> (RelaxedInstance, RelaxedWitness) {
+        let n_public = self.matrices.num_instance_variables - 1;
+        let instance = RelaxedInstance {
+            u: Fr377::zero(),
+            x: vec![Fr377::zero(); n_public],
+            cm_w: G1::zero(),
+            cm_e: G1::zero(),
+        };
+        let witness = RelaxedWitness {
+            w: vec![Fr377::zero(); self.matrices.num_witness_variables],
+            e: vec![Fr377::zero(); self.matrices.num_constraints],
+        };
+        (instance, witness)
+    }
+
+    /// The step from `z_i` as a fresh instance: `u = 1` and `E = 0`.
+    pub fn step(&self, z_i: Fr377) -> Result<(RelaxedInstance, RelaxedWitness), SynthesisError> {
+        let (x, w) = CubicStepCircuit { z_i }.assignment()?;
+        let instance = RelaxedInstance {
+            u: Fr377::one(),
+            x,
+            cm_w: self.commit(&w),
+            cm_e: G1::zero(),
+        };
+        let e = vec![Fr377::zero(); self.matrices.num_constraints];
+        Ok((instance, RelaxedWitness { w, e }))
+    }
+
+    /// `(Az, Bz, Cz)` for `z = (u, x, w)`.
+    fn products(&self, instance: &RelaxedInstance, witness: &RelaxedWitness) -> [Vec<Fr377>; 3] {
+        let z: Vec<Fr377> = iter::once(instance.u)
+            .chain(instance.x.iter().copied())
+            .chain(witness.w.iter().copied())
+            .collect();
+        let mul = |m: &[Vec<(Fr377, usize)>]| -> Vec<Fr377> {
+            m.iter()
+                .map(|row| row.iter().map(|(c, i)| *c * z[*i]).sum())
+                .collect()
+        };
+        [
+            mul(&self.matrices.a),
+            mul(&self.matrices.b),
+            mul(&self.matrices.c),
+        ]
+    }
+
+    /// Whether `witness` satisfies `instance`, commitments included.
+    pub fn is_satisfied(&self, instance: &RelaxedInstance, witness: &RelaxedWitness) -> bool {
+        let [az, bz, cz] = self.products(instance, witness);
+        let relation = (0..self.matrices.num_constraints)
+            .all(|i| az[i] * bz[i] == instance.u * cz[i] + witness.e[i]);
+        relation
+            && instance.cm_w == self.commit(&witness.w)
+            && instance.cm_e == self.commit(&witness.e)
+    }
+
+    /// Folds `incoming` into `running` with the cross term `T` and a
+    /// challenge hashed from both instances and the commitment to `T`.
+    pub fn fold(
+        &self,
+        (running, running_w): (&RelaxedInstance, &RelaxedWitness),
+        (incoming, incoming_w): (&RelaxedInstance, &RelaxedWitness),
+    ) -> Result<(RelaxedInstance, RelaxedWitness), BenchError> {
+        let [az1, bz1, cz1] = self.products(running, running_w);
+        let [az2, bz2, cz2] = self.products(incoming, incoming_w);
+        let t: Vec<Fr377> = (0..self.matrices.num_constraints)
+            .map(|i| az1[i] * bz2[i] + az2[i] * bz1[i] - running.u * cz2[i] - incoming.u * cz1[i])
+            .collect();
+        let cm_t = self.commit(&t);
+        let r = challenge(running, incoming, &cm_t)?;
+        let r2 = r.square();
+
+        let add = |a: &[Fr377], b: &[Fr377]| -> Vec<Fr377> {
+            a.iter().zip(b).map(|(a, b)| *a + r * b).collect()
+        };
+        let instance = RelaxedInstance {
+            u: running.u + r * incoming.u,
+            x: add(&running.x, &incoming.x),
+            cm_w: running.cm_w + incoming.cm_w * r,
+            cm_e: running.cm_e + cm_t * r + incoming.cm_e * r2,
+        };
+        let e = running_w
+            .e
+            .iter()
+            .zip(&t)
+            .zip(&incoming_w.e)
+            .map(|((e1, t), e2)| *e1 + r * t + r2 * e2)
+            .collect();
+        let witness = RelaxedWitness {
+            w: add(&running_w.w, &incoming_w.w),
+            e,
+        };
+        Ok((instance, witness))
+    }
+}
+
+/// Fiat-Shamir challenge of one fold.
+fn challenge(
+    running: &RelaxedInstance,
+    incoming: &RelaxedInstance,
+    cm_t: &G1,
+) -> Result<Fr377, BenchError> {
+    let mut hasher = blake3::Hasher::new();
+    for instance in [running, incoming] {
+        instance.u.serialize_compressed(&mut hasher)?;
+        instance.x.serialize_compressed(&mut hasher)?;
+        instance
+            .cm_w
+            .into_affine()
+            .serialize_compressed(&mut hasher)?;
+        instance
+            .cm_e
+            .into_affine()
+            .serialize_compressed(&mut hasher)?;
+    }
+    cm_t.into_affine().serialize_compressed(&mut hasher)?;
+    Ok(Fr377::from_le_bytes_mod_order(hasher.finalize().as_bytes()))
+}
+
+/// BLS12-377 circuit of the relaxed R1CS of `CubicFolding`. All of `u`, `x`,
+/// `w` and `E` are public inputs, so that the BW6-761 circuit can tie `w` and
+/// `E` to their commitments.
+#[derive(Clone, Debug)]
+pub struct RelaxedR1csCircuit {
+    pub matrices: ConstraintMatrices<Fr377>,
+    pub instance: RelaxedInstance,
+    pub witness: RelaxedWitness,
+}
+
+impl RelaxedR1csCircuit {
+    pub fn public_inputs(&self) -> Vec<Fr377> {
+        iter::once(self.instance.u)
+            .chain(self.instance.x.iter().copied())
+            .chain(self.witness.w.iter().copied())
+            .chain(self.witness.e.iter().copied())
+            .collect()
+    }
+}
+
+impl ConstraintSynthesizer<Fr377> for RelaxedR1csCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr377>) -> Result<(), SynthesisError> {
+        let u = FpVar::new_input(cs.clone(), || Ok(self.instance.u))?;
+        let x = Vec::<FpVar<Fr377>>::new_input(cs.clone(), || Ok(self.instance.x))?;
+        let w = Vec::<FpVar<Fr377>>::new_input(cs.clone(), || Ok(self.witness.w))?;
+        let e = Vec::<FpVar<Fr377>>::new_input(cs, || Ok(self.witness.e))?;
+
+        let z: Vec<FpVar<Fr377>> = iter::once(u.clone()).chain(x).chain(w).collect();
+        let lc = |row: &[(Fr377, usize)]| {
+            row.iter()
+                .fold(FpVar::zero(), |acc, (c, i)| acc + &z[*i] * *c)
+        };
+        for (i, e_i) in e.iter().enumerate() {
+            let (a, b, c) = (
+                lc(&self.matrices.a[i]),
+                lc(&self.matrices.b[i]),
+                lc(&self.matrices.c[i]),
+            );
+            a.mul_equals(&b, &(&u * &c + e_i))?;
+        }
+        Ok(())
+    }
+}
+
+/// Bits of an `Fr377`, which is non-native in the BW6-761 circuit.
+fn to_bits(x: &Fr377) -> Vec<bool> {
+    let mut bits = x.into_bigint().to_bits_le();
+    bits.truncate(Fr377::MODULUS_BIT_SIZE as usize);
+    bits
+}
+
+/// Public inputs of a point as `ProjectiveVar` allocates it: the affine
+/// coordinates and `z = 1`, or `(0, 1, 0)` for the identity.
+fn point_inputs(p: &G1) -> [Fq377; 3] {
+    match p.into_affine().xy() {
+        Some((x, y)) => [*x, *y, Fq377::one()],
+        None => [Fq377::zero(), Fq377::one(), Fq377::zero()],
+    }
+}
+
+/// BW6-761 circuit verifying a BLS12-377 Groth16 proof of
+/// `RelaxedR1csCircuit` and opening the commitments of the folded instance
+/// to its `w` and `E`.
+///
+/// The BW6-761 scalar field is the BLS12-377 base field, so the constraints
+/// are over `Fq377`: the commitments are native, the field elements of the
+/// relaxed R1CS are handled bit by bit.
+#[derive(Clone, Debug)]
+pub struct Bw6DeciderCircuit {
+    pub vk: VerifyingKey<Bls12_377>,
+    pub generators: Vec<G1Affine>,
+    pub instance: RelaxedInstance,
+    pub witness: RelaxedWitness,
+    /// Inner proof; `Proof::default()` is enough for the circuit-specific setup.
+    pub proof: Proof<Bls12_377>,
+}
+
+impl Bw6DeciderCircuit {
+    /// Public inputs of the BW6-761 proof: the bits of `u` and `x`, then the
+    /// commitments to `w` and `E`.
+    pub fn outer_public_inputs(&self) -> Vec<Fq377> {
+        iter::once(&self.instance.u)
+            .chain(&self.instance.x)
+            .flat_map(to_bits)
+            .map(Fq377::from)
+            .chain(point_inputs(&self.instance.cm_w))
+            .chain(point_inputs(&self.instance.cm_e))
+            .collect()
+    }
+}
+
+impl ConstraintSynthesizer<Fq377> for Bw6DeciderCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq377>) -> Result<(), SynthesisError> {
+        let vk = VerifyingKeyVar::<Bls12_377, PairingVar>::new_constant(cs.clone(), &self.vk)?;
+        let bits = |values: &[Fr377], mode| {
+            values
+                .iter()
+                .map(|v| Vec::<Boolean<Fq377>>::new_variable(cs.clone(), || Ok(to_bits(v)), mode))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let public: Vec<Fr377> = iter::once(self.instance.u)
+            .chain(self.instance.x.iter().copied())
+            .collect();
+        let public_bits = bits(&public, AllocationMode::Input)?;
+        let w_bits = bits(&self.witness.w, AllocationMode::Witness)?;
+        let e_bits = bits(&self.witness.e, AllocationMode::Witness)?;
+        let cm_w = G1Var::new_input(cs.clone(), || Ok(self.instance.cm_w))?;
+        let cm_e = G1Var::new_input(cs.clone(), || Ok(self.instance.cm_e))?;
+
+        let commit = |bits: &[Vec<Boolean<Fq377>>]| {
+            bits.iter()
+                .zip(&self.generators)
+                .try_fold(G1Var::zero(), |acc, (bits, g)| {
+                    let g = G1Var::new_constant(cs.clone(), G1::from(*g))?;
+                    Ok::<_, SynthesisError>(acc + g.scalar_mul_le(bits.iter())?)
+                })
+        };
+        commit(&w_bits)?.enforce_equal(&cm_w)?;
+        commit(&e_bits)?.enforce_equal(&cm_e)?;
+
+        let inputs = BooleanInputVar::new(
+            public_bits
+                .into_iter()
+                .chain(w_bits)
+                .chain(e_bits)
+                .collect(),
+        );
+        let proof = ProofVar::<Bls12_377, PairingVar>::new_witness(cs, || Ok(self.proof))?;
+        Groth16VerifierGadget::<Bls12_377, PairingVar>::verify(&vk, &inputs, &proof)?
+            .enforce_equal(&Boolean::TRUE)
+    }
+}
+
+fn synthesis(e: SynthesisError) -> BenchError {
+    folding_schemes::Error::from(e).into()
+}
+
+/// Folds `config.steps` cubic steps from `z_0 = 3` with `CubicFolding`, then
+/// proves and verifies the folded instance with the BLS12-377/BW6-761
+/// decider, once per trial. The decider preprocess covers both Groth16
+/// setups and its proving time both proofs; the proof is the BW6-761 one.
+pub fn run_bw6_bench(label: &str, config: &BenchConfig) -> Result<Vec<BenchResult>, BenchError> {
+    if config.dry_run {
+        println!(
+            "{} Dry run: the BW6-761 decider has no folding scheme circuits",
+            label
+        );
+        return Ok(vec![]);
+    }
+    run_trials(label, config, |result| {
+        println!("\nRunning {} benchmark ({}):", label, BuildInfo::current());
+        let total_start = Stopwatch::start();
+        let mut rng = rng();
+
+        phase_start(label, "preprocess");
+        let start = Stopwatch::start();
+        let folding = CubicFolding::setup(&mut rng).map_err(synthesis)?;
+        result.preprocess = start.elapsed();
+        phase_end(label, "preprocess", result.preprocess);
+        println!(
+            "{} Folding scheme preprocess: {:?}",
+            label, result.preprocess
+        );
+
+        let (dummy, dummy_witness) = folding.dummy();
+        phase_start(label, "decider preprocess");
+        let start = Stopwatch::start();
+        let inner = RelaxedR1csCircuit {
+            matrices: folding.matrices.clone(),
+            instance: dummy.clone(),
+            witness: dummy_witness.clone(),
+        };
+        let (inner_pk, inner_vk) =
+            Groth16::<Bls12_377>::circuit_specific_setup(inner, &mut rng).map_err(synthesis)?;
+        let outer = Bw6DeciderCircuit {
+            vk: inner_vk.clone(),
+            generators: folding.generators.clone(),
+            instance: dummy,
+            witness: dummy_witness,
+            proof: Proof::default(),
+        };
+        let (outer_pk, outer_vk) =
+            Groth16::<BW6_761>::circuit_specific_setup(outer, &mut rng).map_err(synthesis)?;
+        result.decider_preprocess = start.elapsed();
+        phase_end(label, "decider preprocess", result.decider_preprocess);
+        println!(
+            "{} Decider preprocess: {:?}",
+            label, result.decider_preprocess
+        );
+        result.decider_pk_size = inner_pk.compressed_size() + outer_pk.compressed_size();
+        result.decider_vk_size = outer_vk.compressed_size();
+        println!(
+            "{} Decider proving key: {} bytes, verifying key: {} bytes",
+            label, result.decider_pk_size, result.decider_vk_size
+        );
+
+        result.n_steps = config.steps;
+        phase_start(label, "fold");
+        let mut z_i = Fr377::from(3_u32);
+        let mut folded: Option<(RelaxedInstance, RelaxedWitness)> = None;
+        for _ in 0..config.steps {
+            let start = Stopwatch::start();
+            let step = folding.step(z_i).map_err(synthesis)?;
+            folded = Some(match folded {
+                Some((u, w)) => folding.fold((&u, &w), (&step.0, &step.1))?,
+                None => step,
+            });
+            result.steps.push(start.elapsed());
+            z_i = CubicStepCircuit::next(z_i);
+        }
+        phase_end(label, "fold", result.steps.iter().sum());
+        println!("{} Average proving time: {:?}", label, result.avg_step());
+        let (instance, witness) =
+            folded.ok_or_else(|| BenchError::InvalidInput(format!("{} folds no steps", label)))?;
+        if !folding.is_satisfied(&instance, &witness) {
+            return Err(BenchError::Unsatisfied(format!(
+                "{} folded instance",
+                label
+            )));
+        }
+        if !config.runs(Phase::DeciderProve) {
+            result.total = total_start.elapsed();
+            return Ok(());
+        }
+
+        phase_start(label, "prove");
+        let start = Stopwatch::start();
+        let inner = RelaxedR1csCircuit {
+            matrices: folding.matrices.clone(),
+            instance: instance.clone(),
+            witness: witness.clone(),
+        };
+        let inner_proof =
+            Groth16::<Bls12_377>::prove(&inner_pk, inner, &mut rng).map_err(synthesis)?;
+        let decider = Bw6DeciderCircuit {
+            vk: inner_vk,
+            generators: folding.generators.clone(),
+            instance,
+            witness,
+            proof: inner_proof,
+        };
+        let public_inputs = decider.outer_public_inputs();
+        let proof = Groth16::<BW6_761>::prove(&outer_pk, decider, &mut rng).map_err(synthesis)?;
+        result.decider_prove = start.elapsed();
+        phase_end(label, "prove", result.decider_prove);
+        result.proof_size = proof.compressed_size();
+        println!(
+            "{} Generated Decider proof: {:?}",
+            label, result.decider_prove
+        );
+
+        if config.runs(Phase::DeciderVerify) {
+            phase_start(label, "verify");
+            let start = Stopwatch::start();
+            let verified =
+                Groth16::<BW6_761>::verify(&outer_vk, &public_inputs, &proof).map_err(synthesis)?;
+            result.decider_verify = start.elapsed();
+            phase_end(label, "verify", result.decider_verify);
+            println!("{} Verification time: {:?}", label, result.decider_verify);
+            if !verified {
+                return Err(BenchError::Verification(format!(
+                    "{} decider proof rejected",
+                    label
+                )));
+            }
+        }
+        result.total = total_start.elapsed();
+        println!("{} Total time: {:?}", label, result.total);
+        Ok(())
+    })
+}
@@ -0,0 +1,130 @@
+//! On-disk cache of preprocessed params.
+//!
+//! Entries are files named after a key built from the circuit, curve and
+//! scheme plus a hash of whatever else shapes the params, holding them in
+//! arkworks' compressed encoding. The cache is on by default under
+//! `target/params`; `SONOBE_BENCH_CACHE_DIR` moves it and
+//! `SONOBE_BENCH_NO_CACHE` (or `--no-cache`) disables it.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::error::BenchError;
+
+#[derive(Clone, Debug)]
+pub struct ParamCache {
+    /// `None` when caching is disabled.
+    pub dir: Option<PathBuf>,
+}
+
+impl ParamCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: Some(dir.into()),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self { dir: None }
+    }
+
+    pub fn from_env() -> Self {
+        if std::env::var_os("SONOBE_BENCH_NO_CACHE").is_some() {
+            return Self::disabled();
+        }
+        Self::new(
+            std::env::var_os("SONOBE_BENCH_CACHE_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("target/params")),
+        )
+    }
+
+    /// Cache key of a circuit under a curve cycle and folding scheme.
+    /// `inputs` are the rest of what the params depend on (circuit params,
+    /// Poseidon config, seed) and go in as a hash, so that changing any of
+    /// them misses the cache.
+    pub fn key(circuit: &str, curve: &str, scheme: &str, inputs: &impl Hash) -> String {
+        let mut hasher = DefaultHasher::new();
+        inputs.hash(&mut hasher);
+        let mut parts: Vec<String> = [circuit, curve, scheme]
+            .iter()
+            .map(|s| s.replace(|c: char| !c.is_ascii_alphanumeric(), "_"))
+            .collect();
+        parts.push(format!("{:016x}", hasher.finish()));
+        parts.join("-")
+    }
+
+    /// Returns the params cached under `key`, or computes them with `f` and
+    /// stores them, along with the time it took to load them on a hit.
+    /// Entries that fail to deserialize or validate are recomputed.
+    pub fn get_or_insert_with<T>(
+        &self,
+        label: &str,
+        key: &str,
+        f: impl FnOnce() -> Result<T, BenchError>,
+    ) -> Result<(T, Option<Duration>), BenchError>
+    where
+        T: CanonicalSerialize + CanonicalDeserialize,
+    {
+        let Some(dir) = &self.dir else {
+            return Ok((f()?, None));
+        };
+        let path = dir.join(format!("{}.bin", key));
+
+        let start = Instant::now();
+        if let Ok(file) = File::open(&path) {
+            match T::deserialize_compressed(BufReader::new(file)) {
+                Ok(params) => {
+                    let elapsed = start.elapsed();
+                    println!("{} Loaded cached params {:?}: {:?}", label, path, elapsed);
+                    return Ok((params, Some(elapsed)));
+                }
+                Err(e) => println!("{} Ignoring stale params cache {:?}: {}", label, path, e),
+            }
+        }
+
+        let params = f()?;
+        let start = Instant::now();
+        fs::create_dir_all(dir)?;
+        let file = File::create(&path)?;
+        params.serialize_compressed(BufWriter::new(file))?;
+        println!("{} Cached params to {:?}: {:?}", label, path, start.elapsed());
+        Ok((params, None))
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_param_cache_reuse() {
+        let dir = std::env::temp_dir().join(format!("param-cache-{}", std::process::id()));
+        let cache = ParamCache::new(&dir);
+        let key = ParamCache::key("cubic", "BN254", "Nova", &(1_usize, Some(7_u64)));
+        assert!(key.starts_with("cubic-BN254-Nova-"));
+        assert_eq!(key, ParamCache::key("cubic", "BN254", "Nova", &(1_usize, Some(7_u64))));
+        assert_ne!(key, ParamCache::key("cubic", "BN254", "Nova", &(1_usize, Some(8_u64))));
+
+        let (params, load) = cache
+            .get_or_insert_with("test", &key, || Ok((Fr::from(1_u32), vec![Fr::from(2_u32)])))
+            .unwrap();
+        assert!(load.is_none());
+        // second lookup must come from disk rather than the closure
+        let (cached, load): ((Fr, Vec<Fr>), _) =
+            cache.get_or_insert_with("test", &key, || unreachable!()).unwrap();
+        assert_eq!(cached, params);
+        assert!(load.is_some());
+
+        let (uncached, _) = ParamCache::disabled()
+            .get_or_insert_with("test", &key, || Ok((Fr::from(3_u32), vec![])))
+            .unwrap();
+        assert_eq!(uncached.0, Fr::from(3_u32));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
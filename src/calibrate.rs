@@ -0,0 +1,88 @@
+//! Step counts calibrated to a time budget, with `--target`.
+//!
+//! A fixed `--steps` takes minutes on a laptop for what a server folds in
+//! seconds, and the other way round leaves the server with too few steps for
+//! a stable mean. With `BenchConfig::target`, every benchmark first folds
+//! `PROBE_STEPS` steps without a decider, and then runs with as many steps
+//! as fit in the target at the mean probe step, so that the folding phase
+//! takes about the same time, and its mean the same confidence, on every
+//! machine. The probe preprocesses like any run unless the params are
+//! cached.
+use std::time::Duration;
+
+use crate::config::{BenchConfig, Phase};
+use crate::error::BenchError;
+use crate::registry::BenchFn;
+
+/// Steps folded to time a step before calibrating.
+pub const PROBE_STEPS: usize = 3;
+
+/// Steps of `step` each that fit in `target`, at least one.
+pub fn calibrated_steps(target: Duration, step: Duration) -> usize {
+    let steps = target.as_secs_f64() / step.as_secs_f64().max(f64::MIN_POSITIVE);
+    (steps.round() as usize).max(1)
+}
+
+/// `config` with its steps calibrated for `run` when it has a target, see
+/// the module docs.
+pub fn calibrate(
+    name: &str,
+    run: &BenchFn,
+    config: &BenchConfig,
+) -> Result<BenchConfig, BenchError> {
+    let Some(target) = config.target.filter(|_| !config.dry_run) else {
+        return Ok(config.clone());
+    };
+    let mut probe = config.clone();
+    probe.steps = PROBE_STEPS;
+    probe.trials = 1;
+    probe.median = false;
+    probe.phases = vec![Phase::Fold];
+    probe.target = None;
+    let results = run(&probe)?;
+    let step = results
+        .first()
+        .map(|r| r.avg_step())
+        .filter(|step| !step.is_zero())
+        .ok_or_else(|| BenchError::InvalidInput(format!("{} folds no step to calibrate", name)))?;
+
+    let mut config = config.clone();
+    config.steps = calibrated_steps(target, step);
+    println!(
+        "{} Calibrated to {} steps: {:?} per step over {} probe steps, target {:?}",
+        name, config.steps, step, PROBE_STEPS, target
+    );
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::BenchResult;
+
+    #[test]
+    fn test_calibrate() {
+        let ms = Duration::from_millis;
+        assert_eq!(calibrated_steps(ms(1000), ms(30)), 33);
+        assert_eq!(calibrated_steps(ms(10), ms(30)), 1);
+
+        // 20ms per step, the probe folding only
+        let run = move |config: &BenchConfig| -> Result<Vec<BenchResult>, BenchError> {
+            let mut result = BenchResult::new("probe");
+            result.steps = vec![ms(20); config.steps];
+            if config.steps == PROBE_STEPS {
+                assert_eq!(config.phases, [Phase::Fold]);
+            }
+            Ok(vec![result])
+        };
+        let config = BenchConfig::builder()
+            .steps(5)
+            .target(Some(Duration::from_secs(2)))
+            .build()
+            .unwrap();
+        assert_eq!(calibrate("probe", &run, &config).unwrap().steps, 100);
+
+        let config = BenchConfig::builder().steps(5).build().unwrap();
+        assert_eq!(calibrate("probe", &run, &config).unwrap().steps, 5);
+    }
+}
@@ -0,0 +1,251 @@
+//! Declarative benchmark campaigns, run with `sonobe-bench run --config`.
+//!
+//! A TOML file declares the matrix to run and how, e.g.
+//!
+//! ```toml
+//! schemes = ["nova", "nova-pedersen"]
+//! curves = ["bn254"]
+//! circuits = ["cubic", "sha256"]
+//! steps = [5, 20]
+//! trials = 3
+//! median = true
+//! seed = 42
+//! phases = ["fold", "prove"]
+//! jobs = 1
+//!
+//! [output]
+//! json = "results.json"
+//! csv = "results.csv"
+//! markdown = "results.md"
+//! gbench_json = "gbench.json"
+//! samples = "samples.csv"
+//! latex = "results.tex"
+//! ```
+//!
+//! Every key is optional. A missing `schemes`, `curves` or `circuits` matches
+//! them all, and the others default as their `sonobe-bench` flags do. Each
+//! step count is a run of its own; with more than one, the output files get
+//! the step count appended to their stem (`results-5.json`).
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use crate::config::{BenchConfig, OutputSink, Phase};
+use crate::error::BenchError;
+
+#[derive(Clone, Debug)]
+pub struct Campaign {
+    /// Registry filters, one per `scheme/curve/circuit` of the matrix.
+    pub filters: Vec<String>,
+    /// One configuration per step count.
+    pub runs: Vec<BenchConfig>,
+}
+
+impl Campaign {
+    pub fn load(path: &Path) -> Result<Self, BenchError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(s: &str) -> Result<Self, BenchError> {
+        let table: Table = s
+            .parse()
+            .map_err(|e: toml::de::Error| BenchError::InvalidInput(e.message().to_string()))?;
+        for key in table.keys() {
+            if !KEYS.contains(&key.as_str()) {
+                return Err(invalid(key, format!("expected one of {}", KEYS.join(", "))));
+            }
+        }
+
+        let axis = |key: &str| -> Result<Vec<String>, BenchError> {
+            let values = strings(&table, key)?;
+            Ok(if values.is_empty() { vec!["*".to_string()] } else { values })
+        };
+        let mut filters = Vec::new();
+        for scheme in axis("schemes")? {
+            for curve in axis("curves")? {
+                for circuit in axis("circuits")? {
+                    filters.push(format!("{}/{}/{}", scheme, curve, circuit));
+                }
+            }
+        }
+
+        let steps = match table.get("steps") {
+            None => vec![BenchConfig::default().steps],
+            Some(Value::Integer(_)) => vec![usize_of(&table, "steps")?.unwrap_or_default()],
+            Some(_) => integers(&table, "steps")?,
+        };
+        let phases = strings(&table, "phases")?
+            .iter()
+            .map(|p| p.parse::<Phase>().map_err(|e| invalid("phases", e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = outputs(&table)?;
+
+        let mut runs = Vec::with_capacity(steps.len());
+        for &n in &steps {
+            let suffix = (steps.len() > 1).then(|| n.to_string());
+            let mut builder = BenchConfig::builder().steps(n).sinks(
+                std::iter::once(OutputSink::Terminal).chain(
+                    outputs.iter().map(|(sink, path)| sink(with_suffix(path, suffix.as_deref()))),
+                ),
+            );
+            if let Some(trials) = usize_of(&table, "trials")? {
+                builder = builder.trials(trials);
+            }
+            if let Some(median) = bool_of(&table, "median")? {
+                builder = builder.median(median);
+            }
+            if let Some(seed) = usize_of(&table, "seed")? {
+                builder = builder.seed(seed as u64);
+            }
+            if let Some(jobs) = usize_of(&table, "jobs")? {
+                builder = builder.jobs(jobs);
+            }
+            if !phases.is_empty() {
+                builder = builder.phases(phases.iter().copied());
+            }
+            runs.push(builder.build()?);
+        }
+        Ok(Self { filters, runs })
+    }
+}
+
+const KEYS: [&str; 10] = [
+    "schemes", "curves", "circuits", "steps", "trials", "median", "seed", "phases", "jobs",
+    "output",
+];
+
+type Sink = fn(PathBuf) -> OutputSink;
+
+/// The `[output]` table.
+fn outputs(table: &Table) -> Result<Vec<(Sink, PathBuf)>, BenchError> {
+    let output = match table.get("output") {
+        None => return Ok(Vec::new()),
+        Some(Value::Table(output)) => output,
+        Some(_) => return Err(invalid("output", "expected a table")),
+    };
+    let mut sinks = Vec::new();
+    for (key, value) in output {
+        let sink: Sink = match key.as_str() {
+            "json" => OutputSink::Json,
+            "csv" => OutputSink::Csv,
+            "markdown" => OutputSink::Markdown,
+            "gbench_json" => OutputSink::GoogleBenchmark,
+            "samples" => OutputSink::Samples,
+            "latex" => OutputSink::Latex,
+            _ => {
+                return Err(invalid(
+                    &format!("output.{}", key),
+                    "expected json, csv, markdown, gbench_json, samples or latex",
+                ))
+            }
+        };
+        let path = value
+            .as_str()
+            .ok_or_else(|| invalid(&format!("output.{}", key), "expected a path"))?;
+        sinks.push((sink, PathBuf::from(path)));
+    }
+    Ok(sinks)
+}
+
+/// `path` with `-suffix` appended to its stem.
+fn with_suffix(path: &Path, suffix: Option<&str>) -> PathBuf {
+    let Some(suffix) = suffix else {
+        return path.to_path_buf();
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, suffix, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name)
+}
+
+fn invalid(key: &str, msg: impl std::fmt::Display) -> BenchError {
+    BenchError::InvalidInput(format!("{}: {}", key, msg))
+}
+
+fn strings(table: &Table, key: &str) -> Result<Vec<String>, BenchError> {
+    match table.get(key) {
+        None => Ok(Vec::new()),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|v| v.as_str().map(str::to_string).ok_or_else(|| invalid(key, "expected strings")))
+            .collect(),
+        Some(_) => Err(invalid(key, "expected an array of strings")),
+    }
+}
+
+fn integers(table: &Table, key: &str) -> Result<Vec<usize>, BenchError> {
+    match table.get(key) {
+        None => Ok(Vec::new()),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|v| {
+                v.as_integer()
+                    .and_then(|i| usize::try_from(i).ok())
+                    .ok_or_else(|| invalid(key, "expected non-negative integers"))
+            })
+            .collect(),
+        Some(_) => Err(invalid(key, "expected an array of integers")),
+    }
+}
+
+fn usize_of(table: &Table, key: &str) -> Result<Option<usize>, BenchError> {
+    table
+        .get(key)
+        .map(|v| {
+            v.as_integer()
+                .and_then(|i| usize::try_from(i).ok())
+                .ok_or_else(|| invalid(key, "expected a non-negative integer"))
+        })
+        .transpose()
+}
+
+fn bool_of(table: &Table, key: &str) -> Result<Option<bool>, BenchError> {
+    table
+        .get(key)
+        .map(|v| v.as_bool().ok_or_else(|| invalid(key, "expected true or false")))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_campaign() {
+        let campaign = Campaign::parse(
+            r#"
+            schemes = ["nova"]
+            curves = ["bn254", "mnt"]
+            steps = [3, 10]
+            trials = 2
+            seed = 7
+            phases = ["fold"]
+
+            [output]
+            json = "out/results.json"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(campaign.filters, ["nova/bn254/*", "nova/mnt/*"]);
+        assert_eq!(campaign.runs.len(), 2);
+        let run = &campaign.runs[1];
+        assert_eq!((run.steps, run.trials, run.seed), (10, 2, Some(7)));
+        assert_eq!(run.phases, [Phase::Fold]);
+        assert_eq!(
+            run.sinks,
+            [OutputSink::Terminal, OutputSink::Json("out/results-10.json".into())]
+        );
+
+        let single = Campaign::parse("steps = 4\n[output]\ncsv = \"r.csv\"").unwrap();
+        assert_eq!(single.filters, ["*/*/*"]);
+        assert_eq!(single.runs[0].steps, 4);
+        assert_eq!(single.runs[0].sinks[1], OutputSink::Csv("r.csv".into()));
+
+        assert!(Campaign::parse("step = 4").is_err());
+        assert!(Campaign::parse("steps = [0]").is_err());
+        assert!(Campaign::parse("phases = [\"prove\"]").is_err());
+        assert!(Campaign::parse("[output]\nhtml = \"r.html\"").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Shape of a CCS (customizable constraint system) instance.
+//!
+//! HyperNova folds CCS rather than R1CS, and its prover cost follows the
+//! number and density of the matrices and the degree of the multisets more
+//! than the row count alone, so those are reported next to the timings.
+use ark_ff::PrimeField;
+use folding_schemes::arith::ccs::CCS;
+use std::fmt;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CcsStats {
+    /// Constraints, `m`.
+    pub rows: usize,
+    /// Witness and public input length, `n`.
+    pub columns: usize,
+    pub public_inputs: usize,
+    /// Matrices, `t`.
+    pub matrices: usize,
+    /// Multisets, `q`.
+    pub multisets: usize,
+    /// Maximum multiset size, `d`.
+    pub degree: usize,
+    /// Non-zero entries of each matrix.
+    pub nonzeros: Vec<usize>,
+}
+
+impl CcsStats {
+    pub fn new<F: PrimeField>(ccs: &CCS<F>) -> Self {
+        Self {
+            rows: ccs.m,
+            columns: ccs.n,
+            public_inputs: ccs.l,
+            matrices: ccs.t,
+            multisets: ccs.q,
+            degree: ccs.d,
+            nonzeros: ccs
+                .M
+                .iter()
+                .map(|m| m.coeffs.iter().map(Vec::len).sum())
+                .collect(),
+        }
+    }
+
+    pub fn total_nonzeros(&self) -> usize {
+        self.nonzeros.iter().sum()
+    }
+}
+
+impl fmt::Display for CcsStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} rows, {} columns, {} matrices, {} multisets, degree {}, {} non-zero entries {:?}",
+            self.rows,
+            self.columns,
+            self.matrices,
+            self.multisets,
+            self.degree,
+            self.total_nonzeros(),
+            self.nonzeros
+        )
+    }
+}
@@ -0,0 +1,358 @@
+//! AES-128 encryption chain, a boolean-heavy symmetric cipher unlike the hash
+//! chains.
+//!
+//! The state is a 128-bit block and the external input a 128-bit key, both
+//! little-endian in a field element (byte 0 of the block is the low byte).
+//! Each step expands the key and encrypts the state `k` times in a row.
+//!
+//! R1CS has no lookups, so an S-box is emulated as a multiplexer tree over
+//! the 256-entry table: the first level selects between constants for free,
+//! the other 127 selections cost one constraint each, and the output is split
+//! back into bits. XORs with variables cost one constraint per bit.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{boolean::Boolean, fields::fp::FpVar, R1CSVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::uint64::range_check_var;
+use super::utils::field_to_bytes_le;
+
+pub const BLOCK_BYTES: usize = 16;
+const ROUNDS: usize = 10;
+const RCON: [u8; ROUNDS] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Multiplication in `GF(2^8)` modulo `x^8 + x^4 + x^3 + x + 1`.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+fn xtime(a: u8) -> u8 {
+    (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// The AES S-box: the inverse in `GF(2^8)` (0 for 0) followed by the affine
+/// map.
+pub fn sbox() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    for (x, s) in table.iter_mut().enumerate() {
+        let inv = (1..=255u8).find(|&y| gf_mul(x as u8, y) == 1).unwrap_or(0);
+        *s = inv
+            ^ inv.rotate_left(1)
+            ^ inv.rotate_left(2)
+            ^ inv.rotate_left(3)
+            ^ inv.rotate_left(4)
+            ^ 0x63;
+    }
+    table
+}
+
+/// What the cipher needs of bytes and their variables.
+trait AesByte: Clone + Sized {
+    type Err;
+
+    fn constant(b: u8) -> Self;
+    fn xor(&self, other: &Self) -> Result<Self, Self::Err>;
+    /// Multiplication by `x` in `GF(2^8)`.
+    fn xtime(&self) -> Result<Self, Self::Err>;
+    fn sub_byte(&self, sbox: &[u8; 256]) -> Result<Self, Self::Err>;
+}
+
+impl AesByte for u8 {
+    type Err = std::convert::Infallible;
+
+    fn constant(b: u8) -> Self {
+        b
+    }
+
+    fn xor(&self, other: &Self) -> Result<Self, Self::Err> {
+        Ok(self ^ other)
+    }
+
+    fn xtime(&self) -> Result<Self, Self::Err> {
+        Ok(xtime(*self))
+    }
+
+    fn sub_byte(&self, sbox: &[u8; 256]) -> Result<Self, Self::Err> {
+        Ok(sbox[*self as usize])
+    }
+}
+
+/// A byte as 8 bits, least significant first.
+impl<F: PrimeField> AesByte for Vec<Boolean<F>> {
+    type Err = SynthesisError;
+
+    fn constant(b: u8) -> Self {
+        (0..8)
+            .map(|j| Boolean::constant((b >> j) & 1 == 1))
+            .collect()
+    }
+
+    fn xor(&self, other: &Self) -> Result<Self, Self::Err> {
+        self.iter().zip(other).map(|(a, b)| a.xor(b)).collect()
+    }
+
+    fn xtime(&self) -> Result<Self, Self::Err> {
+        // shift left, and add 0x1b = 0b00011011 if the top bit was set
+        (0..8)
+            .map(|j| match j {
+                0 => Ok(self[7].clone()),
+                1 | 3 | 4 => self[j - 1].xor(&self[7]),
+                _ => Ok(self[j - 1].clone()),
+            })
+            .collect()
+    }
+
+    fn sub_byte(&self, sbox: &[u8; 256]) -> Result<Self, Self::Err> {
+        let cs = self.cs();
+        if cs.is_none() {
+            let b = self
+                .value()?
+                .iter()
+                .rev()
+                .fold(0u8, |b, bit| (b << 1) | *bit as u8);
+            return Ok(Self::constant(sbox[b as usize]));
+        }
+        let bit = FpVar::from(self[0].clone());
+        let mut layer: Vec<FpVar<F>> = sbox
+            .chunks(2)
+            .map(|pair| {
+                let (f, t) = (F::from(pair[0]), F::from(pair[1]));
+                &bit * (t - f) + f
+            })
+            .collect();
+        for bit in &self[1..] {
+            let bit = FpVar::from(bit.clone());
+            layer = layer
+                .chunks(2)
+                .map(|pair| &pair[0] + &bit * (&pair[1] - &pair[0]))
+                .collect();
+        }
+        range_check_var(cs, &layer[0], 8)
+    }
+}
+
+/// The 11 round keys of `key`.
+fn expand_key<T: AesByte>(key: &[T], sbox: &[u8; 256]) -> Result<Vec<Vec<T>>, T::Err> {
+    let mut words: Vec<Vec<T>> = key.chunks(4).map(<[T]>::to_vec).collect();
+    for i in 4..4 * (ROUNDS + 1) {
+        let mut temp = words[i - 1].clone();
+        if i % 4 == 0 {
+            temp.rotate_left(1);
+            temp = temp
+                .iter()
+                .map(|b| b.sub_byte(sbox))
+                .collect::<Result<_, _>>()?;
+            temp[0] = temp[0].xor(&T::constant(RCON[i / 4 - 1]))?;
+        }
+        let word = words[i - 4]
+            .iter()
+            .zip(&temp)
+            .map(|(a, b)| a.xor(b))
+            .collect::<Result<_, _>>()?;
+        words.push(word);
+    }
+    Ok(words.chunks(4).map(|w| w.concat()).collect())
+}
+
+fn add_round_key<T: AesByte>(state: &[T], round_key: &[T]) -> Result<Vec<T>, T::Err> {
+    state.iter().zip(round_key).map(|(a, b)| a.xor(b)).collect()
+}
+
+/// Bytes are in column-major order: byte `r + 4c` is row `r` of column `c`.
+fn shift_rows<T: AesByte>(state: &[T]) -> Vec<T> {
+    (0..BLOCK_BYTES)
+        .map(|i| {
+            let (r, c) = (i % 4, i / 4);
+            state[r + 4 * ((c + r) % 4)].clone()
+        })
+        .collect()
+}
+
+fn mix_columns<T: AesByte>(state: &[T]) -> Result<Vec<T>, T::Err> {
+    let mut mixed = Vec::with_capacity(BLOCK_BYTES);
+    for column in state.chunks(4) {
+        let doubled = column
+            .iter()
+            .map(AesByte::xtime)
+            .collect::<Result<Vec<_>, _>>()?;
+        // row r: 2 * s_r + 3 * s_{r+1} + s_{r+2} + s_{r+3}
+        for r in 0..4 {
+            let next = (r + 1) % 4;
+            mixed.push(
+                doubled[r]
+                    .xor(&doubled[next])?
+                    .xor(&column[next])?
+                    .xor(&column[(r + 2) % 4])?
+                    .xor(&column[(r + 3) % 4])?,
+            );
+        }
+    }
+    Ok(mixed)
+}
+
+fn encrypt<T: AesByte>(
+    round_keys: &[Vec<T>],
+    block: &[T],
+    sbox: &[u8; 256],
+) -> Result<Vec<T>, T::Err> {
+    let mut state = add_round_key(block, &round_keys[0])?;
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        let substituted = state
+            .iter()
+            .map(|b| b.sub_byte(sbox))
+            .collect::<Result<Vec<_>, _>>()?;
+        state = shift_rows(&substituted);
+        if round < ROUNDS {
+            state = mix_columns(&state)?;
+        }
+        state = add_round_key(&state, round_key)?;
+    }
+    Ok(state)
+}
+
+/// AES-128 encryption of `block` under `key`.
+pub fn aes128_encrypt(key: &[u8; BLOCK_BYTES], block: &[u8; BLOCK_BYTES]) -> [u8; BLOCK_BYTES] {
+    let sbox = sbox();
+    let ciphertext =
+        expand_key(key, &sbox).and_then(|round_keys| encrypt(&round_keys, block, &sbox));
+    match ciphertext {
+        Ok(c) => c.try_into().unwrap(),
+        Err(never) => match never {},
+    }
+}
+
+fn to_block<F: PrimeField>(x: F) -> Option<Vec<u8>> {
+    let bytes = field_to_bytes_le(x);
+    bytes[BLOCK_BYTES..]
+        .iter()
+        .all(|b| *b == 0)
+        .then(|| bytes[..BLOCK_BYTES].to_vec())
+}
+
+/// The bytes of a 128-bit value.
+fn to_block_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    x: &FpVar<F>,
+) -> Result<Vec<Vec<Boolean<F>>>, SynthesisError> {
+    let bits = range_check_var(cs, x, 8 * BLOCK_BYTES)?;
+    Ok(bits.chunks(8).map(<[Boolean<F>]>::to_vec).collect())
+}
+
+#[derive(Clone, Debug)]
+pub struct AesFCircuit<F: PrimeField> {
+    /// Blocks encrypted per step.
+    pub n_blocks: usize,
+    pub sbox: [u8; 256],
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for AesFCircuit<F> {
+    type Params = usize;
+
+    fn new(n_blocks: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_blocks,
+            sbox: sbox(),
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        1
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let (Some(mut block), Some(key)) = (to_block(z_i[0]), to_block(external_inputs[0])) else {
+            return Err(Error::NotSatisfied);
+        };
+        let key: [u8; BLOCK_BYTES] = key.try_into().unwrap();
+        for _ in 0..self.n_blocks {
+            block = aes128_encrypt(&key, &block.try_into().unwrap()).to_vec();
+        }
+        Ok(vec![F::from_le_bytes_mod_order(&block)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut block = to_block_var(cs.clone(), &z_i[0])?;
+        let key = to_block_var(cs, &external_inputs[0])?;
+        let round_keys = expand_key(&key, &self.sbox)?;
+        for _ in 0..self.n_blocks {
+            block = encrypt(&round_keys, &block, &self.sbox)?;
+        }
+        Ok(vec![Boolean::le_bits_to_fp_var(&block.concat())?])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::alloc::AllocVar;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::Rng;
+
+    #[test]
+    fn test_aes_native_vs_gadget() {
+        // FIPS-197, appendix C.1
+        let key: [u8; BLOCK_BYTES] = core::array::from_fn(|i| i as u8);
+        let plaintext: [u8; BLOCK_BYTES] = core::array::from_fn(|i| 0x11 * i as u8);
+        let ciphertext = [
+            0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+            0xc5, 0x5a,
+        ];
+        assert_eq!(aes128_encrypt(&key, &plaintext), ciphertext);
+
+        let circuit = AesFCircuit::<Fr>::new(1).unwrap();
+        let z_i = vec![Fr::from_le_bytes_mod_order(&plaintext)];
+        let external_inputs = vec![Fr::from_le_bytes_mod_order(&key)];
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        assert_eq!(z_i1, vec![Fr::from_le_bytes_mod_order(&ciphertext)]);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_aes_consistent() {
+        let circuit = AesFCircuit::<Fr>::new(1).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let (block, key) = (rng.gen::<u128>(), rng.gen::<u128>());
+            (vec![Fr::from(block)], vec![Fr::from(key)])
+        });
+    }
+}
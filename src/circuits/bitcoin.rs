@@ -0,0 +1,269 @@
+//! Bitcoin header-chain verification: one block header per step.
+//!
+//! The state is `[hash_lo, hash_hi, height]`, where the two halves hold the 32
+//! bytes of the last block hash (in header byte order) as little-endian 128-bit
+//! integers. The rest of the header comes in as external inputs
+//! `[version, merkle_root_lo, merkle_root_hi, time, bits, nonce]`, each the
+//! little-endian integer of its bytes. A step checks that the header extends
+//! the state, computes its double SHA-256 and checks it against the target
+//! encoded in `bits`.
+//!
+//! Targets are assumed to have an exponent of at most 31 (true for every
+//! mainnet block), which keeps both the target and any valid hash below 2^248,
+//! so the comparison can be done in the native field.
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    uint8::UInt8,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::uint64::range_check_var;
+
+pub const HEADER_LEN: usize = 80;
+
+/// Widths in bits of the external inputs.
+const INPUT_BITS: [usize; 6] = [32, 128, 128, 32, 32, 32];
+
+const MAX_EXPONENT: u64 = 31;
+
+fn le_int<F: PrimeField>(bytes: &[u8]) -> F {
+    F::from_le_bytes_mod_order(bytes)
+}
+
+/// Splits a raw 80-byte header into the previous-hash state halves and the
+/// external inputs of `BitcoinHeaderFCircuit`.
+pub fn header_external_inputs<F: PrimeField>(header: &[u8; HEADER_LEN]) -> ((F, F), Vec<F>) {
+    (
+        (le_int(&header[4..20]), le_int(&header[20..36])),
+        vec![
+            le_int(&header[0..4]),
+            le_int(&header[36..52]),
+            le_int(&header[52..68]),
+            le_int(&header[68..72]),
+            le_int(&header[72..76]),
+            le_int(&header[76..80]),
+        ],
+    )
+}
+
+/// State `[hash_lo, hash_hi, height]` after the block with the given header.
+pub fn header_state<F: PrimeField>(header: &[u8; HEADER_LEN], height: u64) -> Vec<F> {
+    let hash = Sha256::digest(Sha256::digest(header));
+    vec![le_int(&hash[..16]), le_int(&hash[16..]), F::from(height)]
+}
+
+/// `mantissa * 256^(exponent - 3)` for the compact target encoding.
+fn target_from_bits(bits: u32) -> Option<([u8; 32], usize)> {
+    let exponent = (bits >> 24) as usize;
+    let mantissa = bits & 0x007fffff;
+    if !(3..=MAX_EXPONENT as usize).contains(&exponent) || bits & 0x00800000 != 0 {
+        return None;
+    }
+    let mut target = [0u8; 32];
+    target[exponent - 3..exponent].copy_from_slice(&mantissa.to_le_bytes()[..3]);
+    Some((target, exponent))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BitcoinHeaderFCircuit<F: PrimeField> {
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for BitcoinHeaderFCircuit<F> {
+    type Params = ();
+
+    fn new(_params: Self::Params) -> Result<Self, Error> {
+        Ok(Self { _f: PhantomData })
+    }
+
+    fn state_len(&self) -> usize {
+        3
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        INPUT_BITS.len()
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let to_bytes = |x: &F, len: usize| -> Result<Vec<u8>, Error> {
+            let mut bytes = x.into_bigint().to_bytes_le();
+            if bytes[len..].iter().any(|b| *b != 0) {
+                return Err(Error::NotSatisfied);
+            }
+            bytes.truncate(len);
+            Ok(bytes)
+        };
+        let mut header = to_bytes(&external_inputs[0], 4)?;
+        header.extend(to_bytes(&z_i[0], 16)?);
+        header.extend(to_bytes(&z_i[1], 16)?);
+        for (x, n_bits) in external_inputs[1..].iter().zip(&INPUT_BITS[1..]) {
+            header.extend(to_bytes(x, n_bits / 8)?);
+        }
+
+        let hash = Sha256::digest(Sha256::digest(&header));
+        let bits = u32::from_le_bytes(header[72..76].try_into().unwrap());
+        let (target, _) = target_from_bits(bits).ok_or(Error::NotSatisfied)?;
+        // little-endian 256-bit comparison
+        if hash.iter().rev().cmp(target.iter().rev()) == std::cmp::Ordering::Greater {
+            return Err(Error::NotSatisfied);
+        }
+
+        Ok(vec![
+            le_int(&hash[..16]),
+            le_int(&hash[16..]),
+            z_i[2] + F::one(),
+        ])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let inputs = external_inputs
+            .iter()
+            .zip(INPUT_BITS)
+            .map(|(x, n_bits)| range_check_var(cs.clone(), x, n_bits))
+            .collect::<Result<Vec<_>, _>>()?;
+        let prev_lo = range_check_var(cs.clone(), &z_i[0], 128)?;
+        let prev_hi = range_check_var(cs.clone(), &z_i[1], 128)?;
+
+        let header_bits = [
+            inputs[0].clone(),
+            prev_lo,
+            prev_hi,
+            inputs[1].clone(),
+            inputs[2].clone(),
+            inputs[3].clone(),
+            inputs[4].clone(),
+            inputs[5].clone(),
+        ]
+        .concat();
+        let header: Vec<UInt8<F>> = header_bits.chunks(8).map(UInt8::from_bits_le).collect();
+        let hash = Sha256Gadget::digest(&Sha256Gadget::digest(&header)?.0)?;
+        let hash_bits = hash.0.to_bits_le()?;
+
+        // target = mantissa * 256^(exponent - 3)
+        let n_bits = &inputs[4];
+        n_bits[23].enforce_equal(&Boolean::FALSE)?;
+        let mantissa = Boolean::le_bits_to_fp_var(&n_bits[..23])?;
+        let exponent = Boolean::le_bits_to_fp_var(&n_bits[24..])?;
+        let mut exponent_in_range = FpVar::zero();
+        let mut scale = FpVar::zero();
+        for k in 3..=MAX_EXPONENT {
+            let is_k = FpVar::from(exponent.is_eq(&FpVar::constant(F::from(k)))?);
+            exponent_in_range += &is_k;
+            scale += is_k * F::from(256_u64).pow([k - 3]);
+        }
+        exponent_in_range.enforce_equal(&FpVar::one())?;
+        let target = mantissa * scale;
+
+        // hash <= target
+        for bit in &hash_bits[248..] {
+            bit.enforce_equal(&Boolean::FALSE)?;
+        }
+        let hash_value = Boolean::le_bits_to_fp_var(&hash_bits[..248])?;
+        range_check_var(cs, &(target - hash_value), 248)?;
+
+        Ok(vec![
+            Boolean::le_bits_to_fp_var(&hash_bits[..128])?,
+            Boolean::le_bits_to_fp_var(&hash_bits[128..])?,
+            &z_i[2] + FpVar::one(),
+        ])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::Rng;
+
+    const GENESIS: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+    const BLOCK_1: &str = "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299";
+
+    fn header(hex: &str) -> [u8; HEADER_LEN] {
+        (0..HEADER_LEN)
+            .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_bitcoin_header_native_vs_gadget() {
+        let circuit = BitcoinHeaderFCircuit::<Fr>::new(()).unwrap();
+        let mut z = vec![Fr::from(0_u32); 3];
+        for (height, hex) in [GENESIS, BLOCK_1].iter().enumerate() {
+            let header = header(hex);
+            let (_, external_inputs) = header_external_inputs::<Fr>(&header);
+            let z_next = circuit
+                .step_native(height, z.clone(), external_inputs.clone())
+                .unwrap();
+            assert_eq!(z_next, header_state(&header, height as u64 + 1));
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z.clone())).unwrap();
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            let z_next_var = circuit
+                .generate_step_constraints(cs.clone(), height, z_var, external_inputs_var)
+                .unwrap();
+            assert_eq!(z_next_var.value().unwrap(), z_next);
+            assert!(cs.is_satisfied().unwrap());
+            z = z_next;
+        }
+    }
+
+    #[test]
+    fn test_bitcoin_header_rejects_wrong_parent() {
+        let circuit = BitcoinHeaderFCircuit::<Fr>::new(()).unwrap();
+        let (_, external_inputs) = header_external_inputs::<Fr>(&header(BLOCK_1));
+        assert!(circuit
+            .step_native(0, vec![Fr::from(0_u32); 3], external_inputs)
+            .is_err());
+    }
+
+    #[test]
+    fn test_bitcoin_header_consistent() {
+        let circuit = BitcoinHeaderFCircuit::<Fr>::new(()).unwrap();
+        // the easiest target, which about one nonce in 512 meets
+        let bits = ((MAX_EXPONENT as u32) << 24) | 0x007fffff;
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let mut header: [u8; HEADER_LEN] = core::array::from_fn(|_| rng.gen());
+            header[72..76].copy_from_slice(&bits.to_le_bytes());
+            let height = Fr::from(rng.gen::<u64>());
+            for nonce in 0_u32.. {
+                header[76..80].copy_from_slice(&nonce.to_le_bytes());
+                let ((lo, hi), external_inputs) = header_external_inputs::<Fr>(&header);
+                let z_i = vec![lo, hi, height];
+                if circuit
+                    .step_native(0, z_i.clone(), external_inputs.clone())
+                    .is_ok()
+                {
+                    return (z_i, external_inputs);
+                }
+            }
+            unreachable!("no nonce meets the target")
+        });
+    }
+}
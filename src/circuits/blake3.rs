@@ -0,0 +1,210 @@
+//! Blake3 hash chain: each step replaces the state with the (truncated) Blake3
+//! digest of its bytes, `k` times.
+//!
+//! A message of at most 64 bytes is a single chunk with a single block, so its
+//! Blake3 hash is exactly one call to the compression function (with the
+//! CHUNK_START | CHUNK_END | ROOT flags). That compression function is
+//! implemented here over `UInt32`s.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{fields::fp::FpVar, uint32::UInt32, uint8::UInt8, ToBitsGadget};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::utils::{bytes_to_field, bytes_var_to_field, field_to_bytes_le, field_var_to_bytes_le};
+
+const BLOCK_LEN: usize = 64;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const ROOT: u32 = 1 << 3;
+
+#[allow(clippy::too_many_arguments)]
+fn g<F: PrimeField>(
+    v: &mut [UInt32<F>],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+    mx: &UInt32<F>,
+    my: &UInt32<F>,
+) -> Result<(), SynthesisError> {
+    v[a] = UInt32::addmany(&[v[a].clone(), v[b].clone(), mx.clone()])?;
+    v[d] = v[d].xor(&v[a])?.rotr(16);
+    v[c] = UInt32::addmany(&[v[c].clone(), v[d].clone()])?;
+    v[b] = v[b].xor(&v[c])?.rotr(12);
+    v[a] = UInt32::addmany(&[v[a].clone(), v[b].clone(), my.clone()])?;
+    v[d] = v[d].xor(&v[a])?.rotr(8);
+    v[c] = UInt32::addmany(&[v[c].clone(), v[d].clone()])?;
+    v[b] = v[b].xor(&v[c])?.rotr(7);
+    Ok(())
+}
+
+/// Blake3 compression function, returning the 8-word chaining value.
+pub fn compress_gadget<F: PrimeField>(
+    cv: &[UInt32<F>],
+    block: &[UInt32<F>],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> Result<Vec<UInt32<F>>, SynthesisError> {
+    let mut v: Vec<UInt32<F>> = cv.to_vec();
+    v.extend(IV[..4].iter().map(|w| UInt32::constant(*w)));
+    v.push(UInt32::constant(counter as u32));
+    v.push(UInt32::constant((counter >> 32) as u32));
+    v.push(UInt32::constant(block_len));
+    v.push(UInt32::constant(flags));
+
+    let mut m = block.to_vec();
+    for round in 0..7 {
+        g(&mut v, 0, 4, 8, 12, &m[0], &m[1])?;
+        g(&mut v, 1, 5, 9, 13, &m[2], &m[3])?;
+        g(&mut v, 2, 6, 10, 14, &m[4], &m[5])?;
+        g(&mut v, 3, 7, 11, 15, &m[6], &m[7])?;
+        g(&mut v, 0, 5, 10, 15, &m[8], &m[9])?;
+        g(&mut v, 1, 6, 11, 12, &m[10], &m[11])?;
+        g(&mut v, 2, 7, 8, 13, &m[12], &m[13])?;
+        g(&mut v, 3, 4, 9, 14, &m[14], &m[15])?;
+        if round < 6 {
+            m = MSG_PERMUTATION.iter().map(|i| m[*i].clone()).collect();
+        }
+    }
+
+    (0..8).map(|i| v[i].xor(&v[i + 8])).collect()
+}
+
+/// Blake3 hash of a message that fits in a single block.
+pub fn blake3_gadget<F: PrimeField>(input: &[UInt8<F>]) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    assert!(input.len() <= BLOCK_LEN, "multi-block input is not supported");
+
+    let mut block = input.to_vec();
+    block.resize(BLOCK_LEN, UInt8::constant(0));
+    let block = block
+        .chunks(4)
+        .map(|word| {
+            let bits = word
+                .iter()
+                .map(|b| b.to_bits_le())
+                .collect::<Result<Vec<_>, _>>()?
+                .concat();
+            Ok(UInt32::from_bits_le(&bits))
+        })
+        .collect::<Result<Vec<_>, SynthesisError>>()?;
+    let cv: Vec<UInt32<F>> = IV.iter().map(|w| UInt32::constant(*w)).collect();
+
+    let out = compress_gadget(
+        &cv,
+        &block,
+        0,
+        input.len() as u32,
+        CHUNK_START | CHUNK_END | ROOT,
+    )?;
+    Ok(out
+        .iter()
+        .flat_map(|word| word.to_bits_le())
+        .collect::<Vec<_>>()
+        .chunks(8)
+        .map(UInt8::from_bits_le)
+        .collect())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Blake3FCircuit<F: PrimeField> {
+    /// Number of Blake3 compressions per step.
+    pub n_compressions: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for Blake3FCircuit<F> {
+    type Params = usize;
+
+    fn new(n_compressions: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_compressions,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let mut z = z_i[0];
+        for _ in 0..self.n_compressions {
+            let digest = ::blake3::hash(&field_to_bytes_le(z));
+            z = bytes_to_field(digest.as_bytes());
+        }
+        Ok(vec![z])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut z = z_i[0].clone();
+        for _ in 0..self.n_compressions {
+            let digest = blake3_gadget(&field_var_to_bytes_le(&z)?)?;
+            z = bytes_var_to_field(&digest)?;
+        }
+        Ok(vec![z])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_blake3_gadget() {
+        let msg = b"sonobe folding benchmarks".to_vec();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let msg_var = UInt8::new_witness_vec(cs.clone(), &msg).unwrap();
+        let digest = blake3_gadget(&msg_var).unwrap();
+        assert_eq!(digest.value().unwrap(), ::blake3::hash(&msg).as_bytes().to_vec());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_blake3_native_vs_gadget() {
+        let circuit = Blake3FCircuit::<Fr>::new(1).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_blake3_consistent() {
+        assert_fcircuit_consistent(&Blake3FCircuit::<Fr>::new(1).unwrap());
+    }
+}
@@ -0,0 +1,139 @@
+//! Several different step circuits behind a selector, the uniform-IVC
+//! emulation of non-uniform IVC (see `nivc`).
+//!
+//! Branch `j` iterates `t = t^2 + z + j` `branches[j]` times starting from
+//! `t = z`, so the branches differ in both size and constants. The state is
+//! `[z, s]`: every step runs branch `s` on `z`, and `s` cycles through the
+//! branches. All branches are synthesized at every step and the selected
+//! output is kept, which is what a uniform folding scheme pays for a program
+//! made of several circuits.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+#[derive(Clone, Debug)]
+pub struct BranchesFCircuit<F: PrimeField> {
+    /// Constraints of every branch.
+    pub branches: Vec<usize>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> BranchesFCircuit<F> {
+    /// Branch `j` on `z`, natively.
+    pub fn branch_native(&self, j: usize, z: F) -> F {
+        let c = z + F::from(j as u64);
+        (0..self.branches[j]).fold(z, |t, _| t.square() + c)
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for BranchesFCircuit<F> {
+    type Params = Vec<usize>;
+
+    fn new(branches: Self::Params) -> Result<Self, Error> {
+        if branches.is_empty() {
+            return Err(Error::NotSatisfied);
+        }
+        Ok(Self {
+            branches,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        2
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let j = (0..self.branches.len())
+            .find(|j| F::from(*j as u64) == z_i[1])
+            .ok_or(Error::NotSatisfied)?;
+        let next = (j + 1) % self.branches.len();
+        Ok(vec![self.branch_native(j, z_i[0]), F::from(next as u64)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let (z, s) = (&z_i[0], &z_i[1]);
+        let selectors = (0..self.branches.len())
+            .map(|j| Ok(FpVar::from(s.is_eq(&FpVar::constant(F::from(j as u64)))?)))
+            .collect::<Result<Vec<FpVar<F>>, SynthesisError>>()?;
+        // `s` must name a branch
+        selectors
+            .iter()
+            .fold(FpVar::zero(), |acc, b| acc + b)
+            .enforce_equal(&FpVar::one())?;
+
+        let mut next_z = FpVar::zero();
+        let mut next_s = FpVar::zero();
+        for (j, (n, b)) in self.branches.iter().zip(&selectors).enumerate() {
+            let c = z + F::from(j as u64);
+            let t = (0..*n).fold(z.clone(), |t, _| &t * &t + &c);
+            next_z += b * t;
+            next_s += b * F::from(((j + 1) % self.branches.len()) as u64);
+        }
+        Ok(vec![next_z, next_s])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::Rng;
+
+    #[test]
+    fn test_branches_cycle() {
+        let circuit = BranchesFCircuit::<Fr>::new(vec![3, 10, 5]).unwrap();
+        let mut z = vec![Fr::from(3_u32), Fr::from(0_u32)];
+        for i in 0..4 {
+            let z_next = circuit.step_native(i, z.clone(), vec![]).unwrap();
+            assert_eq!(z_next[0], circuit.branch_native(i % 3, z[0]));
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z.clone())).unwrap();
+            let z_next_var = circuit
+                .generate_step_constraints(cs.clone(), i, z_var, vec![])
+                .unwrap();
+            assert_eq!(z_next_var.value().unwrap(), z_next);
+            assert!(cs.is_satisfied().unwrap());
+            z = z_next;
+        }
+        assert_eq!(z[1], Fr::from(1_u32));
+
+        let bad = vec![Fr::from(3_u32), Fr::from(3_u32)];
+        assert!(circuit.step_native(0, bad, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_branches_consistent() {
+        let circuit = BranchesFCircuit::<Fr>::new(vec![3, 10, 5]).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let branch = Fr::from(rng.gen_range(0..3_u64));
+            (vec![Fr::rand(rng), branch], vec![])
+        });
+    }
+}
@@ -0,0 +1,74 @@
+//! Cached loading of Circom artifacts.
+//!
+//! Parsing a Circom `.r1cs` file dominates `CircomFCircuit::new`, and doing it
+//! inside a benchmark pollutes the folding timings. `load_circom` keeps one
+//! parsed circuit per artifact pair (keyed by the SHA-256 of the `.r1cs` and
+//! `.wasm` contents, so edited artifacts are reloaded), shared by every
+//! configuration of the process, and reports how long the load took.
+use ark_ff::PrimeField;
+use sha2::{Digest, Sha256};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use folding_schemes::{frontend::FCircuit, Error};
+use frontends::circom::CircomFCircuit;
+
+type CacheKey = (TypeId, [u8; 32]);
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, Box<dyn Any + Send>>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, Box<dyn Any + Send>>>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+#[derive(Clone, Debug)]
+pub struct LoadedCircom<F: PrimeField> {
+    pub circuit: CircomFCircuit<F>,
+    /// Time spent hashing the artifacts and, on a cache miss, parsing them.
+    pub load_time: Duration,
+    pub cache_hit: bool,
+}
+
+/// Loads a Circom circuit, reusing the parsed artifacts when already loaded.
+pub fn load_circom<F: PrimeField>(
+    r1cs_path: &Path,
+    wasm_path: &Path,
+    state_len: usize,
+    external_inputs_len: usize,
+) -> Result<LoadedCircom<F>, Error> {
+    let start = Instant::now();
+    let mut hasher = Sha256::new();
+    hasher.update(fs::read(r1cs_path)?);
+    hasher.update(fs::read(wasm_path)?);
+    hasher.update(state_len.to_le_bytes());
+    hasher.update(external_inputs_len.to_le_bytes());
+    let key = (TypeId::of::<F>(), hasher.finalize().into());
+
+    let mut cache = cache().lock().unwrap();
+    if let Some(circuit) = cache
+        .get(&key)
+        .and_then(|c| c.downcast_ref::<CircomFCircuit<F>>())
+    {
+        return Ok(LoadedCircom {
+            circuit: circuit.clone(),
+            load_time: start.elapsed(),
+            cache_hit: true,
+        });
+    }
+
+    let circuit = CircomFCircuit::<F>::new((
+        r1cs_path.into(),
+        wasm_path.into(),
+        state_len,
+        external_inputs_len,
+    ))?;
+    cache.insert(key, Box::new(circuit.clone()));
+    Ok(LoadedCircom {
+        circuit,
+        load_time: start.elapsed(),
+        cache_hit: false,
+    })
+}
@@ -0,0 +1,165 @@
+//! `FCircuit` built from closures, for one-off experiments that don't warrant
+//! implementing the trait.
+//!
+//! Only the gadget step is required: without a native closure, `step_native`
+//! runs the gadget on a scratch constraint system and reads the output values.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+use ark_relations::r1cs::{ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use std::fmt;
+use std::sync::Arc;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub type NativeStep<F> = Arc<dyn Fn(usize, Vec<F>, Vec<F>) -> Result<Vec<F>, Error> + Send + Sync>;
+pub type GadgetStep<F> = Arc<
+    dyn Fn(
+            ConstraintSystemRef<F>,
+            usize,
+            Vec<FpVar<F>>,
+            Vec<FpVar<F>>,
+        ) -> Result<Vec<FpVar<F>>, SynthesisError>
+        + Send
+        + Sync,
+>;
+
+#[derive(Clone)]
+pub struct ClosureParams<F: PrimeField> {
+    pub state_len: usize,
+    pub external_inputs_len: usize,
+    pub native: Option<NativeStep<F>>,
+    pub gadget: GadgetStep<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClosureFCircuit<F: PrimeField> {
+    params: ClosureParams<F>,
+}
+
+impl<F: PrimeField> ClosureFCircuit<F> {
+    /// Circuit with the native step derived from the gadget.
+    pub fn from_gadget(
+        state_len: usize,
+        external_inputs_len: usize,
+        gadget: impl Fn(
+                ConstraintSystemRef<F>,
+                usize,
+                Vec<FpVar<F>>,
+                Vec<FpVar<F>>,
+            ) -> Result<Vec<FpVar<F>>, SynthesisError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        Self {
+            params: ClosureParams {
+                state_len,
+                external_inputs_len,
+                native: None,
+                gadget: Arc::new(gadget),
+            },
+        }
+    }
+
+    /// Sets an explicit native step, avoiding the scratch synthesis.
+    pub fn with_native(
+        mut self,
+        native: impl Fn(usize, Vec<F>, Vec<F>) -> Result<Vec<F>, Error> + Send + Sync + 'static,
+    ) -> Self {
+        self.params.native = Some(Arc::new(native));
+        self
+    }
+}
+
+impl<F: PrimeField> fmt::Debug for ClosureParams<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClosureParams")
+            .field("state_len", &self.state_len)
+            .field("external_inputs_len", &self.external_inputs_len)
+            .field("native", &self.native.is_some())
+            .finish()
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for ClosureFCircuit<F> {
+    type Params = ClosureParams<F>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self { params })
+    }
+
+    fn state_len(&self) -> usize {
+        self.params.state_len
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.params.external_inputs_len
+    }
+
+    fn step_native(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        if let Some(native) = &self.params.native {
+            return native(i, z_i, external_inputs);
+        }
+        let cs = ConstraintSystem::<F>::new_ref();
+        let z_i = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(z_i))?;
+        let external_inputs = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(external_inputs))?;
+        let z_i1 = (self.params.gadget)(cs, i, z_i, external_inputs)?;
+        Ok(z_i1.value()?)
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        (self.params.gadget)(cs, i, z_i, external_inputs)
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::CubicFCircuit;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_closure_matches_cubic() {
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        let closure = ClosureFCircuit::<Fr>::from_gadget(1, 0, |_cs, _i, z_i, _| {
+            let z = &z_i[0];
+            Ok(vec![z * z * z + z + FpVar::Constant(Fr::from(5_u32))])
+        });
+        let z_i = vec![Fr::from(3_u32)];
+        assert_eq!(
+            closure.step_native(0, z_i.clone(), vec![]).unwrap(),
+            cubic.step_native(0, z_i.clone(), vec![]).unwrap()
+        );
+
+        let closure = closure.with_native(|_i, z_i, _| Ok(vec![z_i[0] + Fr::from(1_u32)]));
+        assert_eq!(
+            closure.step_native(0, z_i, vec![]).unwrap(),
+            vec![Fr::from(4_u32)]
+        );
+    }
+
+    #[test]
+    fn test_closure_consistent() {
+        let closure = ClosureFCircuit::<Fr>::from_gadget(1, 0, |_cs, _i, z_i, _| {
+            let z = &z_i[0];
+            Ok(vec![z * z * z + z + FpVar::Constant(Fr::from(5_u32))])
+        })
+        .with_native(|_i, z_i, _| {
+            let z = z_i[0];
+            Ok(vec![z * z * z + z + Fr::from(5_u32)])
+        });
+        assert_fcircuit_consistent(&closure);
+    }
+}
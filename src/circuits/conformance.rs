@@ -0,0 +1,130 @@
+//! Conformance of a step circuit's gadget with its `step_native`.
+//!
+//! Folding only ever checks the gadget, while the benchmarks compare the
+//! folded state with `step_native`, so a circuit whose two halves disagree
+//! folds fine and then fails with a state mismatch or a rejected proof far
+//! from the step at fault. `assert_fcircuit_consistent` checks a circuit
+//! step by step on random states instead, before it is benchmarked: the
+//! library circuits run it in their tests, except `circom`'s, which needs
+//! compiled artifacts, and so should contributed ones.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+use ark_relations::r1cs::{ConstraintSystem, SynthesisError};
+use rand_chacha::ChaCha20Rng;
+
+use folding_schemes::frontend::FCircuit;
+
+use crate::config::rng;
+
+/// Steps checked by `assert_fcircuit_consistent`.
+pub const CASES: usize = 8;
+
+/// Synthesizes step `i` of `f_circuit` from `z_i` and `external_inputs`,
+/// both allocated as witnesses, on a fresh `ConstraintSystem`. Returns the
+/// values of the output state and the first unsatisfied constraint, if any.
+pub fn synthesize_step<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+    i: usize,
+    z_i: Vec<F>,
+    external_inputs: Vec<F>,
+) -> Result<(Vec<F>, Option<String>), SynthesisError> {
+    let cs = ConstraintSystem::<F>::new_ref();
+    let z_var = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(z_i))?;
+    let inputs_var = Vec::<FpVar<F>>::new_witness(cs.clone(), || Ok(external_inputs))?;
+    let next = f_circuit.generate_step_constraints(cs.clone(), i, z_var, inputs_var)?;
+    Ok((next.value()?, cs.which_is_unsatisfied()?))
+}
+
+/// `assert_fcircuit_consistent_with` uniformly random states and external
+/// inputs, for circuits defined on any field elements.
+pub fn assert_fcircuit_consistent<F: PrimeField, FC: FCircuit<F>>(f_circuit: &FC) {
+    let (state_len, inputs_len) = (f_circuit.state_len(), f_circuit.external_inputs_len());
+    assert_fcircuit_consistent_with(f_circuit, |rng| {
+        (
+            (0..state_len).map(|_| F::rand(rng)).collect(),
+            (0..inputs_len).map(|_| F::rand(rng)).collect(),
+        )
+    });
+}
+
+/// Checks step `i` of `f_circuit` from `z_i` with `external_inputs`: where
+/// `step_native` accepts the step, the gadget must be satisfied and output
+/// the same state, and where it rejects the step, the gadget must not be
+/// satisfied either, or fail to synthesize, as it may without a witness.
+/// Errs with what differs.
+pub fn check_step<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+    i: usize,
+    z_i: &[F],
+    external_inputs: &[F],
+) -> Result<(), String> {
+    let native = f_circuit.step_native(i, z_i.to_vec(), external_inputs.to_vec());
+    let gadget = synthesize_step(f_circuit, i, z_i.to_vec(), external_inputs.to_vec());
+    let differs = match (native, gadget) {
+        (Ok(native), Ok((next, None))) if next == native => return Ok(()),
+        (Ok(native), Ok((next, None))) => {
+            format!("gadget and native outputs differ: {:?}, {:?}", next, native)
+        }
+        (Ok(_), Ok((_, Some(constraint)))) => format!(
+            "constraint {} unsatisfied, but step_native accepts the step",
+            constraint
+        ),
+        (Ok(_), Err(e)) => format!("synthesis failed: {}", e),
+        (Err(_), Ok((_, None))) => "gadget satisfied, but step_native rejects the step".into(),
+        (Err(_), _) => return Ok(()),
+    };
+    Err(format!(
+        "step {} from {:?} with {:?}: {}",
+        i, z_i, external_inputs, differs
+    ))
+}
+
+/// `check_step` on `CASES` steps of `f_circuit`, on states and external
+/// inputs drawn by `sample`. Panics with the step at fault.
+pub fn assert_fcircuit_consistent_with<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+    mut sample: impl FnMut(&mut ChaCha20Rng) -> (Vec<F>, Vec<F>),
+) {
+    let mut rng = rng();
+    for i in 0..CASES {
+        let (z_i, external_inputs) = sample(&mut rng);
+        assert_eq!(z_i.len(), f_circuit.state_len(), "state length");
+        assert_eq!(
+            external_inputs.len(),
+            f_circuit.external_inputs_len(),
+            "external inputs length"
+        );
+        check_step(f_circuit, i, &z_i, &external_inputs).unwrap_or_else(|e| panic!("{}", e));
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::closure::ClosureFCircuit;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::eq::EqGadget;
+
+    fn doubling() -> ClosureFCircuit<Fr> {
+        ClosureFCircuit::<Fr>::from_gadget(1, 0, |_cs, _i, z_i, _| Ok(vec![&z_i[0] + &z_i[0]]))
+    }
+
+    #[test]
+    fn test_consistent_circuit() {
+        assert_fcircuit_consistent(&doubling());
+        // rejected by both halves
+        let never = ClosureFCircuit::<Fr>::from_gadget(1, 0, |_cs, _i, z_i, _| {
+            z_i[0].enforce_equal(&(&z_i[0] + Fr::from(1_u32)))?;
+            Ok(z_i)
+        })
+        .with_native(|_i, _z_i, _| Err(folding_schemes::Error::NotSatisfied));
+        assert_fcircuit_consistent(&never);
+    }
+
+    #[test]
+    #[should_panic(expected = "gadget and native outputs differ")]
+    fn test_inconsistent_circuit() {
+        let tripling = doubling().with_native(|_i, z_i, _| Ok(vec![z_i[0] * Fr::from(3_u32)]));
+        assert_fcircuit_consistent(&tripling);
+    }
+}
@@ -0,0 +1,143 @@
+//! Fixed-point 2D convolution (zkML image processing).
+//!
+//! Each step convolves a constant `k x k` kernel over a `t x t` tile of pixels
+//! supplied as external inputs, row by row, without padding, rescales every
+//! output back to the fixed-point scale, and accumulates the
+//! `(t - k + 1) x (t - k + 1)` feature map into the state, one frame per step.
+//! Values are unsigned fixed point with `frac_bits` fractional bits, as in
+//! `matmul`.
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::matmul::{fixed_point_weights, rescale, rescale_var};
+
+#[derive(Clone, Debug)]
+pub struct ConvFCircuit<F: PrimeField> {
+    /// Side of the input tile.
+    pub tile: usize,
+    /// Side of the kernel.
+    pub k: usize,
+    pub frac_bits: usize,
+    pub kernel: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> ConvFCircuit<F> {
+    fn out_side(&self) -> usize {
+        self.tile - self.k + 1
+    }
+
+    /// The pixels of `tile` under the kernel placed at output `(row, col)`,
+    /// with their weights.
+    fn window<'a, T>(
+        &'a self,
+        tile: &'a [T],
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (&'a F, &'a T)> {
+        (0..self.k).flat_map(move |dr| {
+            (0..self.k).map(move |dc| {
+                (
+                    &self.kernel[dr][dc],
+                    &tile[(row + dr) * self.tile + col + dc],
+                )
+            })
+        })
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for ConvFCircuit<F> {
+    /// Tile side, kernel side and fractional bits.
+    type Params = (usize, usize, usize);
+
+    fn new((tile, k, frac_bits): Self::Params) -> Result<Self, Error> {
+        assert!(0 < k && k <= tile, "the kernel must fit in the tile");
+        Ok(Self {
+            tile,
+            k,
+            frac_bits,
+            kernel: fixed_point_weights(k, k, frac_bits),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.out_side() * self.out_side()
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.tile * self.tile
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let out = self.out_side();
+        Ok(z_i
+            .into_iter()
+            .enumerate()
+            .map(|(j, z)| {
+                let dot = self
+                    .window(&external_inputs, j / out, j % out)
+                    .fold(F::zero(), |acc, (w, x)| acc + *w * x);
+                z + rescale(dot, self.frac_bits)
+            })
+            .collect())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let out = self.out_side();
+        z_i.into_iter()
+            .enumerate()
+            .map(|(j, z)| {
+                let dot = self
+                    .window(&external_inputs, j / out, j % out)
+                    .fold(FpVar::zero(), |acc, (w, x)| acc + x * *w);
+                Ok(z + rescale_var(&dot, self.frac_bits)?)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_conv_native_vs_gadget() {
+        let frac_bits = 8;
+        let circuit = ConvFCircuit::<Fr>::new((5, 3, frac_bits)).unwrap();
+        assert_eq!(circuit.state_len(), 9);
+        let z_i = vec![Fr::from(0_u32); 9];
+        let x: Vec<Fr> = (1..=25_u32).map(|v| Fr::from(v << frac_bits)).collect();
+        let z_i1 = circuit.step_native(0, z_i.clone(), x.clone()).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let x_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(x)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, x_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_conv_consistent() {
+        assert_fcircuit_consistent(&ConvFCircuit::<Fr>::new((5, 3, 8)).unwrap());
+    }
+}
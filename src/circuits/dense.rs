@@ -0,0 +1,180 @@
+//! Dense layer followed by a ReLU (zkML inference step).
+//!
+//! The state is the `width`-element activation vector; each step computes
+//! `relu(W * z + b)` with a constant square weight matrix and bias. Values are
+//! signed fixed point with `frac_bits` fractional bits, where negative numbers
+//! are represented as `p - |v|`. The ReLU is a range-check-based sign test,
+//! valid as long as every pre-activation satisfies `|v| < 2^range_bits`.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar, fields::FieldVar,
+    select::CondSelectGadget, R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::matmul::{rescale, rescale_var};
+
+/// `max(v, 0)` for `|v| < 2^range_bits`, erroring when `v` is out of range.
+pub fn relu<F: PrimeField>(v: F, range_bits: usize) -> Result<F, Error> {
+    let shifted = (v + F::from(2_u64).pow([range_bits as u64])).into_bigint();
+    if shifted.num_bits() as usize > range_bits + 1 {
+        return Err(Error::NotSatisfied);
+    }
+    Ok(if shifted.get_bit(range_bits) {
+        v
+    } else {
+        F::zero()
+    })
+}
+
+/// Gadget version of `relu`: `v + 2^range_bits` is decomposed into
+/// `range_bits + 1` bits, whose top bit is set exactly when `v >= 0`.
+pub fn relu_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    v: &FpVar<F>,
+    range_bits: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    let shifted = v + F::from(2_u64).pow([range_bits as u64]);
+    let bits = (0..=range_bits)
+        .map(|j| {
+            Boolean::new_witness(cs.clone(), || {
+                Ok(shifted.value()?.into_bigint().get_bit(j))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(&shifted)?;
+    FpVar::conditionally_select(&bits[range_bits], v, &FpVar::zero())
+}
+
+#[derive(Clone, Debug)]
+pub struct DenseReluFCircuit<F: PrimeField> {
+    pub width: usize,
+    pub frac_bits: usize,
+    pub range_bits: usize,
+    pub weights: Vec<Vec<F>>,
+    /// Bias at the scale of the products, `2^(2 * frac_bits)`.
+    pub bias: Vec<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for DenseReluFCircuit<F> {
+    /// Layer width, fractional bits and ReLU range bits.
+    type Params = (usize, usize, usize);
+
+    fn new((width, frac_bits, range_bits): Self::Params) -> Result<Self, Error> {
+        // deterministic weights in (-1, 1) and biases in (-1/4, 1/4)
+        let mut rng = StdRng::seed_from_u64(0);
+        let one = 1_i64 << frac_bits;
+        let weights = (0..width)
+            .map(|_| (0..width).map(|_| F::from(rng.gen_range(-one + 1..one))).collect())
+            .collect();
+        let bias = (0..width)
+            .map(|_| F::from(rng.gen_range(-one / 4 + 1..one / 4) << frac_bits))
+            .collect();
+        Ok(Self {
+            width,
+            frac_bits,
+            range_bits,
+            weights,
+            bias,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.width
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        self.weights
+            .iter()
+            .zip(&self.bias)
+            .map(|(row, b)| {
+                let pre = row
+                    .iter()
+                    .zip(&z_i)
+                    .fold(*b, |acc, (w, z)| acc + *w * z);
+                // ReLU first, so that only non-negative values get rescaled
+                Ok(rescale(relu(pre, self.range_bits)?, self.frac_bits))
+            })
+            .collect()
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.weights
+            .iter()
+            .zip(&self.bias)
+            .map(|(row, b)| {
+                let pre = row
+                    .iter()
+                    .zip(&z_i)
+                    .fold(FpVar::constant(*b), |acc, (w, z)| acc + z * *w);
+                rescale_var(&relu_var(cs.clone(), &pre, self.range_bits)?, self.frac_bits)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_relu() {
+        assert_eq!(relu(Fr::from(5_i64), 16).unwrap(), Fr::from(5_u32));
+        assert_eq!(relu(Fr::from(-5_i64), 16).unwrap(), Fr::from(0_u32));
+        assert!(relu(Fr::from(1_i64 << 20), 16).is_err());
+    }
+
+    #[test]
+    fn test_dense_relu_native_vs_gadget() {
+        let frac_bits = 8;
+        let circuit = DenseReluFCircuit::<Fr>::new((4, frac_bits, 32)).unwrap();
+        let z_i: Vec<Fr> = [3_i64, -2, 1, 0]
+            .iter()
+            .map(|v| Fr::from(v << frac_bits))
+            .collect();
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_dense_relu_consistent() {
+        let frac_bits = 8;
+        let circuit = DenseReluFCircuit::<Fr>::new((4, frac_bits, 32)).unwrap();
+        // activations in (-4, 4), whose pre-activations stay far below 2^32
+        let bound = 4_i64 << frac_bits;
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let z_i = (0..4)
+                .map(|_| Fr::from(rng.gen_range(-bound + 1..bound)))
+                .collect();
+            (z_i, vec![])
+        });
+    }
+}
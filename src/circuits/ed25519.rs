@@ -0,0 +1,655 @@
+//! Ed25519 signature verification (RFC 8032) with emulated field arithmetic,
+//! for data signed outside the folding world: Solana transactions, SSH keys,
+//! TLS transcripts.
+//!
+//! Each step verifies one signature. The external inputs are the standard
+//! encodings `[A[0..32], R[0..32], S[0..32], m[0..msg_len]]`, one byte per
+//! element, so existing signatures can be fed as they are. The points are
+//! decompressed by witnessing their `x` coordinates, `k = SHA-512(R || A || m)`
+//! is computed in-circuit and reduced modulo the group order `l` with the
+//! big-integer gadget of `rsa`, and the cofactored equation
+//! `[8]([S]B - R - [k]A) = 0` is checked, which accepts the same signatures as
+//! ZIP-215 validation except for non-canonical point encodings. The public
+//! key and the message are absorbed into the state:
+//! `z_{i+1} = Poseidon(z_i, A, m)`, 16 bytes per element.
+//!
+//! Curve25519's base field is emulated with `NonNativeFieldVar`: every point
+//! addition costs a handful of emulated multiplications and inversions, and a
+//! signature hundreds of additions, so this is by far the heaviest circuit of
+//! the library.
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ec::{twisted_edwards::TECurveConfig, AffineRepr, CurveGroup, Group};
+use ark_ed25519::{EdwardsAffine, EdwardsConfig, EdwardsProjective, Fq, Fr};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, nonnative::NonNativeFieldVar, FieldVar},
+    select::CondSelectGadget,
+    uint8::UInt8,
+    R1CSVar, ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use num_bigint::BigUint;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use super::rlp::{pack, to_u64};
+use super::rsa::{modmul_var, to_limbs, LIMBS, LIMB_BITS};
+use super::uint64::range_check_var;
+
+const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+const H0: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+type Word<F> = Vec<Boolean<F>>;
+
+fn rotr<F: PrimeField>(w: &Word<F>, n: usize) -> Word<F> {
+    (0..64).map(|j| w[(j + n) % 64].clone()).collect()
+}
+
+fn shr<F: PrimeField>(w: &Word<F>, n: usize) -> Word<F> {
+    (0..64)
+        .map(|j| w.get(j + n).cloned().unwrap_or(Boolean::FALSE))
+        .collect()
+}
+
+fn xor3<F: PrimeField>(a: &Word<F>, b: &Word<F>, c: &Word<F>) -> Result<Word<F>, SynthesisError> {
+    a.iter()
+        .zip(b)
+        .zip(c)
+        .map(|((a, b), c)| a.xor(b)?.xor(c))
+        .collect()
+}
+
+fn constant_word<F: PrimeField>(x: u64) -> Word<F> {
+    (0..64)
+        .map(|j| Boolean::constant((x >> j) & 1 == 1))
+        .collect()
+}
+
+/// Sum of up to 8 `words` modulo `2^64`.
+fn add<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    words: &[&Word<F>],
+) -> Result<Word<F>, SynthesisError> {
+    let mut sum = FpVar::zero();
+    for w in words {
+        sum += Boolean::le_bits_to_fp_var(w)?;
+    }
+    let mut bits = range_check_var(cs, &sum, 64 + 3)?;
+    bits.truncate(64);
+    Ok(bits)
+}
+
+fn sha512_compress<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    h: &mut [Word<F>],
+    block: Vec<Word<F>>,
+) -> Result<(), SynthesisError> {
+    let mut w = block;
+    for t in 16..80 {
+        let s0 = xor3(
+            &rotr(&w[t - 15], 1),
+            &rotr(&w[t - 15], 8),
+            &shr(&w[t - 15], 7),
+        )?;
+        let s1 = xor3(
+            &rotr(&w[t - 2], 19),
+            &rotr(&w[t - 2], 61),
+            &shr(&w[t - 2], 6),
+        )?;
+        w.push(add(cs.clone(), &[&s1, &w[t - 7], &s0, &w[t - 16]])?);
+    }
+
+    // the working variables `a..h`
+    let mut v = h.to_vec();
+    for (t, w_t) in w.iter().enumerate() {
+        let s1 = xor3(&rotr(&v[4], 14), &rotr(&v[4], 18), &rotr(&v[4], 41))?;
+        let ch = v[4]
+            .iter()
+            .zip(&v[5])
+            .zip(&v[6])
+            .map(|((e, f), g)| e.and(&f.xor(g)?)?.xor(g))
+            .collect::<Result<Word<F>, _>>()?;
+        let s0 = xor3(&rotr(&v[0], 28), &rotr(&v[0], 34), &rotr(&v[0], 39))?;
+        let maj = v[0]
+            .iter()
+            .zip(&v[1])
+            .zip(&v[2])
+            .map(|((a, b), c)| a.and(&b.xor(c)?)?.xor(&b.and(c)?))
+            .collect::<Result<Word<F>, _>>()?;
+        let k = constant_word(K[t]);
+        let t1 = [&v[7], &s1, &ch, &k, w_t];
+        let e = add(cs.clone(), &[t1.as_slice(), &[&v[3]]].concat())?;
+        let a = add(cs.clone(), &[t1.as_slice(), &[&s0, &maj]].concat())?;
+        v.rotate_right(1);
+        v[0] = a;
+        v[4] = e;
+    }
+    for (h_i, v_i) in h.iter_mut().zip(&v) {
+        *h_i = add(cs.clone(), &[&*h_i, v_i])?;
+    }
+    Ok(())
+}
+
+/// SHA-512 of `input`.
+pub fn sha512_gadget<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    input: &[UInt8<F>],
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    let mut padded = input.to_vec();
+    padded.push(UInt8::constant(0x80));
+    while padded.len() % 128 != 112 {
+        padded.push(UInt8::constant(0));
+    }
+    padded.extend((8 * input.len() as u128).to_be_bytes().map(UInt8::constant));
+
+    let mut h: Vec<Word<F>> = H0.iter().map(|x| constant_word(*x)).collect();
+    for block in padded.chunks(128) {
+        // big-endian words
+        let words = block
+            .chunks(8)
+            .map(|bytes| {
+                Ok(bytes
+                    .iter()
+                    .rev()
+                    .map(|b| b.to_bits_le())
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat())
+            })
+            .collect::<Result<Vec<Word<F>>, SynthesisError>>()?;
+        sha512_compress(cs.clone(), &mut h, words)?;
+    }
+    Ok(h.iter()
+        .flat_map(|w| w.chunks(8).rev().map(UInt8::from_bits_le))
+        .collect())
+}
+
+/// The point encoded by `bytes` as in RFC 8032: `y` little-endian, with the
+/// parity of `x` in the top bit. Non-canonical `y` is rejected and the
+/// subgroup is not checked.
+pub fn decode_point(bytes: &[u8]) -> Option<EdwardsAffine> {
+    let mut y_bytes = bytes.to_vec();
+    let odd = y_bytes[31] >> 7 == 1;
+    y_bytes[31] &= 0x7f;
+    let y = Fq::from_le_bytes_mod_order(&y_bytes);
+    if y.into_bigint().to_bytes_le() != y_bytes {
+        return None;
+    }
+    let y2 = y.square();
+    let mut x = ((y2 - Fq::ONE) / (EdwardsConfig::COEFF_D * y2 + Fq::ONE)).sqrt()?;
+    if x.into_bigint().is_odd() != odd {
+        x = -x;
+    }
+    (x.into_bigint().is_odd() == odd).then(|| EdwardsAffine::new_unchecked(x, y))
+}
+
+pub fn encode_point(p: &EdwardsAffine) -> [u8; 32] {
+    let mut bytes: [u8; 32] = p.y.into_bigint().to_bytes_le().try_into().unwrap();
+    bytes[31] |= (p.x.into_bigint().is_odd() as u8) << 7;
+    bytes
+}
+
+/// Checks the signature `sig = R || S` on `m` under the encoded public key
+/// `pk`, with the cofactored equation.
+pub fn verify(pk: &[u8], sig: &[u8], m: &[u8]) -> bool {
+    let (Some(a), Some(r)) = (decode_point(pk), decode_point(&sig[..32])) else {
+        return false;
+    };
+    let s = Fr::from_le_bytes_mod_order(&sig[32..]);
+    if s.into_bigint().to_bytes_le() != sig[32..] {
+        return false;
+    }
+    let k = Fr::from_le_bytes_mod_order(&Sha512::digest([&sig[..32], pk, m].concat()));
+    let mut d = EdwardsProjective::generator() * s - r - a * k;
+    for _ in 0..3 {
+        d.double_in_place();
+    }
+    d.is_zero()
+}
+
+/// RFC 8032 key pair producing signatures for `Ed25519FCircuit`.
+#[derive(Clone, Debug)]
+pub struct Ed25519Signer {
+    pub secret: [u8; 32],
+    pub pk: [u8; 32],
+}
+
+impl Ed25519Signer {
+    pub fn new(mut rng: impl RngCore) -> Self {
+        let mut secret = [0; 32];
+        rng.fill_bytes(&mut secret);
+        Self::from_secret(secret)
+    }
+
+    pub fn from_secret(secret: [u8; 32]) -> Self {
+        let (a, _) = Self::expand(&secret);
+        Self {
+            secret,
+            pk: encode_point(&(EdwardsAffine::generator() * a).into_affine()),
+        }
+    }
+
+    /// The clamped secret scalar and the nonce prefix.
+    fn expand(secret: &[u8; 32]) -> (Fr, Vec<u8>) {
+        let h = Sha512::digest(secret);
+        let mut a = h[..32].to_vec();
+        a[0] &= 0xf8;
+        a[31] &= 0x7f;
+        a[31] |= 0x40;
+        (Fr::from_le_bytes_mod_order(&a), h[32..].to_vec())
+    }
+
+    /// The signature `R || S` on `m`.
+    pub fn sign(&self, m: &[u8]) -> [u8; 64] {
+        let (a, prefix) = Self::expand(&self.secret);
+        let r = Fr::from_le_bytes_mod_order(&Sha512::digest([&prefix[..], m].concat()));
+        let r_enc = encode_point(&(EdwardsAffine::generator() * r).into_affine());
+        let k = Fr::from_le_bytes_mod_order(&Sha512::digest([&r_enc[..], &self.pk, m].concat()));
+        let s = r + k * a;
+        [r_enc.to_vec(), s.into_bigint().to_bytes_le()]
+            .concat()
+            .try_into()
+            .unwrap()
+    }
+}
+
+/// `[A, R, S, m]`, the external inputs of `Ed25519FCircuit`.
+pub fn external_inputs<F: PrimeField>(pk: &[u8], sig: &[u8], m: &[u8]) -> Vec<F> {
+    [pk, sig, m].concat().into_iter().map(F::from).collect()
+}
+
+type FqVar<F> = NonNativeFieldVar<Fq, F>;
+
+/// A point of Curve25519 with emulated coordinates.
+#[derive(Clone)]
+struct PointVar<F: PrimeField> {
+    x: FqVar<F>,
+    y: FqVar<F>,
+}
+
+impl<F: PrimeField> PointVar<F> {
+    fn constant(p: EdwardsAffine) -> Self {
+        Self {
+            x: FqVar::constant(p.x),
+            y: FqVar::constant(p.y),
+        }
+    }
+
+    /// The point encoded by the 256 little-endian `bits`, its coordinates
+    /// witnessed and checked against them.
+    fn decode(cs: ConstraintSystemRef<F>, bits: &[Boolean<F>]) -> Result<Self, SynthesisError> {
+        let point = bits.value().ok().map(|bits| {
+            let bytes: Vec<u8> = bits
+                .chunks(8)
+                .map(|b| b.iter().rev().fold(0, |acc, bit| (acc << 1) | *bit as u8))
+                .collect();
+            // an invalid encoding leaves the constraints unsatisfied
+            decode_point(&bytes).unwrap_or(EdwardsAffine::zero())
+        });
+        let x = FqVar::new_witness(cs.clone(), || {
+            point.map(|p| p.x).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let y = FqVar::new_witness(cs, || {
+            point.map(|p| p.y).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        for (y_bit, bit) in y.to_bits_le()?.iter().zip(&bits[..255]) {
+            y_bit.enforce_equal(bit)?;
+        }
+        x.to_bits_le()?[0].enforce_equal(&bits[255])?;
+        let (x2, y2) = (x.square()?, y.square()?);
+        (&y2 - &x2).enforce_equal(&(FqVar::one() + x2 * y2 * EdwardsConfig::COEFF_D))?;
+        Ok(Self { x, y })
+    }
+
+    /// The complete addition law of Curve25519 (`a = -1`), doublings
+    /// included.
+    fn add(&self, other: &Self) -> Result<Self, SynthesisError> {
+        let x1x2 = &self.x * &other.x;
+        let y1y2 = &self.y * &other.y;
+        let t = &x1x2 * &y1y2 * EdwardsConfig::COEFF_D;
+        let x = (&self.x * &other.y + &self.y * &other.x).mul_by_inverse(&(FqVar::one() + &t))?;
+        let y = (y1y2 + x1x2).mul_by_inverse(&(FqVar::one() - t))?;
+        Ok(Self { x, y })
+    }
+
+    fn negate(&self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: self.x.negate()?,
+            y: self.y.clone(),
+        })
+    }
+
+    fn select(bit: &Boolean<F>, a: &Self, b: &Self) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            x: FqVar::conditionally_select(bit, &a.x, &b.x)?,
+            y: FqVar::conditionally_select(bit, &a.y, &b.y)?,
+        })
+    }
+
+    fn scalar_mul_le(&self, bits: &[Boolean<F>]) -> Result<Self, SynthesisError> {
+        let mut acc = Self::constant(EdwardsAffine::zero());
+        for bit in bits.iter().rev() {
+            acc = acc.add(&acc)?;
+            acc = Self::select(bit, &acc.add(self)?, &acc)?;
+        }
+        Ok(acc)
+    }
+
+    /// `[bits]B`, adding the constant multiples `[2^j]B`.
+    fn generator_mul_le(bits: &[Boolean<F>]) -> Result<Self, SynthesisError> {
+        let mut base = EdwardsProjective::generator();
+        let mut acc = Self::constant(EdwardsAffine::zero());
+        for bit in bits {
+            acc = Self::select(bit, &acc.add(&Self::constant(base.into_affine()))?, &acc)?;
+            base.double_in_place();
+        }
+        Ok(acc)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Ed25519FCircuit<F: PrimeField> {
+    /// Message bytes per signature.
+    pub msg_len: usize,
+    pub poseidon_config: PoseidonConfig<F>,
+}
+
+impl<F: PrimeField + Absorb> FCircuit<F> for Ed25519FCircuit<F> {
+    /// Message bytes per signature.
+    type Params = usize;
+
+    fn new(msg_len: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            msg_len,
+            poseidon_config: poseidon_canonical_config::<F>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        96 + self.msg_len
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let bytes = external_inputs
+            .iter()
+            .map(|x| u8::try_from(to_u64(*x)?).ok())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(Error::NotSatisfied)?;
+        let (pk, rest) = bytes.split_at(32);
+        let (sig, m) = rest.split_at(64);
+        if !verify(pk, sig, m) {
+            return Err(Error::NotSatisfied);
+        }
+
+        let mut absorbed = z_i;
+        absorbed.extend(
+            pk.chunks(16)
+                .chain(m.chunks(16))
+                .map(F::from_be_bytes_mod_order),
+        );
+        let mut sponge = PoseidonSponge::new(&self.poseidon_config);
+        sponge.absorb(&absorbed);
+        Ok(vec![sponge.squeeze_field_elements(1)[0]])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let bytes = external_inputs
+            .iter()
+            .map(|b| Ok(UInt8::from_bits_le(&range_check_var(cs.clone(), b, 8)?)))
+            .collect::<Result<Vec<_>, SynthesisError>>()?;
+        let bits = |bytes: &[UInt8<F>]| -> Result<Vec<Boolean<F>>, SynthesisError> {
+            Ok(bytes
+                .iter()
+                .map(|b| b.to_bits_le())
+                .collect::<Result<Vec<_>, _>>()?
+                .concat())
+        };
+        let (pk, rest) = bytes.split_at(32);
+        let (r, rest) = rest.split_at(32);
+        let (s, m) = rest.split_at(32);
+        let a = PointVar::decode(cs.clone(), &bits(pk)?)?;
+        let r_point = PointVar::decode(cs.clone(), &bits(r)?)?;
+        let s_bits = bits(s)?;
+        Boolean::enforce_smaller_or_equal_than_le(&s_bits, (-Fr::ONE).into_bigint())?;
+
+        // `k = SHA-512(R || A || m) mod l`; the digest exceeds `l`, which
+        // `modmul_var` allows with a unit factor since the quotient still fits
+        // its limbs
+        let digest = sha512_gadget(cs.clone(), &[r, pk, m].concat())?;
+        let mut h = bits(&digest)?
+            .chunks(LIMB_BITS)
+            .map(Boolean::le_bits_to_fp_var)
+            .collect::<Result<Vec<_>, _>>()?;
+        h.resize(LIMBS, FpVar::zero());
+        let one: Vec<FpVar<F>> = to_limbs(&BigUint::from(1_u8))
+            .into_iter()
+            .map(FpVar::constant)
+            .collect();
+        let k = modmul_var(cs.clone(), &h, &one, &Fr::MODULUS.into())?;
+        let mut k_bits = Vec::with_capacity(256);
+        for (j, limb) in k.iter().enumerate() {
+            if j < 256 / LIMB_BITS {
+                k_bits.extend(range_check_var(cs.clone(), limb, LIMB_BITS)?);
+            } else {
+                limb.enforce_equal(&FpVar::zero())?;
+            }
+        }
+
+        let mut d = PointVar::generator_mul_le(&s_bits)?
+            .add(&r_point.negate()?)?
+            .add(&a.scalar_mul_le(&k_bits)?.negate()?)?;
+        for _ in 0..3 {
+            d = d.add(&d)?;
+        }
+        d.x.enforce_equal(&FqVar::zero())?;
+        d.y.enforce_equal(&FqVar::one())?;
+
+        let mut absorbed = z_i;
+        absorbed.extend(
+            external_inputs[..32]
+                .chunks(16)
+                .chain(external_inputs[96..].chunks(16))
+                .map(pack),
+        );
+        let mut sponge = PoseidonSpongeVar::new(cs, &self.poseidon_config);
+        sponge.absorb(&absorbed)?;
+        Ok(vec![sponge.squeeze_field_elements(1)?[0].clone()])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr as BnFr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::{rngs::OsRng, Rng};
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|j| u8::from_str_radix(&s[j..j + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Test 2 of RFC 8032, section 7.1.
+    fn rfc8032_vector() -> ([u8; 32], Vec<u8>, Vec<u8>, Vec<u8>) {
+        let secret = hex("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb");
+        let pk = hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c");
+        let sig = hex(concat!(
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da",
+            "085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"
+        ));
+        (secret.try_into().unwrap(), pk, sig, vec![0x72])
+    }
+
+    #[test]
+    fn test_ed25519_native() {
+        let (secret, pk, sig, m) = rfc8032_vector();
+        let signer = Ed25519Signer::from_secret(secret);
+        assert_eq!(signer.pk.to_vec(), pk);
+        assert_eq!(signer.sign(&m).to_vec(), sig);
+        assert!(verify(&pk, &sig, &m));
+
+        let signer = Ed25519Signer::new(OsRng);
+        let m = b"sonobe folding benchmarks";
+        assert!(verify(&signer.pk, &signer.sign(m), m));
+        assert!(!verify(&signer.pk, &signer.sign(b"another message"), m));
+        assert!(!verify(&pk, &signer.sign(m), m));
+    }
+
+    // Millions of constraints, run with
+    // `cargo test --release --features ed25519 -- --ignored test_ed25519_native_vs_gadget`.
+    #[test]
+    #[ignore]
+    fn test_ed25519_native_vs_gadget() {
+        let (_, pk, sig, m) = rfc8032_vector();
+        let circuit = Ed25519FCircuit::<BnFr>::new(m.len()).unwrap();
+        let z_i = vec![BnFr::from(3_u32)];
+        let external_inputs = external_inputs::<BnFr>(&pk, &sig, &m);
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+
+        let cs = ConstraintSystem::<BnFr>::new_ref();
+        let z_i_var = Vec::<FpVar<BnFr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<BnFr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    // as `test_ed25519_native_vs_gadget`, run with
+    // `cargo test --release --features ed25519 -- --ignored test_ed25519_consistent`.
+    #[test]
+    #[ignore]
+    fn test_ed25519_consistent() {
+        let signer = Ed25519Signer::new(OsRng);
+        let circuit = Ed25519FCircuit::<BnFr>::new(16).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let m: [u8; 16] = rng.gen();
+            let z_i = vec![BnFr::from(rng.gen::<u64>())];
+            (
+                z_i,
+                external_inputs::<BnFr>(&signer.pk, &signer.sign(&m), &m),
+            )
+        });
+    }
+}
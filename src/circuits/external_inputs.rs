@@ -0,0 +1,93 @@
+//! Circuit consuming `width` external inputs per step, to benchmark the
+//! external-inputs code path and its marginal cost per input.
+//!
+//! `z_{i+1} = z_i + sum_j x_j^2`, i.e. one constraint per external input.
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExternalInputsFCircuit<F: PrimeField> {
+    pub width: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for ExternalInputsFCircuit<F> {
+    type Params = usize;
+
+    fn new(width: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            width,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.width
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        Ok(vec![external_inputs
+            .iter()
+            .fold(z_i[0], |acc, x| acc + x.square())])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        Ok(vec![external_inputs
+            .iter()
+            .fold(z_i[0].clone(), |acc, x| acc + x * x)])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_external_inputs_native_vs_gadget() {
+        let circuit = ExternalInputsFCircuit::<Fr>::new(3).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let external_inputs: Vec<Fr> = (1..=3_u32).map(Fr::from).collect();
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        assert_eq!(z_i1, vec![Fr::from(17_u32)]);
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_external_inputs_consistent() {
+        assert_fcircuit_consistent(&ExternalInputsFCircuit::<Fr>::new(3).unwrap());
+    }
+}
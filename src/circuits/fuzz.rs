@@ -0,0 +1,367 @@
+//! Property-based tests of the circuit library: `conformance::check_step` on
+//! states and external inputs drawn by proptest, weighted towards the edges
+//! of the field (zero, one, `p - 1`) and of each circuit's domain, which
+//! fixed test vectors rarely reach. Failing cases are shrunk and saved under
+//! `proptest-regressions/`, and replayed first from then on.
+//!
+//! Left out are `closure`, whatever its user writes, `circom` and
+//! `r1cs_file`, which wrap circuits compiled elsewhere, and `ed25519`, at
+//! millions of constraints.
+use ark_bn254::Fr;
+use ark_ed_on_bn254::EdwardsConfig;
+use ark_ff::{One, PrimeField, Zero};
+use num_bigint::BigUint;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use folding_schemes::frontend::FCircuit;
+
+use super::aes::AesFCircuit;
+use super::bitcoin::{header_external_inputs, BitcoinHeaderFCircuit, HEADER_LEN};
+use super::blake3::Blake3FCircuit;
+use super::branches::BranchesFCircuit;
+use super::conformance::check_step;
+use super::conv::ConvFCircuit;
+use super::dense::DenseReluFCircuit;
+use super::external_inputs::ExternalInputsFCircuit;
+use super::keccak::KeccakFCircuit;
+use super::matmul::MatMulFCircuit;
+use super::merkle::{MerkleHash, MerkleUpdateFCircuit, NativeMerkleTree};
+use super::mpt::{StorageProof, StorageProofFCircuit};
+use super::multi_state::MultiStateFCircuit;
+use super::nonnative::NonNativeFCircuit;
+use super::padding::PaddingFCircuit;
+use super::pedersen::PedersenFCircuit;
+use super::poseidon2::{Poseidon2FCircuit, PoseidonVersion};
+use super::rlp::{rlp_list, rlp_string, RlpFCircuit};
+use super::rollup::{NativeRollup, RollupFCircuit};
+use super::rsa::{rsa_2048_modulus, to_limbs, RsaFCircuit};
+use super::schnorr::{SchnorrFCircuit, SchnorrSigner};
+use super::sha256::Sha256FCircuit;
+use super::signature_batch::{signed_entry, SignatureBatchFCircuit};
+use super::sparse_merkle::{sparse_external_inputs, SparseMerkleFCircuit, SparseOp};
+use super::timed::TimedFCircuit;
+use super::toy_vm::{fibonacci_program, ToyVmFCircuit, N_REGS};
+use super::uint64::UInt64FCircuit;
+use crate::CubicFCircuit;
+
+/// Zero, one, `p - 1`, 64-bit integers and elements from 32 random bytes.
+fn field() -> impl Strategy<Value = Fr> {
+    prop_oneof![
+        Just(Fr::zero()),
+        Just(Fr::one()),
+        Just(-Fr::one()),
+        any::<u64>().prop_map(Fr::from),
+        any::<[u8; 32]>().prop_map(|bytes| Fr::from_le_bytes_mod_order(&bytes)),
+    ]
+}
+
+fn fields(n: usize) -> impl Strategy<Value = Vec<Fr>> {
+    vec(field(), n)
+}
+
+/// Step 0 of `f_circuit` passes `check_step`.
+fn check<FC: FCircuit<Fr>>(
+    f_circuit: &FC,
+    z_i: &[Fr],
+    external_inputs: &[Fr],
+) -> Result<(), TestCaseError> {
+    check_step(f_circuit, 0, z_i, external_inputs).map_err(TestCaseError::fail)
+}
+
+/// A header whose bits name the easiest target, with the first nonce that
+/// meets it.
+fn mined_header(circuit: &BitcoinHeaderFCircuit<Fr>, rng: &mut StdRng) -> [u8; HEADER_LEN] {
+    let mut header: [u8; HEADER_LEN] = core::array::from_fn(|_| rng.gen());
+    header[72..76].copy_from_slice(&0x1f7fffff_u32.to_le_bytes());
+    for nonce in 0_u32.. {
+        header[76..80].copy_from_slice(&nonce.to_le_bytes());
+        let ((lo, hi), external_inputs) = header_external_inputs::<Fr>(&header);
+        if circuit
+            .step_native(0, vec![lo, hi, Fr::zero()], external_inputs)
+            .is_ok()
+        {
+            return header;
+        }
+    }
+    unreachable!("no nonce meets the target")
+}
+
+/// `[nonce, gas_price, gas_limit, to, value, data, v, r, s]`.
+fn legacy_transaction(nonce: u64, value: u64, data: &[u8]) -> Vec<u8> {
+    let int = |x: u64| {
+        let bytes: Vec<u8> = x
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        rlp_string(&bytes)
+    };
+    rlp_list(&[
+        int(nonce),
+        int(20_000_000_000),
+        int(21_000),
+        rlp_string(&[0x11; 20]),
+        int(value),
+        rlp_string(data),
+        int(37),
+        rlp_string(&[0x22; 32]),
+        rlp_string(&[0x33; 32]),
+    ])
+}
+
+// Arithmetic circuits, defined on any field elements or checking their
+// domain with a few constraints.
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn fuzz_cubic(z in field()) {
+        check(&CubicFCircuit::<Fr>::new(()).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_timed(z in field()) {
+        check(&TimedFCircuit::<Fr, CubicFCircuit<Fr>>::new(()).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_multi_state(z_i in fields(4)) {
+        check(&MultiStateFCircuit::<Fr>::new(4).unwrap(), &z_i, &[])?;
+    }
+
+    #[test]
+    fn fuzz_external_inputs(z in field(), external_inputs in fields(3)) {
+        check(&ExternalInputsFCircuit::<Fr>::new(3).unwrap(), &[z], &external_inputs)?;
+    }
+
+    #[test]
+    fn fuzz_padding(z in field()) {
+        check(&PaddingFCircuit::<Fr>::new(16).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_poseidon2(z in field(), poseidon2 in any::<bool>()) {
+        let version = if poseidon2 {
+            PoseidonVersion::Poseidon2
+        } else {
+            PoseidonVersion::Poseidon
+        };
+        check(&Poseidon2FCircuit::<Fr>::new((version, 1)).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_pedersen(z in field(), external_inputs in fields(2)) {
+        let circuit = PedersenFCircuit::<EdwardsConfig>::new(3).unwrap();
+        check(&circuit, &[z], &external_inputs)?;
+    }
+
+    #[test]
+    fn fuzz_nonnative(z in field()) {
+        check(&NonNativeFCircuit::<Fr, ark_bn254::Fq>::new(2).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_matmul(z_i in fields(3), external_inputs in fields(4)) {
+        check(&MatMulFCircuit::<Fr>::new((3, 4, 8)).unwrap(), &z_i, &external_inputs)?;
+    }
+
+    #[test]
+    fn fuzz_conv(z_i in fields(4), external_inputs in fields(9)) {
+        check(&ConvFCircuit::<Fr>::new((3, 2, 8)).unwrap(), &z_i, &external_inputs)?;
+    }
+
+    #[test]
+    fn fuzz_dense_relu(
+        z_i in vec(prop_oneof![(-1024_i64..1024).prop_map(Fr::from), field()], 4),
+    ) {
+        check(&DenseReluFCircuit::<Fr>::new((4, 8, 32)).unwrap(), &z_i, &[])?;
+    }
+
+    #[test]
+    fn fuzz_uint64(
+        z in prop_oneof![Just(Fr::from(u64::MAX)), any::<u64>().prop_map(Fr::from), field()],
+    ) {
+        check(&UInt64FCircuit::<Fr>::new(2).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_branches(z in field(), s in prop_oneof![(0..3_u64).prop_map(Fr::from), field()]) {
+        check(&BranchesFCircuit::<Fr>::new(vec![3, 10, 5]).unwrap(), &[z, s], &[])?;
+    }
+
+    // up to past the end of the program, where the VM halts
+    #[test]
+    fn fuzz_toy_vm(
+        pc in prop_oneof![(0..=8_u64).prop_map(Fr::from), field()],
+        regs in fields(N_REGS),
+    ) {
+        let circuit = ToyVmFCircuit::<Fr>::new(fibonacci_program(10)).unwrap();
+        check(&circuit, &[vec![pc], regs].concat(), &[])?;
+    }
+}
+
+// Hashes, trees and byte parsing.
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn fuzz_sha256(z in field()) {
+        check(&Sha256FCircuit::<Fr>::new(1).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_keccak(z in field()) {
+        check(&KeccakFCircuit::<Fr>::new(1).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_blake3(z in field()) {
+        check(&Blake3FCircuit::<Fr>::new(1).unwrap(), &[z], &[])?;
+    }
+
+    #[test]
+    fn fuzz_aes(
+        block in prop_oneof![any::<u128>().prop_map(Fr::from), Just(Fr::from(u128::MAX)), field()],
+        key in prop_oneof![any::<u128>().prop_map(Fr::from), field()],
+    ) {
+        check(&AesFCircuit::<Fr>::new(1).unwrap(), &[block], &[key])?;
+    }
+
+    // the update of a tree holding `leaves`, from its root or from `wrong_root`
+    #[test]
+    fn fuzz_merkle_update(
+        poseidon in any::<bool>(),
+        leaves in vec((0..16_u64, field()), 0..4),
+        index in 0..16_u64,
+        leaf in field(),
+        wrong_root in prop::option::of(field()),
+    ) {
+        let hash = if poseidon { MerkleHash::Poseidon } else { MerkleHash::Sha256 };
+        let circuit = MerkleUpdateFCircuit::<Fr>::new((4, hash)).unwrap();
+        let mut tree = NativeMerkleTree::<Fr>::new(4, hash);
+        for (k, v) in leaves {
+            tree.update(k, v);
+        }
+        let z_i = vec![wrong_root.unwrap_or_else(|| tree.root())];
+        let external_inputs = tree.update_external_inputs(index, leaf);
+        check(&circuit, &z_i, &external_inputs)?;
+    }
+
+    // inserts `value` under free keys and deletes taken ones; a zero value is
+    // rejected
+    #[test]
+    fn fuzz_sparse_merkle(keys in vec(0..256_u64, 1..4), value in field()) {
+        let circuit = SparseMerkleFCircuit::<Fr>::new((8, MerkleHash::Poseidon)).unwrap();
+        let mut tree = NativeMerkleTree::<Fr>::new(8, MerkleHash::Poseidon);
+        for key in keys {
+            let op = if tree.leaf(key).is_zero() {
+                SparseOp::Insert(value)
+            } else {
+                SparseOp::Delete
+            };
+            let z_i = vec![tree.root()];
+            let external_inputs = sparse_external_inputs(&mut tree, key, op);
+            check(&circuit, &z_i, &external_inputs)?;
+        }
+    }
+
+    #[test]
+    fn fuzz_rlp(
+        z_i in fields(3),
+        nonce in any::<u64>(),
+        value in any::<u64>(),
+        data in vec(any::<u8>(), 0..160),
+    ) {
+        let circuit = RlpFCircuit::<Fr>::new((300, 9, vec![0, 4, 8])).unwrap();
+        let tx = legacy_transaction(nonce, value, &data);
+        check(&circuit, &z_i, &circuit.external_inputs(&tx))?;
+    }
+}
+
+// Bignum arithmetic and signatures, up to hundreds of thousands of
+// constraints a case.
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4))]
+
+    #[test]
+    fn fuzz_bitcoin_header(seed in any::<u64>(), height in field()) {
+        let circuit = BitcoinHeaderFCircuit::<Fr>::new(()).unwrap();
+        let header = mined_header(&circuit, &mut StdRng::seed_from_u64(seed));
+        let ((lo, hi), external_inputs) = header_external_inputs::<Fr>(&header);
+        check(&circuit, &[lo, hi, height], &external_inputs)?;
+    }
+
+    #[test]
+    fn fuzz_storage_proof(
+        seed in any::<u64>(),
+        slot in any::<[u8; 32]>(),
+        mut value in vec(any::<u8>(), 1..=32),
+        acc in field(),
+    ) {
+        // without leading zeros
+        value[0] = value[0].max(1);
+        let circuit = StorageProofFCircuit::<Fr>::new(1).unwrap();
+        let proof = StorageProof::synthetic(1, slot, &value, &mut StdRng::seed_from_u64(seed));
+        let z_i = [proof.root_state(), vec![acc]].concat();
+        check(&circuit, &z_i, &proof.external_inputs())?;
+    }
+
+    // from 0 up to the whole balance of 1000
+    #[test]
+    fn fuzz_rollup(
+        seed in any::<u64>(),
+        verify_signatures in any::<bool>(),
+        amounts in vec(prop_oneof![Just(0_u64), Just(1000), 0..=1000_u64], 2),
+    ) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let circuit = RollupFCircuit::<EdwardsConfig>::new((4, 2, verify_signatures)).unwrap();
+        let mut rollup = NativeRollup::<EdwardsConfig>::new(&mut rng, 4, 3, 1000);
+        let z_i = vec![rollup.root()];
+        let external_inputs = [
+            rollup.transfer(&mut rng, 0, 1, amounts[0], verify_signatures),
+            rollup.transfer(&mut rng, 1, 2, amounts[1], verify_signatures),
+        ]
+        .concat();
+        check(&circuit, &z_i, &external_inputs)?;
+    }
+
+    #[test]
+    fn fuzz_rsa(
+        edge in 0..4_u8,
+        bytes in vec(any::<u8>(), 256),
+        exponent in vec(any::<bool>(), 2),
+    ) {
+        let modulus = rsa_2048_modulus();
+        let a = match edge {
+            0 => BigUint::from(0_u8),
+            1 => BigUint::from(1_u8),
+            2 => &modulus - 1_u8,
+            _ => BigUint::from_bytes_le(&bytes) % &modulus,
+        };
+        let circuit = RsaFCircuit::<Fr>::new((modulus, 2)).unwrap();
+        let exponent: Vec<Fr> = exponent.into_iter().map(Fr::from).collect();
+        check(&circuit, &to_limbs::<Fr>(&a), &exponent)?;
+    }
+
+    #[test]
+    fn fuzz_schnorr(seed in any::<u64>(), z in field(), m in field()) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let signer = SchnorrSigner::<EdwardsConfig>::new(&mut rng);
+        let circuit = SchnorrFCircuit::<EdwardsConfig>::new(signer.pk).unwrap();
+        check(&circuit, &[z], &signer.sign(&mut rng, m))?;
+    }
+
+    #[test]
+    fn fuzz_signature_batch(seed in any::<u64>(), z in field(), messages in fields(2)) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let circuit = SignatureBatchFCircuit::<EdwardsConfig>::new(2).unwrap();
+        let mut external_inputs = vec![];
+        for m in messages {
+            let signer = SchnorrSigner::<EdwardsConfig>::new(&mut rng);
+            external_inputs.extend(signed_entry(&signer, &mut rng, m));
+        }
+        check(&circuit, &[z], &external_inputs)?;
+    }
+}
@@ -0,0 +1,326 @@
+//! Keccak-256 hash chain: each step replaces the state with the (truncated)
+//! Keccak-256 digest of its bytes, `k` times.
+//!
+//! arkworks has no Keccak gadget, so Keccak-f[1600] is implemented here over
+//! lanes of 64 `Boolean`s: rotations are free rewirings and the cost is all in
+//! the XORs of theta and the AND/XORs of chi.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    uint8::UInt8,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use sha3::{Digest, Keccak256};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::uint64::range_check_var;
+use super::utils::{bytes_to_field, bytes_var_to_field, field_to_bytes_le, field_var_to_bytes_le};
+
+/// Keccak-256 rate in bytes.
+pub const RATE: usize = 136;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rho rotation offsets, indexed `[x][y]`.
+const ROTATIONS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+type Lane<F> = Vec<Boolean<F>>;
+
+fn rotl<F: PrimeField>(lane: &Lane<F>, n: usize) -> Lane<F> {
+    (0..64).map(|j| lane[(j + 64 - n) % 64].clone()).collect()
+}
+
+fn xor<F: PrimeField>(a: &Lane<F>, b: &Lane<F>) -> Result<Lane<F>, SynthesisError> {
+    a.iter().zip(b).map(|(a, b)| a.xor(b)).collect()
+}
+
+/// Keccak-f[1600] permutation over 25 lanes indexed `x + 5 * y`.
+fn keccak_f<F: PrimeField>(mut a: Vec<Lane<F>>) -> Result<Vec<Lane<F>>, SynthesisError> {
+    for rc in ROUND_CONSTANTS {
+        // theta
+        let mut c = Vec::with_capacity(5);
+        for x in 0..5 {
+            let mut c_x = a[x].clone();
+            for y in 1..5 {
+                c_x = xor(&c_x, &a[x + 5 * y])?;
+            }
+            c.push(c_x);
+        }
+        for x in 0..5 {
+            let d_x = xor(&c[(x + 4) % 5], &rotl(&c[(x + 1) % 5], 1))?;
+            for y in 0..5 {
+                a[x + 5 * y] = xor(&a[x + 5 * y], &d_x)?;
+            }
+        }
+
+        // rho and pi
+        let mut b = vec![Vec::new(); 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(&a[x + 5 * y], ROTATIONS[x][y]);
+            }
+        }
+
+        // chi
+        for x in 0..5 {
+            for y in 0..5 {
+                let b1 = &b[(x + 1) % 5 + 5 * y];
+                let b2 = &b[(x + 2) % 5 + 5 * y];
+                a[x + 5 * y] = b[x + 5 * y]
+                    .iter()
+                    .zip(b1.iter().zip(b2))
+                    .map(|(b0, (b1, b2))| b1.not().and(b2)?.xor(b0))
+                    .collect::<Result<_, _>>()?;
+            }
+        }
+
+        // iota
+        for (j, bit) in a[0].iter_mut().enumerate() {
+            if (rc >> j) & 1 == 1 {
+                *bit = bit.not();
+            }
+        }
+    }
+    Ok(a)
+}
+
+/// Keccak-256 of a message shorter than one block.
+pub fn keccak256_gadget<F: PrimeField>(
+    input: &[UInt8<F>],
+) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    assert!(input.len() < RATE, "multi-block input is not supported");
+
+    let mut padding = vec![0u8; RATE - input.len()];
+    padding[0] |= 0x01;
+    *padding.last_mut().unwrap() |= 0x80;
+    let mut block = input.to_vec();
+    block.extend(padding.into_iter().map(UInt8::constant));
+
+    let bits = block
+        .iter()
+        .map(|b| b.to_bits_le())
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    let mut state: Vec<Lane<F>> = bits.chunks(64).map(|lane| lane.to_vec()).collect();
+    state.resize(25, vec![Boolean::FALSE; 64]);
+
+    let state = keccak_f(state)?;
+    Ok(state[..4]
+        .concat()
+        .chunks(8)
+        .map(UInt8::from_bits_le)
+        .collect())
+}
+
+/// Keccak-256 of the first `len` bytes of `input`, one byte per variable, for
+/// a length known only to the prover. Returns the digest bytes.
+///
+/// Every block a message of up to `input.len()` bytes may need is absorbed,
+/// and the digest is selected from the state after the block holding the
+/// padding. Locating it costs an equality check per byte of `input`, and
+/// padding every byte costs its bit decomposition, which also range-checks
+/// the message bytes.
+pub fn keccak256_var_len_gadget<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    input: &[FpVar<F>],
+    len: &FpVar<F>,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let n_blocks = input.len() / RATE + 1;
+    // `at[j]` iff `j == len`, and `before` iff `j < len`
+    let at = (0..=input.len())
+        .map(|j| len.is_eq(&FpVar::constant(F::from(j as u64))))
+        .collect::<Result<Vec<_>, _>>()?;
+    at.iter()
+        .fold(FpVar::zero(), |sum, a| sum + FpVar::from(a.clone()))
+        .enforce_equal(&FpVar::one())?;
+    let final_block: Vec<FpVar<F>> = at
+        .chunks(RATE)
+        .map(|block| {
+            block
+                .iter()
+                .fold(FpVar::zero(), |sum, a| sum + FpVar::from(a.clone()))
+        })
+        .collect();
+
+    let mut before = FpVar::one();
+    let mut bits = Vec::with_capacity(8 * RATE * n_blocks);
+    for j in 0..n_blocks * RATE {
+        let mut byte = FpVar::zero();
+        if let Some(a) = at.get(j) {
+            before -= FpVar::from(a.clone());
+            byte += FpVar::from(a.clone());
+            if let Some(x) = input.get(j) {
+                byte += &before * x;
+            }
+        }
+        if j % RATE == RATE - 1 {
+            byte += &final_block[j / RATE] * F::from(0x80_u64);
+        }
+        bits.extend(range_check_var(cs.clone(), &byte, 8)?);
+    }
+
+    let mut state = vec![vec![Boolean::FALSE; 64]; 25];
+    let mut digest = vec![FpVar::zero(); 32];
+    for (block, is_final) in bits.chunks(8 * RATE).zip(&final_block) {
+        for (lane, block_lane) in state.iter_mut().zip(block.chunks(64)) {
+            *lane = xor(lane, &block_lane.to_vec())?;
+        }
+        state = keccak_f(state)?;
+        for (d, byte) in digest.iter_mut().zip(state[..4].concat().chunks(8)) {
+            *d += is_final * Boolean::le_bits_to_fp_var(byte)?;
+        }
+    }
+    Ok(digest)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakFCircuit<F: PrimeField> {
+    /// Number of Keccak-256 hashes per step.
+    pub n_hashes: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for KeccakFCircuit<F> {
+    type Params = usize;
+
+    fn new(n_hashes: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_hashes,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let mut z = z_i[0];
+        for _ in 0..self.n_hashes {
+            let digest = Keccak256::digest(field_to_bytes_le(z));
+            z = bytes_to_field(&digest);
+        }
+        Ok(vec![z])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut z = z_i[0].clone();
+        for _ in 0..self.n_hashes {
+            let digest = keccak256_gadget(&field_var_to_bytes_le(&z)?)?;
+            z = bytes_var_to_field(&digest)?;
+        }
+        Ok(vec![z])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_keccak256_gadget() {
+        let msg = b"sonobe folding benchmarks".to_vec();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let msg_var = UInt8::new_witness_vec(cs.clone(), &msg).unwrap();
+        let digest = keccak256_gadget(&msg_var).unwrap();
+        assert_eq!(digest.value().unwrap(), Keccak256::digest(&msg).to_vec());
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_keccak256_var_len_gadget() {
+        let msg: Vec<u8> = (0..200_u32).map(|b| b as u8).collect();
+        // the padding shares the last byte of the first block, and spills into
+        // the second one
+        for len in [RATE - 1, msg.len()] {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let msg_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || {
+                Ok(msg.iter().map(|b| Fr::from(*b)).collect())
+            })
+            .unwrap();
+            let len_var = FpVar::new_witness(cs.clone(), || Ok(Fr::from(len as u64))).unwrap();
+            let digest = keccak256_var_len_gadget(cs.clone(), &msg_var, &len_var).unwrap();
+            let expected: Vec<Fr> = Keccak256::digest(&msg[..len])
+                .iter()
+                .map(|b| Fr::from(*b))
+                .collect();
+            assert_eq!(digest.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_keccak_native_vs_gadget() {
+        let circuit = KeccakFCircuit::<Fr>::new(1).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_keccak_consistent() {
+        assert_fcircuit_consistent(&KeccakFCircuit::<Fr>::new(1).unwrap());
+    }
+}
@@ -0,0 +1,142 @@
+//! Fixed-point matrix-vector multiplication (zkML layer).
+//!
+//! Each step multiplies a constant `n x m` weight matrix by an `m`-element
+//! input vector supplied as external inputs, rescales the result back to the
+//! fixed-point scale, and accumulates it into the `n`-element state. Values are
+//! unsigned fixed point with `frac_bits` fractional bits.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{boolean::Boolean, fields::fp::FpVar, fields::FieldVar, ToBitsGadget};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Floor division by `2^frac_bits` of the canonical representation of `x`.
+pub fn rescale<F: PrimeField>(x: F, frac_bits: usize) -> F {
+    let mut x = x.into_bigint();
+    x.divn(frac_bits as u32);
+    F::from_bigint(x).unwrap()
+}
+
+/// Gadget version of `rescale`, which costs a full bit decomposition of `x`.
+pub fn rescale_var<F: PrimeField>(
+    x: &FpVar<F>,
+    frac_bits: usize,
+) -> Result<FpVar<F>, SynthesisError> {
+    let bits = x.to_bits_le()?;
+    Boolean::le_bits_to_fp_var(&bits[frac_bits..])
+}
+
+/// Deterministic weights in `[0, 1)`, so runs are comparable.
+pub fn fixed_point_weights<F: PrimeField>(n: usize, m: usize, frac_bits: usize) -> Vec<Vec<F>> {
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..n)
+        .map(|_| {
+            (0..m)
+                .map(|_| F::from(rng.gen_range(0..1u64 << frac_bits)))
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct MatMulFCircuit<F: PrimeField> {
+    pub n: usize,
+    pub m: usize,
+    pub frac_bits: usize,
+    pub weights: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> FCircuit<F> for MatMulFCircuit<F> {
+    /// Rows, columns and fractional bits.
+    type Params = (usize, usize, usize);
+
+    fn new((n, m, frac_bits): Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n,
+            m,
+            frac_bits,
+            weights: fixed_point_weights(n, m, frac_bits),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.n
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.m
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        Ok(self
+            .weights
+            .iter()
+            .zip(z_i)
+            .map(|(row, z)| {
+                let dot = row
+                    .iter()
+                    .zip(&external_inputs)
+                    .fold(F::zero(), |acc, (w, x)| acc + *w * x);
+                z + rescale(dot, self.frac_bits)
+            })
+            .collect())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.weights
+            .iter()
+            .zip(z_i)
+            .map(|(row, z)| {
+                let dot = row
+                    .iter()
+                    .zip(&external_inputs)
+                    .fold(FpVar::zero(), |acc, (w, x)| acc + x * *w);
+                Ok(z + rescale_var(&dot, self.frac_bits)?)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_matmul_native_vs_gadget() {
+        let frac_bits = 8;
+        let circuit = MatMulFCircuit::<Fr>::new((3, 4, frac_bits)).unwrap();
+        let z_i = vec![Fr::from(0_u32); 3];
+        let x: Vec<Fr> = (1..=4_u32).map(|v| Fr::from(v << frac_bits)).collect();
+        let z_i1 = circuit.step_native(0, z_i.clone(), x.clone()).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let x_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(x)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, x_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_matmul_consistent() {
+        assert_fcircuit_consistent(&MatMulFCircuit::<Fr>::new((3, 4, 8)).unwrap());
+    }
+}
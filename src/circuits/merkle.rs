@@ -0,0 +1,302 @@
+//! Merkle path update: each step checks a membership proof of `old_leaf`
+//! against the root in the state, then replaces it with `new_leaf` and outputs
+//! the new root.
+//!
+//! The leaves and path are external inputs, laid out as
+//! `[old_leaf, new_leaf, siblings[0..depth], directions[0..depth]]` from the leaf
+//! level up, where a direction of 1 means the current node is a right child.
+use ark_crypto_primitives::{
+    crh::sha256::constraints::Sha256Gadget,
+    sponge::{
+        constraints::CryptographicSpongeVar,
+        poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+        CryptographicSponge,
+    },
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{eq::EqGadget, fields::fp::FpVar, fields::FieldVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use super::utils::{bytes_to_field, bytes_var_to_field, field_to_bytes_le, field_var_to_bytes_le};
+
+/// Two-to-one hash used for the tree nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleHash {
+    Poseidon,
+    Sha256,
+}
+
+/// Native and gadget two-to-one hashing, shared by the tree circuits.
+#[derive(Clone, Debug)]
+pub struct NodeHasher<F: PrimeField> {
+    pub hash: MerkleHash,
+    pub poseidon_config: PoseidonConfig<F>,
+}
+
+impl<F: PrimeField> NodeHasher<F> {
+    pub fn new(hash: MerkleHash) -> Self {
+        Self {
+            hash,
+            poseidon_config: poseidon_canonical_config::<F>(),
+        }
+    }
+
+    pub fn hash(&self, left: F, right: F) -> F {
+        match self.hash {
+            MerkleHash::Poseidon => {
+                let mut sponge = PoseidonSponge::new(&self.poseidon_config);
+                sponge.absorb(&vec![left, right]);
+                sponge.squeeze_field_elements(1)[0]
+            }
+            MerkleHash::Sha256 => {
+                let mut bytes = field_to_bytes_le(left);
+                bytes.extend(field_to_bytes_le(right));
+                bytes_to_field(&Sha256::digest(bytes))
+            }
+        }
+    }
+
+    pub fn hash_var(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        left: &FpVar<F>,
+        right: &FpVar<F>,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        match self.hash {
+            MerkleHash::Poseidon => {
+                let mut sponge = PoseidonSpongeVar::new(cs, &self.poseidon_config);
+                sponge.absorb(&vec![left.clone(), right.clone()])?;
+                Ok(sponge.squeeze_field_elements(1)?[0].clone())
+            }
+            MerkleHash::Sha256 => {
+                let mut bytes = field_var_to_bytes_le(left)?;
+                bytes.extend(field_var_to_bytes_le(right)?);
+                bytes_var_to_field(&Sha256Gadget::digest(&bytes)?.0)
+            }
+        }
+    }
+
+    /// Root obtained by walking `path` up from `leaf`.
+    pub fn root_from_path(&self, leaf: F, siblings: &[F], directions: &[F]) -> F {
+        siblings
+            .iter()
+            .zip(directions)
+            .fold(leaf, |node, (sibling, direction)| {
+                if direction.is_one() {
+                    self.hash(*sibling, node)
+                } else {
+                    self.hash(node, *sibling)
+                }
+            })
+    }
+
+    /// Gadget version of `root_from_path`, also constraining the directions to
+    /// be bits.
+    pub fn root_from_path_var(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        leaf: &FpVar<F>,
+        siblings: &[FpVar<F>],
+        directions: &[FpVar<F>],
+    ) -> Result<FpVar<F>, SynthesisError> {
+        let mut node = leaf.clone();
+        for (sibling, direction) in siblings.iter().zip(directions) {
+            (direction * (direction - FpVar::one())).enforce_equal(&FpVar::zero())?;
+            let left = &node + direction * (sibling - &node);
+            let right = sibling + &node - &left;
+            node = self.hash_var(cs.clone(), &left, &right)?;
+        }
+        Ok(node)
+    }
+}
+
+/// Sparse native Merkle tree, used to produce the external inputs of the tree
+/// circuits. Empty leaves are zero and untouched subtrees are never stored.
+#[derive(Clone, Debug)]
+pub struct NativeMerkleTree<F: PrimeField> {
+    pub depth: usize,
+    pub hasher: NodeHasher<F>,
+    /// Root of an empty subtree at each level, leaves first.
+    empty: Vec<F>,
+    nodes: HashMap<(usize, u64), F>,
+}
+
+impl<F: PrimeField> NativeMerkleTree<F> {
+    pub fn new(depth: usize, hash: MerkleHash) -> Self {
+        let hasher = NodeHasher::new(hash);
+        let mut empty = vec![F::zero()];
+        for level in 0..depth {
+            empty.push(hasher.hash(empty[level], empty[level]));
+        }
+        Self {
+            depth,
+            hasher,
+            empty,
+            nodes: HashMap::new(),
+        }
+    }
+
+    fn node(&self, level: usize, index: u64) -> F {
+        *self
+            .nodes
+            .get(&(level, index))
+            .unwrap_or(&self.empty[level])
+    }
+
+    pub fn root(&self) -> F {
+        self.node(self.depth, 0)
+    }
+
+    pub fn leaf(&self, index: u64) -> F {
+        self.node(0, index)
+    }
+
+    /// Siblings and directions of the path from leaf `index` to the root.
+    pub fn path(&self, index: u64) -> (Vec<F>, Vec<F>) {
+        (0..self.depth)
+            .map(|level| {
+                let node_index = index >> level;
+                (
+                    self.node(level, node_index ^ 1),
+                    F::from(node_index & 1),
+                )
+            })
+            .unzip()
+    }
+
+    pub fn update(&mut self, index: u64, leaf: F) {
+        let mut node = leaf;
+        self.nodes.insert((0, index), node);
+        for level in 0..self.depth {
+            let node_index = index >> level;
+            let sibling = self.node(level, node_index ^ 1);
+            node = if node_index & 1 == 1 {
+                self.hasher.hash(sibling, node)
+            } else {
+                self.hasher.hash(node, sibling)
+            };
+            self.nodes.insert((level + 1, node_index >> 1), node);
+        }
+    }
+
+    /// Applies the update to the tree and returns the matching external inputs
+    /// for `MerkleUpdateFCircuit`.
+    pub fn update_external_inputs(&mut self, index: u64, new_leaf: F) -> Vec<F> {
+        let old_leaf = self.leaf(index);
+        let (siblings, directions) = self.path(index);
+        self.update(index, new_leaf);
+        [vec![old_leaf, new_leaf], siblings, directions].concat()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MerkleUpdateFCircuit<F: PrimeField> {
+    pub depth: usize,
+    pub hasher: NodeHasher<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for MerkleUpdateFCircuit<F> {
+    /// Tree depth and node hash.
+    type Params = (usize, MerkleHash);
+
+    fn new((depth, hash): Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            depth,
+            hasher: NodeHasher::new(hash),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        2 + 2 * self.depth
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let (leaves, path) = external_inputs.split_at(2);
+        let (siblings, directions) = path.split_at(self.depth);
+        if self.hasher.root_from_path(leaves[0], siblings, directions) != z_i[0] {
+            return Err(Error::NotSatisfied);
+        }
+        Ok(vec![self
+            .hasher
+            .root_from_path(leaves[1], siblings, directions)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let (leaves, path) = external_inputs.split_at(2);
+        let (siblings, directions) = path.split_at(self.depth);
+        self.hasher
+            .root_from_path_var(cs.clone(), &leaves[0], siblings, directions)?
+            .enforce_equal(&z_i[0])?;
+        Ok(vec![self
+            .hasher
+            .root_from_path_var(cs, &leaves[1], siblings, directions)?])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::Rng;
+
+    #[test]
+    fn test_merkle_update_native_vs_gadget() {
+        for hash in [MerkleHash::Poseidon, MerkleHash::Sha256] {
+            let circuit = MerkleUpdateFCircuit::<Fr>::new((4, hash)).unwrap();
+            let mut tree = NativeMerkleTree::<Fr>::new(4, hash);
+            tree.update(3, Fr::from(7_u32));
+            let z_i = vec![tree.root()];
+            let external_inputs = tree.update_external_inputs(5, Fr::from(42_u32));
+            let z_i1 = circuit
+                .step_native(0, z_i.clone(), external_inputs.clone())
+                .unwrap();
+            assert_eq!(z_i1, vec![tree.root()]);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            let z_i1_var = circuit
+                .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+                .unwrap();
+            assert_eq!(z_i1_var.value().unwrap(), z_i1);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_merkle_update_consistent() {
+        for hash in [MerkleHash::Poseidon, MerkleHash::Sha256] {
+            let circuit = MerkleUpdateFCircuit::<Fr>::new((4, hash)).unwrap();
+            let mut tree = NativeMerkleTree::<Fr>::new(4, hash);
+            assert_fcircuit_consistent_with(&circuit, |rng| {
+                let z_i = vec![tree.root()];
+                let (index, leaf) = (rng.gen_range(0..16), Fr::rand(rng));
+                (z_i, tree.update_external_inputs(index, leaf))
+            });
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Library of step circuits to benchmark beyond `CubicFCircuit`.
+pub mod aes;
+pub mod bitcoin;
+pub mod blake3;
+pub mod branches;
+#[cfg(feature = "circom")]
+pub mod circom;
+pub mod closure;
+pub mod conformance;
+pub mod conv;
+pub mod dense;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
+pub mod external_inputs;
+#[cfg(all(test, feature = "bn254"))]
+mod fuzz;
+pub mod keccak;
+pub mod matmul;
+pub mod merkle;
+pub mod mpt;
+pub mod multi_state;
+pub mod nonnative;
+pub mod padding;
+pub mod pedersen;
+pub mod poseidon2;
+pub mod r1cs_file;
+pub mod rlp;
+pub mod rollup;
+pub mod rsa;
+pub mod schnorr;
+pub mod sha256;
+pub mod signature_batch;
+pub mod sparse_merkle;
+pub mod timed;
+pub mod toy_vm;
+pub mod uint64;
+pub mod utils;
@@ -0,0 +1,443 @@
+//! Ethereum storage proofs: each step verifies one Merkle-Patricia-Trie proof
+//! of a storage slot against the storage root in the state, the core workload
+//! of bridges and light clients.
+//!
+//! Proofs have a fixed shape per circuit: `depth` branch nodes followed by the
+//! leaf, walked along the nibbles of `keccak(slot)`, so extension nodes and
+//! nodes inlined in their parent are not supported. Every node is hashed with
+//! Keccak-256 at its actual length, up to `BRANCH_MAX` bytes for branches
+//! (four blocks) and `LEAF_MAX` for the leaf (one block).
+//!
+//! A branch is parsed item by item: every child is either empty or a 32-byte
+//! hash, and the one under the key's nibble must be the hash of the next node.
+//! The leaf must hold the remaining nibbles of the key, hex-prefix encoded, and
+//! the RLP-encoded value, read as a big-endian integer reduced into the field.
+//!
+//! The state is `[root_hi, root_lo, acc]`, the root as two big-endian 128-bit
+//! halves and `acc = Poseidon(acc, slot_hi, slot_lo, value)` over the proven
+//! slots. The external inputs are `[slot[0..32], (len, node[0..BRANCH_MAX])`
+//! for every branch, `leaf_len, leaf[0..LEAF_MAX]]`, one byte per element and
+//! zero-padded.
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use rand::Rng;
+use sha3::{Digest, Keccak256};
+
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use super::keccak::{keccak256_var_len_gadget, RATE};
+use super::rlp::{
+    be_value_var, constant, one_hot, pack, rlp_list, rlp_list_items, rlp_string, select, to_u64,
+};
+use super::uint64::range_check_var;
+
+/// Largest branch node: a 3-byte list header, 16 hashes and an empty value.
+pub const BRANCH_MAX: usize = 3 + 16 * 33 + 1;
+/// Largest leaf the circuit hashes, in one Keccak block.
+pub const LEAF_MAX: usize = RATE - 1;
+const KEY_NIBBLES: usize = 64;
+
+/// Hex-prefix encoding of the path of a leaf.
+fn hex_prefix(nibbles: &[u8]) -> Vec<u8> {
+    let (first, rest) = if nibbles.len() % 2 == 1 {
+        (0x30 | nibbles[0], &nibbles[1..])
+    } else {
+        (0x20, nibbles)
+    };
+    std::iter::once(first)
+        .chain(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]))
+        .collect()
+}
+
+fn key_nibbles(slot: &[u8]) -> Vec<u8> {
+    Keccak256::digest(slot)
+        .iter()
+        .flat_map(|b| [b >> 4, b & 0x0f])
+        .collect()
+}
+
+/// A storage proof of the shape `StorageProofFCircuit` verifies.
+#[derive(Clone, Debug)]
+pub struct StorageProof {
+    pub root: [u8; 32],
+    pub slot: [u8; 32],
+    /// Branch nodes, from the root down.
+    pub branches: Vec<Vec<u8>>,
+    pub leaf: Vec<u8>,
+}
+
+impl StorageProof {
+    /// A proof of `value` (big-endian, without leading zeros) under `slot`
+    /// through `depth` branches, whose other children are random hashes or
+    /// empty.
+    pub fn synthetic(depth: usize, slot: [u8; 32], value: &[u8], rng: &mut impl Rng) -> Self {
+        let nibbles = key_nibbles(&slot);
+        let leaf = rlp_list(&[
+            rlp_string(&hex_prefix(&nibbles[depth..])),
+            rlp_string(&rlp_string(value)),
+        ]);
+        let mut hash: [u8; 32] = Keccak256::digest(&leaf).into();
+        let mut branches = Vec::with_capacity(depth);
+        for &nibble in nibbles[..depth].iter().rev() {
+            let mut items: Vec<Vec<u8>> = (0..16)
+                .map(|k| {
+                    if k == nibble {
+                        rlp_string(&hash)
+                    } else if rng.gen_bool(0.5) {
+                        rlp_string(&rng.gen::<[u8; 32]>())
+                    } else {
+                        rlp_string(&[])
+                    }
+                })
+                .collect();
+            items.push(rlp_string(&[]));
+            let node = rlp_list(&items);
+            hash = Keccak256::digest(&node).into();
+            branches.push(node);
+        }
+        branches.reverse();
+        Self {
+            root: hash,
+            slot,
+            branches,
+            leaf,
+        }
+    }
+
+    /// `[root_hi, root_lo]`, the storage root as the state expects it.
+    pub fn root_state<F: PrimeField>(&self) -> Vec<F> {
+        self.root
+            .chunks(16)
+            .map(F::from_be_bytes_mod_order)
+            .collect()
+    }
+
+    pub fn external_inputs<F: PrimeField>(&self) -> Vec<F> {
+        let padded = |node: &[u8], max: usize| {
+            let mut bytes: Vec<F> = node.iter().map(|b| F::from(*b)).collect();
+            bytes.resize(max, F::zero());
+            [vec![F::from(node.len() as u64)], bytes].concat()
+        };
+        let mut inputs: Vec<F> = self.slot.iter().map(|b| F::from(*b)).collect();
+        for node in &self.branches {
+            inputs.extend(padded(node, BRANCH_MAX));
+        }
+        inputs.extend(padded(&self.leaf, LEAF_MAX));
+        inputs
+    }
+}
+
+/// The 32-byte child of the branch `node` under `nibble`.
+fn branch_child_var<F: PrimeField>(
+    node: &[FpVar<F>],
+    len: &FpVar<F>,
+    nibble: &FpVar<F>,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    // the list header matches the node length
+    let is_f8 = FpVar::from(node[0].is_eq(&constant(0xf8))?);
+    let is_f9 = FpVar::from(node[0].is_eq(&constant(0xf9))?);
+    let is_short = FpVar::one() - &is_f8 - &is_f9;
+    (&is_f8 * (&node[1] - len + F::from(2_u64))).enforce_equal(&FpVar::zero())?;
+    (&is_f9 * (&node[1] * F::from(256_u64) + &node[2] - len + F::from(3_u64)))
+        .enforce_equal(&FpVar::zero())?;
+    (&is_short * (&node[0] - len - F::from(0xbf_u64))).enforce_equal(&FpVar::zero())?;
+
+    let nibble_flags = one_hot(nibble, 16)?;
+    let mut offset = FpVar::one() + &is_f8 + is_f9 * F::from(2_u64);
+    let mut child_offset = FpVar::zero();
+    let mut child_is_hash = FpVar::zero();
+    for nibble_flag in &nibble_flags {
+        let prefix = select(&one_hot(&offset, BRANCH_MAX)?, node, 0);
+        let is_hash = FpVar::from(prefix.is_eq(&constant(0xa0))?);
+        let is_empty = FpVar::from(prefix.is_eq(&constant(0x80))?);
+        (&is_hash + is_empty).enforce_equal(&FpVar::one())?;
+        child_offset += nibble_flag * &offset;
+        child_is_hash += nibble_flag * &is_hash;
+        offset = offset + FpVar::one() + is_hash * F::from(32_u64);
+    }
+    child_is_hash.enforce_equal(&FpVar::one())?;
+    // an empty value, and nothing after it
+    select(&one_hot(&offset, BRANCH_MAX)?, node, 0).enforce_equal(&constant(0x80))?;
+    len.enforce_equal(&(offset + FpVar::one()))?;
+
+    let flags = one_hot(&child_offset, BRANCH_MAX)?;
+    Ok((1..=32).map(|t| select(&flags, node, t)).collect())
+}
+
+/// The value of the leaf `node` whose path is `nibbles`.
+fn leaf_value_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    node: &[FpVar<F>],
+    len: &FpVar<F>,
+    nibbles: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let is_f8 = FpVar::from(node[0].is_eq(&constant(0xf8))?);
+    let is_short = FpVar::one() - &is_f8;
+    (&is_f8 * (&node[1] - len + F::from(2_u64))).enforce_equal(&FpVar::zero())?;
+    (&is_short * (&node[0] - len - F::from(0xbf_u64))).enforce_equal(&FpVar::zero())?;
+    // the payload, after a header of 1 or 2 bytes
+    let payload: Vec<FpVar<F>> = (1..node.len() - 1)
+        .map(|t| &node[t] + &is_f8 * (&node[t + 1] - &node[t]))
+        .collect();
+
+    let (first, rest) = if nibbles.len() % 2 == 1 {
+        (&nibbles[0] + F::from(0x30_u64), &nibbles[1..])
+    } else {
+        (constant(0x20), nibbles)
+    };
+    let path: Vec<FpVar<F>> = std::iter::once(first)
+        .chain(
+            rest.chunks(2)
+                .map(|pair| &pair[0] * F::from(16_u64) + &pair[1]),
+        )
+        .collect();
+    let mut vo = 0;
+    if path.len() > 1 {
+        payload[0].enforce_equal(&constant(0x80 + path.len() as u64))?;
+        vo += 1;
+    }
+    for p in &path {
+        payload[vo].enforce_equal(p)?;
+        vo += 1;
+    }
+
+    // either a single byte below 0x80, or `0x80 + 1 + n`, `0x80 + n` and
+    // the `n` bytes of the value
+    let q = &payload[vo];
+    let is_byte = FpVar::from(range_check_var(cs, q, 8)?[7].not());
+    let is_string = FpVar::one() - &is_byte;
+    (&is_string * (q - &payload[vo + 1] - FpVar::one())).enforce_equal(&FpVar::zero())?;
+    let n = &is_string * (&payload[vo + 1] - F::from(0x80_u64));
+    let value = be_value_var(&payload, &constant(vo as u64 + 2), &n)?;
+    len.enforce_equal(&(FpVar::one() + &is_f8 + constant(vo as u64 + 1) + is_string + &n))?;
+    Ok(value + is_byte * q)
+}
+
+#[derive(Clone, Debug)]
+pub struct StorageProofFCircuit<F: PrimeField> {
+    /// Branch nodes above the leaf.
+    pub depth: usize,
+    pub poseidon_config: PoseidonConfig<F>,
+}
+
+impl<F: PrimeField + Absorb> StorageProofFCircuit<F> {
+    /// The value proven by `external_inputs` under `root`, if valid.
+    fn verify(&self, root: &[F], external_inputs: &[F]) -> Option<([F; 2], F)> {
+        let bytes = |xs: &[F]| {
+            xs.iter()
+                .map(|x| u8::try_from(to_u64(*x)?).ok())
+                .collect::<Option<Vec<u8>>>()
+        };
+        let (slot, mut rest) = external_inputs.split_at(32);
+        let slot = bytes(slot)?;
+        let nibbles = key_nibbles(&slot);
+        let mut node = |max: usize| {
+            let (node, tail) = rest.split_at(1 + max);
+            rest = tail;
+            let len = usize::try_from(to_u64(node[0])?).ok()?;
+            bytes(node.get(1..=len).filter(|_| len <= max)?)
+        };
+
+        let mut expected = Vec::with_capacity(32);
+        for half in root {
+            let half = half.into_bigint();
+            if half.num_bits() > 128 {
+                return None;
+            }
+            let be = half.to_bytes_be();
+            expected.extend_from_slice(&be[be.len() - 16..]);
+        }
+        for &nibble in &nibbles[..self.depth] {
+            let branch = node(BRANCH_MAX)?;
+            let items = rlp_list_items(&branch)?;
+            if Keccak256::digest(&branch).as_slice() != expected
+                || items.len() != 17
+                || !items[16].is_empty()
+                || items[..16].iter().any(|c| !c.is_empty() && c.len() != 32)
+                || items[nibble as usize].is_empty()
+            {
+                return None;
+            }
+            expected = items[nibble as usize].to_vec();
+        }
+        let leaf = node(LEAF_MAX)?;
+        let items = rlp_list_items(&leaf)?;
+        if Keccak256::digest(&leaf).as_slice() != expected
+            || items.len() != 2
+            || items[0] != hex_prefix(&nibbles[self.depth..])
+        {
+            return None;
+        }
+        let value = match items[1] {
+            [b] if *b < 0x80 => &items[1][..],
+            [prefix, value @ ..] if *prefix as usize == 0x80 + value.len() && value.len() <= 32 => {
+                value
+            }
+            _ => return None,
+        };
+        let slot_halves = [
+            F::from_be_bytes_mod_order(&slot[..16]),
+            F::from_be_bytes_mod_order(&slot[16..]),
+        ];
+        Some((slot_halves, F::from_be_bytes_mod_order(value)))
+    }
+}
+
+impl<F: PrimeField + Absorb> FCircuit<F> for StorageProofFCircuit<F> {
+    /// Branch nodes above the leaf.
+    type Params = usize;
+
+    fn new(depth: Self::Params) -> Result<Self, Error> {
+        assert!(depth < KEY_NIBBLES, "the leaf holds at least one nibble");
+        Ok(Self {
+            depth,
+            poseidon_config: poseidon_canonical_config::<F>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        3
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        32 + self.depth * (1 + BRANCH_MAX) + 1 + LEAF_MAX
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let (slot, value) = self
+            .verify(&z_i[..2], &external_inputs)
+            .ok_or(Error::NotSatisfied)?;
+        let mut sponge = PoseidonSponge::new(&self.poseidon_config);
+        sponge.absorb(&vec![z_i[2], slot[0], slot[1], value]);
+        Ok(vec![z_i[0], z_i[1], sponge.squeeze_field_elements(1)[0]])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let (slot, mut rest) = external_inputs.split_at(32);
+        let key = keccak256_var_len_gadget(cs.clone(), slot, &constant(32))?;
+        let mut nibbles = Vec::with_capacity(KEY_NIBBLES);
+        for byte in &key {
+            let bits = range_check_var(cs.clone(), byte, 8)?;
+            nibbles.push(Boolean::le_bits_to_fp_var(&bits[4..])?);
+            nibbles.push(Boolean::le_bits_to_fp_var(&bits[..4])?);
+        }
+
+        let mut expected = (z_i[0].clone(), z_i[1].clone());
+        let mut next_node = |max: usize| {
+            let (node, tail) = rest.split_at(1 + max);
+            rest = tail;
+            (node[0].clone(), node[1..].to_vec())
+        };
+        let check_hash = |len: &FpVar<F>, node: &[FpVar<F>], expected: &(FpVar<F>, FpVar<F>)| {
+            let digest = keccak256_var_len_gadget(cs.clone(), node, len)?;
+            pack(&digest[..16]).enforce_equal(&expected.0)?;
+            pack(&digest[16..]).enforce_equal(&expected.1)
+        };
+        for nibble in &nibbles[..self.depth] {
+            let (len, node) = next_node(BRANCH_MAX);
+            check_hash(&len, &node, &expected)?;
+            let child = branch_child_var(&node, &len, nibble)?;
+            expected = (pack(&child[..16]), pack(&child[16..]));
+        }
+        let (len, leaf) = next_node(LEAF_MAX);
+        check_hash(&len, &leaf, &expected)?;
+        let value = leaf_value_var(cs.clone(), &leaf, &len, &nibbles[self.depth..])?;
+
+        let mut sponge = PoseidonSpongeVar::new(cs, &self.poseidon_config);
+        sponge.absorb(&vec![
+            z_i[2].clone(),
+            pack(&slot[..16]),
+            pack(&slot[16..]),
+            value,
+        ])?;
+        Ok(vec![
+            z_i[0].clone(),
+            z_i[1].clone(),
+            sponge.squeeze_field_elements(1)?[0].clone(),
+        ])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_storage_proof_native_vs_gadget() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // one byte below 0x80, and a full 32-byte word
+        for (depth, value) in [(1, vec![0x2a]), (1, vec![0xff; 32])] {
+            let proof = StorageProof::synthetic(depth, [7; 32], &value, &mut rng);
+            let circuit = StorageProofFCircuit::<Fr>::new(depth).unwrap();
+            let z_i = [proof.root_state(), vec![Fr::from(0_u32)]].concat();
+            let external_inputs = proof.external_inputs::<Fr>();
+            assert_eq!(external_inputs.len(), circuit.external_inputs_len());
+            let z_i1 = circuit
+                .step_native(0, z_i.clone(), external_inputs.clone())
+                .unwrap();
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            let z_i1_var = circuit
+                .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+                .unwrap();
+            assert_eq!(z_i1_var.value().unwrap(), z_i1);
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // deeper proofs, and a proof of another slot, natively
+        let circuit = StorageProofFCircuit::<Fr>::new(4).unwrap();
+        let proof = StorageProof::synthetic(4, [1; 32], &[1, 2, 3], &mut rng);
+        let z_i = [proof.root_state(), vec![Fr::from(0_u32)]].concat();
+        assert!(circuit
+            .step_native(0, z_i.clone(), proof.external_inputs())
+            .is_ok());
+        let mut other_slot = proof.clone();
+        other_slot.slot[0] ^= 1;
+        assert!(circuit
+            .step_native(0, z_i, other_slot.external_inputs())
+            .is_err());
+    }
+
+    #[test]
+    fn test_storage_proof_consistent() {
+        let circuit = StorageProofFCircuit::<Fr>::new(1).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let mut value: Vec<u8> = (0..rng.gen_range(1..=32)).map(|_| rng.gen()).collect();
+            // without leading zeros
+            value[0] = value[0].max(1);
+            let proof = StorageProof::synthetic(1, rng.gen(), &value, rng);
+            let z_i = [proof.root_state(), vec![Fr::rand(rng)]].concat();
+            (z_i, proof.external_inputs())
+        });
+    }
+}
@@ -0,0 +1,91 @@
+//! Cubic circuit over a state of `N` elements, with `N` chosen at runtime, for
+//! studying how the IVC cost scales with the state width.
+//!
+//! Each element is updated as `z_j^3 + z_{j+1} + 5` (indices mod `N`), so every
+//! element depends on its neighbour and none can be optimized away.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+#[derive(Clone, Copy, Debug)]
+pub struct MultiStateFCircuit<F: PrimeField> {
+    pub state_len: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for MultiStateFCircuit<F> {
+    type Params = usize;
+
+    fn new(state_len: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            state_len,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.state_len
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let n = self.state_len;
+        Ok((0..n)
+            .map(|j| z_i[j] * z_i[j] * z_i[j] + z_i[(j + 1) % n] + F::from(5_u32))
+            .collect())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let n = self.state_len;
+        let five = FpVar::<F>::new_constant(cs.clone(), F::from(5u32))?;
+        Ok((0..n)
+            .map(|j| &z_i[j] * &z_i[j] * &z_i[j] + &z_i[(j + 1) % n] + &five)
+            .collect())
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_multi_state_native_vs_gadget() {
+        let circuit = MultiStateFCircuit::<Fr>::new(5).unwrap();
+        let z_i: Vec<Fr> = (0..5_u32).map(Fr::from).collect();
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_multi_state_consistent() {
+        assert_fcircuit_consistent(&MultiStateFCircuit::<Fr>::new(5).unwrap());
+    }
+}
@@ -0,0 +1,123 @@
+//! Emulated (non-native) field arithmetic stress circuit.
+//!
+//! Each step reads the low `PACKED_BITS` bits of the state as an element `a`
+//! of a foreign field `TF` (e.g. a 256-bit field), iterates `acc = acc^2 + a`
+//! `n_ops` times with emulated arithmetic, and writes the low `PACKED_BITS` bits
+//! of the result back to the state. Every iteration is one emulated
+//! multiplication and one emulated addition.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, nonnative::NonNativeFieldVar, FieldVar},
+    R1CSVar, ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Bits carried between the native state and the emulated field, fitting in
+/// both.
+const PACKED_BITS: usize = 248;
+
+fn truncate<F: PrimeField, TF: PrimeField>(x: F) -> TF {
+    let mut bits = x.into_bigint().to_bits_le();
+    bits.truncate(PACKED_BITS);
+    TF::from_bigint(TF::BigInt::from_bits_le(&bits)).unwrap()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct NonNativeFCircuit<F: PrimeField, TF: PrimeField> {
+    /// Emulated multiply-adds per step.
+    pub n_ops: usize,
+    _f: PhantomData<(F, TF)>,
+}
+
+impl<F: PrimeField, TF: PrimeField> FCircuit<F> for NonNativeFCircuit<F, TF> {
+    type Params = usize;
+
+    fn new(n_ops: Self::Params) -> Result<Self, Error> {
+        assert!(TF::MODULUS_BIT_SIZE as usize > PACKED_BITS);
+        Ok(Self {
+            n_ops,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let a: TF = truncate(z_i[0]);
+        let acc = (0..self.n_ops).fold(a, |acc, _| acc.square() + a);
+        Ok(vec![truncate(acc)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let z_bits = z_i[0].to_bits_le()?;
+        let a = NonNativeFieldVar::<TF, F>::new_witness(cs, || Ok(truncate::<F, TF>(z_i[0].value()?)))?;
+        // link the emulated element to the low bits of the state
+        let a_bits = a.to_bits_le()?;
+        for (j, bit) in a_bits.iter().enumerate() {
+            if j < PACKED_BITS {
+                bit.enforce_equal(&z_bits[j])?;
+            } else {
+                bit.enforce_equal(&Boolean::FALSE)?;
+            }
+        }
+
+        let mut acc = a.clone();
+        for _ in 0..self.n_ops {
+            acc = acc.square()? + &a;
+        }
+        Ok(vec![Boolean::le_bits_to_fp_var(
+            &acc.to_bits_le()?[..PACKED_BITS],
+        )?])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::{Fq, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_nonnative_native_vs_gadget() {
+        let circuit = NonNativeFCircuit::<Fr, Fq>::new(3).unwrap();
+        let z_i = vec![-Fr::from(3_u32)];
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_nonnative_consistent() {
+        assert_fcircuit_consistent(&NonNativeFCircuit::<Fr, Fq>::new(3).unwrap());
+    }
+}
@@ -0,0 +1,88 @@
+//! Synthetic circuit generating exactly `K` constraints per step, for sweeping
+//! the augmented-circuit size precisely.
+//!
+//! The step iterates `t = t^2 + z` `K` times starting from `t = z`; each
+//! squaring is one multiplication constraint and the additions are linear
+//! combinations, so the step contributes exactly `K` constraints.
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+#[derive(Clone, Copy, Debug)]
+pub struct PaddingFCircuit<F: PrimeField> {
+    pub n_constraints: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for PaddingFCircuit<F> {
+    type Params = usize;
+
+    fn new(n_constraints: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_constraints,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let z = z_i[0];
+        Ok(vec![(0..self.n_constraints).fold(z, |t, _| t.square() + z)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let z = &z_i[0];
+        Ok(vec![(0..self.n_constraints).fold(z.clone(), |t, _| &t * &t + z)])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_padding_constraint_count() {
+        let circuit = PaddingFCircuit::<Fr>::new(1000).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(cs.num_constraints(), 1000);
+    }
+
+    #[test]
+    fn test_padding_consistent() {
+        assert_fcircuit_consistent(&PaddingFCircuit::<Fr>::new(100).unwrap());
+    }
+}
@@ -0,0 +1,157 @@
+//! Pedersen hash chain over the embedded twisted Edwards curve (e.g.
+//! BabyJubJub for BN254), a curve-arithmetic workload unlike the sponges.
+//!
+//! Each step hashes `n` field elements, the state followed by `n - 1`
+//! external inputs, as `sum_k x_k * G_k` with one fixed generator per input,
+//! and keeps the x-coordinate as the new state. In-circuit, every input costs
+//! its bit decomposition and a fixed-base scalar multiplication with
+//! precomputed doublings, i.e. one point addition per bit.
+use ark_ec::{
+    twisted_edwards::{Projective, TECurveConfig},
+    CurveGroup, Group,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    fields::fp::FpVar,
+    groups::{curves::twisted_edwards::AffineVar, CurveVar},
+    ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::UniformRand;
+use rand::{rngs::StdRng, SeedableRng};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+/// Deterministic generators, so runs are comparable.
+pub fn pedersen_generators<P: TECurveConfig>(n: usize) -> Vec<Projective<P>> {
+    (0..n as u64)
+        .map(|k| Projective::<P>::rand(&mut StdRng::seed_from_u64(k)))
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct PedersenFCircuit<P: TECurveConfig>
+where
+    P::BaseField: PrimeField,
+{
+    /// Inputs hashed per step, the state included.
+    pub input_len: usize,
+    pub generators: Vec<Projective<P>>,
+    /// `2^j * G_k` for every bit `j` of an input, per generator.
+    powers: Vec<Vec<Projective<P>>>,
+}
+
+impl<P: TECurveConfig> PedersenFCircuit<P>
+where
+    P::BaseField: PrimeField,
+{
+    pub fn hash(&self, input: &[P::BaseField]) -> P::BaseField {
+        let point: Projective<P> = self
+            .generators
+            .iter()
+            .zip(input)
+            .map(|(g, x)| g.mul_bigint(x.into_bigint()))
+            .sum();
+        point.into_affine().x
+    }
+
+    pub fn hash_var(
+        &self,
+        input: &[FpVar<P::BaseField>],
+    ) -> Result<FpVar<P::BaseField>, SynthesisError> {
+        let mut point = AffineVar::<P, FpVar<P::BaseField>>::zero();
+        for (powers, x) in self.powers.iter().zip(input) {
+            point.precomputed_base_scalar_mul_le(x.to_bits_le()?.into_iter().zip(powers))?;
+        }
+        Ok(point.x)
+    }
+}
+
+impl<P: TECurveConfig> FCircuit<P::BaseField> for PedersenFCircuit<P>
+where
+    P::BaseField: PrimeField,
+{
+    /// Inputs hashed per step, at least 1.
+    type Params = usize;
+
+    fn new(input_len: Self::Params) -> Result<Self, Error> {
+        assert!(input_len > 0, "the state is always hashed");
+        let generators = pedersen_generators::<P>(input_len);
+        let bits = P::BaseField::MODULUS_BIT_SIZE as usize;
+        let powers = generators
+            .iter()
+            .map(|g| {
+                std::iter::successors(Some(*g), |p| Some(p.double()))
+                    .take(bits)
+                    .collect()
+            })
+            .collect();
+        Ok(Self {
+            input_len,
+            generators,
+            powers,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.input_len - 1
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<P::BaseField>,
+        external_inputs: Vec<P::BaseField>,
+    ) -> Result<Vec<P::BaseField>, Error> {
+        Ok(vec![self.hash(&[z_i, external_inputs].concat())])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<P::BaseField>,
+        _i: usize,
+        z_i: Vec<FpVar<P::BaseField>>,
+        external_inputs: Vec<FpVar<P::BaseField>>,
+    ) -> Result<Vec<FpVar<P::BaseField>>, SynthesisError> {
+        Ok(vec![self.hash_var(&[z_i, external_inputs].concat())?])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_ed_on_bn254::EdwardsConfig;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_pedersen_native_vs_gadget() {
+        let circuit = PedersenFCircuit::<EdwardsConfig>::new(3).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let external_inputs = vec![Fr::from(5_u32), -Fr::from(1_u32)];
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_pedersen_consistent() {
+        assert_fcircuit_consistent(&PedersenFCircuit::<EdwardsConfig>::new(3).unwrap());
+    }
+}
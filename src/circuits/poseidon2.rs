@@ -0,0 +1,293 @@
+//! Poseidon and Poseidon2 hash chains of the same width, to compare the two
+//! permutations on identical chain lengths.
+//!
+//! Each step replaces the state with the hash of it, `k` times: the state is
+//! absorbed into a width-3 sponge (rate 2, capacity 1) and one element is
+//! squeezed, i.e. one permutation per hash. Both use `x^5` S-boxes, 8 full
+//! rounds and the partial rounds of their reference instances for 254-bit
+//! fields (57 for Poseidon, 56 for Poseidon2).
+//!
+//! Poseidon2 replaces Poseidon's dense MDS matrix with cheap structured
+//! matrices. That saves native work only: linear layers are free in R1CS, so
+//! in-circuit the two differ by about the one partial round.
+//! Its round constants are drawn from the same Grain LFSR as Poseidon's
+//! rather than taken from the Poseidon2 reference, which changes no cost.
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{
+        constraints::PoseidonSpongeVar, find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge,
+    },
+    CryptographicSponge,
+};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::{fp::FpVar, FieldVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const WIDTH: usize = 3;
+const ALPHA: u64 = 5;
+const FULL_ROUNDS: usize = 8;
+const POSEIDON_PARTIAL_ROUNDS: usize = 57;
+const POSEIDON2_PARTIAL_ROUNDS: usize = 56;
+/// Diagonal of the internal matrix `M_I = diag + 1` for width 3.
+const INTERNAL_DIAGONAL: [u64; WIDTH] = [1, 1, 2];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PoseidonVersion {
+    Poseidon,
+    Poseidon2,
+}
+
+/// Width-3 Poseidon config of the chain.
+pub fn poseidon_config<F: PrimeField>() -> PoseidonConfig<F> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        WIDTH - 1,
+        FULL_ROUNDS as u64,
+        POSEIDON_PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(FULL_ROUNDS, POSEIDON_PARTIAL_ROUNDS, ALPHA, mds, ark, WIDTH - 1, 1)
+}
+
+/// Round constants of the Poseidon2 permutation: `WIDTH` per full round, the
+/// first one per partial round.
+#[derive(Clone, Debug)]
+pub struct Poseidon2Params<F: PrimeField> {
+    pub ark: Vec<[F; WIDTH]>,
+}
+
+impl<F: PrimeField> Poseidon2Params<F> {
+    pub fn new() -> Self {
+        let (ark, _) = find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
+            WIDTH - 1,
+            FULL_ROUNDS as u64,
+            POSEIDON2_PARTIAL_ROUNDS as u64,
+            0,
+        );
+        Self {
+            ark: ark.into_iter().map(|row| [row[0], row[1], row[2]]).collect(),
+        }
+    }
+
+    /// The permutation, generic over native and in-circuit arithmetic.
+    fn permute<T: Poseidon2Arith<F>>(&self, mut state: [T; WIDTH]) -> Result<[T; WIDTH], T::Err> {
+        state = external_matrix(state);
+        let (first, rest) = self.ark.split_at(FULL_ROUNDS / 2);
+        let (partial, last) = rest.split_at(POSEIDON2_PARTIAL_ROUNDS);
+        for round in first {
+            for (x, c) in state.iter_mut().zip(round) {
+                *x = x.add_constant(*c).pow5()?;
+            }
+            state = external_matrix(state);
+        }
+        for round in partial {
+            state[0] = state[0].add_constant(round[0]).pow5()?;
+            state = internal_matrix(state);
+        }
+        for round in last {
+            for (x, c) in state.iter_mut().zip(round) {
+                *x = x.add_constant(*c).pow5()?;
+            }
+            state = external_matrix(state);
+        }
+        Ok(state)
+    }
+
+    pub fn hash(&self, x: F) -> F {
+        match self.permute([Native(x), Native(F::zero()), Native(F::zero())]) {
+            Ok(state) => state[0].0,
+            Err(never) => match never {},
+        }
+    }
+
+    pub fn hash_var(&self, x: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        let state = self.permute([x.clone(), FpVar::zero(), FpVar::zero()])?;
+        Ok(state[0].clone())
+    }
+}
+
+impl<F: PrimeField> Default for Poseidon2Params<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `circ(2, 1, 1)`: every element plus the sum.
+fn external_matrix<T: Clone + std::ops::Add<Output = T>>(state: [T; WIDTH]) -> [T; WIDTH] {
+    let sum = state[0].clone() + state[1].clone() + state[2].clone();
+    state.map(|x| x + sum.clone())
+}
+
+/// `diag(1, 1, 2) + 1`: every element times its diagonal entry plus the sum.
+fn internal_matrix<T: Clone + std::ops::Add<Output = T>>(state: [T; WIDTH]) -> [T; WIDTH] {
+    let sum = state[0].clone() + state[1].clone() + state[2].clone();
+    let mut i = 0;
+    state.map(|x| {
+        let scaled = (1..INTERNAL_DIAGONAL[i]).fold(x.clone(), |acc, _| acc + x.clone());
+        i += 1;
+        scaled + sum.clone()
+    })
+}
+
+/// What `permute` needs of field elements and their variables.
+trait Poseidon2Arith<F>: Clone + std::ops::Add<Output = Self> + Sized {
+    type Err;
+
+    fn add_constant(&self, c: F) -> Self;
+    fn pow5(&self) -> Result<Self, Self::Err>;
+}
+
+#[derive(Clone, Copy)]
+struct Native<F>(F);
+
+impl<F: PrimeField> std::ops::Add for Native<F> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Native(self.0 + other.0)
+    }
+}
+
+impl<F: PrimeField> Poseidon2Arith<F> for Native<F> {
+    type Err = std::convert::Infallible;
+
+    fn add_constant(&self, c: F) -> Self {
+        Native(self.0 + c)
+    }
+
+    fn pow5(&self) -> Result<Self, Self::Err> {
+        Ok(Native(self.0.pow([ALPHA])))
+    }
+}
+
+impl<F: PrimeField> Poseidon2Arith<F> for FpVar<F> {
+    type Err = SynthesisError;
+
+    fn add_constant(&self, c: F) -> Self {
+        self + c
+    }
+
+    fn pow5(&self) -> Result<Self, Self::Err> {
+        let x4 = self.square()?.square()?;
+        Ok(x4 * self)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Poseidon2FCircuit<F: PrimeField> {
+    pub version: PoseidonVersion,
+    /// Number of hashes per step.
+    pub n_hashes: usize,
+    pub poseidon_config: PoseidonConfig<F>,
+    pub poseidon2: Poseidon2Params<F>,
+}
+
+impl<F: PrimeField> Poseidon2FCircuit<F> {
+    fn hash(&self, x: F) -> F {
+        match self.version {
+            PoseidonVersion::Poseidon => {
+                let mut sponge = PoseidonSponge::new(&self.poseidon_config);
+                sponge.absorb(&x);
+                sponge.squeeze_field_elements(1)[0]
+            }
+            PoseidonVersion::Poseidon2 => self.poseidon2.hash(x),
+        }
+    }
+
+    fn hash_var(&self, cs: ConstraintSystemRef<F>, x: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        match self.version {
+            PoseidonVersion::Poseidon => {
+                let mut sponge = PoseidonSpongeVar::new(cs, &self.poseidon_config);
+                sponge.absorb(x)?;
+                Ok(sponge.squeeze_field_elements(1)?[0].clone())
+            }
+            PoseidonVersion::Poseidon2 => self.poseidon2.hash_var(x),
+        }
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for Poseidon2FCircuit<F> {
+    /// Permutation and hashes per step.
+    type Params = (PoseidonVersion, usize);
+
+    fn new((version, n_hashes): Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            version,
+            n_hashes,
+            poseidon_config: poseidon_config(),
+            poseidon2: Poseidon2Params::new(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        Ok(vec![(0..self.n_hashes).fold(z_i[0], |z, _| self.hash(z))])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut z = z_i[0].clone();
+        for _ in 0..self.n_hashes {
+            z = self.hash_var(cs.clone(), &z)?;
+        }
+        Ok(vec![z])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_poseidon2_native_vs_gadget() {
+        for version in [PoseidonVersion::Poseidon, PoseidonVersion::Poseidon2] {
+            let circuit = Poseidon2FCircuit::<Fr>::new((version, 2)).unwrap();
+            let z_i = vec![Fr::from(3_u32)];
+            let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+            assert_ne!(z_i1, z_i);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+            let z_i1_var = circuit
+                .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+                .unwrap();
+            assert_eq!(z_i1_var.value().unwrap(), z_i1);
+            assert!(cs.is_satisfied().unwrap());
+            if version == PoseidonVersion::Poseidon2 {
+                // 8 * 3 + 56 S-boxes per hash, 3 constraints each
+                assert_eq!(cs.num_constraints(), 2 * 80 * 3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_poseidon2_consistent() {
+        for version in [PoseidonVersion::Poseidon, PoseidonVersion::Poseidon2] {
+            assert_fcircuit_consistent(&Poseidon2FCircuit::<Fr>::new((version, 2)).unwrap());
+        }
+    }
+}
@@ -0,0 +1,453 @@
+//! Step circuits from compiled `.r1cs` files, for benchmarking existing
+//! circuits without writing gadget code.
+//!
+//! The `.r1cs` file (iden3 binary format, as written by `circom --r1cs`) gives
+//! the constraints; a witness generator gives the values of all wires for a
+//! step. The wires follow Circom's order for the main component: wire 0 is
+//! the constant one, then the next state (`state_len` outputs), the current
+//! state (`state_len` inputs), the external inputs, and the internal wires.
+//!
+//! Unlike `circom::load_circom` this needs no `.wasm`: the generator is any
+//! closure, or with `command_witness` a native witness calculator (Circom's
+//! C++ one, `./circuit input.json witness.wtns`). sonobe synthesizes the
+//! augmented circuit once from a zero state to get its shape, so the generator
+//! must accept an all-zero state.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    fields::fp::{AllocatedFp, FpVar},
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use crate::error::BenchError;
+
+/// Linear combination of wires, `(wire, coefficient)`.
+pub type Terms<F> = Vec<(usize, F)>;
+
+/// Constraints of a `.r1cs` file, `A · B = C` each.
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub struct R1csFile<F: PrimeField> {
+    pub n_wires: usize,
+    pub n_pub_out: usize,
+    pub n_pub_in: usize,
+    pub n_prv_in: usize,
+    pub constraints: Vec<[Terms<F>; 3]>,
+}
+
+/// Reader over the sections of the iden3 binary formats (`.r1cs`, `.wtns`).
+struct Sections<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Sections<'a> {
+    /// Checks the magic and returns the sections, `(type, contents)`.
+    fn parse(bytes: &'a [u8], magic: &[u8; 4]) -> Result<Vec<(u32, &'a [u8])>, BenchError> {
+        let mut reader = Sections { bytes };
+        if reader.take(4)? != magic {
+            return Err(invalid(format!("not a {} file", String::from_utf8_lossy(magic))));
+        }
+        let _version = reader.u32()?;
+        let n_sections = reader.u32()?;
+        (0..n_sections)
+            .map(|_| {
+                let kind = reader.u32()?;
+                let len = reader.u64()? as usize;
+                Ok((kind, reader.take(len)?))
+            })
+            .collect()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], BenchError> {
+        if self.bytes.len() < n {
+            return Err(invalid("truncated file".to_string()));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, BenchError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, BenchError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// A field element of `n8` bytes, little-endian.
+    fn field<F: PrimeField>(&mut self, n8: usize) -> Result<F, BenchError> {
+        Ok(F::from_le_bytes_mod_order(self.take(n8)?))
+    }
+
+    /// Reads `n8` and the prime of a header and checks it is `F`'s modulus.
+    fn prime<F: PrimeField>(&mut self) -> Result<usize, BenchError> {
+        let n8 = self.u32()? as usize;
+        let prime = self.take(n8)?;
+        let mut modulus = F::MODULUS.to_bytes_le();
+        if modulus.len() < n8 {
+            modulus.resize(n8, 0);
+        }
+        if prime != modulus.as_slice() {
+            return Err(invalid("compiled for another field".to_string()));
+        }
+        Ok(n8)
+    }
+}
+
+fn invalid(msg: String) -> BenchError {
+    BenchError::InvalidInput(msg)
+}
+
+fn section<'a>(sections: &[(u32, &'a [u8])], kind: u32) -> Result<Sections<'a>, BenchError> {
+    sections
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, bytes)| Sections { bytes })
+        .ok_or_else(|| invalid(format!("missing section {}", kind)))
+}
+
+impl<F: PrimeField> R1csFile<F> {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, BenchError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, BenchError> {
+        let sections = Sections::parse(bytes, b"r1cs")?;
+        let mut header = section(&sections, 1)?;
+        let n8 = header.prime::<F>()?;
+        let n_wires = header.u32()? as usize;
+        let n_pub_out = header.u32()? as usize;
+        let n_pub_in = header.u32()? as usize;
+        let n_prv_in = header.u32()? as usize;
+        let _n_labels = header.u64()?;
+        let n_constraints = header.u32()? as usize;
+
+        let mut body = section(&sections, 2)?;
+        let mut terms = || -> Result<Terms<F>, BenchError> {
+            let n = body.u32()? as usize;
+            (0..n)
+                .map(|_| {
+                    let wire = body.u32()? as usize;
+                    if wire >= n_wires {
+                        return Err(invalid(format!("wire {} out of range", wire)));
+                    }
+                    Ok((wire, body.field(n8)?))
+                })
+                .collect()
+        };
+        let constraints = (0..n_constraints)
+            .map(|_| Ok([terms()?, terms()?, terms()?]))
+            .collect::<Result<_, BenchError>>()?;
+        Ok(Self {
+            n_wires,
+            n_pub_out,
+            n_pub_in,
+            n_prv_in,
+            constraints,
+        })
+    }
+}
+
+/// Reads a `.wtns` file: the values of all wires, wire 0 first.
+pub fn read_wtns<F: PrimeField>(bytes: &[u8]) -> Result<Vec<F>, BenchError> {
+    let sections = Sections::parse(bytes, b"wtns")?;
+    let mut header = section(&sections, 1)?;
+    let n8 = header.prime::<F>()?;
+    let n_witness = header.u32()? as usize;
+    let mut values = section(&sections, 2)?;
+    (0..n_witness).map(|_| values.field(n8)).collect()
+}
+
+/// Computes all wire values of a step from the current state and the external
+/// inputs.
+pub type WitnessFn<F> = Arc<dyn Fn(&[F], &[F]) -> Result<Vec<F>, BenchError> + Send + Sync>;
+
+/// Witness generator running `program args.. input.json witness.wtns`, with
+/// the input `{"ivc_input": [..], "external_inputs": [..]}` in decimal.
+pub fn command_witness<F: PrimeField>(
+    program: impl Into<PathBuf>,
+    args: Vec<String>,
+) -> WitnessFn<F> {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let program = program.into();
+    Arc::new(move |z_i: &[F], external_inputs: &[F]| {
+        let decimal = |values: &[F]| {
+            values.iter().map(|v| format!("\"{}\"", v.into_bigint())).collect::<Vec<_>>().join(",")
+        };
+        let dir = std::env::temp_dir().join(format!(
+            "sonobe-bench-witness-{}-{}",
+            std::process::id(),
+            CALLS.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        let (input, output) = (dir.join("input.json"), dir.join("witness.wtns"));
+        fs::write(
+            &input,
+            format!(
+                "{{\"ivc_input\": [{}], \"external_inputs\": [{}]}}",
+                decimal(z_i),
+                decimal(external_inputs)
+            ),
+        )?;
+        let status = Command::new(&program).args(&args).arg(&input).arg(&output).status()?;
+        let witness = if status.success() {
+            read_wtns(&fs::read(&output)?)
+        } else {
+            Err(invalid(format!("{:?} exited with {}", program, status)))
+        };
+        fs::remove_dir_all(&dir)?;
+        witness
+    })
+}
+
+#[derive(Clone)]
+pub struct R1csParams<F: PrimeField> {
+    pub r1cs: Arc<R1csFile<F>>,
+    pub state_len: usize,
+    pub external_inputs_len: usize,
+    pub witness: WitnessFn<F>,
+}
+
+impl<F: PrimeField> fmt::Debug for R1csParams<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("R1csParams")
+            .field("wires", &self.r1cs.n_wires)
+            .field("constraints", &self.r1cs.constraints.len())
+            .field("state_len", &self.state_len)
+            .field("external_inputs_len", &self.external_inputs_len)
+            .finish()
+    }
+}
+
+/// Hashes the constraints and layout; the witness generator does not shape
+/// the params.
+impl<F: PrimeField> Hash for R1csParams<F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.r1cs.hash(state);
+        self.state_len.hash(state);
+        self.external_inputs_len.hash(state);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct R1csFCircuit<F: PrimeField> {
+    params: R1csParams<F>,
+}
+
+impl<F: PrimeField> R1csFCircuit<F> {
+    /// Witness of a step, checked to have one value per wire.
+    fn witness(&self, z_i: &[F], external_inputs: &[F]) -> Result<Vec<F>, BenchError> {
+        let witness = (self.params.witness)(z_i, external_inputs)?;
+        if witness.len() != self.params.r1cs.n_wires {
+            return Err(invalid(format!(
+                "witness has {} values for {} wires",
+                witness.len(),
+                self.params.r1cs.n_wires
+            )));
+        }
+        Ok(witness)
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for R1csFCircuit<F> {
+    type Params = R1csParams<F>;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        let r1cs = &params.r1cs;
+        let inputs = params.state_len + params.external_inputs_len;
+        if r1cs.n_pub_out != params.state_len || r1cs.n_pub_in + r1cs.n_prv_in != inputs {
+            return Err(io::Error::other(format!(
+                "circuit has {} outputs and {} inputs, expected {} and {}",
+                r1cs.n_pub_out,
+                r1cs.n_pub_in + r1cs.n_prv_in,
+                params.state_len,
+                inputs
+            ))
+            .into());
+        }
+        Ok(Self { params })
+    }
+
+    fn state_len(&self) -> usize {
+        self.params.state_len
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.params.external_inputs_len
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let witness = self
+            .witness(&z_i, &external_inputs)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(witness[1..=self.params.state_len].to_vec())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let r1cs = &self.params.r1cs;
+        let state_len = self.params.state_len;
+        // no values while only the shape is synthesized
+        let witness = match (z_i.value(), external_inputs.value()) {
+            (Ok(z_i), Ok(external_inputs)) => Some(
+                self.witness(&z_i, &external_inputs)
+                    .map_err(|_| SynthesisError::Unsatisfiable)?,
+            ),
+            _ => None,
+        };
+        let witness = witness.as_ref();
+        let value =
+            |wire: usize| move || witness.map(|w| w[wire]).ok_or(SynthesisError::AssignmentMissing);
+
+        // every wire as `coefficient * variable`
+        let mut wires = Vec::with_capacity(r1cs.n_wires);
+        wires.push((F::one(), Variable::One));
+        let z_i1 = (1..=state_len)
+            .map(|wire| AllocatedFp::new_witness(cs.clone(), value(wire)))
+            .collect::<Result<Vec<_>, _>>()?;
+        wires.extend(z_i1.iter().map(|z| (F::one(), z.variable)));
+        for input in z_i.iter().chain(&external_inputs) {
+            wires.push(match input {
+                FpVar::Constant(c) => (*c, Variable::One),
+                FpVar::Var(v) => (F::one(), v.variable),
+            });
+        }
+        for wire in wires.len()..r1cs.n_wires {
+            wires.push((F::one(), cs.new_witness_variable(value(wire))?));
+        }
+
+        let lc = |terms: &Terms<F>| {
+            terms.iter().fold(LinearCombination::zero(), |lc, (wire, coeff)| {
+                let (scale, variable) = wires[*wire];
+                lc + (*coeff * scale, variable)
+            })
+        };
+        for [a, b, c] in &r1cs.constraints {
+            cs.enforce_constraint(lc(a), lc(b), lc(c))?;
+        }
+        Ok(z_i1.into_iter().map(FpVar::Var).collect())
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::CubicFCircuit;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn push_terms(out: &mut Vec<u8>, terms: &[(u32, u64)]) {
+        out.extend((terms.len() as u32).to_le_bytes());
+        for (wire, coeff) in terms {
+            out.extend(wire.to_le_bytes());
+            out.extend(Fr::from(*coeff).into_bigint().to_bytes_le());
+        }
+    }
+
+    fn push_section(out: &mut Vec<u8>, kind: u32, contents: &[u8]) {
+        out.extend(kind.to_le_bytes());
+        out.extend((contents.len() as u64).to_le_bytes());
+        out.extend(contents);
+    }
+
+    /// `x^3 + x + 5` with wires `[1, out, x, x2, x3]`.
+    fn cubic_r1cs() -> Vec<u8> {
+        let modulus = Fr::MODULUS.to_bytes_le();
+        let mut header = Vec::new();
+        header.extend(32_u32.to_le_bytes());
+        header.extend(&modulus);
+        for n in [5_u32, 1, 0, 1] {
+            header.extend(n.to_le_bytes());
+        }
+        header.extend(5_u64.to_le_bytes());
+        header.extend(3_u32.to_le_bytes());
+
+        let mut constraints = Vec::new();
+        // x * x = x2, x2 * x = x3, (x3 + x + 5) * 1 = out
+        for (a, b, c) in [
+            (vec![(2, 1)], vec![(2, 1)], vec![(3, 1)]),
+            (vec![(3, 1)], vec![(2, 1)], vec![(4, 1)]),
+            (vec![(4, 1), (2, 1), (0, 5)], vec![(0, 1)], vec![(1, 1)]),
+        ] {
+            push_terms(&mut constraints, &a);
+            push_terms(&mut constraints, &b);
+            push_terms(&mut constraints, &c);
+        }
+
+        let mut out = b"r1cs".to_vec();
+        out.extend(1_u32.to_le_bytes());
+        out.extend(2_u32.to_le_bytes());
+        push_section(&mut out, 1, &header);
+        push_section(&mut out, 2, &constraints);
+        out
+    }
+
+    fn cubic_witness(z_i: &[Fr], _: &[Fr]) -> Result<Vec<Fr>, BenchError> {
+        let x = z_i[0];
+        Ok(vec![Fr::from(1_u32), x * x * x + x + Fr::from(5_u32), x, x * x, x * x * x])
+    }
+
+    #[test]
+    fn test_r1cs_file_matches_cubic() {
+        let r1cs = R1csFile::<Fr>::parse(&cubic_r1cs()).unwrap();
+        assert_eq!((r1cs.n_wires, r1cs.constraints.len()), (5, 3));
+        let circuit = R1csFCircuit::<Fr>::new(R1csParams {
+            r1cs: Arc::new(r1cs),
+            state_len: 1,
+            external_inputs_len: 0,
+            witness: Arc::new(cubic_witness),
+        })
+        .unwrap();
+
+        let z_i = vec![Fr::from(3_u32)];
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        assert_eq!(
+            circuit.step_native(0, z_i.clone(), vec![]).unwrap(),
+            cubic.step_native(0, z_i.clone(), vec![]).unwrap()
+        );
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1 = circuit.generate_step_constraints(cs.clone(), 0, z, vec![]).unwrap();
+        assert_eq!(z_i1.value().unwrap(), vec![Fr::from(35_u32)]);
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut bytes = cubic_r1cs();
+        bytes[0] = b'x';
+        assert!(R1csFile::<Fr>::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_r1cs_file_consistent() {
+        let circuit = R1csFCircuit::<Fr>::new(R1csParams {
+            r1cs: Arc::new(R1csFile::<Fr>::parse(&cubic_r1cs()).unwrap()),
+            state_len: 1,
+            external_inputs_len: 0,
+            witness: Arc::new(cubic_witness),
+        })
+        .unwrap();
+        assert_fcircuit_consistent(&circuit);
+    }
+}
@@ -0,0 +1,395 @@
+//! RLP decoding, the first step of processing any Ethereum data (transactions,
+//! receipts, block headers, trie nodes) in a circuit.
+//!
+//! Each step decodes a flat RLP list of `n_items` strings, supplied as a
+//! zero-padded byte payload of up to `max_len` bytes, and adds the fields at
+//! the chosen indices, read as big-endian integers of at most 32 bytes reduced
+//! into the field, to the state: with the value field of a stream of
+//! transactions, the state is their total value.
+//!
+//! The items sit at offsets known only to the prover, so every header is read
+//! through a one-hot selection over the payload and every exposed field
+//! through 32 of them: the cost is linear in `max_len` per item and per field.
+//! Length prefixes are limited to two bytes, enough for payloads under 64 KiB.
+//!
+//! The external inputs are `[len, payload[0..max_len]]`, one byte per element.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::uint64::range_check_var;
+
+fn rlp_length_prefix(len: usize, short_base: u8) -> Vec<u8> {
+    if len < 56 {
+        vec![short_base + len as u8]
+    } else {
+        let len_bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        [vec![short_base + 55 + len_bytes.len() as u8], len_bytes].concat()
+    }
+}
+
+/// RLP encoding of the string `bytes`.
+pub fn rlp_string(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        bytes.to_vec()
+    } else {
+        [rlp_length_prefix(bytes.len(), 0x80), bytes.to_vec()].concat()
+    }
+}
+
+/// RLP encoding of the list of the already encoded `items`.
+pub fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    [rlp_length_prefix(payload.len(), 0xc0), payload].concat()
+}
+
+/// Payloads of the strings of the RLP list `bytes`, or `None` if it is not a
+/// list of strings with length prefixes of at most two bytes.
+pub fn rlp_list_items(bytes: &[u8]) -> Option<Vec<&[u8]>> {
+    // (header length, payload length) of the item at the start of `b`
+    fn header(b: &[u8]) -> Option<(usize, usize)> {
+        let long = |base: u8| {
+            let n = (b[0] - base) as usize;
+            let len = b
+                .get(1..1 + n)
+                .filter(|_| n <= 2)?
+                .iter()
+                .fold(0, |l, x| (l << 8) | *x as usize);
+            Some((1 + n, len))
+        };
+        match *b.first()? {
+            0x00..=0x7f => Some((0, 1)),
+            p @ 0x80..=0xb7 => Some((1, (p - 0x80) as usize)),
+            0xb8..=0xbf => long(0xb7),
+            p @ 0xc0..=0xf7 => Some((1, (p - 0xc0) as usize)),
+            0xf8..=0xff => long(0xf7),
+        }
+    }
+
+    if *bytes.first()? < 0xc0 {
+        return None;
+    }
+    let (h, len) = header(bytes)?;
+    if h + len != bytes.len() {
+        return None;
+    }
+    let mut rest = &bytes[h..];
+    let mut items = Vec::new();
+    while !rest.is_empty() {
+        let (h, len) = header(rest)?;
+        let item = rest.get(h..h + len)?;
+        if rest[0] >= 0xc0 {
+            return None;
+        }
+        items.push(item);
+        rest = &rest[h + len..];
+    }
+    Some(items)
+}
+
+pub fn to_u64<F: PrimeField>(x: F) -> Option<u64> {
+    let x = x.into_bigint();
+    (x.num_bits() <= 64).then(|| x.as_ref()[0])
+}
+
+/// `flags[j]` iff `idx == j`, for `j < n`.
+pub fn one_hot<F: PrimeField>(idx: &FpVar<F>, n: usize) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    (0..n)
+        .map(|j| Ok(FpVar::from(idx.is_eq(&FpVar::constant(F::from(j as u64)))?)))
+        .collect()
+}
+
+/// `bytes[idx + shift]`, with `flags` the one-hot encoding of `idx`.
+pub fn select<F: PrimeField>(flags: &[FpVar<F>], bytes: &[FpVar<F>], shift: usize) -> FpVar<F> {
+    flags
+        .iter()
+        .zip(bytes.iter().skip(shift))
+        .fold(FpVar::zero(), |acc, (f, b)| acc + f * b)
+}
+
+/// Big-endian integer of `bytes`.
+pub fn pack<F: PrimeField>(bytes: &[FpVar<F>]) -> FpVar<F> {
+    bytes
+        .iter()
+        .fold(FpVar::zero(), |acc, b| acc * F::from(256_u64) + b)
+}
+
+pub fn constant<F: PrimeField>(x: u64) -> FpVar<F> {
+    FpVar::constant(F::from(x))
+}
+
+/// Big-endian integer of the `len <= 32` bytes of `bytes` from `start`.
+pub fn be_value_var<F: PrimeField>(
+    bytes: &[FpVar<F>],
+    start: &FpVar<F>,
+    len: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let flags = one_hot(start, bytes.len())?;
+    let len_flags = one_hot(len, 33)?;
+    len_flags
+        .iter()
+        .fold(FpVar::zero(), |sum, f| sum + f)
+        .enforce_equal(&FpVar::one())?;
+    let mut inside = FpVar::one() - &len_flags[0];
+    let mut value = FpVar::zero();
+    for t in 0..32 {
+        value = &value + &inside * (&value * F::from(255_u64) + select(&flags, bytes, t));
+        inside -= &len_flags[t + 1];
+    }
+    Ok(value)
+}
+
+/// Whether the item at `offset` of `bytes` is a list, and the lengths of its
+/// header and payload.
+fn item_header_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    bytes: &[FpVar<F>],
+    offset: &FpVar<F>,
+) -> Result<(Boolean<F>, FpVar<F>, FpVar<F>), SynthesisError> {
+    let flags = one_hot(offset, bytes.len())?;
+    let bits = range_check_var(cs, &select(&flags, bytes, 0), 8)?;
+    let is_list = bits[7].and(&bits[6])?;
+    // a prefix of `0xb8 + n - 1` or `0xf8 + n - 1` followed by `n <= 2` length
+    // bytes
+    let is_long = bits[7].and(&bits[5])?.and(&bits[4])?.and(&bits[3])?;
+    is_long
+        .and(&bits[2].or(&bits[1])?)?
+        .enforce_equal(&Boolean::FALSE)?;
+    let is_long2 = FpVar::from(is_long.and(&bits[0])?);
+    let is_long = FpVar::from(is_long);
+    let is_short = FpVar::from(bits[7].clone()) - &is_long;
+    let is_single = FpVar::from(bits[7].not());
+
+    let b1 = select(&flags, bytes, 1);
+    let long_len = &b1 + &is_long2 * (&b1 * F::from(255_u64) + select(&flags, bytes, 2));
+    // below the long forms, the length is the low 6 bits of the prefix
+    let short_len = Boolean::le_bits_to_fp_var(&bits[..6])?;
+    let header = &is_short + &is_long * F::from(2_u64) + is_long2;
+    let payload = is_single + is_short * short_len + is_long * long_len;
+    Ok((is_list, header, payload))
+}
+
+/// `(offset, length)` of the payloads of the `n_items` strings of the RLP list
+/// in the first `len` bytes of `bytes`.
+pub fn rlp_list_items_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    bytes: &[FpVar<F>],
+    len: &FpVar<F>,
+    n_items: usize,
+) -> Result<Vec<(FpVar<F>, FpVar<F>)>, SynthesisError> {
+    // `len <= bytes.len()`, so that every item starts inside `bytes`
+    one_hot(len, bytes.len() + 1)?
+        .iter()
+        .fold(FpVar::zero(), |sum, f| sum + f)
+        .enforce_equal(&FpVar::one())?;
+    let (is_list, header, payload) = item_header_var(cs.clone(), bytes, &FpVar::zero())?;
+    is_list.enforce_equal(&Boolean::TRUE)?;
+    len.enforce_equal(&(&header + payload))?;
+
+    let mut offset = header;
+    let mut items = Vec::with_capacity(n_items);
+    for _ in 0..n_items {
+        let (is_list, header, payload) = item_header_var(cs.clone(), bytes, &offset)?;
+        is_list.enforce_equal(&Boolean::FALSE)?;
+        let start = offset + header;
+        offset = &start + &payload;
+        items.push((start, payload));
+    }
+    len.enforce_equal(&offset)?;
+    Ok(items)
+}
+
+#[derive(Clone, Debug)]
+pub struct RlpFCircuit<F: PrimeField> {
+    /// Largest payload, in bytes.
+    pub max_len: usize,
+    /// Items of the list.
+    pub n_items: usize,
+    /// Indices of the items added to the state.
+    pub fields: Vec<usize>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> RlpFCircuit<F> {
+    /// `[len, payload]`, `payload` padded to `max_len` bytes.
+    pub fn external_inputs(&self, payload: &[u8]) -> Vec<F> {
+        let mut bytes: Vec<F> = payload.iter().map(|b| F::from(*b)).collect();
+        bytes.resize(self.max_len, F::zero());
+        [vec![F::from(payload.len() as u64)], bytes].concat()
+    }
+}
+
+impl<F: PrimeField> FCircuit<F> for RlpFCircuit<F> {
+    /// Largest payload, items of the list and indices of the exposed items.
+    type Params = (usize, usize, Vec<usize>);
+
+    fn new((max_len, n_items, fields): Self::Params) -> Result<Self, Error> {
+        assert!(max_len < 1 << 16, "length prefixes have at most two bytes");
+        assert!(
+            fields.iter().all(|f| *f < n_items),
+            "exposed items must be in the list"
+        );
+        Ok(Self {
+            max_len,
+            n_items,
+            fields,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        self.fields.len()
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        1 + self.max_len
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let payload = external_inputs[1..]
+            .iter()
+            .map(|x| u8::try_from(to_u64(*x)?).ok())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(Error::NotSatisfied)?;
+        let len = to_u64(external_inputs[0])
+            .and_then(|len| usize::try_from(len).ok())
+            .filter(|len| *len <= self.max_len)
+            .ok_or(Error::NotSatisfied)?;
+        let items = rlp_list_items(&payload[..len])
+            .filter(|items| items.len() == self.n_items)
+            .ok_or(Error::NotSatisfied)?;
+        z_i.into_iter()
+            .zip(&self.fields)
+            .map(|(z, f)| match items[*f] {
+                item if item.len() <= 32 => Ok(z + F::from_be_bytes_mod_order(item)),
+                _ => Err(Error::NotSatisfied),
+            })
+            .collect()
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let (len, payload) = (&external_inputs[0], &external_inputs[1..]);
+        for byte in payload {
+            range_check_var(cs.clone(), byte, 8)?;
+        }
+        let items = rlp_list_items_var(cs, payload, len, self.n_items)?;
+        z_i.into_iter()
+            .zip(&self.fields)
+            .map(|(z, f)| {
+                let (start, item_len) = &items[*f];
+                Ok(z + be_value_var(payload, start, item_len)?)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::Rng;
+
+    /// The items of a legacy transaction `[nonce, gas_price, gas_limit, to,
+    /// value, data, v, r, s]`.
+    fn legacy_transaction(nonce: u64, value: u64, data: &[u8]) -> Vec<Vec<u8>> {
+        let int = |x: u64| {
+            let bytes: Vec<u8> = x
+                .to_be_bytes()
+                .into_iter()
+                .skip_while(|b| *b == 0)
+                .collect();
+            rlp_string(&bytes)
+        };
+        vec![
+            int(nonce),
+            int(20_000_000_000),
+            int(21_000),
+            rlp_string(&[0x11; 20]),
+            int(value),
+            rlp_string(data),
+            int(37),
+            rlp_string(&[0x22; 32]),
+            rlp_string(&[0x33; 32]),
+        ]
+    }
+
+    #[test]
+    fn test_rlp_native_vs_gadget() {
+        // nonce, value and s
+        let circuit = RlpFCircuit::<Fr>::new((300, 9, vec![0, 4, 8])).unwrap();
+        let z_i = vec![Fr::from(1_u32); 3];
+        // an empty, a single-byte and a long data field, under a 1-byte and
+        // a 2-byte list length
+        for (nonce, data) in [(0, vec![]), (9, vec![0x7f]), (300, vec![0xab; 160])] {
+            let tx = rlp_list(&legacy_transaction(nonce, 1_000_000_000_000_000_000, &data));
+            let external_inputs = circuit.external_inputs(&tx);
+            let z_i1 = circuit
+                .step_native(0, z_i.clone(), external_inputs.clone())
+                .unwrap();
+            assert_eq!(z_i1[0], Fr::from(nonce + 1));
+            assert_eq!(z_i1[1], Fr::from(1_000_000_000_000_000_001_u64));
+            assert_eq!(
+                z_i1[2],
+                Fr::from_be_bytes_mod_order(&[0x33; 32]) + Fr::from(1_u32)
+            );
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            let z_i1_var = circuit
+                .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+                .unwrap();
+            assert_eq!(z_i1_var.value().unwrap(), z_i1);
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // a list with an item too many, and a truncated one
+        let mut items = legacy_transaction(1, 2, &[]);
+        let tx = rlp_list(&items);
+        items.push(rlp_string(&[1]));
+        assert!(circuit
+            .step_native(0, z_i.clone(), circuit.external_inputs(&rlp_list(&items)))
+            .is_err());
+        let mut truncated = circuit.external_inputs(&tx);
+        truncated[0] -= Fr::from(1_u32);
+        assert!(circuit.step_native(0, z_i, truncated).is_err());
+    }
+
+    #[test]
+    fn test_rlp_consistent() {
+        let circuit = RlpFCircuit::<Fr>::new((300, 9, vec![0, 4, 8])).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let data: Vec<u8> = (0..rng.gen_range(0..160)).map(|_| rng.gen()).collect();
+            let tx = rlp_list(&legacy_transaction(rng.gen(), rng.gen(), &data));
+            let z_i = (0..3).map(|_| Fr::rand(rng)).collect();
+            (z_i, circuit.external_inputs(&tx))
+        });
+    }
+}
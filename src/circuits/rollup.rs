@@ -0,0 +1,393 @@
+//! Rollup state transition: a batch of account transfers per step against a
+//! Poseidon Merkle tree of accounts.
+//!
+//! The state is the accounts root. An account leaf is
+//! `Poseidon(pk.x, pk.y, balance, nonce)`, with keys on the embedded curve `P`.
+//! Each transfer in the batch is an external-input record laid out as
+//!
+//! ```text
+//! [sender (pk.x, pk.y, balance, nonce), sender siblings, sender directions,
+//!  receiver (pk.x, pk.y, balance, nonce), receiver siblings, receiver directions,
+//!  amount, (R.x, R.y, s) if signatures are enabled]
+//! ```
+//!
+//! where the receiver path is taken in the tree after the sender update. When
+//! enabled, the sender signs `Poseidon(receiver.pk.x, receiver.pk.y, amount,
+//! nonce)` with the Schnorr scheme of `schnorr`. Balances are `u64`s.
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ec::twisted_edwards::{Affine, TECurveConfig};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{eq::EqGadget, fields::fp::FpVar, fields::FieldVar};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use rand::RngCore;
+
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use super::merkle::{MerkleHash, NativeMerkleTree, NodeHasher};
+use super::schnorr::{affine_var, verify, verify_var, SchnorrSigner};
+use super::uint64::range_check_var;
+
+const ACCOUNT_LEN: usize = 4;
+
+fn poseidon<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, input: &[F]) -> F {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&input.to_vec());
+    sponge.squeeze_field_elements(1)[0]
+}
+
+fn poseidon_var<F: PrimeField + Absorb>(
+    cs: ConstraintSystemRef<F>,
+    config: &PoseidonConfig<F>,
+    input: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(cs, config);
+    sponge.absorb(&input.to_vec())?;
+    Ok(sponge.squeeze_field_elements(1)?[0].clone())
+}
+
+fn is_u64<F: PrimeField>(x: F) -> bool {
+    x.into_bigint().num_bits() <= 64
+}
+
+/// Offsets into a transfer record.
+#[derive(Clone, Copy, Debug)]
+struct Layout {
+    depth: usize,
+}
+
+impl Layout {
+    fn sender(&self) -> usize {
+        0
+    }
+    fn receiver(&self) -> usize {
+        ACCOUNT_LEN + 2 * self.depth
+    }
+    fn amount(&self) -> usize {
+        2 * (ACCOUNT_LEN + 2 * self.depth)
+    }
+    fn signature(&self) -> usize {
+        self.amount() + 1
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RollupFCircuit<P: TECurveConfig>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    pub depth: usize,
+    pub batch_size: usize,
+    pub verify_signatures: bool,
+    pub hasher: NodeHasher<P::BaseField>,
+    pub poseidon_config: PoseidonConfig<P::BaseField>,
+}
+
+impl<P: TECurveConfig> RollupFCircuit<P>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    fn record_len(&self) -> usize {
+        Layout { depth: self.depth }.signature() + if self.verify_signatures { 3 } else { 0 }
+    }
+}
+
+impl<P: TECurveConfig> FCircuit<P::BaseField> for RollupFCircuit<P>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    /// Tree depth, transfers per step and whether to check signatures.
+    type Params = (usize, usize, bool);
+
+    fn new((depth, batch_size, verify_signatures): Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            depth,
+            batch_size,
+            verify_signatures,
+            hasher: NodeHasher::new(MerkleHash::Poseidon),
+            poseidon_config: poseidon_canonical_config::<P::BaseField>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.batch_size * self.record_len()
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<P::BaseField>,
+        external_inputs: Vec<P::BaseField>,
+    ) -> Result<Vec<P::BaseField>, Error> {
+        let layout = Layout { depth: self.depth };
+        let d = self.depth;
+        let mut root = z_i[0];
+        for tx in external_inputs.chunks(self.record_len()) {
+            let sender = &tx[layout.sender()..layout.receiver()];
+            let receiver = &tx[layout.receiver()..layout.amount()];
+            let amount = tx[layout.amount()];
+
+            // sender: check membership, debit and bump the nonce
+            let account = &sender[..ACCOUNT_LEN];
+            let (siblings, directions) = sender[ACCOUNT_LEN..].split_at(d);
+            let leaf = poseidon(&self.poseidon_config, account);
+            if self.hasher.root_from_path(leaf, siblings, directions) != root {
+                return Err(Error::NotSatisfied);
+            }
+            let balance = account[2] - amount;
+            if !is_u64(amount) || !is_u64(balance) {
+                return Err(Error::NotSatisfied);
+            }
+            if self.verify_signatures {
+                let m = poseidon(
+                    &self.poseidon_config,
+                    &[receiver[0], receiver[1], amount, account[3]],
+                );
+                let sig = &tx[layout.signature()..];
+                let pk = Affine::<P>::new_unchecked(account[0], account[1]);
+                if !pk.is_on_curve() || !verify(&self.poseidon_config, pk, m, (sig[0], sig[1]), sig[2]) {
+                    return Err(Error::NotSatisfied);
+                }
+            }
+            let leaf = poseidon(
+                &self.poseidon_config,
+                &[account[0], account[1], balance, account[3] + P::BaseField::one()],
+            );
+            root = self.hasher.root_from_path(leaf, siblings, directions);
+
+            // receiver: check membership in the updated tree and credit
+            let account = &receiver[..ACCOUNT_LEN];
+            let (siblings, directions) = receiver[ACCOUNT_LEN..].split_at(d);
+            let leaf = poseidon(&self.poseidon_config, account);
+            if self.hasher.root_from_path(leaf, siblings, directions) != root {
+                return Err(Error::NotSatisfied);
+            }
+            let balance = account[2] + amount;
+            if !is_u64(balance) {
+                return Err(Error::NotSatisfied);
+            }
+            let leaf = poseidon(
+                &self.poseidon_config,
+                &[account[0], account[1], balance, account[3]],
+            );
+            root = self.hasher.root_from_path(leaf, siblings, directions);
+        }
+        Ok(vec![root])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<P::BaseField>,
+        _i: usize,
+        z_i: Vec<FpVar<P::BaseField>>,
+        external_inputs: Vec<FpVar<P::BaseField>>,
+    ) -> Result<Vec<FpVar<P::BaseField>>, SynthesisError> {
+        let layout = Layout { depth: self.depth };
+        let d = self.depth;
+        let config = &self.poseidon_config;
+        let mut root = z_i[0].clone();
+        for tx in external_inputs.chunks(self.record_len()) {
+            let sender = &tx[layout.sender()..layout.receiver()];
+            let receiver = &tx[layout.receiver()..layout.amount()];
+            let amount = &tx[layout.amount()];
+            range_check_var(cs.clone(), amount, 64)?;
+
+            let account = &sender[..ACCOUNT_LEN];
+            let (siblings, directions) = sender[ACCOUNT_LEN..].split_at(d);
+            let leaf = poseidon_var(cs.clone(), config, account)?;
+            self.hasher
+                .root_from_path_var(cs.clone(), &leaf, siblings, directions)?
+                .enforce_equal(&root)?;
+            let balance = &account[2] - amount;
+            range_check_var(cs.clone(), &balance, 64)?;
+            if self.verify_signatures {
+                let m = poseidon_var(
+                    cs.clone(),
+                    config,
+                    &[receiver[0].clone(), receiver[1].clone(), amount.clone(), account[3].clone()],
+                )?;
+                let sig = &tx[layout.signature()..];
+                let pk = affine_var::<P>(&account[0], &account[1])?;
+                verify_var(cs.clone(), config, &pk, &m, (&sig[0], &sig[1]), &sig[2])?;
+            }
+            let leaf = poseidon_var(
+                cs.clone(),
+                config,
+                &[account[0].clone(), account[1].clone(), balance, &account[3] + FpVar::one()],
+            )?;
+            root = self
+                .hasher
+                .root_from_path_var(cs.clone(), &leaf, siblings, directions)?;
+
+            let account = &receiver[..ACCOUNT_LEN];
+            let (siblings, directions) = receiver[ACCOUNT_LEN..].split_at(d);
+            let leaf = poseidon_var(cs.clone(), config, account)?;
+            self.hasher
+                .root_from_path_var(cs.clone(), &leaf, siblings, directions)?
+                .enforce_equal(&root)?;
+            let balance = &account[2] + amount;
+            range_check_var(cs.clone(), &balance, 64)?;
+            let leaf = poseidon_var(
+                cs.clone(),
+                config,
+                &[account[0].clone(), account[1].clone(), balance, account[3].clone()],
+            )?;
+            root = self
+                .hasher
+                .root_from_path_var(cs.clone(), &leaf, siblings, directions)?;
+        }
+        Ok(vec![root])
+    }
+}
+
+/// Native rollup producing the external inputs of `RollupFCircuit`.
+pub struct NativeRollup<P: TECurveConfig>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    pub signers: Vec<SchnorrSigner<P>>,
+    /// `(balance, nonce)` per account.
+    pub accounts: Vec<(u64, u64)>,
+    pub tree: NativeMerkleTree<P::BaseField>,
+    pub poseidon_config: PoseidonConfig<P::BaseField>,
+}
+
+impl<P: TECurveConfig> NativeRollup<P>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    /// `n_accounts` fresh accounts holding `balance` each.
+    pub fn new(mut rng: impl RngCore, depth: usize, n_accounts: usize, balance: u64) -> Self {
+        let mut rollup = Self {
+            signers: (0..n_accounts).map(|_| SchnorrSigner::new(&mut rng)).collect(),
+            accounts: vec![(balance, 0); n_accounts],
+            tree: NativeMerkleTree::new(depth, MerkleHash::Poseidon),
+            poseidon_config: poseidon_canonical_config::<P::BaseField>(),
+        };
+        for index in 0..n_accounts {
+            let leaf = rollup.leaf(index);
+            rollup.tree.update(index as u64, leaf);
+        }
+        rollup
+    }
+
+    fn account(&self, index: usize) -> Vec<P::BaseField> {
+        let pk = self.signers[index].pk;
+        let (balance, nonce) = self.accounts[index];
+        vec![pk.x, pk.y, balance.into(), nonce.into()]
+    }
+
+    fn leaf(&self, index: usize) -> P::BaseField {
+        poseidon(&self.poseidon_config, &self.account(index))
+    }
+
+    pub fn root(&self) -> P::BaseField {
+        self.tree.root()
+    }
+
+    /// Applies a transfer and returns its external-input record.
+    pub fn transfer(
+        &mut self,
+        rng: impl RngCore,
+        from: usize,
+        to: usize,
+        amount: u64,
+        sign: bool,
+    ) -> Vec<P::BaseField> {
+        let sender = self.account(from);
+        let (siblings, directions) = self.tree.path(from as u64);
+        let mut record = [sender.clone(), siblings, directions].concat();
+        self.accounts[from].0 -= amount;
+        self.accounts[from].1 += 1;
+        self.tree.update(from as u64, self.leaf(from));
+
+        let receiver = self.account(to);
+        let (siblings, directions) = self.tree.path(to as u64);
+        record.extend([receiver.clone(), siblings, directions].concat());
+        self.accounts[to].0 += amount;
+        self.tree.update(to as u64, self.leaf(to));
+
+        record.push(amount.into());
+        if sign {
+            let m = poseidon(
+                &self.poseidon_config,
+                &[receiver[0], receiver[1], amount.into(), sender[3]],
+            );
+            record.extend(&self.signers[from].sign(rng, m)[1..]);
+        }
+        record
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_ed_on_bn254::EdwardsConfig;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::{rngs::OsRng, Rng};
+
+    #[test]
+    fn test_rollup_native_vs_gadget() {
+        for verify_signatures in [false, true] {
+            let circuit =
+                RollupFCircuit::<EdwardsConfig>::new((4, 2, verify_signatures)).unwrap();
+            let mut rollup = NativeRollup::<EdwardsConfig>::new(OsRng, 4, 3, 100);
+            let z_i = vec![rollup.root()];
+            let external_inputs = [
+                rollup.transfer(OsRng, 0, 1, 30, verify_signatures),
+                rollup.transfer(OsRng, 1, 2, 50, verify_signatures),
+            ]
+            .concat();
+            let z_i1 = circuit
+                .step_native(0, z_i.clone(), external_inputs.clone())
+                .unwrap();
+            assert_eq!(z_i1, vec![rollup.root()]);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            let z_i1_var = circuit
+                .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+                .unwrap();
+            assert_eq!(z_i1_var.value().unwrap(), z_i1);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_rollup_consistent() {
+        for verify_signatures in [false, true] {
+            let circuit =
+                RollupFCircuit::<EdwardsConfig>::new((4, 2, verify_signatures)).unwrap();
+            let mut rollup = NativeRollup::<EdwardsConfig>::new(OsRng, 4, 3, 1000);
+            assert_fcircuit_consistent_with(&circuit, |rng| {
+                let z_i = vec![rollup.root()];
+                let mut external_inputs = vec![];
+                for _ in 0..2 {
+                    let from = rng.gen_range(0..3);
+                    let to = (from + rng.gen_range(1..3)) % 3;
+                    let amount = rng.gen_range(0..10);
+                    external_inputs.extend(rollup.transfer(
+                        &mut *rng,
+                        from,
+                        to,
+                        amount,
+                        verify_signatures,
+                    ));
+                }
+                (z_i, external_inputs)
+            });
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! RSA-group exponentiation with 2048-bit big-integer gadgets, the workload
+//! of RSA accumulators and Wesolowski/Pietrzak-style VDFs.
+//!
+//! The state is an element `A` of `Z_N^*` as `LIMBS` little-endian 64-bit
+//! limbs. Each step takes `k` exponent bits as external inputs, most
+//! significant first, and sets `A = A^e mod N` by square-and-multiply, i.e.
+//! two modular multiplications per bit. The state must stay below `N`.
+//!
+//! A modular multiplication `a * b = q * N + r` is checked limb-wise: `q` and
+//! `r` are range-checked witnesses, and the coefficients of
+//! `a * b - q * N - r` in base `2^64` are shown to vanish with signed carries.
+//! `r < N` is not enforced, which only lets a prover pick a non-canonical
+//! representative.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use num_bigint::{BigInt, BigUint};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::uint64::range_check_var;
+
+pub const LIMB_BITS: usize = 64;
+pub const LIMBS: usize = 32;
+/// A coefficient of `a * b - q * N` is below `2 * LIMBS * 2^128 = 2^134` in
+/// absolute value, so carries are below `2^71`; they are offset by `2^71`.
+const CARRY_BITS: usize = LIMB_BITS + 8;
+
+/// A fixed odd 2048-bit modulus. Its factorization does not change the cost,
+/// so it is drawn deterministically rather than generated as a product of
+/// two primes.
+pub fn rsa_2048_modulus() -> BigUint {
+    let mut bytes = [0u8; LIMBS * LIMB_BITS / 8];
+    StdRng::seed_from_u64(0).fill_bytes(&mut bytes);
+    bytes[0] |= 1;
+    bytes[bytes.len() - 1] |= 0x80;
+    BigUint::from_bytes_le(&bytes)
+}
+
+/// The `LIMBS` limbs of `x` as field elements.
+pub fn to_limbs<F: PrimeField>(x: &BigUint) -> Vec<F> {
+    let mut limbs: Vec<F> = x.to_u64_digits().into_iter().map(F::from).collect();
+    limbs.resize(LIMBS, F::zero());
+    limbs
+}
+
+fn limb_u64<F: PrimeField>(x: F) -> u64 {
+    x.into_bigint().as_ref()[0]
+}
+
+pub fn from_limbs<F: PrimeField>(limbs: &[F]) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::default(), |acc, l| (acc << LIMB_BITS) + limb_u64(*l))
+}
+
+fn to_field<F: PrimeField>(x: &BigUint) -> F {
+    F::from_le_bytes_mod_order(&x.to_bytes_le())
+}
+
+/// Carries of `a * b - q * n - r` in base `2^64`, one per coefficient but the
+/// last, offset to be non-negative.
+fn carries(a: &BigUint, b: &BigUint, q: &BigUint, r: &BigUint, n: &BigUint) -> Vec<BigUint> {
+    let limbs = |x: &BigUint| {
+        let mut l: Vec<BigInt> = x
+            .to_u64_digits()
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        l.resize(LIMBS, BigInt::default());
+        l
+    };
+    let (a, b, q, r, n) = (limbs(a), limbs(b), limbs(q), limbs(r), limbs(n));
+    let mut d = vec![BigInt::default(); 2 * LIMBS - 1];
+    for i in 0..LIMBS {
+        for j in 0..LIMBS {
+            d[i + j] += &a[i] * &b[j] - &q[i] * &n[j];
+        }
+        d[i] -= &r[i];
+    }
+    let offset = BigInt::from(1) << (CARRY_BITS - 1);
+    let mut carry = BigInt::default();
+    d[..2 * LIMBS - 2]
+        .iter()
+        .map(|d_k| {
+            carry = (d_k + &carry) >> LIMB_BITS;
+            (&carry + &offset).to_biguint().expect("carry out of range")
+        })
+        .collect()
+}
+
+/// Allocates `LIMBS` range-checked limbs with the value `x`, if known.
+fn alloc_limbs<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    x: Option<&BigUint>,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let limbs = x.map(to_limbs::<F>);
+    (0..LIMBS)
+        .map(|k| {
+            let limb = FpVar::new_witness(cs.clone(), || {
+                limbs.as_ref().map(|l| l[k]).ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            range_check_var(cs.clone(), &limb, LIMB_BITS)?;
+            Ok(limb)
+        })
+        .collect()
+}
+
+/// `a * b mod n`, for `a`, `b` below `n`.
+pub fn modmul_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    a: &[FpVar<F>],
+    b: &[FpVar<F>],
+    n: &BigUint,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let values = (|| -> Result<_, SynthesisError> {
+        let a = from_limbs(&a.value()?);
+        let b = from_limbs(&b.value()?);
+        let product = &a * &b;
+        let (q, r) = (&product / n, &product % n);
+        let carries = carries(&a, &b, &q, &r, n);
+        Ok((q, r, carries))
+    })()
+    .ok();
+    let q = alloc_limbs(cs.clone(), values.as_ref().map(|v| &v.0))?;
+    let r = alloc_limbs(cs.clone(), values.as_ref().map(|v| &v.1))?;
+    let carries = (0..2 * LIMBS - 2)
+        .map(|k| {
+            let carry = FpVar::new_witness(cs.clone(), || {
+                values
+                    .as_ref()
+                    .map(|v| to_field(&v.2[k]))
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            range_check_var(cs.clone(), &carry, CARRY_BITS)?;
+            Ok(carry - F::from(2_u64).pow([CARRY_BITS as u64 - 1]))
+        })
+        .collect::<Result<Vec<_>, SynthesisError>>()?;
+
+    let n_limbs = to_limbs::<F>(n);
+    let mut d = vec![FpVar::<F>::zero(); 2 * LIMBS - 1];
+    for i in 0..LIMBS {
+        for j in 0..LIMBS {
+            d[i + j] += &a[i] * &b[j] - &q[i] * n_limbs[j];
+        }
+        d[i] -= &r[i];
+    }
+    let base = F::from(2_u64).pow([LIMB_BITS as u64]);
+    let mut carry_in = FpVar::zero();
+    for (k, d_k) in d.iter().enumerate() {
+        let carry_out = carries.get(k).cloned().unwrap_or_else(FpVar::zero);
+        (d_k + &carry_in).enforce_equal(&(&carry_out * base))?;
+        carry_in = carry_out;
+    }
+    Ok(r)
+}
+
+#[derive(Clone, Debug)]
+pub struct RsaFCircuit<F: PrimeField> {
+    pub modulus: BigUint,
+    /// Exponent bits per step.
+    pub exp_bits: usize,
+    _f: std::marker::PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for RsaFCircuit<F> {
+    /// Modulus of at most 2048 bits and exponent bits per step.
+    type Params = (BigUint, usize);
+
+    fn new((modulus, exp_bits): Self::Params) -> Result<Self, Error> {
+        assert!(modulus.bits() as usize <= LIMBS * LIMB_BITS);
+        assert!(F::MODULUS_BIT_SIZE as usize > 2 * LIMB_BITS + 8);
+        Ok(Self {
+            modulus,
+            exp_bits,
+            _f: std::marker::PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        LIMBS
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.exp_bits
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let a = from_limbs(&z_i);
+        let is_bit = |b: &F| b.is_zero() || b.is_one();
+        if a >= self.modulus || !external_inputs.iter().all(is_bit) {
+            return Err(Error::NotSatisfied);
+        }
+        let e = external_inputs
+            .iter()
+            .fold(BigUint::default(), |e, b| (e << 1) + u8::from(b.is_one()));
+        Ok(to_limbs(&a.modpow(&e, &self.modulus)))
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut acc = to_limbs::<F>(&BigUint::from(1u8))
+            .into_iter()
+            .map(FpVar::constant)
+            .collect::<Vec<_>>();
+        for bit in &external_inputs {
+            (bit * (bit - FpVar::one())).enforce_equal(&FpVar::zero())?;
+            acc = modmul_var(cs.clone(), &acc, &acc, &self.modulus)?;
+            let multiplied = modmul_var(cs.clone(), &acc, &z_i, &self.modulus)?;
+            acc = acc
+                .iter()
+                .zip(&multiplied)
+                .map(|(a, m)| a + bit * (m - a))
+                .collect();
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::Rng;
+
+    #[test]
+    fn test_rsa_native_vs_gadget() {
+        let modulus = rsa_2048_modulus();
+        assert_eq!(modulus.bits(), 2048);
+        let circuit = RsaFCircuit::<Fr>::new((modulus.clone(), 3)).unwrap();
+        let a = &modulus - 12345u32;
+        let z_i = to_limbs::<Fr>(&a);
+        // e = 0b101
+        let external_inputs = vec![Fr::from(1_u32), Fr::from(0_u32), Fr::from(1_u32)];
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+        assert_eq!(from_limbs(&z_i1), a.modpow(&BigUint::from(5u8), &modulus));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_rsa_consistent() {
+        let modulus = rsa_2048_modulus();
+        let circuit = RsaFCircuit::<Fr>::new((modulus.clone(), 3)).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let bytes: Vec<u8> = (0..LIMBS * LIMB_BITS / 8).map(|_| rng.gen()).collect();
+            let a = BigUint::from_bytes_le(&bytes) % &modulus;
+            let bits = (0..3).map(|_| Fr::from(rng.gen::<bool>())).collect();
+            (to_limbs(&a), bits)
+        });
+    }
+}
@@ -0,0 +1,268 @@
+//! Schnorr signatures over Poseidon on the embedded twisted Edwards curve
+//! (e.g. BabyJubJub for BN254), the circuit-friendly counterpart of ECDSA.
+//!
+//! The public key is fixed by the circuit. Each step takes a message and its
+//! signature `(R, s)` as external inputs, laid out as `[m, R.x, R.y, s]`,
+//! checks `s * G == R + e * A` with `e = Poseidon(R, A, m)`, and absorbs the
+//! message into the state: `z_{i+1} = Poseidon(z_i, m)`.
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ec::{
+    twisted_edwards::{Affine, Projective, TECurveConfig},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    groups::{curves::twisted_edwards::AffineVar, CurveVar},
+    ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::UniformRand;
+use rand::RngCore;
+
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+type ScalarField<P> = <P as ark_ec::CurveConfig>::ScalarField;
+
+/// Schnorr challenge `Poseidon(R.x, R.y, A.x, A.y, m)`.
+fn challenge<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, r: (F, F), pk: (F, F), m: F) -> F {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&vec![r.0, r.1, pk.0, pk.1, m]);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+/// Reduces a base field element into the curve's scalar field.
+fn to_scalar<P: TECurveConfig>(x: P::BaseField) -> ScalarField<P>
+where
+    P::BaseField: PrimeField,
+{
+    ScalarField::<P>::from_le_bytes_mod_order(&x.into_bigint().to_bytes_le())
+}
+
+/// Checks a signature `(R, s)` on `m` under the public key `pk`.
+pub fn verify<P: TECurveConfig>(
+    config: &PoseidonConfig<P::BaseField>,
+    pk: Affine<P>,
+    m: P::BaseField,
+    r: (P::BaseField, P::BaseField),
+    s: P::BaseField,
+) -> bool
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    let r_point = Affine::<P>::new_unchecked(r.0, r.1);
+    if !r_point.is_on_curve() {
+        return false;
+    }
+    let e = challenge(config, r, (pk.x, pk.y), m);
+    Affine::<P>::generator() * to_scalar::<P>(s) == r_point.into_group() + pk * to_scalar::<P>(e)
+}
+
+/// Point from its coordinates, constrained to be on the curve:
+/// `a * x^2 + y^2 = 1 + d * x^2 * y^2`.
+pub fn affine_var<P: TECurveConfig>(
+    x: &FpVar<P::BaseField>,
+    y: &FpVar<P::BaseField>,
+) -> Result<AffineVar<P, FpVar<P::BaseField>>, SynthesisError>
+where
+    P::BaseField: PrimeField,
+{
+    let x2 = x.square()?;
+    let y2 = y.square()?;
+    (&x2 * P::COEFF_A + &y2).enforce_equal(&(x2 * y2 * P::COEFF_D + FpVar::one()))?;
+    Ok(AffineVar::new(x.clone(), y.clone()))
+}
+
+/// Gadget version of `verify`, enforcing that the signature is valid.
+pub fn verify_var<P: TECurveConfig>(
+    cs: ConstraintSystemRef<P::BaseField>,
+    config: &PoseidonConfig<P::BaseField>,
+    pk: &AffineVar<P, FpVar<P::BaseField>>,
+    m: &FpVar<P::BaseField>,
+    r: (&FpVar<P::BaseField>, &FpVar<P::BaseField>),
+    s: &FpVar<P::BaseField>,
+) -> Result<(), SynthesisError>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    let r_point = affine_var::<P>(r.0, r.1)?;
+
+    let mut sponge = PoseidonSpongeVar::new(cs, config);
+    sponge.absorb(&vec![r.0.clone(), r.1.clone(), pk.x.clone(), pk.y.clone(), m.clone()])?;
+    let e = sponge.squeeze_field_elements(1)?[0].clone();
+
+    let g = AffineVar::<P, FpVar<P::BaseField>>::constant(Projective::<P>::from(
+        Affine::<P>::generator(),
+    ));
+    let lhs = g.scalar_mul_le(s.to_bits_le()?.iter())?;
+    let rhs = r_point + pk.scalar_mul_le(e.to_bits_le()?.iter())?;
+    lhs.enforce_equal(&rhs)
+}
+
+/// Key pair producing the external inputs of `SchnorrFCircuit`.
+#[derive(Clone, Debug)]
+pub struct SchnorrSigner<P: TECurveConfig>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    pub sk: ScalarField<P>,
+    pub pk: Affine<P>,
+    pub poseidon_config: PoseidonConfig<P::BaseField>,
+}
+
+impl<P: TECurveConfig> SchnorrSigner<P>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    pub fn new(mut rng: impl RngCore) -> Self {
+        let sk = ScalarField::<P>::rand(&mut rng);
+        Self {
+            sk,
+            pk: (Affine::<P>::generator() * sk).into_affine(),
+            poseidon_config: poseidon_canonical_config::<P::BaseField>(),
+        }
+    }
+
+    /// Signs `m`, returning `[m, R.x, R.y, s]`.
+    pub fn sign(&self, mut rng: impl RngCore, m: P::BaseField) -> Vec<P::BaseField> {
+        let k = ScalarField::<P>::rand(&mut rng);
+        let r = (Affine::<P>::generator() * k).into_affine();
+        let e = challenge(&self.poseidon_config, (r.x, r.y), (self.pk.x, self.pk.y), m);
+        let s = k + to_scalar::<P>(e) * self.sk;
+        vec![
+            m,
+            r.x,
+            r.y,
+            P::BaseField::from_le_bytes_mod_order(&s.into_bigint().to_bytes_le()),
+        ]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SchnorrFCircuit<P: TECurveConfig>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    pub pk: Affine<P>,
+    pub poseidon_config: PoseidonConfig<P::BaseField>,
+}
+
+impl<P: TECurveConfig> FCircuit<P::BaseField> for SchnorrFCircuit<P>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    /// Public key of the signer.
+    type Params = Affine<P>;
+
+    fn new(pk: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            pk,
+            poseidon_config: poseidon_canonical_config::<P::BaseField>(),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        4
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<P::BaseField>,
+        external_inputs: Vec<P::BaseField>,
+    ) -> Result<Vec<P::BaseField>, Error> {
+        let m = external_inputs[0];
+        if !verify(
+            &self.poseidon_config,
+            self.pk,
+            m,
+            (external_inputs[1], external_inputs[2]),
+            external_inputs[3],
+        ) {
+            return Err(Error::NotSatisfied);
+        }
+
+        let mut sponge = PoseidonSponge::new(&self.poseidon_config);
+        sponge.absorb(&vec![z_i[0], m]);
+        Ok(vec![sponge.squeeze_field_elements(1)[0]])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<P::BaseField>,
+        _i: usize,
+        z_i: Vec<FpVar<P::BaseField>>,
+        external_inputs: Vec<FpVar<P::BaseField>>,
+    ) -> Result<Vec<FpVar<P::BaseField>>, SynthesisError> {
+        let m = &external_inputs[0];
+        let pk = AffineVar::<P, FpVar<P::BaseField>>::constant(self.pk.into_group());
+        verify_var(
+            cs.clone(),
+            &self.poseidon_config,
+            &pk,
+            m,
+            (&external_inputs[1], &external_inputs[2]),
+            &external_inputs[3],
+        )?;
+
+        let mut sponge = PoseidonSpongeVar::new(cs, &self.poseidon_config);
+        sponge.absorb(&vec![z_i[0].clone(), m.clone()])?;
+        Ok(vec![sponge.squeeze_field_elements(1)?[0].clone()])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_ed_on_bn254::EdwardsConfig;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_schnorr_native_vs_gadget() {
+        let signer = SchnorrSigner::<EdwardsConfig>::new(OsRng);
+        let circuit = SchnorrFCircuit::<EdwardsConfig>::new(signer.pk).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let external_inputs = signer.sign(OsRng, Fr::from(42_u32));
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs.clone())).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+
+        // a signature on a different message is rejected
+        let mut forged = external_inputs;
+        forged[0] += Fr::from(1_u32);
+        assert!(circuit.step_native(0, z_i, forged).is_err());
+    }
+
+    #[test]
+    fn test_schnorr_consistent() {
+        let signer = SchnorrSigner::<EdwardsConfig>::new(OsRng);
+        let circuit = SchnorrFCircuit::<EdwardsConfig>::new(signer.pk).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let (z, m) = (Fr::rand(rng), Fr::rand(rng));
+            (vec![z], signer.sign(&mut *rng, m))
+        });
+    }
+}
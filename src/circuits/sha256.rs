@@ -0,0 +1,97 @@
+//! SHA-256 hash chain: each step replaces the state with the (truncated)
+//! SHA-256 digest of its bytes, `k` times. Hashing a 32-byte state fits in one
+//! block, so a step costs exactly `k` compressions.
+use ark_crypto_primitives::crh::sha256::constraints::Sha256Gadget;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::utils::{bytes_to_field, bytes_var_to_field, field_to_bytes_le, field_var_to_bytes_le};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256FCircuit<F: PrimeField> {
+    /// Number of SHA-256 compressions per step.
+    pub n_compressions: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for Sha256FCircuit<F> {
+    type Params = usize;
+
+    fn new(n_compressions: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_compressions,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let mut z = z_i[0];
+        for _ in 0..self.n_compressions {
+            let digest = Sha256::digest(field_to_bytes_le(z));
+            z = bytes_to_field(&digest);
+        }
+        Ok(vec![z])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut z = z_i[0].clone();
+        for _ in 0..self.n_compressions {
+            let digest = Sha256Gadget::digest(&field_var_to_bytes_le(&z)?)?;
+            z = bytes_var_to_field(&digest.0)?;
+        }
+        Ok(vec![z])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_sha256_native_vs_gadget() {
+        let circuit = Sha256FCircuit::<Fr>::new(2).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_sha256_consistent() {
+        assert_fcircuit_consistent(&Sha256FCircuit::<Fr>::new(1).unwrap());
+    }
+}
@@ -0,0 +1,190 @@
+//! Batches of `B` Schnorr signatures per step (see `schnorr`), from any
+//! signers, to trade fewer and fatter steps against more and thinner ones at
+//! the same total number of signatures.
+//!
+//! The public keys are not fixed by the circuit: each signature comes with its
+//! key, and the external inputs are `B` entries `[A.x, A.y, m, R.x, R.y, s]`.
+//! Every key is constrained to be on the curve and multiplied as a variable
+//! base. The messages are absorbed into the state:
+//! `z_{i+1} = Poseidon(z_i, m_1, ..., m_B)`.
+use ark_crypto_primitives::sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, PoseidonConfig, PoseidonSponge},
+    Absorb, CryptographicSponge,
+};
+use ark_ec::twisted_edwards::{Affine, TECurveConfig};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use rand::RngCore;
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, transcript::poseidon::poseidon_canonical_config, Error};
+
+use super::schnorr::{affine_var, verify, verify_var, SchnorrSigner};
+
+/// External inputs per signature.
+pub const ENTRY_LEN: usize = 6;
+
+/// `[A.x, A.y, m, R.x, R.y, s]` for a signature by `signer` on `m`.
+pub fn signed_entry<P: TECurveConfig>(
+    signer: &SchnorrSigner<P>,
+    rng: impl RngCore,
+    m: P::BaseField,
+) -> Vec<P::BaseField>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    [vec![signer.pk.x, signer.pk.y], signer.sign(rng, m)].concat()
+}
+
+#[derive(Clone, Debug)]
+pub struct SignatureBatchFCircuit<P: TECurveConfig>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    /// Signatures per step.
+    pub batch_size: usize,
+    pub poseidon_config: PoseidonConfig<P::BaseField>,
+    _p: PhantomData<P>,
+}
+
+impl<P: TECurveConfig> FCircuit<P::BaseField> for SignatureBatchFCircuit<P>
+where
+    P::BaseField: PrimeField + Absorb,
+{
+    /// Signatures per step.
+    type Params = usize;
+
+    fn new(batch_size: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            batch_size,
+            poseidon_config: poseidon_canonical_config::<P::BaseField>(),
+            _p: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        ENTRY_LEN * self.batch_size
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<P::BaseField>,
+        external_inputs: Vec<P::BaseField>,
+    ) -> Result<Vec<P::BaseField>, Error> {
+        let mut absorbed = z_i;
+        for entry in external_inputs.chunks(ENTRY_LEN) {
+            let pk = Affine::<P>::new_unchecked(entry[0], entry[1]);
+            if !pk.is_on_curve()
+                || !verify(
+                    &self.poseidon_config,
+                    pk,
+                    entry[2],
+                    (entry[3], entry[4]),
+                    entry[5],
+                )
+            {
+                return Err(Error::NotSatisfied);
+            }
+            absorbed.push(entry[2]);
+        }
+
+        let mut sponge = PoseidonSponge::new(&self.poseidon_config);
+        sponge.absorb(&absorbed);
+        Ok(vec![sponge.squeeze_field_elements(1)[0]])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<P::BaseField>,
+        _i: usize,
+        z_i: Vec<FpVar<P::BaseField>>,
+        external_inputs: Vec<FpVar<P::BaseField>>,
+    ) -> Result<Vec<FpVar<P::BaseField>>, SynthesisError> {
+        let mut absorbed = z_i;
+        for entry in external_inputs.chunks(ENTRY_LEN) {
+            let pk = affine_var::<P>(&entry[0], &entry[1])?;
+            verify_var(
+                cs.clone(),
+                &self.poseidon_config,
+                &pk,
+                &entry[2],
+                (&entry[3], &entry[4]),
+                &entry[5],
+            )?;
+            absorbed.push(entry[2].clone());
+        }
+
+        let mut sponge = PoseidonSpongeVar::new(cs, &self.poseidon_config);
+        sponge.absorb(&absorbed)?;
+        Ok(vec![sponge.squeeze_field_elements(1)?[0].clone()])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_ed_on_bn254::EdwardsConfig;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_signature_batch_native_vs_gadget() {
+        let signers: Vec<_> = (0..3)
+            .map(|_| SchnorrSigner::<EdwardsConfig>::new(OsRng))
+            .collect();
+        let circuit = SignatureBatchFCircuit::<EdwardsConfig>::new(signers.len()).unwrap();
+        let z_i = vec![Fr::from(3_u32)];
+        let external_inputs: Vec<Fr> = signers
+            .iter()
+            .enumerate()
+            .flat_map(|(k, signer)| signed_entry(signer, OsRng, Fr::from(k as u64)))
+            .collect();
+        let z_i1 = circuit
+            .step_native(0, z_i.clone(), external_inputs.clone())
+            .unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i.clone())).unwrap();
+        let external_inputs_var =
+            Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs.clone())).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+
+        // a signature checked against another signer's key is rejected
+        let mut forged = external_inputs;
+        forged[0] = signers[1].pk.x;
+        forged[1] = signers[1].pk.y;
+        assert!(circuit.step_native(0, z_i, forged).is_err());
+    }
+
+    #[test]
+    fn test_signature_batch_consistent() {
+        let signers: Vec<_> = (0..3)
+            .map(|_| SchnorrSigner::<EdwardsConfig>::new(OsRng))
+            .collect();
+        let circuit = SignatureBatchFCircuit::<EdwardsConfig>::new(signers.len()).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let z_i = vec![Fr::rand(rng)];
+            let mut external_inputs = vec![];
+            for signer in &signers {
+                let m = Fr::rand(rng);
+                external_inputs.extend(signed_entry(signer, &mut *rng, m));
+            }
+            (z_i, external_inputs)
+        });
+    }
+}
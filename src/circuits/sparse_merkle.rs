@@ -0,0 +1,206 @@
+//! Sparse Merkle tree insertion and deletion, the way identity and nullifier
+//! sets are maintained: each step inserts a value under an empty key or
+//! deletes the value under a key, and outputs the new root.
+//!
+//! Unlike `MerkleUpdateFCircuit`, the path is not free: its directions are the
+//! bits of the key, least significant (leaf level) first, so a key can only be
+//! proven at its own leaf. Empty leaves are zero and stored values are
+//! non-zero, so an insertion shows the leaf was empty and a deletion shows it
+//! held the value.
+//!
+//! The external inputs are `[op, key, value, siblings[0..depth]]`, where `op`
+//! is 1 for an insertion and 0 for a deletion.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use super::merkle::{MerkleHash, NativeMerkleTree, NodeHasher};
+use super::uint64::range_check_var;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SparseOp<F> {
+    Insert(F),
+    Delete,
+}
+
+/// Applies `op` at `key` to the tree and returns the matching external inputs
+/// for `SparseMerkleFCircuit`.
+pub fn sparse_external_inputs<F: PrimeField>(
+    tree: &mut NativeMerkleTree<F>,
+    key: u64,
+    op: SparseOp<F>,
+) -> Vec<F> {
+    let (siblings, _) = tree.path(key);
+    let (flag, value, new_leaf) = match op {
+        SparseOp::Insert(value) => (F::one(), value, value),
+        SparseOp::Delete => (F::zero(), tree.leaf(key), F::zero()),
+    };
+    tree.update(key, new_leaf);
+    [vec![flag, F::from(key), value], siblings].concat()
+}
+
+#[derive(Clone, Debug)]
+pub struct SparseMerkleFCircuit<F: PrimeField> {
+    pub depth: usize,
+    pub hasher: NodeHasher<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for SparseMerkleFCircuit<F> {
+    /// Tree depth, at most 64, and node hash.
+    type Params = (usize, MerkleHash);
+
+    fn new((depth, hash): Self::Params) -> Result<Self, Error> {
+        assert!(depth <= 64, "keys are u64");
+        Ok(Self {
+            depth,
+            hasher: NodeHasher::new(hash),
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        3 + self.depth
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let (op, key, value) = (external_inputs[0], external_inputs[1], external_inputs[2]);
+        let siblings = &external_inputs[3..];
+        let key_bits = key.into_bigint();
+        if !(op.is_zero() || op.is_one())
+            || value.is_zero()
+            || key_bits.num_bits() as usize > self.depth
+        {
+            return Err(Error::NotSatisfied);
+        }
+        let directions: Vec<F> = (0..self.depth)
+            .map(|level| F::from(key_bits.get_bit(level)))
+            .collect();
+        let (old_leaf, new_leaf) = if op.is_one() {
+            (F::zero(), value)
+        } else {
+            (value, F::zero())
+        };
+        if self.hasher.root_from_path(old_leaf, siblings, &directions) != z_i[0] {
+            return Err(Error::NotSatisfied);
+        }
+        Ok(vec![self.hasher.root_from_path(
+            new_leaf,
+            siblings,
+            &directions,
+        )])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let (op, key, value) = (
+            &external_inputs[0],
+            &external_inputs[1],
+            &external_inputs[2],
+        );
+        let siblings = &external_inputs[3..];
+        (op * (op - FpVar::one())).enforce_equal(&FpVar::zero())?;
+        value.enforce_not_equal(&FpVar::zero())?;
+        let directions: Vec<FpVar<F>> = range_check_var(cs.clone(), key, self.depth)?
+            .into_iter()
+            .map(FpVar::from)
+            .collect();
+        let new_leaf = op * value;
+        let old_leaf = value - &new_leaf;
+        self.hasher
+            .root_from_path_var(cs.clone(), &old_leaf, siblings, &directions)?
+            .enforce_equal(&z_i[0])?;
+        Ok(vec![self.hasher.root_from_path_var(
+            cs,
+            &new_leaf,
+            siblings,
+            &directions,
+        )?])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_ff::Zero;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::Rng;
+
+    #[test]
+    fn test_sparse_merkle_native_vs_gadget() {
+        let circuit = SparseMerkleFCircuit::<Fr>::new((8, MerkleHash::Poseidon)).unwrap();
+        let mut tree = NativeMerkleTree::<Fr>::new(8, MerkleHash::Poseidon);
+        let empty_root = tree.root();
+        let ops = [
+            (200, SparseOp::Insert(Fr::from(7_u32))),
+            (3, SparseOp::Insert(Fr::from(9_u32))),
+            (200, SparseOp::Delete),
+        ];
+        for (key, op) in ops {
+            let z_i = vec![tree.root()];
+            let external_inputs = sparse_external_inputs(&mut tree, key, op);
+            let z_i1 = circuit
+                .step_native(0, z_i.clone(), external_inputs.clone())
+                .unwrap();
+            assert_eq!(z_i1, vec![tree.root()]);
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+            let external_inputs_var =
+                Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(external_inputs)).unwrap();
+            let z_i1_var = circuit
+                .generate_step_constraints(cs.clone(), 0, z_i_var, external_inputs_var)
+                .unwrap();
+            assert_eq!(z_i1_var.value().unwrap(), z_i1);
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // inserting under a taken key
+        let mut replay = tree.clone();
+        let external_inputs =
+            sparse_external_inputs(&mut replay, 3, SparseOp::Insert(Fr::from(1_u32)));
+        assert!(circuit
+            .step_native(0, vec![tree.root()], external_inputs)
+            .is_err());
+        sparse_external_inputs(&mut tree, 3, SparseOp::Delete);
+        assert_eq!(tree.root(), empty_root);
+    }
+
+    #[test]
+    fn test_sparse_merkle_consistent() {
+        let circuit = SparseMerkleFCircuit::<Fr>::new((8, MerkleHash::Poseidon)).unwrap();
+        let mut tree = NativeMerkleTree::<Fr>::new(8, MerkleHash::Poseidon);
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            let z_i = vec![tree.root()];
+            // few enough keys that some get deleted again
+            let key = rng.gen_range(0..4);
+            let op = if tree.leaf(key).is_zero() {
+                SparseOp::Insert(Fr::rand(rng))
+            } else {
+                SparseOp::Delete
+            };
+            (z_i, sparse_external_inputs(&mut tree, key, op))
+        });
+    }
+}
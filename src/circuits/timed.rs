@@ -0,0 +1,154 @@
+//! `FCircuit` wrapper timing the step circuit's own work inside `prove_step`.
+//!
+//! A folding step computes the next state natively (`step_native`), then
+//! synthesizes the augmented circuit around `generate_step_constraints`, then
+//! runs the folding prover. Only the whole step is visible from outside, so
+//! the wrapper adds the time of the first two to thread-local counters that
+//! the folding loop drains after every step; what remains is the prover. For
+//! circuits whose witness comes from an external calculator (Circom, `.r1cs`)
+//! the witness computation can dominate the step.
+//!
+//! It also records where the step circuit's constraints fall in the
+//! constraint system it is synthesized into, which tells the augmented
+//! circuit's own constraints before and after it apart.
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+use crate::clock::Stopwatch;
+
+thread_local! {
+    static NATIVE: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    static CONSTRAINTS: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    static STEP_SPAN: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+}
+
+/// Time spent in the step circuit since the last `take_times` on this thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StepCircuitTimes {
+    /// `step_native`, i.e. the witness computation.
+    pub native: Duration,
+    /// `generate_step_constraints`.
+    pub constraints: Duration,
+}
+
+/// Returns and resets the times accumulated by `TimedFCircuit` on this thread.
+pub fn take_times() -> StepCircuitTimes {
+    StepCircuitTimes {
+        native: NATIVE.with(|t| t.take()),
+        constraints: CONSTRAINTS.with(|t| t.take()),
+    }
+}
+
+/// Number of constraints before and after the last
+/// `generate_step_constraints` on this thread, and resets it.
+pub fn take_step_span() -> Option<(usize, usize)> {
+    STEP_SPAN.with(|s| s.take())
+}
+
+fn add(counter: &'static std::thread::LocalKey<Cell<Duration>>, start: Stopwatch) {
+    let elapsed = start.elapsed();
+    counter.with(|t| t.set(t.get() + elapsed));
+}
+
+#[derive(Clone, Debug)]
+pub struct TimedFCircuit<F: PrimeField, FC> {
+    inner: FC,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField, FC> TimedFCircuit<F, FC> {
+    pub fn wrap(inner: FC) -> Self {
+        Self {
+            inner,
+            _f: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &FC {
+        &self.inner
+    }
+}
+
+impl<F: PrimeField, FC: FCircuit<F>> FCircuit<F> for TimedFCircuit<F, FC> {
+    type Params = FC::Params;
+
+    fn new(params: Self::Params) -> Result<Self, Error> {
+        Ok(Self::wrap(FC::new(params)?))
+    }
+
+    fn state_len(&self) -> usize {
+        self.inner.state_len()
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        self.inner.external_inputs_len()
+    }
+
+    fn step_native(
+        &self,
+        i: usize,
+        z_i: Vec<F>,
+        external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let start = Stopwatch::start();
+        let z_i1 = self.inner.step_native(i, z_i, external_inputs);
+        add(&NATIVE, start);
+        z_i1
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        i: usize,
+        z_i: Vec<FpVar<F>>,
+        external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let before = cs.num_constraints();
+        let start = Stopwatch::start();
+        let z_i1 = self.inner.generate_step_constraints(cs.clone(), i, z_i, external_inputs);
+        add(&CONSTRAINTS, start);
+        STEP_SPAN.with(|s| s.set(Some((before, cs.num_constraints()))));
+        z_i1
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::CubicFCircuit;
+    use crate::circuits::conformance::assert_fcircuit_consistent;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::alloc::AllocVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn test_timed_fcircuit() {
+        take_times();
+        let circuit = TimedFCircuit::<Fr, CubicFCircuit<Fr>>::new(()).unwrap();
+        let z_1 = circuit.step_native(0, vec![Fr::from(3_u32)], vec![]).unwrap();
+        assert_eq!(z_1, circuit.inner().step_native(0, vec![Fr::from(3_u32)], vec![]).unwrap());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_0 = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(vec![Fr::from(3_u32)])).unwrap();
+        circuit.generate_step_constraints(cs.clone(), 0, z_0, vec![]).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(take_step_span(), Some((0, cs.num_constraints())));
+        assert_eq!(take_step_span(), None);
+
+        let times = take_times();
+        assert!(times.native > Duration::ZERO);
+        assert!(times.constraints > Duration::ZERO);
+        assert_eq!(take_times(), StepCircuitTimes::default());
+    }
+
+    #[test]
+    fn test_timed_consistent() {
+        assert_fcircuit_consistent(&TimedFCircuit::<Fr, CubicFCircuit<Fr>>::new(()).unwrap());
+    }
+}
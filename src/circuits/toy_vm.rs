@@ -0,0 +1,228 @@
+//! Toy zkVM: a register machine executing one instruction per step.
+//!
+//! The program is a constant ROM baked into the circuit and the state is
+//! `[pc, r_0, ..., r_{N_REGS-1}]`. Each step fetches the instruction at `pc`
+//! through a one-hot selector over the ROM, evaluates every opcode and keeps the
+//! selected result, which is the usual shape of a folding-based zkVM step. A `pc`
+//! past the end of the program halts the machine: the state no longer changes.
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+pub const N_REGS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    /// `rd = rs1 + rs2`
+    Add { rd: usize, rs1: usize, rs2: usize },
+    /// `rd = rs1 - rs2`
+    Sub { rd: usize, rs1: usize, rs2: usize },
+    /// `rd = rs1 * rs2`
+    Mul { rd: usize, rs1: usize, rs2: usize },
+    /// `rd = imm`
+    Li { rd: usize, imm: i64 },
+    /// `pc = target` if `rs != 0`
+    Jnz { rs: usize, target: usize },
+}
+
+impl Instruction {
+    fn operands(&self) -> (usize, usize) {
+        match *self {
+            Self::Add { rs1, rs2, .. } | Self::Sub { rs1, rs2, .. } | Self::Mul { rs1, rs2, .. } => {
+                (rs1, rs2)
+            }
+            Self::Jnz { rs, .. } => (rs, 0),
+            Self::Li { .. } => (0, 0),
+        }
+    }
+
+    fn rd(&self) -> Option<usize> {
+        match *self {
+            Self::Add { rd, .. }
+            | Self::Sub { rd, .. }
+            | Self::Mul { rd, .. }
+            | Self::Li { rd, .. } => Some(rd),
+            Self::Jnz { .. } => None,
+        }
+    }
+}
+
+/// Computes the `n`-th Fibonacci number into `r0`, looping `n` times.
+pub fn fibonacci_program(n: i64) -> Vec<Instruction> {
+    use Instruction::*;
+    vec![
+        Li { rd: 3, imm: 1 },
+        Li { rd: 1, imm: 1 },
+        Li { rd: 2, imm: n },
+        // (r0, r1) = (r0 + r1, r0)
+        Add { rd: 0, rs1: 0, rs2: 1 },
+        Sub { rd: 1, rs1: 0, rs2: 1 },
+        Sub { rd: 2, rs1: 2, rs2: 3 },
+        Jnz { rs: 2, target: 3 },
+    ]
+}
+
+#[derive(Clone, Debug)]
+pub struct ToyVmFCircuit<F: PrimeField> {
+    pub program: Vec<Instruction>,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for ToyVmFCircuit<F> {
+    type Params = Vec<Instruction>;
+
+    fn new(program: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            program,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1 + N_REGS
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let pc = z_i[0];
+        let mut regs = z_i[1..].to_vec();
+        let Some(instruction) = (0..self.program.len())
+            .find(|k| F::from(*k as u64) == pc)
+            .map(|k| self.program[k])
+        else {
+            return Ok(z_i);
+        };
+
+        let mut next_pc = pc + F::one();
+        match instruction {
+            Instruction::Add { rd, rs1, rs2 } => regs[rd] = regs[rs1] + regs[rs2],
+            Instruction::Sub { rd, rs1, rs2 } => regs[rd] = regs[rs1] - regs[rs2],
+            Instruction::Mul { rd, rs1, rs2 } => regs[rd] = regs[rs1] * regs[rs2],
+            Instruction::Li { rd, imm } => regs[rd] = F::from(imm),
+            Instruction::Jnz { rs, target } => {
+                if !regs[rs].is_zero() {
+                    next_pc = F::from(target as u64);
+                }
+            }
+        }
+        Ok([vec![next_pc], regs].concat())
+    }
+
+    fn generate_step_constraints(
+        &self,
+        _cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let pc = &z_i[0];
+        let regs = &z_i[1..];
+
+        // fetch: one-hot selector over the ROM
+        let selectors = (0..self.program.len())
+            .map(|k| Ok(FpVar::from(pc.is_eq(&FpVar::constant(F::from(k as u64)))?)))
+            .collect::<Result<Vec<FpVar<F>>, SynthesisError>>()?;
+        // sum of the selectors of the instructions matching `filter`, times
+        // their `value`; linear since the program is constant
+        let select = |filter: &dyn Fn(&Instruction) -> Option<F>| -> FpVar<F> {
+            self.program
+                .iter()
+                .zip(&selectors)
+                .filter_map(|(instruction, s)| filter(instruction).map(|v| s * v))
+                .fold(FpVar::zero(), |acc, v| acc + v)
+        };
+        let flag = |f: fn(&Instruction) -> bool| select(&|ins| f(ins).then_some(F::one()));
+
+        // decode and read operands
+        let mut a = FpVar::zero();
+        let mut b = FpVar::zero();
+        for (instruction, s) in self.program.iter().zip(&selectors) {
+            let (rs1, rs2) = instruction.operands();
+            a += s * &regs[rs1];
+            b += s * &regs[rs2];
+        }
+        let is_add = flag(|ins| matches!(ins, Instruction::Add { .. }));
+        let is_sub = flag(|ins| matches!(ins, Instruction::Sub { .. }));
+        let is_mul = flag(|ins| matches!(ins, Instruction::Mul { .. }));
+        let is_jnz = flag(|ins| matches!(ins, Instruction::Jnz { .. }));
+        let imm = select(&|ins| match ins {
+            Instruction::Li { imm, .. } => Some(F::from(*imm)),
+            _ => None,
+        });
+        let target = select(&|ins| match ins {
+            Instruction::Jnz { target, .. } => Some(F::from(*target as u64)),
+            _ => None,
+        });
+        let in_range = selectors.iter().fold(FpVar::zero(), |acc, s| acc + s);
+
+        // execute
+        let result = &is_add * (&a + &b) + &is_sub * (&a - &b) + is_mul * (&a * &b) + imm;
+        let mut next_regs = Vec::with_capacity(N_REGS);
+        for (j, r) in regs.iter().enumerate() {
+            let is_rd = select(&|ins| (ins.rd() == Some(j)).then_some(F::one()));
+            next_regs.push(r + is_rd * (&result - r));
+        }
+        let jump = is_jnz * FpVar::from(a.is_zero()?.not());
+        let next_pc = pc + &in_range + jump * (target - pc - FpVar::one());
+
+        Ok([vec![next_pc], next_regs].concat())
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_r1cs_std::{alloc::AllocVar, R1CSVar};
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::UniformRand;
+    use rand::Rng;
+
+    #[test]
+    fn test_toy_vm_fibonacci() {
+        let circuit = ToyVmFCircuit::<Fr>::new(fibonacci_program(10)).unwrap();
+        let mut z = vec![Fr::from(0_u32); 1 + N_REGS];
+        // 3 setup instructions, 4 per loop iteration, plus a halted step
+        for i in 0..3 + 4 * 10 + 1 {
+            let z_next = circuit.step_native(i, z.clone(), vec![]).unwrap();
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            let z_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z.clone())).unwrap();
+            let z_next_var = circuit
+                .generate_step_constraints(cs.clone(), i, z_var, vec![])
+                .unwrap();
+            assert_eq!(z_next_var.value().unwrap(), z_next);
+            assert!(cs.is_satisfied().unwrap());
+            z = z_next;
+        }
+        assert_eq!(z[0], Fr::from(7_u32));
+        assert_eq!(z[1], Fr::from(55_u32));
+    }
+
+    #[test]
+    fn test_toy_vm_consistent() {
+        let program = fibonacci_program(10);
+        let circuit = ToyVmFCircuit::<Fr>::new(program.clone()).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| {
+            // up to one past the last instruction, where the VM halts
+            let pc = Fr::from(rng.gen_range(0..=program.len() as u64));
+            let regs: Vec<Fr> = (0..N_REGS).map(|_| Fr::rand(rng)).collect();
+            ([vec![pc], regs].concat(), vec![])
+        });
+    }
+}
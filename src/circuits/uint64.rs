@@ -0,0 +1,153 @@
+//! Range-check and `u64` arithmetic heavy circuit, the kind of workload that
+//! bit decomposition makes expensive in R1CS.
+//!
+//! The state is a single `u64` (range-checked on entry). Each round does a
+//! rotate/shift/xor mix, a wrapping three-operand addition and a carry-style
+//! comparison:
+//!
+//! ```text
+//! y = rotr(x, 13) ^ (x >> 7)
+//! s = x + y + C            (mod 2^64)
+//! x = s + (s < y)          (mod 2^64)
+//! ```
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar, uint64::UInt64, R1CSVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use std::marker::PhantomData;
+
+use folding_schemes::{frontend::FCircuit, Error};
+
+const C: u64 = 0x9E3779B97F4A7C15;
+
+/// Decomposes `v` into `n_bits` witness bits, enforcing `v < 2^n_bits`.
+pub fn range_check_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    v: &FpVar<F>,
+    n_bits: usize,
+) -> Result<Vec<Boolean<F>>, SynthesisError> {
+    let bits = (0..n_bits)
+        .map(|j| Boolean::new_witness(cs.clone(), || Ok(v.value()?.into_bigint().get_bit(j))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(v)?;
+    Ok(bits)
+}
+
+fn shr<F: PrimeField>(x: &UInt64<F>, n: usize) -> UInt64<F> {
+    let mut bits = x.to_bits_le()[n..].to_vec();
+    bits.resize(64, Boolean::FALSE);
+    UInt64::from_bits_le(&bits)
+}
+
+/// `a < b`, from the top bit of `a - b + 2^64`.
+fn less_than<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    a: &UInt64<F>,
+    b: &UInt64<F>,
+) -> Result<Boolean<F>, SynthesisError> {
+    let a = Boolean::le_bits_to_fp_var(&a.to_bits_le())?;
+    let b = Boolean::le_bits_to_fp_var(&b.to_bits_le())?;
+    let diff = a - b + F::from(2_u64).pow([64]);
+    Ok(range_check_var(cs, &diff, 65)?[64].not())
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct UInt64FCircuit<F: PrimeField> {
+    pub n_rounds: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> FCircuit<F> for UInt64FCircuit<F> {
+    type Params = usize;
+
+    fn new(n_rounds: Self::Params) -> Result<Self, Error> {
+        Ok(Self {
+            n_rounds,
+            _f: PhantomData,
+        })
+    }
+
+    fn state_len(&self) -> usize {
+        1
+    }
+
+    fn external_inputs_len(&self) -> usize {
+        0
+    }
+
+    fn step_native(
+        &self,
+        _i: usize,
+        z_i: Vec<F>,
+        _external_inputs: Vec<F>,
+    ) -> Result<Vec<F>, Error> {
+        let z = z_i[0].into_bigint();
+        if z.num_bits() > 64 {
+            return Err(Error::NotSatisfied);
+        }
+        let mut x = z.as_ref()[0];
+        for _ in 0..self.n_rounds {
+            let y = x.rotate_right(13) ^ (x >> 7);
+            let s = x.wrapping_add(y).wrapping_add(C);
+            x = s.wrapping_add((s < y) as u64);
+        }
+        Ok(vec![F::from(x)])
+    }
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        _i: usize,
+        z_i: Vec<FpVar<F>>,
+        _external_inputs: Vec<FpVar<F>>,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut x = UInt64::from_bits_le(&range_check_var(cs.clone(), &z_i[0], 64)?);
+        for _ in 0..self.n_rounds {
+            let y = x.rotr(13).xor(&shr(&x, 7))?;
+            let s = UInt64::addmany(&[x.clone(), y.clone(), UInt64::constant(C)])?;
+            let mut carry = vec![less_than(cs.clone(), &s, &y)?];
+            carry.resize(64, Boolean::FALSE);
+            x = UInt64::addmany(&[s, UInt64::from_bits_le(&carry)])?;
+        }
+        Ok(vec![Boolean::le_bits_to_fp_var(&x.to_bits_le())?])
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::conformance::assert_fcircuit_consistent_with;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::Rng;
+
+    #[test]
+    fn test_uint64_native_vs_gadget() {
+        let circuit = UInt64FCircuit::<Fr>::new(8).unwrap();
+        let z_i = vec![Fr::from(u64::MAX - 3)];
+        let z_i1 = circuit.step_native(0, z_i.clone(), vec![]).unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let z_i_var = Vec::<FpVar<Fr>>::new_witness(cs.clone(), || Ok(z_i)).unwrap();
+        let z_i1_var = circuit
+            .generate_step_constraints(cs.clone(), 0, z_i_var, vec![])
+            .unwrap();
+        assert_eq!(z_i1_var.value().unwrap(), z_i1);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_uint64_rejects_out_of_range_state() {
+        let circuit = UInt64FCircuit::<Fr>::new(1).unwrap();
+        assert!(circuit
+            .step_native(0, vec![Fr::from(u64::MAX) + Fr::from(1_u32)], vec![])
+            .is_err());
+    }
+
+    #[test]
+    fn test_uint64_consistent() {
+        let circuit = UInt64FCircuit::<Fr>::new(8).unwrap();
+        assert_fcircuit_consistent_with(&circuit, |rng| (vec![Fr::from(rng.gen::<u64>())], vec![]));
+    }
+}
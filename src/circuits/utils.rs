@@ -0,0 +1,51 @@
+//! Conversions between the IVC state and the byte strings hashed by the
+//! bit-oriented circuits. Native and gadget versions produce the same bytes.
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{boolean::Boolean, fields::fp::FpVar, uint8::UInt8, ToBitsGadget};
+use ark_relations::r1cs::SynthesisError;
+
+/// Number of bytes packed back into a field element, small enough to fit in
+/// the scalar field of every supported curve.
+pub const PACKED_BYTES: usize = 31;
+
+/// Little-endian bytes of the canonical representation of `x`, with the last
+/// byte zero-padded.
+pub fn field_to_bytes_le<F: PrimeField>(x: F) -> Vec<u8> {
+    let mut bits = x.into_bigint().to_bits_le();
+    bits.truncate(F::MODULUS_BIT_SIZE as usize);
+    bits.chunks(8)
+        .map(|byte| {
+            byte.iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, b)| acc | ((*b as u8) << i))
+        })
+        .collect()
+}
+
+/// Packs the first `PACKED_BYTES` of `bytes` into a field element.
+pub fn bytes_to_field<F: PrimeField>(bytes: &[u8]) -> F {
+    F::from_le_bytes_mod_order(&bytes[..PACKED_BYTES])
+}
+
+/// Gadget version of `field_to_bytes_le`.
+pub fn field_var_to_bytes_le<F: PrimeField>(x: &FpVar<F>) -> Result<Vec<UInt8<F>>, SynthesisError> {
+    let bits = x.to_bits_le()?;
+    Ok(bits
+        .chunks(8)
+        .map(|byte| {
+            let mut byte = byte.to_vec();
+            byte.resize(8, Boolean::FALSE);
+            UInt8::from_bits_le(&byte)
+        })
+        .collect())
+}
+
+/// Gadget version of `bytes_to_field`.
+pub fn bytes_var_to_field<F: PrimeField>(bytes: &[UInt8<F>]) -> Result<FpVar<F>, SynthesisError> {
+    let bits = bytes[..PACKED_BYTES]
+        .iter()
+        .map(|b| b.to_bits_le())
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    Boolean::le_bits_to_fp_var(&bits)
+}
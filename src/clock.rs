@@ -0,0 +1,150 @@
+//! Phase timing, from `Instant` or, with the `cycles` feature, the CPU's
+//! cycle counter.
+//!
+//! `Instant` goes through the OS clock, whose call overhead and jitter blur
+//! phases of a few microseconds such as transcript absorption. With the
+//! feature, `Stopwatch` reads the counter directly: `rdtsc` between `lfence`s
+//! on x86_64, so that it is not reordered with the timed code, and
+//! `cntvct_el0` after an `isb` on aarch64. Both tick at a constant rate
+//! rather than the core clock (see `perf` for core cycles); the x86_64 rate
+//! is calibrated against `Instant` once, and durations are derived from the
+//! ticks. On other targets, and without the feature, `Stopwatch` falls back
+//! to `Instant` and counts no cycles.
+use std::time::{Duration, Instant};
+
+#[cfg(all(feature = "cycles", target_arch = "x86_64"))]
+mod counter {
+    use std::arch::x86_64::{_mm_lfence, _rdtsc};
+    use std::sync::OnceLock;
+    use std::time::{Duration, Instant};
+
+    pub fn read() -> Option<u64> {
+        // SAFETY: `lfence` (SSE2) and `rdtsc` are available on every x86_64 CPU
+        #[allow(unused_unsafe)]
+        let ticks = unsafe {
+            _mm_lfence();
+            let ticks = _rdtsc();
+            _mm_lfence();
+            ticks
+        };
+        Some(ticks)
+    }
+
+    pub fn frequency() -> Option<u64> {
+        static FREQUENCY: OnceLock<u64> = OnceLock::new();
+        Some(*FREQUENCY.get_or_init(|| {
+            let start = Instant::now();
+            let ticks = read().unwrap();
+            while start.elapsed() < Duration::from_millis(50) {}
+            let ticks = read().unwrap() - ticks;
+            (ticks as f64 / start.elapsed().as_secs_f64()) as u64
+        }))
+    }
+}
+
+#[cfg(all(feature = "cycles", target_arch = "aarch64"))]
+mod counter {
+    use std::arch::asm;
+
+    pub fn read() -> Option<u64> {
+        let ticks: u64;
+        // SAFETY: the virtual counter is readable from EL0 on Linux and macOS
+        unsafe { asm!("isb", "mrs {}, cntvct_el0", out(reg) ticks, options(nostack)) };
+        Some(ticks)
+    }
+
+    pub fn frequency() -> Option<u64> {
+        let frequency: u64;
+        // SAFETY: as for `cntvct_el0`
+        unsafe { asm!("mrs {}, cntfrq_el0", out(reg) frequency, options(nomem, nostack)) };
+        Some(frequency)
+    }
+}
+
+#[cfg(not(all(
+    feature = "cycles",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+mod counter {
+    pub fn read() -> Option<u64> {
+        None
+    }
+
+    pub fn frequency() -> Option<u64> {
+        None
+    }
+}
+
+/// Ticks per second of the cycle counter, if it is used.
+pub fn counter_frequency() -> Option<u64> {
+    counter::frequency()
+}
+
+/// Duration of `ticks` of a counter ticking `frequency` times per second.
+pub fn ticks_to_duration(ticks: u64, frequency: u64) -> Duration {
+    Duration::from_nanos((ticks as u128 * 1_000_000_000 / frequency as u128) as u64)
+}
+
+/// Drop-in for `Instant` that also counts cycles with the `cycles` feature.
+#[derive(Clone, Copy, Debug)]
+pub struct Stopwatch {
+    instant: Instant,
+    ticks: Option<u64>,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        // calibrate before, not inside, the first timed phase
+        counter::frequency();
+        let instant = Instant::now();
+        Self {
+            instant,
+            ticks: counter::read(),
+        }
+    }
+
+    /// Time since `start`, and the counter ticks if counted.
+    pub fn stop(&self) -> (Duration, Option<u64>) {
+        let ticks = counter::read()
+            .zip(self.ticks)
+            .map(|(end, start)| end - start);
+        match ticks.zip(counter::frequency()) {
+            Some((ticks, frequency)) => (ticks_to_duration(ticks, frequency), Some(ticks)),
+            None => (self.instant.elapsed(), None),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.stop().0
+    }
+}
+
+/// Prints the cycles of `phase`, if counted.
+pub fn report_cycles(label: &str, phase: &str, cycles: Option<u64>) {
+    if let Some(cycles) = cycles {
+        println!("{} {} cycles: {}", label, phase, cycles);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_to_duration() {
+        assert_eq!(
+            ticks_to_duration(3_000_000_000, 3_000_000_000),
+            Duration::from_secs(1)
+        );
+        assert_eq!(ticks_to_duration(24, 24_000_000), Duration::from_micros(1));
+    }
+
+    #[test]
+    fn test_stopwatch() {
+        let start = Stopwatch::start();
+        std::thread::sleep(Duration::from_millis(20));
+        let (elapsed, cycles) = start.stop();
+        assert!(elapsed >= Duration::from_millis(19), "{:?}", elapsed);
+        assert_eq!(cycles.is_some(), counter_frequency().is_some());
+    }
+}
@@ -0,0 +1,137 @@
+//! A/B comparison of two registry configurations.
+//!
+//! The two are run interleaved, alternating which goes first, so that machine
+//! noise (thermal throttling, background load) hits both alike, and their
+//! steps are paired by round, trial and step index, i.e. at the same running
+//! instance size. `stats::paired_test` then tells whether the difference is
+//! more than noise.
+use std::time::Duration;
+
+use crate::config::BenchConfig;
+use crate::error::BenchError;
+use crate::registry::BenchEntry;
+use crate::stats::{paired_test, PairedTest};
+
+/// Significance level of `Comparison::print`.
+pub const ALPHA: f64 = 0.05;
+
+#[derive(Debug)]
+pub struct Comparison {
+    pub a: String,
+    pub b: String,
+    /// Paired step times of `a` and `b`.
+    pub a_steps: Vec<Duration>,
+    pub b_steps: Vec<Duration>,
+    pub test: PairedTest,
+}
+
+impl Comparison {
+    /// Relative difference of the mean step time of `a` over `b`.
+    pub fn relative_diff(&self) -> f64 {
+        let b = mean_secs(&self.b_steps);
+        if b > 0.0 {
+            self.test.mean_diff / b
+        } else {
+            0.0
+        }
+    }
+
+    pub fn print(&self) {
+        println!(
+            "\n{} vs {}: {:.4} ms vs {:.4} ms per step ({:+.2}%) over {} pairs",
+            self.a,
+            self.b,
+            mean_secs(&self.a_steps) * 1e3,
+            mean_secs(&self.b_steps) * 1e3,
+            self.relative_diff() * 100.0,
+            self.test.pairs
+        );
+        println!(
+            "paired t-test: t = {:.3}, p = {:.4}; Wilcoxon signed-rank: z = {:.3}, p = {:.4}",
+            self.test.t, self.test.t_p_value, self.test.wilcoxon_z, self.test.wilcoxon_p_value
+        );
+        if self.test.significant(ALPHA) {
+            println!("The difference is significant at the {}% level", ALPHA * 100.0);
+        } else {
+            println!("The difference is not significant at the {}% level", ALPHA * 100.0);
+        }
+    }
+}
+
+fn mean_secs(steps: &[Duration]) -> f64 {
+    if steps.is_empty() {
+        return 0.0;
+    }
+    steps.iter().map(Duration::as_secs_f64).sum::<f64>() / steps.len() as f64
+}
+
+/// Runs `a` and `b` `rounds` times each with `config`, interleaved, and tests
+/// the difference of their step times.
+pub fn compare(
+    a: &BenchEntry,
+    b: &BenchEntry,
+    config: &BenchConfig,
+    rounds: usize,
+) -> Result<Comparison, BenchError> {
+    let mut a_steps = Vec::new();
+    let mut b_steps = Vec::new();
+    for round in 0..rounds {
+        println!("\nRound {}/{}", round + 1, rounds);
+        let (a_results, b_results) = if round % 2 == 0 {
+            let a_results = (a.run)(config)?;
+            (a_results, (b.run)(config)?)
+        } else {
+            let b_results = (b.run)(config)?;
+            ((a.run)(config)?, b_results)
+        };
+        for (a_result, b_result) in a_results.iter().zip(&b_results) {
+            let pairs = a_result.steps.len().min(b_result.steps.len());
+            a_steps.extend_from_slice(&a_result.steps[..pairs]);
+            b_steps.extend_from_slice(&b_result.steps[..pairs]);
+        }
+    }
+    let test = paired_test(&a_steps, &b_steps).ok_or_else(|| {
+        BenchError::InvalidInput(format!(
+            "{} and {} need at least two paired steps",
+            a.name, b.name
+        ))
+    })?;
+    Ok(Comparison {
+        a: a.name.clone(),
+        b: b.name.clone(),
+        a_steps,
+        b_steps,
+        test,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::result::BenchResult;
+    use std::sync::Arc;
+
+    fn entry(name: &str, step_us: u64) -> BenchEntry {
+        BenchEntry {
+            name: name.to_string(),
+            run: Arc::new(move |config: &BenchConfig| {
+                let mut result = BenchResult::new("test");
+                result.steps =
+                    (0..config.steps as u64).map(|i| Duration::from_micros(step_us + i)).collect();
+                Ok(vec![result])
+            }),
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let config = BenchConfig::builder().steps(4).build().unwrap();
+        let comparison = compare(&entry("slow", 110), &entry("fast", 100), &config, 3).unwrap();
+        assert_eq!(comparison.test.pairs, 12);
+        assert!((comparison.relative_diff() - 10.0 / 101.5).abs() < 1e-9);
+        assert!(comparison.test.significant(ALPHA));
+
+        let same = compare(&entry("a", 100), &entry("b", 100), &config, 2).unwrap();
+        assert!(!same.test.significant(ALPHA));
+    }
+}
@@ -0,0 +1,611 @@
+//! Harness settings shared by the benchmarks, set from the command line by the
+//! `sonobe-bench` binary or from the environment when running `cargo test`.
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig};
+use ark_ff::PrimeField;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::affinity::check_cores;
+use crate::cache::ParamCache;
+use crate::error::BenchError;
+
+/// Steps folded when neither `--steps` nor `SONOBE_BENCH_STEPS` is given.
+pub const DEFAULT_STEPS: usize = 10;
+
+/// Powers of tau loaded from `BenchConfig::srs` when `--srs-powers` is not
+/// given, enough for the augmented circuits of the default benchmarks.
+pub const DEFAULT_SRS_POWERS: usize = 1 << 16;
+
+static N_STEPS: OnceLock<usize> = OnceLock::new();
+
+/// Overrides the number of folding steps for the rest of the process.
+pub fn set_n_steps(n_steps: usize) {
+    assert!(n_steps > 0, "at least one step must be folded");
+    N_STEPS.set(n_steps).expect("number of steps already set");
+}
+
+/// Number of folding steps per benchmark: the value passed to `set_n_steps`,
+/// else `SONOBE_BENCH_STEPS`, else `DEFAULT_STEPS`.
+pub fn n_steps() -> usize {
+    *N_STEPS.get_or_init(|| {
+        std::env::var("SONOBE_BENCH_STEPS").map_or(DEFAULT_STEPS, |s| match s.parse() {
+            Ok(n) if n > 0 => n,
+            _ => panic!("SONOBE_BENCH_STEPS must be a positive integer"),
+        })
+    })
+}
+
+static TIMEOUT: OnceLock<Option<Duration>> = OnceLock::new();
+
+pub fn set_timeout(timeout: Option<Duration>) {
+    TIMEOUT.set(timeout).expect("timeout already set");
+}
+
+/// Time budget of each benchmark configuration: the one passed to
+/// `set_timeout`, else `SONOBE_BENCH_TIMEOUT` seconds, else unlimited.
+pub fn timeout() -> Option<Duration> {
+    *TIMEOUT.get_or_init(|| {
+        std::env::var("SONOBE_BENCH_TIMEOUT").ok().map(|s| {
+            Duration::from_secs(s.parse().expect("SONOBE_BENCH_TIMEOUT must be in seconds"))
+        })
+    })
+}
+
+static VERIFY_EVERY: OnceLock<Option<usize>> = OnceLock::new();
+
+pub fn set_verify_every(every: Option<usize>) {
+    VERIFY_EVERY.set(every).expect("IVC verification interval already set");
+}
+
+/// Run `FoldingScheme::verify` on the IVC every this many folding steps: the
+/// interval passed to `set_verify_every`, else `SONOBE_BENCH_VERIFY_EVERY`,
+/// else never.
+pub fn verify_every() -> Option<usize> {
+    *VERIFY_EVERY.get_or_init(|| {
+        std::env::var("SONOBE_BENCH_VERIFY_EVERY").ok().map(|s| match s.parse() {
+            Ok(every) if every > 0 => every,
+            _ => panic!("SONOBE_BENCH_VERIFY_EVERY must be a positive step count"),
+        })
+    })
+}
+
+static NEGATIVE_TESTS: OnceLock<bool> = OnceLock::new();
+
+pub fn set_negative_tests(enabled: bool) {
+    NEGATIVE_TESTS.set(enabled).expect("negative tests already set");
+}
+
+/// Whether every decider proof is also checked to be rejected once mutated
+/// (see `bench::check_rejections`): the value passed to `set_negative_tests`,
+/// else whether `SONOBE_BENCH_NEGATIVE_TESTS` is set.
+pub fn negative_tests() -> bool {
+    *NEGATIVE_TESTS.get_or_init(|| std::env::var_os("SONOBE_BENCH_NEGATIVE_TESTS").is_some())
+}
+
+static CHECK_SAT: OnceLock<bool> = OnceLock::new();
+
+pub fn set_check_sat(enabled: bool) {
+    CHECK_SAT.set(enabled).expect("check sat already set");
+}
+
+/// Whether every folded step is re-synthesized and checked for satisfaction
+/// (see `bench::check_step_satisfaction`): the value passed to
+/// `set_check_sat`, else whether `SONOBE_BENCH_CHECK_SAT` is set.
+pub fn check_sat() -> bool {
+    *CHECK_SAT.get_or_init(|| std::env::var_os("SONOBE_BENCH_CHECK_SAT").is_some())
+}
+
+static VP_ROUND_TRIP: OnceLock<bool> = OnceLock::new();
+
+pub fn set_vp_round_trip(enabled: bool) {
+    VP_ROUND_TRIP.set(enabled).expect("vp round trip already set");
+}
+
+/// Whether every decider proof is also verified with verifier params read
+/// back from their bytes (see `bench::check_vp_round_trip`): the value passed
+/// to `set_vp_round_trip`, else whether `SONOBE_BENCH_VP_ROUND_TRIP` is set.
+pub fn vp_round_trip() -> bool {
+    *VP_ROUND_TRIP.get_or_init(|| std::env::var_os("SONOBE_BENCH_VP_ROUND_TRIP").is_some())
+}
+
+/// How much the folding loop prints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Summaries only.
+    Quiet,
+    /// Summaries and a progress bar.
+    Normal,
+    /// One line per step instead of the progress bar.
+    Verbose,
+    /// Also the IVC state after every step.
+    Trace,
+}
+
+static VERBOSITY: OnceLock<Verbosity> = OnceLock::new();
+
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.set(verbosity).expect("verbosity already set");
+}
+
+/// The verbosity passed to `set_verbosity`, else `SONOBE_BENCH_VERBOSITY`
+/// (0 to 3), else `Verbose`, which keeps `cargo test` output as detailed as
+/// before progress bars existed.
+pub fn verbosity() -> Verbosity {
+    *VERBOSITY.get_or_init(|| {
+        match std::env::var("SONOBE_BENCH_VERBOSITY").as_deref() {
+            Ok("0") => Verbosity::Quiet,
+            Ok("1") => Verbosity::Normal,
+            Ok("3") => Verbosity::Trace,
+            _ => Verbosity::Verbose,
+        }
+    })
+}
+
+static RNG: OnceLock<Mutex<ChaCha20Rng>> = OnceLock::new();
+
+/// Makes every `rng()` of the rest of the process derive from `seed`.
+pub fn set_seed(seed: u64) {
+    RNG.set(Mutex::new(ChaCha20Rng::seed_from_u64(seed)))
+        .expect("RNG already in use");
+}
+
+/// Fresh RNG for one phase of a benchmark. With a seed (`set_seed`, else
+/// `SONOBE_BENCH_SEED`) the streams are derived deterministically, in call
+/// order, from it; otherwise they are seeded from the OS.
+pub fn rng() -> ChaCha20Rng {
+    ChaCha20Rng::from_rng(&mut *master_rng().lock().unwrap()).unwrap()
+}
+
+/// Restarts the streams of `rng()` from `seed`, so that trials run with the
+/// same seed draw the same params and inputs.
+pub fn reseed(seed: u64) {
+    *master_rng().lock().unwrap() = ChaCha20Rng::seed_from_u64(seed);
+}
+
+fn master_rng() -> &'static Mutex<ChaCha20Rng> {
+    RNG.get_or_init(|| {
+        Mutex::new(match std::env::var("SONOBE_BENCH_SEED") {
+            Ok(seed) => ChaCha20Rng::seed_from_u64(
+                seed.parse().expect("SONOBE_BENCH_SEED must be an integer"),
+            ),
+            Err(_) => ChaCha20Rng::from_entropy(),
+        })
+    })
+}
+
+/// Parameters of the Poseidon sponge used by the folding transcripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PoseidonParams {
+    pub rate: usize,
+    pub full_rounds: usize,
+    pub partial_rounds: usize,
+    pub alpha: u64,
+}
+
+impl PoseidonParams {
+    /// The parameters of sonobe's `poseidon_canonical_config`.
+    pub const CANONICAL: Self = Self {
+        rate: 4,
+        full_rounds: 8,
+        partial_rounds: 60,
+        alpha: 5,
+    };
+    /// Width 3, the usual choice for 2-to-1 hashing.
+    pub const RATE2: Self = Self {
+        rate: 2,
+        full_rounds: 8,
+        partial_rounds: 57,
+        alpha: 5,
+    };
+
+    pub fn config<F: PrimeField>(&self) -> PoseidonConfig<F> {
+        let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
+            self.rate,
+            self.full_rounds as u64,
+            self.partial_rounds as u64,
+            0,
+        );
+        PoseidonConfig::new(
+            self.full_rounds,
+            self.partial_rounds,
+            self.alpha,
+            mds,
+            ark,
+            self.rate,
+            1,
+        )
+    }
+}
+
+/// A preset name (`canonical`, `rate2`) or `rate,full_rounds,partial_rounds,alpha`,
+/// with a positive rate and an even number of at least two full rounds (they
+/// are split evenly around the partial ones).
+impl FromStr for PoseidonParams {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "canonical" => return Ok(Self::CANONICAL),
+            "rate2" => return Ok(Self::RATE2),
+            _ => {}
+        }
+        let parts = s
+            .split(',')
+            .map(|p| p.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid Poseidon parameters {:?}: {}", s, e))?;
+        match parts[..] {
+            [0, ..] => Err(format!("Poseidon rate must be positive, got {:?}", s)),
+            [_, full_rounds, ..] if full_rounds < 2 || full_rounds % 2 != 0 => Err(format!(
+                "Poseidon full rounds must be even and at least 2, got {:?}",
+                s
+            )),
+            [rate, full_rounds, partial_rounds, alpha] => Ok(Self {
+                rate: rate as usize,
+                full_rounds: full_rounds as usize,
+                partial_rounds: partial_rounds as usize,
+                alpha,
+            }),
+            _ => Err(format!(
+                "expected a preset or rate,full_rounds,partial_rounds,alpha, got {:?}",
+                s
+            )),
+        }
+    }
+}
+
+static POSEIDON: OnceLock<PoseidonParams> = OnceLock::new();
+
+pub fn set_poseidon(params: PoseidonParams) {
+    POSEIDON.set(params).expect("Poseidon parameters already set");
+}
+
+/// Poseidon parameters of the transcripts: the ones passed to
+/// `set_poseidon`, else `SONOBE_BENCH_POSEIDON`, else `CANONICAL`. Fails if
+/// `SONOBE_BENCH_POSEIDON` does not parse.
+pub fn poseidon_params() -> Result<PoseidonParams, BenchError> {
+    if let Some(params) = POSEIDON.get() {
+        return Ok(*params);
+    }
+    let params = match std::env::var("SONOBE_BENCH_POSEIDON") {
+        Ok(s) => s
+            .parse()
+            .map_err(|e| BenchError::InvalidInput(format!("SONOBE_BENCH_POSEIDON: {}", e)))?,
+        Err(_) => PoseidonParams::CANONICAL,
+    };
+    Ok(*POSEIDON.get_or_init(|| params))
+}
+
+/// `poseidon_params()` instantiated over `F`.
+pub fn poseidon_config<F: PrimeField>() -> Result<PoseidonConfig<F>, BenchError> {
+    Ok(poseidon_params()?.config())
+}
+
+/// A phase of the pipeline after preprocessing, in the order they run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    Fold,
+    DeciderProve,
+    DeciderVerify,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Fold, Phase::DeciderProve, Phase::DeciderVerify];
+}
+
+/// `fold`, `prove` or `verify`.
+impl FromStr for Phase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fold" => Ok(Phase::Fold),
+            "prove" => Ok(Phase::DeciderProve),
+            "verify" => Ok(Phase::DeciderVerify),
+            _ => Err(format!("expected fold, prove or verify, got {:?}", s)),
+        }
+    }
+}
+
+/// Where the results of a run are written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputSink {
+    Terminal,
+    Json(PathBuf),
+    Csv(PathBuf),
+    Markdown(PathBuf),
+    /// Google Benchmark's JSON format, see `report::GoogleBenchmarkReporter`.
+    GoogleBenchmark(PathBuf),
+    /// Every timing sample, see `report::SamplesReporter`.
+    Samples(PathBuf),
+    /// booktabs tables for papers, see `report::LatexReporter`.
+    Latex(PathBuf),
+}
+
+/// Settings of one run of the pipelines, built with `BenchConfig::builder()`.
+///
+/// `BenchConfig::default()` reflects the process-wide settings above, so the
+/// `sonobe-bench` flags and `SONOBE_BENCH_*` variables still apply.
+#[derive(Clone, Debug)]
+pub struct BenchConfig {
+    /// Folding steps per trial.
+    pub steps: usize,
+    /// Times every benchmark is repeated.
+    pub trials: usize,
+    /// Keep only the median trial by total time instead of all of them, to
+    /// smooth out machine-level noise such as thermal throttling.
+    pub median: bool,
+    /// Seed every trial restarts from, see `reseed`. `None` keeps drawing
+    /// from `rng()`.
+    pub seed: Option<u64>,
+    pub sinks: Vec<OutputSink>,
+    /// The phases to run, a prefix of `Phase::ALL`: each phase needs the
+    /// output of the previous one.
+    pub phases: Vec<Phase>,
+    /// Directory of the params cache, `None` to preprocess every time.
+    pub cache_dir: Option<PathBuf>,
+    /// File of per-step external inputs fed instead of the benchmarks' own,
+    /// see `inputs`.
+    pub external_inputs: Option<PathBuf>,
+    /// Configurations `suite::run_suite` runs concurrently. 1 runs them one
+    /// after the other, for timings that need the machine to themselves;
+    /// with more, a seeded run is no longer reproducible since they draw
+    /// from the same `rng()` streams.
+    pub jobs: usize,
+    /// Only synthesize the circuits and report their sizes, see `dry_run`.
+    pub dry_run: bool,
+    /// Time the folding phase should take: `steps` is then calibrated for
+    /// every benchmark, see `calibrate`.
+    pub target: Option<Duration>,
+    /// Directory the BN254 decider proofs are exported to for snarkjs, one
+    /// subdirectory per benchmark, see `snarkjs`.
+    pub snarkjs_dir: Option<PathBuf>,
+    /// Directory the BN254 decider proofs are saved to with their verifier
+    /// params and public state, one subdirectory per benchmark, see
+    /// `artifacts`.
+    pub artifacts_dir: Option<PathBuf>,
+    /// SRS file the BN254 Nova benchmarks load their KZG keys from instead
+    /// of sampling them, see `srs::load_srs`.
+    pub srs: Option<PathBuf>,
+    /// Powers of tau loaded from `srs`.
+    pub srs_powers: usize,
+    /// Also time the MSMs, FFTs and Poseidon permutations of the curves of
+    /// the benchmarks, see `primitives`.
+    pub primitives: bool,
+    /// Cores the workers of every benchmark are pinned to, one worker per
+    /// core; empty to leave them to the global rayon pool, see `affinity`.
+    pub pin_cores: Vec<usize>,
+    /// Run every benchmark on a single rayon worker, see `affinity`.
+    pub no_work_stealing: bool,
+}
+
+impl BenchConfig {
+    pub fn builder() -> BenchConfigBuilder {
+        BenchConfigBuilder {
+            config: Self::default(),
+        }
+    }
+
+    pub fn runs(&self, phase: Phase) -> bool {
+        self.phases.contains(&phase)
+    }
+
+    pub fn param_cache(&self) -> ParamCache {
+        match &self.cache_dir {
+            Some(dir) => ParamCache::new(dir),
+            None => ParamCache::disabled(),
+        }
+    }
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            steps: n_steps(),
+            trials: 1,
+            median: false,
+            seed: None,
+            sinks: vec![OutputSink::Terminal],
+            phases: Phase::ALL.to_vec(),
+            cache_dir: ParamCache::from_env().dir,
+            external_inputs: None,
+            jobs: 1,
+            dry_run: false,
+            target: None,
+            snarkjs_dir: None,
+            artifacts_dir: None,
+            srs: None,
+            srs_powers: DEFAULT_SRS_POWERS,
+            primitives: false,
+            pin_cores: Vec::new(),
+            no_work_stealing: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BenchConfigBuilder {
+    config: BenchConfig,
+}
+
+impl BenchConfigBuilder {
+    pub fn steps(mut self, steps: usize) -> Self {
+        self.config.steps = steps;
+        self
+    }
+
+    pub fn trials(mut self, trials: usize) -> Self {
+        self.config.trials = trials;
+        self
+    }
+
+    pub fn median(mut self, median: bool) -> Self {
+        self.config.median = median;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = Some(seed);
+        self
+    }
+
+    pub fn sinks(mut self, sinks: impl IntoIterator<Item = OutputSink>) -> Self {
+        self.config.sinks = sinks.into_iter().collect();
+        self
+    }
+
+    pub fn phases(mut self, phases: impl IntoIterator<Item = Phase>) -> Self {
+        self.config.phases = phases.into_iter().collect();
+        self.config.phases.sort();
+        self.config.phases.dedup();
+        self
+    }
+
+    pub fn cache_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.config.cache_dir = dir;
+        self
+    }
+
+    pub fn external_inputs(mut self, path: Option<PathBuf>) -> Self {
+        self.config.external_inputs = path;
+        self
+    }
+
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.config.jobs = jobs;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.config.dry_run = dry_run;
+        self
+    }
+
+    pub fn target(mut self, target: Option<Duration>) -> Self {
+        self.config.target = target;
+        self
+    }
+
+    pub fn snarkjs_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.config.snarkjs_dir = dir;
+        self
+    }
+
+    pub fn artifacts_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.config.artifacts_dir = dir;
+        self
+    }
+
+    pub fn srs(mut self, path: Option<PathBuf>, n_powers: usize) -> Self {
+        self.config.srs = path;
+        self.config.srs_powers = n_powers;
+        self
+    }
+
+    pub fn primitives(mut self, primitives: bool) -> Self {
+        self.config.primitives = primitives;
+        self
+    }
+
+    pub fn pin_cores(mut self, cores: impl IntoIterator<Item = usize>) -> Self {
+        self.config.pin_cores = cores.into_iter().collect();
+        self
+    }
+
+    pub fn no_work_stealing(mut self, no_work_stealing: bool) -> Self {
+        self.config.no_work_stealing = no_work_stealing;
+        self
+    }
+
+    pub fn build(self) -> Result<BenchConfig, BenchError> {
+        let config = self.config;
+        if config.steps == 0 {
+            return Err(BenchError::InvalidInput("at least one step must be folded".into()));
+        }
+        if config.trials == 0 {
+            return Err(BenchError::InvalidInput("at least one trial must run".into()));
+        }
+        if config.jobs == 0 {
+            return Err(BenchError::InvalidInput("at least one job must run".into()));
+        }
+        if config.srs.is_some() && config.srs_powers == 0 {
+            return Err(BenchError::InvalidInput("the SRS needs at least one power".into()));
+        }
+        if config.target.is_some_and(|t| t.is_zero()) {
+            return Err(BenchError::InvalidInput("the target time must be positive".into()));
+        }
+        if cfg!(feature = "dhat-heap") && config.jobs > 1 {
+            return Err(BenchError::InvalidInput(
+                "the dhat-heap profiler runs one benchmark at a time".into(),
+            ));
+        }
+        check_cores(&config.pin_cores)?;
+        if config.phases.is_empty() || !Phase::ALL.starts_with(&config.phases) {
+            return Err(BenchError::InvalidInput(format!(
+                "phases {:?} skip a phase the later ones depend on",
+                config.phases
+            )));
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poseidon_params() {
+        assert_eq!("canonical".parse(), Ok(PoseidonParams::CANONICAL));
+        assert_eq!("2, 8, 57, 5".parse(), Ok(PoseidonParams::RATE2));
+        assert!("0,8,57,5".parse::<PoseidonParams>().is_err());
+        assert!("2,7,57,5".parse::<PoseidonParams>().is_err());
+        assert!("2,0,57,5".parse::<PoseidonParams>().is_err());
+        assert!("2,8,57".parse::<PoseidonParams>().is_err());
+    }
+
+    #[test]
+    fn test_config_builder() {
+        let config = BenchConfig::builder()
+            .steps(3)
+            .trials(2)
+            .median(true)
+            .seed(7)
+            .phases([Phase::DeciderProve, Phase::Fold])
+            .cache_dir(None)
+            .build()
+            .unwrap();
+        assert_eq!(config.steps, 3);
+        assert!(config.median);
+        assert!(!BenchConfig::default().median);
+        assert_eq!(config.phases, [Phase::Fold, Phase::DeciderProve]);
+        assert!(!config.runs(Phase::DeciderVerify));
+        assert!(config.param_cache().dir.is_none());
+
+        assert!(BenchConfig::builder().steps(0).build().is_err());
+        assert!(BenchConfig::builder().trials(0).build().is_err());
+        assert!(BenchConfig::builder().jobs(0).build().is_err());
+        assert!(BenchConfig::builder()
+            .target(Some(Duration::ZERO))
+            .build()
+            .is_err());
+        assert!(BenchConfig::builder()
+            .phases([Phase::Fold, Phase::DeciderVerify])
+            .build()
+            .is_err());
+        assert!(BenchConfig::builder().phases([]).build().is_err());
+        assert!(BenchConfig::builder()
+            .srs(Some(PathBuf::from("srs.ptau")), 0)
+            .build()
+            .is_err());
+        assert!(BenchConfig::builder()
+            .pin_cores([usize::MAX])
+            .build()
+            .is_err());
+    }
+}
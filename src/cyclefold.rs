@@ -0,0 +1,167 @@
+//! Cost split between the primary and the secondary (CycleFold) curve.
+//!
+//! Nova with CycleFold proves the augmented circuit on the primary curve and
+//! delegates its non-native scalar multiplications to the CycleFold circuit
+//! on the secondary curve (Grumpkin for BN254, MNT6 for MNT4). Totals hide
+//! how much of a step each side costs, which is what a curve cycle trades:
+//! `cyclefold_split` folds with `MsmCounter` commitment schemes and splits
+//! every step's time into the commitments on either curve and the rest
+//! (witness generation, synthesis and folding, CycleFold's own witness
+//! included), and the constraints into the augmented circuit, with its
+//! CycleFold checks, and the CycleFold circuit.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use folding_schemes::{frontend::FCircuit, FoldingScheme};
+
+use crate::bench::CircuitSize;
+use crate::config::rng;
+use crate::error::BenchError;
+use crate::msm::take_msm_stats_over;
+
+/// Mean cost of a folding step on either curve of the cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CycleFoldSplit {
+    /// Constraints of the augmented circuit, on the primary curve.
+    pub primary_constraints: usize,
+    /// Those of them checking the CycleFold instances, if the augmented
+    /// circuit could be broken down.
+    pub cyclefold_checks: Option<usize>,
+    /// Constraints of the CycleFold circuit, on the secondary curve.
+    pub secondary_constraints: usize,
+    /// The whole step.
+    pub step: Duration,
+    /// Commitments on the primary curve.
+    pub primary_commitments: Duration,
+    /// Commitments on the secondary curve.
+    pub secondary_commitments: Duration,
+}
+
+impl CycleFoldSplit {
+    /// Share of the step time spent committing on the secondary curve.
+    pub fn secondary_time_share(&self) -> f64 {
+        self.secondary_commitments.as_secs_f64() / self.step.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+
+    /// Share of the constraints of both circuits in the CycleFold circuit.
+    pub fn secondary_constraint_share(&self) -> f64 {
+        let total = (self.primary_constraints + self.secondary_constraints).max(1);
+        self.secondary_constraints as f64 / total as f64
+    }
+}
+
+impl fmt::Display for CycleFoldSplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let step = self.step.as_secs_f64().max(f64::MIN_POSITIVE);
+        let percent = |d: Duration| d.as_secs_f64() / step * 100.0;
+        let rest = self
+            .step
+            .saturating_sub(self.primary_commitments + self.secondary_commitments);
+        write!(
+            f,
+            "step {:?}: primary commitments {:?} ({:.1}%), secondary commitments {:?} ({:.1}%), \
+             rest {:?} ({:.1}%); constraints: primary {}",
+            self.step,
+            self.primary_commitments,
+            percent(self.primary_commitments),
+            self.secondary_commitments,
+            percent(self.secondary_commitments),
+            rest,
+            percent(rest),
+            self.primary_constraints
+        )?;
+        if let Some(checks) = self.cyclefold_checks {
+            write!(f, " (CycleFold checks {})", checks)?;
+        }
+        write!(
+            f,
+            ", secondary {} ({:.1}%)",
+            self.secondary_constraints,
+            100.0 * self.secondary_constraint_share()
+        )
+    }
+}
+
+/// Folds `n_steps` with `FS` built over `MsmCounter` commitment schemes,
+/// printing the split of every step, and returns the mean split.
+pub fn cyclefold_split<C1, C2, FC, FS>(
+    label: &str,
+    fs_params: &(FS::ProverParam, FS::VerifierParam),
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    mut external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<CycleFoldSplit, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + CircuitSize,
+{
+    assert!(n_steps > 0, "no steps to split");
+    let mut fs = FS::init(fs_params, f_circuit, z_0)?;
+    let sizes = CycleFoldSplit {
+        primary_constraints: fs.augmented_constraints(),
+        cyclefold_checks: fs.augmented_breakdown()?.map(|b| b.cyclefold),
+        secondary_constraints: fs.cyclefold_constraints(),
+        ..Default::default()
+    };
+    take_msm_stats_over::<C2>();
+
+    let mut total = sizes;
+    for i in 0..n_steps {
+        let inputs = external_inputs(i);
+        let start = Instant::now();
+        fs.prove_step(rng(), inputs, None)?;
+        let step = start.elapsed();
+        let (secondary, primary) = take_msm_stats_over::<C2>();
+        let split = CycleFoldSplit {
+            step,
+            primary_commitments: primary.time,
+            secondary_commitments: secondary.time,
+            ..sizes
+        };
+        println!("{} prove_step {}: {}", label, i, split);
+        total.step += split.step;
+        total.primary_commitments += split.primary_commitments;
+        total.secondary_commitments += split.secondary_commitments;
+    }
+
+    let n = n_steps as u32;
+    let mean = CycleFoldSplit {
+        step: total.step / n,
+        primary_commitments: total.primary_commitments / n,
+        secondary_commitments: total.secondary_commitments / n,
+        ..total
+    };
+    println!("{} mean {}", label, mean);
+    Ok(mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cyclefold_split_display() {
+        let split = CycleFoldSplit {
+            primary_constraints: 30_000,
+            cyclefold_checks: Some(10_000),
+            secondary_constraints: 10_000,
+            step: Duration::from_millis(100),
+            primary_commitments: Duration::from_millis(50),
+            secondary_commitments: Duration::from_millis(20),
+        };
+        assert_eq!(split.secondary_constraint_share(), 0.25);
+        assert!((split.secondary_time_share() - 0.2).abs() < 1e-9);
+        assert_eq!(
+            split.to_string(),
+            "step 100ms: primary commitments 50ms (50.0%), secondary commitments 20ms (20.0%), \
+             rest 30ms (30.0%); constraints: primary 30000 (CycleFold checks 10000), \
+             secondary 10000 (25.0%)"
+        );
+    }
+}
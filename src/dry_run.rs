@@ -0,0 +1,165 @@
+//! Sizes of a configuration without running any prover, with `--dry-run`.
+//!
+//! The augmented and CycleFold circuits are only synthesized, which takes
+//! seconds where a run can take hours, and the sizes that follow from them
+//! are reported: constraints and witness lengths, the commitment keys the
+//! preprocess would generate (the KZG SRS for `nova`), and the IVC proof,
+//! i.e. the folding state of `ivc_proof`, dominated by the witnesses and
+//! error vectors. The decider proof is left out: its size does not depend on
+//! the circuit (see `BenchError::ProofSizeVaries`).
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use std::fmt;
+
+use folding_schemes::frontend::FCircuit;
+
+use crate::bench::{BenchCase, PreprocessStages};
+use crate::error::BenchError;
+use crate::overhead::step_constraints;
+use crate::result::BenchResult;
+
+/// Shape of an R1CS circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct R1csShape {
+    pub constraints: usize,
+    /// Witness length, without the public inputs and the constant.
+    pub witness: usize,
+    pub public_inputs: usize,
+}
+
+/// Circuits of a folding scheme, as its preprocess would synthesize them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitShapes {
+    pub augmented: R1csShape,
+    pub cyclefold: R1csShape,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DryRun {
+    pub step_constraints: usize,
+    pub shapes: CircuitShapes,
+    /// Bytes of a primary-curve witness.
+    pub witness_bytes: usize,
+    /// Points of the primary commitment key, e.g. the KZG SRS powers.
+    pub srs_points: usize,
+    /// Compressed bytes of the primary and CycleFold commitment keys.
+    pub commitment_key_bytes: usize,
+    /// Estimated compressed bytes of the IVC proof.
+    pub ivc_proof_bytes: usize,
+}
+
+fn compressed_size(value: impl CanonicalSerialize) -> usize {
+    value.compressed_size()
+}
+
+impl DryRun {
+    /// Sizes following from `shapes` on the cycle `C1`/`C2`, with the
+    /// commitment key lengths of `Nova::preprocess`.
+    pub fn new<C1, C2>(step_constraints: usize, shapes: CircuitShapes) -> Self
+    where
+        C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+        C2: CurveGroup,
+    {
+        let f1 = compressed_size(C1::ScalarField::zero());
+        let f2 = compressed_size(C2::ScalarField::zero());
+        let g1 = compressed_size(C1::Affine::generator());
+        let g2 = compressed_size(C2::Affine::generator());
+        let (a, cf) = (shapes.augmented, shapes.cyclefold);
+        let srs_points = a.constraints;
+        let cf_points = cf.constraints.max(cf.witness);
+        Self {
+            step_constraints,
+            shapes,
+            witness_bytes: a.witness * f1,
+            srs_points,
+            commitment_key_bytes: srs_points * g1 + cf_points * g2,
+            // running and incoming witnesses with their error vectors, and
+            // the running CycleFold witness with its own
+            ivc_proof_bytes: 2 * (a.witness + a.constraints) * f1
+                + (cf.witness + cf.constraints) * f2,
+        }
+    }
+}
+
+impl fmt::Display for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, cf) = (self.shapes.augmented, self.shapes.cyclefold);
+        write!(
+            f,
+            "step circuit {} constraints; augmented circuit {} constraints, witness {} \
+             ({} bytes), {} public inputs; CycleFold circuit {} constraints, witness {}; \
+             commitment keys {} bytes ({} SRS points); IVC proof ~{} bytes",
+            self.step_constraints,
+            a.constraints,
+            a.witness,
+            self.witness_bytes,
+            a.public_inputs,
+            cf.constraints,
+            cf.witness,
+            self.commitment_key_bytes,
+            self.srs_points,
+            self.ivc_proof_bytes
+        )
+    }
+}
+
+/// Reports the sizes of `case` without proving, as the single result of a
+/// run with only its constraint counts set.
+pub fn dry_run<C1, C2, FC, FS>(
+    case: BenchCase<C1::ScalarField, FC, FS::PreprocessorParam>,
+    n_steps: usize,
+) -> Result<Vec<BenchResult>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: PreprocessStages<C1, C2, FC>,
+{
+    let step = step_constraints(&case.f_circuit)?;
+    let Some(shapes) = FS::circuit_shapes(&case.prep_param)? else {
+        println!(
+            "{} Dry run: step circuit {} constraints; the scheme's circuits cannot be \
+             synthesized without preprocessing",
+            case.label, step
+        );
+        return Ok(vec![]);
+    };
+    let dry_run = DryRun::new::<C1, C2>(step, shapes);
+    println!("{} Dry run: {}", case.label, dry_run);
+
+    let mut result = BenchResult::new(&case.label);
+    result.n_steps = n_steps;
+    result.augmented_constraints = shapes.augmented.constraints;
+    result.cyclefold_constraints = shapes.cyclefold.constraints;
+    Ok(vec![result])
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use ark_bn254::G1Projective as G1;
+    use ark_grumpkin::Projective as G2;
+
+    #[test]
+    fn test_dry_run_sizes() {
+        let shapes = CircuitShapes {
+            augmented: R1csShape {
+                constraints: 100,
+                witness: 90,
+                public_inputs: 2,
+            },
+            cyclefold: R1csShape {
+                constraints: 10,
+                witness: 20,
+                public_inputs: 5,
+            },
+        };
+        let dry_run = DryRun::new::<G1, G2>(2, shapes);
+        assert_eq!(dry_run.witness_bytes, 90 * 32);
+        assert_eq!(dry_run.srs_points, 100);
+        assert_eq!(dry_run.commitment_key_bytes, (100 + 20) * 32);
+        assert_eq!(dry_run.ivc_proof_bytes, (2 * 190 + 30) * 32);
+    }
+}
@@ -0,0 +1,119 @@
+//! Host benchmark of decider verification with a preloaded key, for verifiers
+//! on devices or in kernels (`no-std-verifier` feature).
+//!
+//! The verifier itself, `PreloadedVerifier`, lives in the `no_std`
+//! `sonobe-bench-verifier` crate and only checks the Groth16 part of the BN254
+//! `DeciderEth` proofs: sonobe's `Decider::verify` needs `std`, so the KZG
+//! openings and the folding of the last instances stay on the host.
+//! `bench_preloaded_verification` times it next to the whole decider
+//! verification.
+use ark_bn254::{Fr, G1Projective as G1};
+use ark_grumpkin::Projective as G2;
+use ark_serialize::CanonicalSerialize;
+use std::time::{Duration, Instant};
+
+use folding_schemes::{folding::nova::PreprocessorParam, frontend::FCircuit, Decider};
+pub use sonobe_bench_verifier::PreloadedVerifier;
+
+use crate::batch::groth16_part;
+use crate::bench::{no_external_inputs, run_folding_bench};
+use crate::config::poseidon_config;
+use crate::error::BenchError;
+use crate::solidity::{DeciderBn, NovaBn};
+
+/// Verification of one decider proof with a `PreloadedVerifier`.
+#[derive(Clone, Copy, Debug)]
+pub struct PreloadedVerification {
+    /// Compressed bytes the verifier stores and receives.
+    pub vk_bytes: usize,
+    pub inputs_bytes: usize,
+    pub proof_bytes: usize,
+    /// `PreloadedVerifier::new`, once.
+    pub load: Duration,
+    /// Mean `PreloadedVerifier::verify`.
+    pub verify: Duration,
+    /// Mean `DeciderBn::verify` of the whole decider proof, for context.
+    pub decider_verify: Duration,
+}
+
+/// Folds `n_steps` of `f_circuit` from `z_0`, proves the decider, then
+/// times `iterations` preloaded verifications of its Groth16 part and as
+/// many `DeciderBn::verify`.
+pub fn bench_preloaded_verification<FC: FCircuit<Fr>>(
+    label: &str,
+    f_circuit: FC,
+    z_0: Vec<Fr>,
+    n_steps: usize,
+    iterations: usize,
+) -> Result<PreloadedVerification, BenchError> {
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+    let (nova, decider_vp, proof) = run_folding_bench::<G1, G2, FC, NovaBn<FC>, DeciderBn<FC>>(
+        label,
+        prep_param,
+        f_circuit,
+        z_0,
+        n_steps,
+        no_external_inputs,
+    )?;
+    let (inputs, snark_proof) = groth16_part(&nova, &proof)?;
+    let (mut vk_bytes, mut inputs_bytes, mut proof_bytes) = (vec![], vec![], vec![]);
+    decider_vp.1.serialize_compressed(&mut vk_bytes)?;
+    inputs.serialize_compressed(&mut inputs_bytes)?;
+    snark_proof.serialize_compressed(&mut proof_bytes)?;
+
+    let start = Instant::now();
+    let verifier = PreloadedVerifier::new(&vk_bytes)?;
+    let load = start.elapsed();
+    let iterations = iterations.max(1);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        if !verifier.verify(&inputs_bytes, &proof_bytes)? {
+            return Err(BenchError::Verification(format!(
+                "{} preloaded verifier rejected the Groth16 proof",
+                label
+            )));
+        }
+    }
+    let verify = start.elapsed() / iterations as u32;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let verified = DeciderBn::<FC>::verify(
+            decider_vp.clone(),
+            nova.i,
+            nova.z_0.clone(),
+            nova.z_i.clone(),
+            &nova.U_i,
+            &nova.u_i,
+            &proof,
+        )?;
+        if !verified {
+            return Err(BenchError::Verification(format!(
+                "{} decider proof rejected",
+                label
+            )));
+        }
+    }
+    let decider_verify = start.elapsed() / iterations as u32;
+
+    let preloaded = PreloadedVerification {
+        vk_bytes: vk_bytes.len(),
+        inputs_bytes: inputs_bytes.len(),
+        proof_bytes: proof_bytes.len(),
+        load,
+        verify,
+        decider_verify,
+    };
+    println!(
+        "{} Preloaded verifier: key {} bytes loaded in {:?}, inputs {} bytes, proof {} \
+         bytes, verify {:?} (decider verify {:?})",
+        label,
+        preloaded.vk_bytes,
+        preloaded.load,
+        preloaded.inputs_bytes,
+        preloaded.proof_bytes,
+        preloaded.verify,
+        preloaded.decider_verify
+    );
+    Ok(preloaded)
+}
@@ -0,0 +1,75 @@
+//! Energy measurement through the Linux RAPL powercap counters, enabled by
+//! the `rapl` feature.
+//!
+//! The package-level counters (`/sys/class/powercap/intel-rapl:N`) are read
+//! around each phase; reading them usually needs root. Without the feature,
+//! on other platforms or when the counters are unreadable, `EnergySample`
+//! does nothing, so the pipeline can call it unconditionally.
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+use std::{fs, path::PathBuf};
+
+#[derive(Clone, Debug)]
+pub struct EnergySample {
+    #[cfg(all(feature = "rapl", target_os = "linux"))]
+    start: Vec<(PathBuf, u64)>,
+}
+
+#[cfg(all(feature = "rapl", target_os = "linux"))]
+fn read_uj(path: &std::path::Path, file: &str) -> Option<u64> {
+    fs::read_to_string(path.join(file)).ok()?.trim().parse().ok()
+}
+
+impl EnergySample {
+    /// Snapshot of the counters, `None` when they are unavailable.
+    pub fn start() -> Option<Self> {
+        #[cfg(all(feature = "rapl", target_os = "linux"))]
+        {
+            let start: Vec<(PathBuf, u64)> = fs::read_dir("/sys/class/powercap")
+                .ok()?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("intel-rapl:") && n.matches(':').count() == 1)
+                })
+                .filter_map(|p| read_uj(&p, "energy_uj").map(|e| (p, e)))
+                .collect();
+            if start.is_empty() {
+                return None;
+            }
+            Some(Self { start })
+        }
+        #[cfg(not(all(feature = "rapl", target_os = "linux")))]
+        None
+    }
+
+    /// Joules consumed by all packages since `start`, accounting for counter
+    /// wraparound.
+    pub fn joules(&self) -> f64 {
+        #[cfg(all(feature = "rapl", target_os = "linux"))]
+        {
+            let uj: u64 = self
+                .start
+                .iter()
+                .map(|(path, start)| {
+                    let end = read_uj(path, "energy_uj").unwrap_or(*start);
+                    if end >= *start {
+                        end - start
+                    } else {
+                        read_uj(path, "max_energy_range_uj").unwrap_or(0) - start + end
+                    }
+                })
+                .sum();
+            uj as f64 / 1e6
+        }
+        #[cfg(not(all(feature = "rapl", target_os = "linux")))]
+        0.0
+    }
+}
+
+/// Prints the energy of `sample` for `phase`, if measured, and returns it.
+pub fn report_energy(label: &str, phase: &str, sample: Option<EnergySample>) -> Option<f64> {
+    let joules = sample?.joules();
+    println!("{} {} energy: {:.3} J", label, phase, joules);
+    Some(joules)
+}
@@ -0,0 +1,78 @@
+//! Errors of the benchmark pipeline.
+//!
+//! Pipeline functions return `Result<_, BenchError>` so that a failing
+//! configuration (e.g. an SRS too small for the circuit) is reported and the
+//! remaining ones still run.
+use ark_serialize::SerializationError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum BenchError {
+    /// Preprocess, folding or decider proving failed.
+    Folding(folding_schemes::Error),
+    Io(std::io::Error),
+    Serialization(SerializationError),
+    /// A proof that should verify was rejected, or one that should not was
+    /// accepted.
+    Verification(String),
+    /// The folded state differs from the one computed by `step_native`.
+    NativeMismatch(String),
+    /// A step circuit's constraints are not satisfied by its own witness, see
+    /// `bench::check_step_satisfaction`.
+    Unsatisfied(String),
+    /// The decider proof size changed with the chain length.
+    ProofSizeVaries(String),
+    /// Malformed input such as a truncated `.ptau` file.
+    InvalidInput(String),
+    /// The reference Nova implementation failed, see `reference`.
+    #[cfg(feature = "nova-snark")]
+    NovaSnark(nova_snark::errors::NovaError),
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BenchError::Folding(e) => write!(f, "folding scheme error: {}", e),
+            BenchError::Io(e) => write!(f, "I/O error: {}", e),
+            BenchError::Serialization(e) => write!(f, "serialization error: {}", e),
+            BenchError::Verification(msg) => write!(f, "verification failed: {}", msg),
+            BenchError::NativeMismatch(msg) => {
+                write!(f, "folded state differs from native execution: {}", msg)
+            }
+            BenchError::Unsatisfied(msg) => write!(f, "step constraints unsatisfied: {}", msg),
+            BenchError::ProofSizeVaries(msg) => {
+                write!(f, "decider proof size is not constant: {}", msg)
+            }
+            BenchError::InvalidInput(msg) => write!(f, "invalid input: {}", msg),
+            #[cfg(feature = "nova-snark")]
+            BenchError::NovaSnark(e) => write!(f, "nova-snark error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BenchError {}
+
+impl From<folding_schemes::Error> for BenchError {
+    fn from(e: folding_schemes::Error) -> Self {
+        BenchError::Folding(e)
+    }
+}
+
+impl From<std::io::Error> for BenchError {
+    fn from(e: std::io::Error) -> Self {
+        BenchError::Io(e)
+    }
+}
+
+impl From<SerializationError> for BenchError {
+    fn from(e: SerializationError) -> Self {
+        BenchError::Serialization(e)
+    }
+}
+
+#[cfg(feature = "nova-snark")]
+impl From<nova_snark::errors::NovaError> for BenchError {
+    fn from(e: nova_snark::errors::NovaError) -> Self {
+        BenchError::NovaSnark(e)
+    }
+}
@@ -0,0 +1,248 @@
+//! Cost model fitted from the CSV reports of past runs, for `sonobe-bench
+//! estimate`: predicts the step and decider times of a circuit before
+//! writing it.
+//!
+//! A folding step is modelled as the step circuit's witness and constraint
+//! generation, linear in its constraints, plus the prover, linear in the
+//! points of its MSMs: the commitments to the witness and to the cross term
+//! of the augmented circuit, whose lengths are both close to its constraint
+//! count. The intercepts of the two fits add up to the fixed overhead of a
+//! step. Results without the step split (see `BenchResult::step_split`)
+//! only feed the prover fit, and only when no result has it. The decider is
+//! linear in the augmented constraints.
+//!
+//! The augmented circuit is the step circuit plus the median overhead of the
+//! recorded results, which come mostly from one-element states, plus the
+//! Poseidon absorptions of every further state element: `z_0` and `z_i` are
+//! hashed into the incoming and into the next instance.
+use std::time::Duration;
+
+use crate::config::PoseidonParams;
+use crate::error::BenchError;
+use crate::history::{HistoryRow, HistoryRun};
+use crate::sweep::linear_fit;
+
+/// `fixed + per_unit * units` nanoseconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinearCost {
+    pub per_unit: f64,
+    pub fixed: f64,
+}
+
+impl LinearCost {
+    /// Least-squares fit of `points`, if they span at least two sizes.
+    pub fn fit(points: &[(f64, f64)]) -> Option<Self> {
+        let first = points.first()?.0;
+        if points.iter().all(|(x, _)| *x == first) {
+            return None;
+        }
+        let (per_unit, fixed) = linear_fit(points);
+        Some(Self { per_unit, fixed })
+    }
+
+    /// Predicted nanoseconds for `units`.
+    pub fn at(&self, units: f64) -> f64 {
+        self.fixed + self.per_unit * units
+    }
+}
+
+/// MSM points of a folding step with an augmented circuit of `constraints`.
+pub fn msm_points(constraints: usize) -> usize {
+    2 * constraints
+}
+
+/// Constraints of the augmented circuit per state element beyond the first,
+/// with the transcript parameters `poseidon`.
+pub fn constraints_per_state_element(poseidon: PoseidonParams) -> f64 {
+    let alpha = poseidon.alpha;
+    // square-and-multiply
+    let sbox = (63 - alpha.leading_zeros() + alpha.count_ones() - 1) as f64;
+    let width = poseidon.rate + 1;
+    let permutation = sbox * (poseidon.full_rounds * width + poseidon.partial_rounds) as f64;
+    // `z_0` and `z_i`, in two hashes
+    4.0 * permutation / poseidon.rate as f64
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CostModel {
+    /// Step circuit witness and constraint generation, per step constraint;
+    /// `None` when no result has the step split.
+    pub synthesis: Option<LinearCost>,
+    /// The rest of a step, per MSM point.
+    pub prover: LinearCost,
+    /// Decider proving, per augmented constraint.
+    pub decider: Option<LinearCost>,
+    /// Augmented constraints on top of the step circuit's.
+    pub augmented_overhead: f64,
+    pub per_state_element: f64,
+    /// Results the model was fitted on.
+    pub samples: usize,
+}
+
+/// A predicted run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub augmented_constraints: usize,
+    pub msm_points: usize,
+    pub step: Duration,
+    pub decider: Option<Duration>,
+}
+
+fn nanos(ns: f64) -> Duration {
+    Duration::from_nanos(ns.max(0.0) as u64)
+}
+
+impl CostModel {
+    /// Fits the model on the completed results of `runs` whose benchmark
+    /// name starts with `prefix`, e.g. `nova/bn254/`.
+    pub fn fit(
+        runs: &[HistoryRun],
+        prefix: &str,
+        poseidon: PoseidonParams,
+    ) -> Result<Self, BenchError> {
+        let rows: Vec<&HistoryRow> = runs
+            .iter()
+            .flat_map(|run| &run.rows)
+            .filter(|r| r.completed() && r.name.starts_with(prefix))
+            .filter(|r| r.metric("augmented_constraints").is_some_and(|c| c > 0.0))
+            .collect();
+        let points = |f: &dyn Fn(&HistoryRow) -> Option<(f64, f64)>| {
+            rows.iter().filter_map(|r| f(r)).collect::<Vec<_>>()
+        };
+        let msm =
+            |r: &HistoryRow| Some(msm_points(r.metric("augmented_constraints")? as usize) as f64);
+
+        let synthesis = LinearCost::fit(&points(&|r| {
+            let generation = r.metric("step_witness_ns")? + r.metric("step_constraints_ns")?;
+            Some((r.metric("step_circuit_constraints")?, generation))
+        }));
+        let prover = LinearCost::fit(&points(&|r| match synthesis {
+            Some(_) => Some((msm(r)?, r.metric("step_prover_ns")?)),
+            None => Some((msm(r)?, r.metric("avg_step_ns")?)),
+        }))
+        .ok_or_else(|| {
+            BenchError::InvalidInput(format!(
+                "the reports need results of `{}` at two sizes or more to fit a cost model",
+                prefix
+            ))
+        })?;
+        let decider = LinearCost::fit(&points(&|r| {
+            let ns = r.metric("decider_prove_ns").filter(|&ns| ns > 0.0)?;
+            Some((r.metric("augmented_constraints")?, ns))
+        }));
+
+        let mut overheads: Vec<f64> = rows
+            .iter()
+            .filter_map(|r| {
+                Some(r.metric("augmented_constraints")? - r.metric("step_circuit_constraints")?)
+            })
+            .collect();
+        overheads.sort_by(f64::total_cmp);
+        Ok(Self {
+            synthesis,
+            prover,
+            decider,
+            augmented_overhead: overheads.get(overheads.len() / 2).copied().unwrap_or(0.0),
+            per_state_element: constraints_per_state_element(poseidon),
+            samples: rows.len(),
+        })
+    }
+
+    /// Fixed nanoseconds of a step.
+    pub fn fixed_step(&self) -> f64 {
+        self.synthesis.map_or(0.0, |s| s.fixed) + self.prover.fixed
+    }
+
+    pub fn augmented_constraints(&self, step_constraints: usize, state_width: usize) -> usize {
+        let extra = state_width.saturating_sub(1) as f64 * self.per_state_element;
+        (step_constraints as f64 + self.augmented_overhead + extra).round() as usize
+    }
+
+    /// Step and decider times of a step circuit of `step_constraints` over a
+    /// state of `state_width` elements.
+    pub fn predict(&self, step_constraints: usize, state_width: usize) -> Estimate {
+        let augmented_constraints = self.augmented_constraints(step_constraints, state_width);
+        let msm_points = msm_points(augmented_constraints);
+        let synthesis = self
+            .synthesis
+            .map_or(0.0, |s| s.at(step_constraints as f64));
+        Estimate {
+            augmented_constraints,
+            msm_points,
+            step: nanos(synthesis + self.prover.at(msm_points as f64)),
+            decider: self
+                .decider
+                .map(|d| nanos(d.at(augmented_constraints as f64))),
+        }
+    }
+
+    pub fn print(&self, prefix: &str) {
+        println!("Cost model of `{}` over {} results", prefix, self.samples);
+        if let Some(synthesis) = self.synthesis {
+            println!("  per step constraint  {:>12.1} ns", synthesis.per_unit);
+        }
+        println!("  per MSM point        {:>12.1} ns", self.prover.per_unit);
+        println!("  fixed per step       {:>12.2?}", nanos(self.fixed_step()));
+        if let Some(decider) = self.decider {
+            println!("  decider per constraint {:>10.1} ns", decider.per_unit);
+            println!("  decider fixed        {:>12.2?}", nanos(decider.fixed));
+        }
+        println!(
+            "  augmented overhead   {:>12.0} constraints, {:.0} per state element",
+            self.augmented_overhead, self.per_state_element
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::parse_csv;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_cost_model() {
+        let mut csv = "name,status,trial,avg_step_ns,step_witness_ns,step_constraints_ns,\
+                       step_prover_ns,decider_prove_ns,augmented_constraints,\
+                       step_circuit_constraints\n"
+            .to_string();
+        for (i, step) in [1000, 4000, 16000].into_iter().enumerate() {
+            let augmented = step + 20000;
+            let (generation, prover) = (100 * step, 1_000_000 + 20 * msm_points(augmented));
+            csv += &format!(
+                "nova/bn254/p{},ok,0,{},{},{},{},{},{},{}\n",
+                i,
+                generation + prover,
+                generation / 2,
+                generation / 2,
+                prover,
+                5_000_000 + 300 * augmented,
+                augmented,
+                step
+            );
+        }
+        csv += "pasta/p,ok,0,1,0,0,1,1,100,10\n";
+        let runs = [HistoryRun {
+            path: PathBuf::from("sweep.csv"),
+            rows: parse_csv(&csv).unwrap(),
+        }];
+        let model = CostModel::fit(&runs, "nova/bn254/", PoseidonParams::RATE2).unwrap();
+        assert_eq!(model.samples, 3);
+        assert!((model.synthesis.unwrap().per_unit - 100.0).abs() < 1e-6);
+        assert!((model.prover.per_unit - 20.0).abs() < 1e-6);
+        assert!((model.fixed_step() - 1e6).abs() < 1e-3);
+        assert_eq!(model.augmented_overhead, 20000.0);
+        // 3 constraints per S-box, 8 * 3 + 57 S-boxes per permutation
+        assert_eq!(model.per_state_element, 4.0 * 3.0 * 81.0 / 2.0);
+
+        let estimate = model.predict(8000, 1);
+        assert_eq!(estimate.augmented_constraints, 28000);
+        assert_eq!(estimate.msm_points, 56000);
+        let close = |d: Duration, ns: f64| (d.as_nanos() as f64 - ns).abs() <= 1.0;
+        assert!(close(estimate.step, 800_000.0 + 1e6 + 20.0 * 56000.0));
+        assert!(close(estimate.decider.unwrap(), 5e6 + 300.0 * 28000.0));
+        assert_eq!(model.predict(8000, 3).augmented_constraints, 28000 + 972);
+
+        assert!(CostModel::fit(&runs, "pasta/", PoseidonParams::RATE2).is_err());
+    }
+}
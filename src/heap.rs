@@ -0,0 +1,101 @@
+//! Heap profiling with dhat, enabled by the `dhat-heap` feature, which also
+//! installs dhat's allocator in `sonobe-bench`.
+//!
+//! Every benchmark is profiled on its own and its profile written to
+//! `dhat-heap-<benchmark>.json` in the working directory, to be opened with
+//! dhat's viewer (`dh_view.html`); allocation sites are attributed to phases
+//! through their backtraces. Each phase also prints the bytes it allocated
+//! and the live and peak heap at its end. dhat tracks one profile per
+//! process, so benchmarks must run one at a time (`--jobs 1`). Without the
+//! feature, `HeapProfile::start` returns `None` and no reporter is added.
+use std::time::Duration;
+
+use crate::error::BenchError;
+use crate::report::Reporter;
+use crate::suite::SuiteReport;
+
+/// Profile file of `bench`.
+pub fn profile_path(bench: &str) -> String {
+    format!("dhat-heap-{}.json", bench.replace(['/', ' '], "_"))
+}
+
+/// The heap profile of one benchmark, written when dropped.
+pub struct HeapProfile {
+    #[cfg(feature = "dhat-heap")]
+    _profiler: dhat::Profiler,
+}
+
+impl HeapProfile {
+    #[cfg_attr(not(feature = "dhat-heap"), allow(unused_variables))]
+    pub fn start(bench: &str) -> Option<Self> {
+        #[cfg(feature = "dhat-heap")]
+        {
+            Some(Self {
+                _profiler: dhat::Profiler::builder()
+                    .file_name(profile_path(bench))
+                    .build(),
+            })
+        }
+        #[cfg(not(feature = "dhat-heap"))]
+        None
+    }
+}
+
+/// Prints the heap usage of every phase.
+#[derive(Default)]
+pub struct HeapReporter {
+    #[cfg(feature = "dhat-heap")]
+    started: Vec<(String, dhat::HeapStats)>,
+}
+
+/// The phase reporter of the `dhat-heap` feature, if enabled.
+pub fn reporter() -> Option<Box<dyn Reporter>> {
+    cfg!(feature = "dhat-heap").then(|| Box::<HeapReporter>::default() as Box<dyn Reporter>)
+}
+
+impl Reporter for HeapReporter {
+    #[cfg_attr(not(feature = "dhat-heap"), allow(unused_variables))]
+    fn on_phase_start(&mut self, _bench: &str, phase: &str) {
+        #[cfg(feature = "dhat-heap")]
+        self.started
+            .push((phase.to_string(), dhat::HeapStats::get()));
+    }
+
+    #[cfg_attr(not(feature = "dhat-heap"), allow(unused_variables))]
+    fn on_phase_end(&mut self, bench: &str, phase: &str, _elapsed: Duration) {
+        #[cfg(feature = "dhat-heap")]
+        {
+            let Some(i) = self.started.iter().rposition(|(p, _)| p == phase) else {
+                return;
+            };
+            let (_, start) = self.started.remove(i);
+            let end = dhat::HeapStats::get();
+            println!(
+                "{} {} heap: {} bytes in {} blocks allocated, {} bytes live, {} bytes peak so far",
+                bench,
+                phase,
+                end.total_bytes - start.total_bytes,
+                end.total_blocks - start.total_blocks,
+                end.curr_bytes,
+                end.max_bytes
+            );
+        }
+    }
+
+    fn on_suite_end(&mut self, _report: &SuiteReport) -> Result<(), BenchError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_path() {
+        assert_eq!(
+            profile_path("nova/bn254/cubic"),
+            "dhat-heap-nova_bn254_cubic.json"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Past runs, read back from the CSV reports of `report::CsvReporter`.
+//!
+//! Every report is one run of the suite; kept over time (e.g. one per sonobe
+//! upgrade, named by date) they form the history `trends` charts.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::BenchError;
+
+/// One row of a CSV report: one trial of one benchmark, or a benchmark that
+/// did not complete.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryRow {
+    pub name: String,
+    pub status: String,
+    /// Every column by header, empty when the value does not apply.
+    pub columns: HashMap<String, String>,
+}
+
+impl HistoryRow {
+    pub fn completed(&self) -> bool {
+        self.status == "ok"
+    }
+
+    /// The value of `column`, if present and numeric.
+    pub fn metric(&self, column: &str) -> Option<f64> {
+        self.columns.get(column)?.parse().ok()
+    }
+}
+
+/// A run of the suite, as recorded in a CSV report.
+#[derive(Clone, Debug)]
+pub struct HistoryRun {
+    pub path: PathBuf,
+    pub rows: Vec<HistoryRow>,
+}
+
+impl HistoryRun {
+    pub fn load(path: &Path) -> Result<Self, BenchError> {
+        let rows = parse_csv(&std::fs::read_to_string(path)?)
+            .map_err(|e| BenchError::InvalidInput(format!("{:?}: {}", path, e)))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            rows,
+        })
+    }
+
+    /// The sonobe revision the run was built with, abbreviated, or else the
+    /// file name.
+    pub fn label(&self) -> String {
+        self.rows
+            .iter()
+            .find_map(|r| r.columns.get("sonobe_revision").filter(|rev| !rev.is_empty()))
+            .map(|rev| format!("{} ({})", self.file_stem(), rev.get(..8).unwrap_or(rev)))
+            .unwrap_or_else(|| self.file_stem())
+    }
+
+    fn file_stem(&self) -> String {
+        self.path.file_stem().unwrap_or_default().to_string_lossy().into_owned()
+    }
+
+    /// The first completed trial of every benchmark, by name.
+    pub fn first_trials(&self) -> HashMap<&str, &HistoryRow> {
+        let mut first = HashMap::new();
+        for row in self.rows.iter().filter(|r| r.completed()) {
+            first.entry(row.name.as_str()).or_insert(row);
+        }
+        first
+    }
+}
+
+/// The rows of a CSV report. Its values never contain commas or quotes.
+pub fn parse_csv(csv: &str) -> Result<Vec<HistoryRow>, String> {
+    let mut lines = csv.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<&str> = lines.next().ok_or("empty report")?.split(',').collect();
+    if header.first() != Some(&"name") || header.get(1) != Some(&"status") {
+        return Err("not a CSV report of sonobe-bench".to_string());
+    }
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let values: Vec<&str> = line.split(',').collect();
+            if values.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} columns instead of {}",
+                    i + 1,
+                    values.len(),
+                    header.len()
+                ));
+            }
+            Ok(HistoryRow {
+                name: values[0].to_string(),
+                status: values[1].to_string(),
+                columns: header
+                    .iter()
+                    .zip(&values)
+                    .map(|(h, v)| (h.to_string(), v.to_string()))
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "name,status,trial,avg_step_ns,proof_size,sonobe_revision\n\
+                   nova/bn254/cubic,ok,0,4000000,,6d8f297f116e1b36\n\
+                   nova/bn254/cubic,ok,1,5000000,,6d8f297f116e1b36\n\
+                   nova/mnt/cubic,failed,,,,6d8f297f116e1b36\n";
+        let run = HistoryRun {
+            path: PathBuf::from("history/2024-06-01.csv"),
+            rows: parse_csv(csv).unwrap(),
+        };
+        assert_eq!(run.rows.len(), 3);
+        assert_eq!(run.rows[1].metric("avg_step_ns"), Some(5e6));
+        assert_eq!(run.rows[0].metric("proof_size"), None);
+        assert!(!run.rows[2].completed());
+        let first = run.first_trials();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first["nova/bn254/cubic"].metric("avg_step_ns"), Some(4e6));
+        assert_eq!(run.label(), "2024-06-01 (6d8f297f)");
+
+        assert!(parse_csv("").is_err());
+        assert!(parse_csv("a,b\n1,2\n").is_err());
+        assert!(parse_csv("name,status,trial\nx,ok\n").is_err());
+    }
+}
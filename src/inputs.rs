@@ -0,0 +1,159 @@
+//! Per-step external inputs read from a file, to fold circuits such as the
+//! Bitcoin-header or signature-batch ones over captured data instead of
+//! random values.
+//!
+//! The format follows the extension: `.json` holds an array with the array
+//! of inputs of every step; anything else is read as CSV, one line per step
+//! with its inputs separated by commas, skipping blank lines and lines
+//! starting with `#`. Inputs are decimal or `0x`-prefixed hexadecimal
+//! integers below the field modulus, given as numbers or strings in JSON.
+use ark_ff::PrimeField;
+use num_bigint::BigUint;
+use std::path::Path;
+
+use folding_schemes::frontend::FCircuit;
+
+use crate::bench::BenchCase;
+use crate::config::BenchConfig;
+use crate::error::BenchError;
+
+/// Parses a decimal or `0x`-prefixed hexadecimal field element.
+pub fn parse_field<F: PrimeField>(s: &str) -> Result<F, BenchError> {
+    let s = s.trim();
+    let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+        None => BigUint::parse_bytes(s.as_bytes(), 10),
+    }
+    .ok_or_else(|| BenchError::InvalidInput(format!("{:?} is not an integer", s)))?;
+    let f = F::from(value.clone());
+    if Into::<BigUint>::into(f) != value {
+        return Err(BenchError::InvalidInput(format!(
+            "{} is not below the field modulus",
+            s
+        )));
+    }
+    Ok(f)
+}
+
+/// The inputs of every step in CSV, see the module docs.
+pub fn parse_csv<F: PrimeField>(text: &str) -> Result<Vec<Vec<F>>, BenchError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(n, line)| {
+            line.split(',')
+                .map(parse_field)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| BenchError::InvalidInput(format!("line {}: {}", n + 1, e)))
+        })
+        .collect()
+}
+
+/// The inputs of every step in JSON, see the module docs.
+pub fn parse_json<F: PrimeField>(text: &str) -> Result<Vec<Vec<F>>, BenchError> {
+    let json: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| BenchError::InvalidInput(format!("invalid JSON: {}", e)))?;
+    let not_nested = || BenchError::InvalidInput("expected an array of arrays".into());
+    json.as_array()
+        .ok_or_else(not_nested)?
+        .iter()
+        .enumerate()
+        .map(|(step, row)| {
+            row.as_array()
+                .ok_or_else(not_nested)?
+                .iter()
+                .map(|value| match value {
+                    serde_json::Value::String(s) => parse_field(s),
+                    serde_json::Value::Number(n) => parse_field(&n.to_string()),
+                    _ => Err(BenchError::InvalidInput(format!(
+                        "{} is not an input",
+                        value
+                    ))),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| BenchError::InvalidInput(format!("step {}: {}", step, e)))
+        })
+        .collect()
+}
+
+/// Reads the inputs of every step from `path`.
+pub fn load_external_inputs<F: PrimeField>(path: &Path) -> Result<Vec<Vec<F>>, BenchError> {
+    let text = std::fs::read_to_string(path)?;
+    let rows = if path.extension().is_some_and(|ext| ext == "json") {
+        parse_json(&text)
+    } else {
+        parse_csv(&text)
+    };
+    rows.map_err(|e| BenchError::InvalidInput(format!("{}: {}", path.display(), e)))
+}
+
+/// Feeds step `i` with `rows[i]`, once checked that there is a row of
+/// `width` inputs for each of the `n_steps` steps. Every trial reads the
+/// same rows.
+pub fn file_external_inputs<F: PrimeField>(
+    rows: Vec<Vec<F>>,
+    width: usize,
+    n_steps: usize,
+) -> Result<impl FnMut(usize) -> Vec<F> + Send + 'static, BenchError> {
+    if rows.len() < n_steps {
+        return Err(BenchError::InvalidInput(format!(
+            "{} rows of external inputs for {} steps",
+            rows.len(),
+            n_steps
+        )));
+    }
+    if let Some(i) = rows.iter().position(|row| row.len() != width) {
+        return Err(BenchError::InvalidInput(format!(
+            "step {} has {} external inputs, the circuit takes {}",
+            i,
+            rows[i].len(),
+            width
+        )));
+    }
+    Ok(move |i: usize| rows[i].clone())
+}
+
+impl<F: PrimeField, FC: FCircuit<F>, P> BenchCase<F, FC, P> {
+    /// Feeds the inputs of `config.external_inputs`, if set, instead of the
+    /// case's own.
+    pub fn with_config_inputs(self, config: &BenchConfig) -> Result<Self, BenchError> {
+        let Some(path) = &config.external_inputs else {
+            return Ok(self);
+        };
+        let width = self.f_circuit.external_inputs_len();
+        let inputs = file_external_inputs(load_external_inputs(path)?, width, config.steps)?;
+        Ok(self.with_external_inputs(inputs))
+    }
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_parse_inputs() {
+        let csv = "# header\n1, 0x10\n\n3,4\n";
+        let rows: Vec<Vec<Fr>> = parse_csv(csv).unwrap();
+        assert_eq!(
+            rows,
+            [
+                [Fr::from(1_u8), Fr::from(16_u8)],
+                [Fr::from(3_u8), Fr::from(4_u8)]
+            ]
+        );
+        let json = r#"[[1, "0x10"], ["3", 4]]"#;
+        assert_eq!(parse_json::<Fr>(json).unwrap(), rows);
+
+        assert!(parse_csv::<Fr>("1,x").is_err());
+        assert!(parse_json::<Fr>("[1, 2]").is_err());
+        // the BN254 scalar field modulus
+        let p = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        assert!(parse_field::<Fr>(p).is_err());
+
+        let mut inputs = file_external_inputs(rows.clone(), 2, 2).unwrap();
+        assert_eq!(inputs(1), rows[1]);
+        assert!(file_external_inputs(rows.clone(), 2, 3).is_err());
+        assert!(file_external_inputs(rows, 3, 1).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+//! Ctrl-C during a suite.
+//!
+//! The first SIGINT only raises a flag: `timeout::with_timeout` stops
+//! waiting for the running benchmarks, which are recorded as aborted along
+//! with those not started yet, and `suite::run_suite` still hands the report
+//! to every sink, so the completed results are written. A second SIGINT
+//! exits at once. wasm32 has no signals, so the handler and its `ctrlc`
+//! dependency are left out there and `interrupted` stays false.
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Exit status of an interrupted run, as shells report a SIGINT.
+pub const EXIT_CODE: i32 = 130;
+
+/// Installs the SIGINT handler, once per process.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_CODE);
+        }
+        eprintln!("\nInterrupted, writing the completed results (Ctrl-C again to exit now)");
+    })
+}
+
+/// Whether SIGINT was received.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
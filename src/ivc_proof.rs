@@ -0,0 +1,122 @@
+//! The IVC proof without a decider: the folded instances and witnesses a
+//! prover hands to a verifier that checks them directly, without the SNARK
+//! compression of the decider.
+//!
+//! `ivc_proof_bench` times taking the proof out of the folding state, its
+//! serialization and size, and its verification by `FoldingScheme::verify`
+//! once read back from its bytes, as a verifier receiving it would.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{groups::GroupOpsBounds, prelude::CurveVar, ToConstraintFieldGadget};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress};
+use std::time::Duration;
+
+use folding_schemes::{
+    commitment::CommitmentScheme,
+    folding::{circuits::CF2, nova::Nova},
+    frontend::FCircuit,
+    FoldingScheme,
+};
+
+use crate::bench::IvcState;
+use crate::clock::Stopwatch;
+use crate::error::BenchError;
+use crate::report::{phase_end, phase_start};
+use crate::serialization::{measure_serialization, SerializationStats};
+use crate::snapshot::NovaSnapshot;
+
+/// Folding schemes whose state can be handed out as a standalone IVC proof.
+pub trait IvcProof<C1: CurveGroup, C2: CurveGroup> {
+    type Proof: CanonicalSerialize + CanonicalDeserialize;
+
+    fn ivc_proof(&self) -> Self::Proof;
+    /// Overwrites the folding state with the one of `proof`, which must come
+    /// from a scheme initialized with the same params and circuit.
+    fn load_ivc_proof(&mut self, proof: Self::Proof);
+}
+
+/// The Nova IVC proof is the whole folding state: the running and incoming
+/// instances with their witnesses, and the CycleFold running instance.
+impl<C1, GC1, C2, GC2, FC, CS1, CS2, const H: bool> IvcProof<C1, C2>
+    for Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1, H>,
+    CS2: CommitmentScheme<C2, H>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    type Proof = NovaSnapshot<C1, C2>;
+
+    fn ivc_proof(&self) -> Self::Proof {
+        NovaSnapshot::take(self)
+    }
+
+    fn load_ivc_proof(&mut self, proof: Self::Proof) {
+        proof.restore(self)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IvcProofStats {
+    /// Taking the proof out of the folding state.
+    pub generate: Duration,
+    /// Compressed encoding of the proof.
+    pub serialization: SerializationStats,
+    pub verify: Duration,
+}
+
+/// Generates, serializes and verifies the IVC proof of the folded `fs`.
+pub fn ivc_proof_bench<C1, C2, FC, FS>(
+    label: &str,
+    fs: &FS,
+    fs_params: &(FS::ProverParam, FS::VerifierParam),
+    f_circuit: FC,
+) -> Result<IvcProofStats, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + IvcState<C1> + IvcProof<C1, C2>,
+{
+    phase_start(label, "ivc proof");
+    let start = Stopwatch::start();
+    let proof = fs.ivc_proof();
+    let generate = start.elapsed();
+    phase_end(label, "ivc proof", generate);
+    println!("{} IVC proof: {:?}", label, generate);
+    let serialization = measure_serialization(label, "IVC proof", &proof, Compress::Yes)?;
+
+    let mut bytes = Vec::with_capacity(serialization.size);
+    proof.serialize_compressed(&mut bytes)?;
+    let mut verifier = FS::init(fs_params, f_circuit, fs.z_0())?;
+    verifier.load_ivc_proof(<FS as IvcProof<C1, C2>>::Proof::deserialize_compressed(
+        &bytes[..],
+    )?);
+    let (running, incoming, cyclefold) = verifier.instances();
+
+    phase_start(label, "ivc verify");
+    let start = Stopwatch::start();
+    FS::verify(
+        fs_params.1.clone(),
+        verifier.z_0(),
+        verifier.z_i(),
+        verifier.num_steps(),
+        running,
+        incoming,
+        cyclefold,
+    )?;
+    let verify = start.elapsed();
+    phase_end(label, "ivc verify", verify);
+    println!("{} IVC proof verification: {:?}", label, verify);
+    Ok(IvcProofStats {
+        generate,
+        serialization,
+        verify,
+    })
+}
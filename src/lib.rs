@@ -2,20 +2,49 @@
 #![allow(non_camel_case_types)]
 #![allow(clippy::upper_case_acronyms)]
 
+#[cfg(feature = "bn254")]
 use ark_bn254::{
     constraints::GVar, Bn254, Fr, G1Projective as G1Bn,
 };
+#[cfg(feature = "bn254")]
 use ark_grumpkin::{
     constraints::GVar as GVar2, Projective as G2Bn,
 };
+#[cfg(feature = "mnt")]
 use ark_mnt4_298::{
-    Fr as Fr4, MNT4_298, G1Projective as G1Mnt4, 
     g1::{Config as Config4}, Fq as Fq4
 };
+#[cfg(feature = "mnt")]
 use ark_mnt6_298::{
-    Fr as Fr6, G1Projective as G2Mnt6,
+    Fr as Fr6,
     g1::{Config as Config6}, Fq as Fq6
 };
+#[cfg(feature = "mnt753")]
+use ark_mnt4_753::{
+    Fr as Fr4_753, MNT4_753, G1Projective as G1Mnt4_753,
+    g1::{Config as Config4_753}, Fq as Fq4_753
+};
+#[cfg(feature = "mnt753")]
+use ark_mnt6_753::{
+    G1Projective as G2Mnt6_753,
+    g1::{Config as Config6_753}, Fq as Fq6_753
+};
+#[cfg(feature = "pasta")]
+use ark_pallas::{
+    constraints::GVar as GVarPallas, Fr as FrPallas, Projective as G1Pallas,
+};
+#[cfg(feature = "pasta")]
+use ark_vesta::{
+    constraints::GVar as GVarVesta, Projective as G2Vesta,
+};
+#[cfg(feature = "secp")]
+use ark_secp256k1::{
+    Config as ConfigSecp, Fq as FqSecp, Fr as FrSecp, Projective as G1Secp,
+};
+#[cfg(feature = "secp")]
+use ark_secq256k1::{
+    Config as ConfigSecq, Fq as FqSecq, Projective as G2Secq,
+};
 use ark_ff::PrimeField;
 use ark_groth16::Groth16;
 use ark_r1cs_std::{
@@ -27,26 +56,108 @@ use ark_r1cs_std::{
 };
 use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
 use std::marker::PhantomData;
-use std::time::Instant;
 
 use folding_schemes::{
     commitment::{kzg::KZG, pedersen::Pedersen},
     folding::nova::{
-        decider_eth::{prepare_calldata, Decider as DeciderEth},
+        decider_eth::Decider as DeciderEth,
         Nova, PreprocessorParam,
     },
     frontend::FCircuit,
-    transcript::poseidon::poseidon_canonical_config,
-    Decider, Error, FoldingScheme,
+    Error,
 };
 
+pub mod affinity;
+pub mod amortize;
+pub mod artifacts;
+#[cfg(feature = "bn254")]
+pub mod batch;
+pub mod bench;
+pub mod build_info;
+pub mod cache;
+pub mod calibrate;
+pub mod campaign;
+pub mod ccs;
+pub mod circuits;
+pub mod clock;
+pub mod compare;
+pub mod config;
+pub mod cyclefold;
+pub mod dry_run;
+#[cfg(feature = "no-std-verifier")]
+pub mod embedded;
+pub mod energy;
+pub mod error;
+pub mod estimate;
+pub mod heap;
+pub mod history;
+pub mod inputs;
+pub mod interrupt;
+pub mod ivc_proof;
+pub mod manifest;
+#[cfg(unix)]
+pub mod memory;
+pub mod mobile;
+#[cfg(feature = "mock-commitment")]
+pub mod mock;
+pub mod msm;
+pub mod overhead;
+pub mod perf;
+pub mod primitives;
+#[cfg(feature = "nova-snark")]
+pub mod reference;
+pub mod registry;
+pub mod regression;
+pub mod report;
+pub mod result;
+pub mod serialization;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod snapshot;
+pub mod stats;
+pub mod suite;
+pub mod sweep;
+pub mod throughput;
+pub mod timeout;
+pub mod trace;
+pub mod trends;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+#[cfg(feature = "bls")]
+pub mod bw6;
+#[cfg(feature = "bn254")]
+pub mod nivc;
+#[cfg(feature = "bn254")]
+pub mod snarkjs;
+#[cfg(feature = "bn254")]
+pub mod solidity;
+#[cfg(feature = "bn254")]
+pub mod srs;
+
 // Define constraint field variables for MNT4/MNT6
-type FqVar4 = FpVar<Fq4>;
-type FqVar6 = FpVar<Fq6>;
+#[cfg(feature = "mnt")]
+pub type FqVar4 = FpVar<Fq4>;
+#[cfg(feature = "mnt")]
+pub type FqVar6 = FpVar<Fq6>;
 
 // Define the curve variable types using G1 configs
-type GVar4 = ProjectiveVar<Config4, FqVar4>;
-type GVar6 = ProjectiveVar<Config6, FqVar6>;
+#[cfg(feature = "mnt")]
+pub type GVar4 = ProjectiveVar<Config4, FqVar4>;
+#[cfg(feature = "mnt")]
+pub type GVar6 = ProjectiveVar<Config6, FqVar6>;
+
+// Same for the 753-bit MNT cycle
+#[cfg(feature = "mnt753")]
+pub type GVar4_753 = ProjectiveVar<Config4_753, FpVar<Fq4_753>>;
+#[cfg(feature = "mnt753")]
+pub type GVar6_753 = ProjectiveVar<Config6_753, FpVar<Fq6_753>>;
+
+// secp256k1/secq256k1 ship no r1cs gadgets, so build the curve variables from
+// the G1 configs the same way as for MNT
+#[cfg(feature = "secp")]
+pub type GVarSecp = ProjectiveVar<ConfigSecp, FpVar<FqSecp>>;
+#[cfg(feature = "secp")]
+pub type GVarSecq = ProjectiveVar<ConfigSecq, FpVar<FqSecq>>;
 
 /// Test circuit to be folded
 #[derive(Clone, Copy, Debug)]
@@ -95,141 +206,1079 @@ impl<F: PrimeField> FCircuit<F> for CubicFCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{n_steps, poseidon_config};
+    use crate::bench::{
+        no_external_inputs, random_external_inputs, run_folding_bench, run_folding_only_bench,
+    };
     
+    // The cubic circuit on every enabled curve cycle, through the same suite
+    // runner as `sonobe-bench`.
     #[test]
-    fn bench_bn254() {
-        let n_steps = 10;
-        let z_0 = vec![Fr::from(3_u32)];
-        
+    fn bench_suite() {
+        use crate::config::BenchConfig;
+        use crate::registry::select;
+        use crate::suite::{run_suite, Status};
+
+        let report =
+            run_suite(select(&["nova/*/cubic".to_string()]), &BenchConfig::default()).unwrap();
+        assert!(report.failed().is_empty(), "failed: {:?}", report.failed());
+        for entry in &report.entries {
+            let Status::Completed(results) = &entry.status else { unreachable!() };
+            assert_eq!(results[0].steps.len(), n_steps());
+        }
+    }
+
+    #[test]
+    fn bench_suite_dry_run() {
+        use crate::config::BenchConfig;
+        use crate::registry::select;
+        use crate::suite::{run_suite, Status};
+
+        let config = BenchConfig::builder().dry_run(true).build().unwrap();
+        let report = run_suite(select(&["nova/*/cubic".to_string()]), &config).unwrap();
+        assert!(report.failed().is_empty(), "failed: {:?}", report.failed());
+        for entry in &report.entries {
+            let Status::Completed(results) = &entry.status else { unreachable!() };
+            assert!(results[0].augmented_constraints > 0 && results[0].cyclefold_constraints > 0);
+            assert!(results[0].steps.is_empty());
+        }
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_api() {
+        use crate::bench::{run_nova_bench, BenchCase};
+        use crate::config::{BenchConfig, Phase};
+        use crate::solidity::{DeciderBn, NovaBn};
+
         let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>().unwrap(), f_circuit);
+        let config = BenchConfig::builder()
+            .steps(3)
+            .trials(2)
+            .seed(42)
+            .phases([Phase::Fold, Phase::DeciderProve])
+            .cache_dir(None)
+            .build()
+            .unwrap();
+        let results = run_nova_bench::<G1Bn, G2Bn, _, NovaBn<CubicFCircuit<Fr>>, DeciderBn<CubicFCircuit<Fr>>>(
+            BenchCase::new("BN254 API", f_circuit, prep_param),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert_eq!(result.steps.len(), 3);
+            assert!(result.proof_size > 0);
+            assert!(result.total >= result.decider_prove);
+            assert_eq!(result.decider_verify, std::time::Duration::ZERO);
+            assert!(result.decider_pk_size > result.decider_vk_size);
+            let breakdown = result.preprocess_breakdown.unwrap();
+            assert!(!breakdown.commitment_keys.is_zero());
+            assert_eq!(result.preprocess, breakdown.total());
+            let augmented = result.augmented_breakdown.unwrap();
+            assert_eq!(augmented.total(), result.augmented_constraints);
+            // z^3 + z + 5
+            assert_eq!(augmented.step, 2);
+            assert!(augmented.transcript > 0 && augmented.cyclefold > 0);
+        }
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_hypernova() {
+        use crate::config::BenchConfig;
+        use crate::registry::select;
+        use crate::suite::{run_suite, Status};
+
+        let report =
+            run_suite(select(&["hypernova/bn254/cubic".to_string()]), &BenchConfig::default())
+                .unwrap();
+        let Status::Completed(results) = &report.entries[0].status else {
+            panic!("{:?}", report.entries[0].status)
+        };
+        let ccs = results[0].ccs.as_ref().unwrap();
+        // R1CS lifted to CCS: A, B, C under the multisets {A, B} and {C}
+        assert_eq!((ccs.matrices, ccs.multisets, ccs.degree), (3, 2, 2));
+        assert_eq!(ccs.rows, results[0].augmented_constraints);
+        assert!(ccs.total_nonzeros() > 0);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_sha256() {
+        use crate::circuits::sha256::Sha256FCircuit;
         
         pub type N_BN =
-            Nova<G1Bn, GVar, G2Bn, GVar2, CubicFCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2Bn>, false>;
+            Nova<G1Bn, GVar, G2Bn, GVar2, Sha256FCircuit<Fr>, KZG<'static, Bn254>, Pedersen<G2Bn>, false>;
         pub type D_BN = DeciderEth<
             G1Bn,
             GVar,
             G2Bn,
             GVar2,
-            CubicFCircuit<Fr>,
+            Sha256FCircuit<Fr>,
             KZG<'static, Bn254>,
             Pedersen<G2Bn>,
             Groth16<Bn254>,
             N_BN,
         >;
         
-        println!("\nRunning BN254 benchmark:");
-        let total_start = Instant::now();
-        
-        let poseidon_config = poseidon_canonical_config::<Fr>();
-        let mut rng = rand::rngs::OsRng;
-        
-        let nova_preprocess_params = PreprocessorParam::new(poseidon_config.clone(), f_circuit);
-        let nova_params = N_BN::preprocess(&mut rng, &nova_preprocess_params).unwrap();
-        let pp_hash = nova_params.1.pp_hash().unwrap();
-        
-        let mut nova = N_BN::init(&nova_params, f_circuit, z_0).unwrap();
-        let (decider_pp, decider_vp) = D_BN::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
-        
-        let mut total_proving_time = 0;
-        for i in 0..n_steps {
-            let start = Instant::now();
-            nova.prove_step(rng, vec![], None).unwrap();
-            let duration = start.elapsed();
-            total_proving_time += duration.as_micros();
-            println!("BN254 Nova::prove_step {}: {:?}", i, duration);
-        }
-        println!("BN254 Average proving time: {:?}µs", total_proving_time / n_steps as u128);
+        let f_circuit = Sha256FCircuit::<Fr>::new(1).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        run_folding_bench::<G1Bn, G2Bn, _, N_BN, D_BN>(
+            "BN254 SHA-256",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_external_inputs() {
+        use crate::circuits::external_inputs::ExternalInputsFCircuit;
         
-        let start = Instant::now();
-        let proof = D_BN::prove(rng, decider_pp, nova.clone()).unwrap();
-        println!("BN254 Generated Decider proof: {:?}", start.elapsed());
+        pub type N_BN = Nova<
+            G1Bn,
+            GVar,
+            G2Bn,
+            GVar2,
+            ExternalInputsFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<G2Bn>,
+            false,
+        >;
+        pub type D_BN = DeciderEth<
+            G1Bn,
+            GVar,
+            G2Bn,
+            GVar2,
+            ExternalInputsFCircuit<Fr>,
+            KZG<'static, Bn254>,
+            Pedersen<G2Bn>,
+            Groth16<Bn254>,
+            N_BN,
+        >;
         
-        let start = Instant::now();
-        let verified = D_BN::verify(
-            decider_vp.clone(),
-            nova.i,
-            nova.z_0.clone(),
-            nova.z_i.clone(),
-            &nova.U_i,
-            &nova.u_i,
+        let width = 4;
+        let f_circuit = ExternalInputsFCircuit::<Fr>::new(width).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        run_folding_bench::<G1Bn, G2Bn, _, N_BN, D_BN>(
+            "BN254 external inputs",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            random_external_inputs(width),
+        )
+        .unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_solidity() {
+        use crate::solidity::{export_decider_verifier, DeciderBn, NovaBn};
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let (nova, decider_vp, proof) = run_folding_bench::<
+            G1Bn,
+            G2Bn,
+            _,
+            NovaBn<CubicFCircuit<Fr>>,
+            DeciderBn<CubicFCircuit<Fr>>,
+        >(
+            "BN254 Solidity",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+        let verifier =
+            export_decider_verifier("BN254 Solidity", "target/solidity", &nova, decider_vp, proof)
+                .unwrap();
+        assert!(verifier.source.contains(crate::solidity::VERIFIER_CONTRACT));
+
+        #[cfg(feature = "evm")]
+        crate::solidity::measure_verification_gas("BN254 Solidity", &verifier).unwrap();
+    }
+    
+    // Uses the SRS at `SONOBE_BENCH_PTAU` (a `.ptau` file or one written by
+    // `srs::save_srs`) instead of sampling a fresh one.
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_ptau() {
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::srs::load_srs;
+
+        let Some(path) = std::env::var_os("SONOBE_BENCH_PTAU") else {
+            println!("SONOBE_BENCH_PTAU not set, skipping");
+            return;
+        };
+        let (pk, vk) = load_srs("BN254 ptau", path, 1 << 16).unwrap();
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let mut prep_param = PreprocessorParam::new(poseidon_config, f_circuit);
+        prep_param.cs_pp = Some(pk);
+        prep_param.cs_vp = Some(vk);
+        run_folding_bench::<G1Bn, G2Bn, _, NovaBn<CubicFCircuit<Fr>>, DeciderBn<CubicFCircuit<Fr>>>(
+            "BN254 ptau",
+            prep_param,
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    // Loads the SRS at `SONOBE_BENCH_PTAU` memory-mapped and in memory.
+    #[cfg(feature = "mmap-srs")]
+    #[test]
+    fn bench_bn254_srs_mmap() {
+        use crate::srs::compare_srs_loading;
+
+        let Some(path) = std::env::var_os("SONOBE_BENCH_PTAU") else {
+            println!("SONOBE_BENCH_PTAU not set, skipping");
+            return;
+        };
+        compare_srs_loading("BN254 SRS", path, 1 << 16).unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_serialization() {
+        use crate::bench::{preprocess, prove_and_verify};
+        use crate::serialization::compare_compression;
+        use crate::solidity::{DeciderBn, NovaBn};
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        type D = DeciderBn<CubicFCircuit<Fr>>;
+        let label = "BN254 serialization";
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+        let params = preprocess::<G1Bn, G2Bn, _, N, D>(
+            label,
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            z_0.clone(),
+        )
+        .unwrap();
+        compare_compression(label, "Nova prover params", &params.0).unwrap();
+        compare_compression(label, "Nova verifier params", &params.1).unwrap();
+        compare_compression(label, "Decider prover key", &params.2).unwrap();
+        compare_compression(label, "Decider verifier key", &params.3).unwrap();
+
+        let (nova, _, proof) =
+            prove_and_verify::<G1Bn, G2Bn, _, N, D>(label, params, f_circuit, z_0, n_steps(), no_external_inputs)
+                .unwrap();
+        compare_compression(label, "Running instance", &nova.U_i).unwrap();
+        compare_compression(label, "Decider proof", &proof).unwrap();
+    }
+    
+    // Folds 5 steps, snapshots to disk, resumes into a fresh instance and
+    // folds 5 more; the result must match 10 uninterrupted steps.
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_resume() {
+        use crate::bench::{fold_steps, preprocess};
+        use crate::snapshot::NovaSnapshot;
+        use crate::solidity::{DeciderBn, NovaBn};
+        use folding_schemes::{Decider, FoldingScheme};
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        type D = DeciderBn<CubicFCircuit<Fr>>;
+        let label = "BN254 resume";
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+        let (fs_pp, fs_vp, decider_pp, decider_vp) = preprocess::<G1Bn, G2Bn, _, N, D>(
+            label,
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            z_0.clone(),
+        )
+        .unwrap();
+        let fs_params = (fs_pp, fs_vp);
+
+        let mut straight = N::init(&fs_params, f_circuit, z_0.clone()).unwrap();
+        fold_steps(label, &mut straight, 10, no_external_inputs).unwrap();
+
+        let mut first = N::init(&fs_params, f_circuit, z_0.clone()).unwrap();
+        fold_steps(label, &mut first, 5, no_external_inputs).unwrap();
+        let path = std::env::temp_dir().join(format!("nova-snapshot-{}.bin", std::process::id()));
+        NovaSnapshot::take(&first).save(&path).unwrap();
+
+        let mut resumed = N::init(&fs_params, f_circuit, z_0).unwrap();
+        let snapshot = NovaSnapshot::<G1Bn, G2Bn>::load(&path).unwrap();
+        let mut foreign = snapshot.clone();
+        foreign.pp_hash += Fr::from(1_u32);
+        assert!(foreign.restore(&mut resumed).is_err());
+        snapshot.restore(&mut resumed).unwrap();
+        std::fs::remove_file(path).unwrap();
+        fold_steps(label, &mut resumed, 5, no_external_inputs).unwrap();
+        assert_eq!(resumed.i, straight.i);
+        assert_eq!(resumed.z_i, straight.z_i);
+        assert_eq!(resumed.U_i, straight.U_i);
+
+        let mut rng = crate::config::rng();
+        let proof = D::prove(&mut rng, decider_pp, resumed.clone()).unwrap();
+        assert!(D::verify(
+            decider_vp,
+            resumed.i,
+            resumed.z_0.clone(),
+            resumed.z_i.clone(),
+            &resumed.U_i,
+            &resumed.u_i,
             &proof,
         )
+        .unwrap());
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_ivc_proof() {
+        use crate::bench::fold_steps;
+        use crate::ivc_proof::ivc_proof_bench;
+        use crate::solidity::NovaBn;
+        use folding_schemes::FoldingScheme;
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        let label = "BN254 IVC proof";
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+        let fs_params = N::preprocess(
+            &mut crate::config::rng(),
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+        )
         .unwrap();
-        println!("BN254 Verification time: {:?}", start.elapsed());
-        assert!(verified);
-        println!("BN254 Total time: {:?}", total_start.elapsed());
+        let mut fs = N::init(&fs_params, f_circuit, z_0).unwrap();
+        fold_steps(label, &mut fs, n_steps(), no_external_inputs).unwrap();
+        let stats = ivc_proof_bench::<G1Bn, G2Bn, _, N>(label, &fs, &fs_params, f_circuit).unwrap();
+        assert!(stats.serialization.size > 0);
+        assert!(stats.verify > std::time::Duration::ZERO);
     }
     
+    #[cfg(feature = "bn254")]
     #[test]
-    fn bench_mnt() {
-        let n_steps = 10;
-        let z_0 = vec![Fr4::from(3_u32)];
-        
-        let f_circuit = CubicFCircuit::<Fr4>::new(()).unwrap();
-        
-        pub type N_MNT = Nova<
-            G1Mnt4,
-            GVar4,
-            G2Mnt6,
-            GVar6,
-            CubicFCircuit<Fr4>,
-            KZG<'static, MNT4_298>,
-            Pedersen<G2Mnt6>,
+    fn bench_bn254_negative() {
+        use crate::bench::check_rejections;
+        use crate::solidity::{DeciderBn, NovaBn};
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        type D = DeciderBn<CubicFCircuit<Fr>>;
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let (nova, decider_vp, proof) = run_folding_bench::<G1Bn, G2Bn, _, N, D>(
+            "BN254 negative",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+        check_rejections::<G1Bn, G2Bn, _, N, D>("BN254 negative", &nova, &decider_vp, &proof).unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_ivc_verify() {
+        use crate::bench::fold_and_verify_steps;
+        use crate::solidity::NovaBn;
+        use folding_schemes::FoldingScheme;
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let fs_params = N::preprocess(
+            &mut crate::config::rng(),
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+        )
+        .unwrap();
+        let mut nova = N::init(&fs_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+        let (_, verifications) =
+            fold_and_verify_steps("BN254 IVC verify", &mut nova, &fs_params.1, 10, 2, no_external_inputs)
+                .unwrap();
+        let steps: Vec<usize> = verifications.iter().map(|v| v.step).collect();
+        assert_eq!(steps, [2, 4, 6, 8, 10]);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_thread_sweep() {
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::{default_thread_counts, thread_sweep};
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        thread_sweep::<G1Bn, G2Bn, _, NovaBn<CubicFCircuit<Fr>>, DeciderBn<CubicFCircuit<Fr>>>(
+            "BN254 threads",
+            &default_thread_counts(),
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_msm() {
+        use crate::bench::preprocess;
+        use crate::msm::{msm_attribution, MsmCounter};
+
+        type CS1 = MsmCounter<KZG<'static, Bn254>>;
+        type CS2 = MsmCounter<Pedersen<G2Bn>>;
+        type N = Nova<G1Bn, GVar, G2Bn, GVar2, CubicFCircuit<Fr>, CS1, CS2, false>;
+        type D = DeciderEth<G1Bn, GVar, G2Bn, GVar2, CubicFCircuit<Fr>, CS1, CS2, Groth16<Bn254>, N>;
+
+        let label = "BN254 MSM";
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+        let params = preprocess::<G1Bn, G2Bn, _, N, D>(
+            label,
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            z_0.clone(),
+        )
+        .unwrap();
+        let share = msm_attribution::<G1Bn, G2Bn, _, N, D>(label, params, f_circuit, z_0, n_steps(), no_external_inputs)
+            .unwrap();
+        assert!(share > 0.0 && share <= 1.0);
+    }
+    
+    #[cfg(all(feature = "bn254", feature = "mock-commitment"))]
+    #[test]
+    fn bench_bn254_mock_commitment() {
+        use crate::mock::commitment_share_bn254;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let share =
+            commitment_share_bn254("BN254 mock", f_circuit, vec![Fr::from(3_u32)], n_steps())
+                .unwrap();
+        assert!(share.share() >= 0.0 && share.share() < 1.0);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_overhead() {
+        use crate::overhead::compare_overhead_bn254;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let overheads =
+            compare_overhead_bn254("BN254 overhead", f_circuit, vec![Fr::from(3_u32)]).unwrap();
+        assert_eq!(overheads.len(), 3);
+        for o in &overheads {
+            assert_eq!(o.step, 2, "{}", o.scheme);
+            assert!(o.overhead() > 0 && o.cyclefold > 0, "{}", o.scheme);
+        }
+    }
+    
+    #[cfg(feature = "nova-snark")]
+    #[test]
+    fn bench_bn254_reference() {
+        use crate::reference::{compare_stacks, CubicStepCircuit};
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let [sonobe, nova] =
+            compare_stacks("BN254 reference", f_circuit, CubicStepCircuit::default(), 3, n_steps())
+                .unwrap();
+        assert_eq!(sonobe.steps.len(), n_steps());
+        assert_eq!(nova.steps.len(), n_steps());
+        assert!(sonobe.constraints > 0 && nova.constraints > 0);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_nivc() {
+        use crate::nivc::compare_nivc_bn254;
+
+        let comparison = compare_nivc_bn254("BN254 NIVC", vec![8, 64], n_steps()).unwrap();
+        assert_eq!(comparison.branches.len(), 2);
+        assert_eq!(
+            comparison.universal.step_constraints,
+            comparison.selection.step_constraints
+        );
+        assert!(comparison.selection.step_constraints > 8 + 64);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_cyclefold_split() {
+        use crate::cyclefold::cyclefold_split;
+        use crate::msm::MsmCounter;
+        use folding_schemes::FoldingScheme;
+
+        type CS1 = MsmCounter<KZG<'static, Bn254>>;
+        type CS2 = MsmCounter<Pedersen<G2Bn>>;
+        type N = Nova<G1Bn, GVar, G2Bn, GVar2, CubicFCircuit<Fr>, CS1, CS2, false>;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let fs_params = N::preprocess(
+            &mut crate::config::rng(),
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+        )
+        .unwrap();
+        let split = cyclefold_split::<G1Bn, G2Bn, _, N>(
+            "BN254 CycleFold split",
+            &fs_params,
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+        assert!(split.secondary_constraints > 0 && split.cyclefold_checks.unwrap() > 0);
+        assert!(split.secondary_commitments > std::time::Duration::ZERO);
+        assert!(split.primary_commitments + split.secondary_commitments <= split.step);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_throughput() {
+        use crate::solidity::NovaBn;
+        use crate::throughput::run_throughput_bench;
+        use folding_schemes::FoldingScheme;
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let fs_params = N::preprocess(
+            &mut crate::config::rng(),
+            &PreprocessorParam::new(poseidon_config, f_circuit),
+        )
+        .unwrap();
+        let stats = run_throughput_bench::<G1Bn, G2Bn, _, N>(
+            "BN254",
+            &fs_params,
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            4,
+            5,
+            |_, _| vec![],
+        )
+        .unwrap();
+        assert_eq!(stats.chain_latencies.len(), 4);
+    }
+    
+    #[cfg(feature = "no-std-verifier")]
+    #[test]
+    fn bench_bn254_preloaded_verification() {
+        use crate::embedded::bench_preloaded_verification;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let preloaded = bench_preloaded_verification(
+            "BN254 preloaded",
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            10,
+        )
+        .unwrap();
+        assert!(preloaded.verify < preloaded.decider_verify);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_primitives() {
+        use crate::config::poseidon_params;
+        use crate::primitives::{run_primitives, Primitive, LOG_SIZES};
+
+        let times = run_primitives(&["bn254", "unknown"], poseidon_params().unwrap());
+        let count = |curve: &str, primitive| {
+            times.iter().filter(|t| t.curve == curve && t.primitive == primitive).count()
+        };
+        for curve in ["bn254", "grumpkin"] {
+            assert_eq!(count(curve, Primitive::Msm), LOG_SIZES.len());
+            assert_eq!(count(curve, Primitive::Poseidon), 1);
+        }
+        // BN254's scalar field has 2-adicity 28, Grumpkin's only 1
+        assert_eq!(count("bn254", Primitive::Fft), LOG_SIZES.len());
+        assert_eq!(count("grumpkin", Primitive::Fft), 0);
+    }
+    
+    // Saves the decider proof, reads it back and verifies it as `sonobe-bench
+    // verify` does; a tampered final state is rejected.
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_saved_artifacts() {
+        use crate::artifacts::{save_artifacts, verify_artifacts_bn254, DeciderArtifacts};
+        use crate::solidity::{DeciderBn, NovaBn};
+        use ark_serialize::CanonicalSerialize;
+
+        type N = NovaBn<CubicFCircuit<Fr>>;
+        type D = DeciderBn<CubicFCircuit<Fr>>;
+        let label = "BN254 artifacts";
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let (nova, decider_vp, proof) = run_folding_bench::<G1Bn, G2Bn, _, N, D>(
+            label,
+            PreprocessorParam::new(poseidon_config::<Fr>().unwrap(), f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+        let dir = std::env::temp_dir().join(format!("artifacts-{}", std::process::id()));
+        let saved =
+            save_artifacts::<G1Bn, G2Bn, _, N, D>(label, &dir, &nova, &decider_vp, &proof).unwrap();
+        let verified = verify_artifacts_bn254(label, &dir, 2).unwrap();
+        assert_eq!(verified.proof_bytes, saved.proof_bytes);
+        assert_eq!(verified.state_bytes, saved.state_bytes);
+
+        let mut tampered = crate::artifacts::PublicState::<G1Bn> {
+            i: nova.i,
+            z_0: nova.z_0.clone(),
+            z_i: nova.z_i.clone(),
+            U_i: nova.U_i.clone(),
+            u_i: nova.u_i.clone(),
+        };
+        tampered.z_i[0] += Fr::from(1_u32);
+        let mut bytes = vec![];
+        tampered.serialize_compressed(&mut bytes).unwrap();
+        std::fs::write(DeciderArtifacts::in_dir(&dir).state_path, bytes).unwrap();
+        assert!(verify_artifacts_bn254(label, &dir, 1).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    
+    // The cubic circuit satisfies its constraints at every step; one that
+    // enforces `z_i = z_i + 1` does not.
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn test_check_step_satisfaction() {
+        use crate::bench::check_step_satisfaction;
+        use crate::error::BenchError;
+        use ark_r1cs_std::eq::EqGadget;
+
+        #[derive(Clone, Copy, Debug)]
+        struct Broken;
+
+        impl FCircuit<Fr> for Broken {
+            type Params = ();
+
+            fn new(_params: Self::Params) -> Result<Self, Error> {
+                Ok(Self)
+            }
+
+            fn state_len(&self) -> usize {
+                1
+            }
+
+            fn external_inputs_len(&self) -> usize {
+                0
+            }
+
+            fn step_native(&self, _i: usize, z_i: Vec<Fr>, _: Vec<Fr>) -> Result<Vec<Fr>, Error> {
+                Ok(z_i)
+            }
+
+            fn generate_step_constraints(
+                &self,
+                _cs: ConstraintSystemRef<Fr>,
+                _i: usize,
+                z_i: Vec<FpVar<Fr>>,
+                _external_inputs: Vec<FpVar<Fr>>,
+            ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+                z_i[0].enforce_equal(&(&z_i[0] + Fr::from(1_u32)))?;
+                Ok(z_i)
+            }
+        }
+
+        let cubic = CubicFCircuit::<Fr>::new(()).unwrap();
+        let z_0 = vec![Fr::from(3_u32)];
+        check_step_satisfaction("cubic", &cubic, z_0.clone(), &[vec![], vec![], vec![]]).unwrap();
+        let err = check_step_satisfaction("broken", &Broken, z_0, &[vec![]]).unwrap_err();
+        assert!(matches!(err, BenchError::Unsatisfied(_)), "{}", err);
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn test_cubic_consistent() {
+        use crate::circuits::conformance::assert_fcircuit_consistent;
+
+        assert_fcircuit_consistent(&CubicFCircuit::<Fr>::new(()).unwrap());
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_amortized() {
+        use crate::amortize::bench_amortized;
+        use crate::solidity::{DeciderBn, NovaBn};
+
+        type Cubic = CubicFCircuit<Fr>;
+        let f_circuit = Cubic::new(()).unwrap();
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>().unwrap(), f_circuit);
+        let amortized = bench_amortized::<G1Bn, G2Bn, _, NovaBn<Cubic>, DeciderBn<Cubic>>(
+            "BN254 amortized",
+            &prep_param,
+            f_circuit,
+            |chain| vec![Fr::from(3 + chain as u64)],
+            3,
+            n_steps(),
+            |_, _| vec![],
+        )
+        .unwrap();
+        amortized.print("BN254 amortized");
+        assert_eq!(amortized.chains.len(), 3);
+        assert!(amortized.per_chain(3) < amortized.per_chain(1));
+    }
+    
+    // Finds the largest padding circuit that folds and decides within
+    // `SONOBE_BENCH_MEM_LIMIT_MIB` (4 GiB by default); see `memory`.
+    #[cfg(all(feature = "bn254", unix))]
+    #[test]
+    fn bench_bn254_memory_limit() {
+        use crate::circuits::padding::PaddingFCircuit;
+        use crate::memory::{child_config, largest_completing};
+        use crate::solidity::{DeciderBn, NovaBn};
+
+        let sizes = [1 << 10, 1 << 12, 1 << 14, 1 << 16, 1 << 18];
+        let Some(i) = child_config().unwrap() else {
+            let limit_mib = std::env::var("SONOBE_BENCH_MEM_LIMIT_MIB")
+                .map_or(4096, |l| l.parse().unwrap());
+            let largest = largest_completing(
+                "BN254 memory",
+                "tests::bench_bn254_memory_limit",
+                sizes.len(),
+                limit_mib,
+            )
+            .unwrap();
+            match largest {
+                Some(i) => println!(
+                    "BN254 memory: largest step circuit within {} MiB has {} constraints",
+                    limit_mib, sizes[i]
+                ),
+                None => println!("BN254 memory: nothing completes within {} MiB", limit_mib),
+            }
+            return;
+        };
+
+        let f_circuit = PaddingFCircuit::<Fr>::new(sizes[i]).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        run_folding_bench::<G1Bn, G2Bn, _, NovaBn<PaddingFCircuit<Fr>>, DeciderBn<PaddingFCircuit<Fr>>>(
+            &format!("BN254 padding {}", sizes[i]),
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            3,
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    // Chain lengths come from `SONOBE_BENCH_STEP_SWEEP` (comma-separated),
+    // 10,100,1000 by default. Takes long, run with
+    // `cargo test --release -- --ignored bench_bn254_step_sweep`.
+    #[cfg(feature = "bn254")]
+    #[test]
+    #[ignore]
+    fn bench_bn254_step_sweep() {
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::step_sweep;
+
+        let step_counts: Vec<usize> = std::env::var("SONOBE_BENCH_STEP_SWEEP")
+            .map_or(vec![10, 100, 1000], |s| {
+                s.split(',').map(|n| n.trim().parse().unwrap()).collect()
+            });
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        step_sweep::<G1Bn, G2Bn, _, NovaBn<CubicFCircuit<Fr>>, DeciderBn<CubicFCircuit<Fr>>>(
+            "BN254 steps",
+            &step_counts,
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_constant_proof_size() {
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::step_sweep;
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr>().unwrap();
+        let points = step_sweep::<G1Bn, G2Bn, _, NovaBn<CubicFCircuit<Fr>>, DeciderBn<CubicFCircuit<Fr>>>(
+            "BN254 proof size",
+            &[1, 5, 20],
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            no_external_inputs,
+        )
+        .unwrap();
+        assert!(points.iter().all(|p| p.proof_size == points[0].proof_size));
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_state_sweep() {
+        use crate::circuits::multi_state::MultiStateFCircuit;
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::param_sweep;
+
+        param_sweep::<G1Bn, G2Bn, _, NovaBn<MultiStateFCircuit<Fr>>, DeciderBn<MultiStateFCircuit<Fr>>>(
+            "BN254 state",
+            "state_len",
+            &[1, 2, 4, 8, 16, 32],
+            n_steps(),
+            |state_len| {
+                let f_circuit = MultiStateFCircuit::<Fr>::new(state_len).unwrap();
+                let poseidon_config = poseidon_config::<Fr>().unwrap();
+                (
+                    f_circuit,
+                    PreprocessorParam::new(poseidon_config, f_circuit),
+                    vec![Fr::from(3_u32); state_len],
+                )
+            },
+            |_, _| vec![],
+        )
+        .unwrap();
+    }
+    
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_external_inputs_sweep() {
+        use crate::circuits::external_inputs::ExternalInputsFCircuit;
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::{marginal_cost, param_sweep};
+
+        let points = param_sweep::<
+            G1Bn,
+            G2Bn,
+            _,
+            NovaBn<ExternalInputsFCircuit<Fr>>,
+            DeciderBn<ExternalInputsFCircuit<Fr>>,
+        >(
+            "BN254 external inputs",
+            "external_inputs_len",
+            &[0, 4, 16, 64, 256],
+            n_steps(),
+            |width| {
+                let f_circuit = ExternalInputsFCircuit::<Fr>::new(width).unwrap();
+                let poseidon_config = poseidon_config::<Fr>().unwrap();
+                (
+                    f_circuit,
+                    PreprocessorParam::new(poseidon_config, f_circuit),
+                    vec![Fr::from(3_u32)],
+                )
+            },
+            |width, i| random_external_inputs(width)(i),
+        )
+        .unwrap();
+        marginal_cost("BN254 external inputs", "external input", &points);
+    }
+    
+    // Step circuits of up to 2^20 constraints, run with
+    // `cargo test --release -- --ignored bench_bn254_circuit_size_sweep`.
+    #[cfg(feature = "bn254")]
+    #[test]
+    #[ignore]
+    fn bench_bn254_circuit_size_sweep() {
+        use crate::circuits::padding::PaddingFCircuit;
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::{param_sweep, scaling_fit};
+
+        let sizes: Vec<usize> = (10..=20).map(|log| 1 << log).collect();
+        let points = param_sweep::<G1Bn, G2Bn, _, NovaBn<PaddingFCircuit<Fr>>, DeciderBn<PaddingFCircuit<Fr>>>(
+            "BN254 circuit size",
+            "constraints",
+            &sizes,
+            n_steps(),
+            |n_constraints| {
+                let f_circuit = PaddingFCircuit::<Fr>::new(n_constraints).unwrap();
+                let poseidon_config = poseidon_config::<Fr>().unwrap();
+                (
+                    f_circuit,
+                    PreprocessorParam::new(poseidon_config, f_circuit),
+                    vec![Fr::from(3_u32)],
+                )
+            },
+            |_, _| vec![],
+        )
+        .unwrap();
+        scaling_fit("BN254 circuit size", &points);
+    }
+    
+    // Transcript parameters are swept here regardless of the configured ones.
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bench_bn254_poseidon_sweep() {
+        use crate::config::PoseidonParams;
+        use crate::solidity::{DeciderBn, NovaBn};
+        use crate::sweep::param_sweep;
+
+        let presets = [PoseidonParams::CANONICAL, PoseidonParams::RATE2];
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let points = param_sweep::<G1Bn, G2Bn, _, NovaBn<CubicFCircuit<Fr>>, DeciderBn<CubicFCircuit<Fr>>>(
+            "BN254 Poseidon",
+            "preset",
+            &[0, 1],
+            n_steps(),
+            |i| {
+                (
+                    f_circuit,
+                    PreprocessorParam::new(presets[i].config::<Fr>(), f_circuit),
+                    vec![Fr::from(3_u32)],
+                )
+            },
+            |_, _| vec![],
+        )
+        .unwrap();
+        for (preset, point) in presets.iter().zip(points) {
+            println!(
+                "BN254 Poseidon {:?}: {} augmented constraints, avg step {:?}",
+                preset, point.augmented_constraints, point.avg_step
+            );
+        }
+    }
+    
+    // Full-security counterpart of `nova/mnt/cubic`, to compare against the
+    // 298-bit toy parameters.
+    #[cfg(feature = "mnt753")]
+    #[test]
+    fn bench_mnt753() {
+        pub type N_MNT753 = Nova<
+            G1Mnt4_753,
+            GVar4_753,
+            G2Mnt6_753,
+            GVar6_753,
+            CubicFCircuit<Fr4_753>,
+            KZG<'static, MNT4_753>,
+            Pedersen<G2Mnt6_753>,
             false
         >;
         
-        pub type D_MNT = DeciderEth<
-            G1Mnt4,
-            GVar4,
-            G2Mnt6,
-            GVar6,
-            CubicFCircuit<Fr4>,
-            KZG<'static, MNT4_298>,
-            Pedersen<G2Mnt6>,
-            Groth16<MNT4_298>,
-            N_MNT,
+        pub type D_MNT753 = DeciderEth<
+            G1Mnt4_753,
+            GVar4_753,
+            G2Mnt6_753,
+            GVar6_753,
+            CubicFCircuit<Fr4_753>,
+            KZG<'static, MNT4_753>,
+            Pedersen<G2Mnt6_753>,
+            Groth16<MNT4_753>,
+            N_MNT753,
         >;
         
-        println!("\nRunning MNT cycle benchmark:");
-        let total_start = Instant::now();
-        
-        let poseidon_config = poseidon_canonical_config::<Fr4>();
-        let mut rng = rand::rngs::OsRng;
-        
-        let nova_preprocess_params = PreprocessorParam::new(poseidon_config.clone(), f_circuit);
-        let nova_params = N_MNT::preprocess(&mut rng, &nova_preprocess_params).unwrap();
-        let pp_hash = nova_params.1.pp_hash().unwrap();
-        
-        let mut nova = N_MNT::init(&nova_params, f_circuit, z_0).unwrap();
-        let (decider_pp, decider_vp) = D_MNT::preprocess(&mut rng, nova_params, nova.clone()).unwrap();
-        
-        let mut total_proving_time = 0;
-        for i in 0..n_steps {
-            let start = Instant::now();
-            nova.prove_step(rng, vec![], None).unwrap();
-            let duration = start.elapsed();
-            total_proving_time += duration.as_micros();
-            println!("MNT Nova::prove_step {}: {:?}", i, duration);
+        let f_circuit = CubicFCircuit::<Fr4_753>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<Fr4_753>().unwrap();
+        run_folding_bench::<G1Mnt4_753, G2Mnt6_753, _, N_MNT753, D_MNT753>(
+            "MNT-753",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr4_753::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    // BLS12-377/BW6-761 is a 2-chain rather than a cycle, see `bw6` for why this
+    // one folds without sonobe and proves through Groth16 recursion.
+    #[cfg(feature = "bls")]
+    #[test]
+    fn bench_bw6_decider() {
+        use crate::bw6::CubicFolding;
+        use crate::config::BenchConfig;
+        use crate::registry::select;
+        use crate::result::DeciderKind;
+        use ark_bls12_377::Fr as Fr377;
+
+        let mut rng = crate::config::rng();
+        let folding = CubicFolding::setup(&mut rng).unwrap();
+        let (u, w) = folding.step(Fr377::from(3_u32)).unwrap();
+        let (u2, w2) = folding.step(Fr377::from(35_u32)).unwrap();
+        let (folded, folded_w) = folding.fold((&u, &w), (&u2, &w2)).unwrap();
+        assert!(folding.is_satisfied(&folded, &folded_w));
+        let mut tampered = folded_w.clone();
+        tampered.e[0] += Fr377::from(1_u32);
+        assert!(!folding.is_satisfied(&folded, &tampered));
+
+        let config = BenchConfig::builder()
+            .steps(n_steps())
+            .cache_dir(None)
+            .build()
+            .unwrap();
+        let entries = select(&["nova-bw6/bls12-377/cubic".to_string()]);
+        assert_eq!(entries.len(), 1);
+        let results = (entries[0].run)(&config).unwrap();
+        for result in &results {
+            assert_eq!(result.steps.len(), n_steps());
+            assert_eq!(result.decider, Some(DeciderKind::Recursive));
+            assert!(result.proof_size > 0 && !result.decider_verify.is_zero());
         }
-        println!("MNT Average proving time: {:?}µs", total_proving_time / n_steps as u128);
+    }
+    
+    // Pasta and secp have no pairing, so there is no KZG/Groth16 decider for
+    // them; these only benchmark the folding loop with Pedersen on both sides.
+    #[cfg(feature = "pasta")]
+    #[test]
+    fn bench_pasta() {
+        pub type N_PASTA = Nova<
+            G1Pallas,
+            GVarPallas,
+            G2Vesta,
+            GVarVesta,
+            CubicFCircuit<FrPallas>,
+            Pedersen<G1Pallas>,
+            Pedersen<G2Vesta>,
+            false
+        >;
         
-        let start = Instant::now();
-        let proof = D_MNT::prove(rng, decider_pp, nova.clone()).unwrap();
-        println!("MNT Generated Decider proof: {:?}", start.elapsed());
+        let f_circuit = CubicFCircuit::<FrPallas>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<FrPallas>().unwrap();
+        run_folding_only_bench::<G1Pallas, G2Vesta, _, N_PASTA>(
+            "Pasta",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![FrPallas::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
+        )
+        .unwrap();
+    }
+    
+    #[cfg(feature = "secp")]
+    #[test]
+    fn bench_secp() {
+        pub type N_SECP = Nova<
+            G1Secp,
+            GVarSecp,
+            G2Secq,
+            GVarSecq,
+            CubicFCircuit<FrSecp>,
+            Pedersen<G1Secp>,
+            Pedersen<G2Secq>,
+            false
+        >;
         
-        let start = Instant::now();
-        let verified = D_MNT::verify(
-            decider_vp.clone(),
-            nova.i,
-            nova.z_0.clone(),
-            nova.z_i.clone(),
-            &nova.U_i,
-            &nova.u_i,
-            &proof,
+        let f_circuit = CubicFCircuit::<FrSecp>::new(()).unwrap();
+        let poseidon_config = poseidon_config::<FrSecp>().unwrap();
+        run_folding_only_bench::<G1Secp, G2Secq, _, N_SECP>(
+            "secp",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![FrSecp::from(3_u32)],
+            n_steps(),
+            no_external_inputs,
         )
         .unwrap();
-        println!("MNT Verification time: {:?}", start.elapsed());
-        assert!(verified);
-        println!("MNT Total time: {:?}", total_start.elapsed());
     }
-}
\ No newline at end of file
+}
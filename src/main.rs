@@ -0,0 +1,884 @@
+//! `sonobe-bench`: runs the registered benchmark configurations (see
+//! `registry`), all of them or those picked with `--filter`.
+use clap::{Parser, Subcommand};
+
+use mnt::{
+    affinity::CoreList,
+    campaign::Campaign,
+    compare::compare,
+    estimate::CostModel,
+    history::HistoryRun,
+    interrupt,
+    manifest::Manifest,
+    mobile,
+    regression::{check, default_thresholds, with_overrides, Threshold},
+    config::{self, BenchConfig, OutputSink, Phase, PoseidonParams, Verbosity},
+    registry::{registry, select},
+    suite::run_suite,
+    sweep::ThreadCounts,
+    trends::Trends,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[cfg(feature = "dhat-heap")]
+#[global_allocator]
+static ALLOC: dhat::Alloc = dhat::Alloc;
+
+#[derive(Debug, Parser)]
+#[command(name = "sonobe-bench", about = "Benchmarks Nova folding and decider proving")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Only run the benchmarks matching `scheme/curve/circuit` (e.g.
+    /// `nova/bn254`, `nova/*/cubic`); may be repeated
+    #[arg(long)]
+    filter: Vec<String>,
+
+    /// Benchmark this compiled Circom `.r1cs` circuit (Nova on BN254), on
+    /// top of the `--filter` selection if any
+    #[arg(long, requires = "witness_cmd")]
+    r1cs: Option<PathBuf>,
+
+    /// Witness calculator of `--r1cs`, run as `<cmd>.. input.json
+    /// witness.wtns` for every step
+    #[arg(long, num_args = 1.., allow_hyphen_values = true)]
+    witness_cmd: Vec<String>,
+
+    /// State length of the `--r1cs` circuit
+    #[arg(long, default_value_t = 1)]
+    state_len: usize,
+
+    /// External inputs per step of the `--r1cs` circuit
+    #[arg(long, default_value_t = 0)]
+    external_inputs_len: usize,
+
+    /// Feed every step the external inputs of this file instead of random
+    /// ones: a CSV line or a JSON array per step (see `inputs`)
+    #[arg(long, value_name = "FILE")]
+    inputs: Option<PathBuf>,
+
+    /// List the selected benchmarks instead of running them
+    #[arg(long)]
+    list: bool,
+
+    /// Only synthesize the circuits of the selected benchmarks and report
+    /// their sizes, the commitment keys and the IVC proof size, without
+    /// proving (see `dry_run`)
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Number of folding steps per benchmark
+    #[arg(long, env = "SONOBE_BENCH_STEPS", default_value_t = config::DEFAULT_STEPS,
+          value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    steps: usize,
+
+    /// Calibrate the steps of every benchmark from a few probe steps so that
+    /// folding takes about this many seconds, instead of `--steps` (see
+    /// `calibrate`)
+    #[arg(long, env = "SONOBE_BENCH_TARGET", value_name = "SECS",
+          value_parser = clap::value_parser!(u64).range(1..))]
+    target: Option<u64>,
+
+    /// Number of times every benchmark is run
+    #[arg(long, env = "SONOBE_BENCH_TRIALS", default_value_t = 1)]
+    trials: usize,
+
+    /// Report only the median of the trials by total time, each trial
+    /// preprocessing afresh unless the params are cached
+    #[arg(long, env = "SONOBE_BENCH_MEDIAN")]
+    median: bool,
+
+    /// Preprocess afresh instead of reusing the params cached under
+    /// `target/params` (or `SONOBE_BENCH_CACHE_DIR`)
+    #[arg(long, env = "SONOBE_BENCH_NO_CACHE")]
+    no_cache: bool,
+
+    /// Benchmarks to run concurrently; keep the default of 1 when timings
+    /// need the machine to themselves
+    #[arg(long, env = "SONOBE_BENCH_JOBS", default_value_t = 1)]
+    jobs: usize,
+
+    /// Phases to run after preprocessing, comma-separated: `fold`, `prove`,
+    /// `verify`; each needs the ones before it
+    #[arg(long, value_delimiter = ',', default_value = "fold,prove,verify")]
+    phases: Vec<Phase>,
+
+    /// Seed for all randomness (params, blinding, inputs), for reproducible runs
+    #[arg(long, env = "SONOBE_BENCH_SEED")]
+    seed: Option<u64>,
+
+    /// Poseidon transcript parameters: `canonical`, `rate2`, or
+    /// `rate,full_rounds,partial_rounds,alpha`
+    #[arg(long, env = "SONOBE_BENCH_POSEIDON", default_value = "canonical")]
+    poseidon: PoseidonParams,
+
+    /// Abandon a benchmark after this many seconds and record it as timed out
+    #[arg(long, env = "SONOBE_BENCH_TIMEOUT")]
+    timeout: Option<u64>,
+
+    /// Verify the IVC after every this many folding steps and report the
+    /// verification latency
+    #[arg(long, env = "SONOBE_BENCH_VERIFY_EVERY", value_parser = clap::value_parser!(u64).range(1..))]
+    verify_every: Option<u64>,
+
+    /// Also check that mutated decider proofs, states and step counts are
+    /// rejected
+    #[arg(long, env = "SONOBE_BENCH_NEGATIVE_TESTS")]
+    negative_tests: bool,
+
+    /// Also re-synthesize every folded step on a fresh constraint system and
+    /// check that its witness satisfies it, timed apart from the steps
+    #[arg(long, env = "SONOBE_BENCH_CHECK_SAT")]
+    check_sat: bool,
+
+    /// Also verify every decider proof with verifier params serialized and
+    /// read back, timing both directions
+    #[arg(long, env = "SONOBE_BENCH_VP_ROUND_TRIP")]
+    vp_round_trip: bool,
+
+    /// Also time MSMs, FFTs and Poseidon permutations on both curves of every
+    /// cycle benchmarked, and report them with the results (see `primitives`)
+    #[arg(long, env = "SONOBE_BENCH_PRIMITIVES")]
+    primitives: bool,
+
+    /// Pin the rayon workers of every benchmark to these cores, one worker
+    /// per core, e.g. `0-3,6`
+    #[arg(long, env = "SONOBE_BENCH_PIN_CORES", value_name = "CORES")]
+    pin_cores: Option<CoreList>,
+
+    /// Run every benchmark on a single rayon worker, so that how parallel
+    /// work is split and stolen does not vary between runs (pinned to the
+    /// first of `--pin-cores`, if given)
+    #[arg(long, env = "SONOBE_BENCH_NO_WORK_STEALING")]
+    no_work_stealing: bool,
+
+    /// Also write the results as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    /// Also write the results as CSV to this file
+    #[arg(long)]
+    csv: Option<PathBuf>,
+
+    /// Also write the results as a Markdown table to this file
+    #[arg(long)]
+    markdown: Option<PathBuf>,
+
+    /// Also write the results in Google Benchmark's JSON format to this file
+    #[arg(long)]
+    gbench_json: Option<PathBuf>,
+
+    /// Also write every timing sample (per step, trial and phase) as CSV to
+    /// this file, for external statistical tools
+    #[arg(long)]
+    samples: Option<PathBuf>,
+
+    /// Also write the results as booktabs LaTeX tables to this file, for
+    /// `\input` into a paper
+    #[arg(long)]
+    latex: Option<PathBuf>,
+
+    /// Also export the Groth16 part of every BN254 decider proof for snarkjs,
+    /// as `proof.json`, `public.json` and `verification_key.json` in a
+    /// subdirectory of this directory per benchmark
+    #[arg(long, value_name = "DIR")]
+    snarkjs: Option<PathBuf>,
+
+    /// Also save every BN254 decider proof with its verifier params and
+    /// public state, in a subdirectory of this directory per benchmark, for
+    /// `sonobe-bench verify`
+    #[arg(long, value_name = "DIR")]
+    save_artifacts: Option<PathBuf>,
+
+    /// Load the KZG keys of the BN254 Nova benchmarks from this SRS, a
+    /// `.ptau` file or one written by `srs::save_srs`, instead of sampling
+    /// them, and report the load time
+    #[arg(long, env = "SONOBE_BENCH_PTAU", value_name = "FILE")]
+    srs: Option<PathBuf>,
+
+    /// Powers of tau to load from `--srs`, at least as many as the
+    /// constraints of the largest augmented circuit
+    #[arg(long, env = "SONOBE_BENCH_SRS_POWERS", default_value_t = config::DEFAULT_SRS_POWERS)]
+    srs_powers: usize,
+
+    /// Run these two benchmarks interleaved instead and test whether their
+    /// step times differ significantly
+    #[arg(long, num_args = 2, value_names = ["A", "B"], conflicts_with = "filter")]
+    compare: Option<Vec<String>>,
+
+    /// Fold this many chains of the cubic circuit (`--steps` each), prove them
+    /// with the BN254 decider and compare verifying their Groth16 proofs one
+    /// by one and batched
+    #[arg(long, value_name = "K", conflicts_with_all = ["filter", "compare"],
+          value_parser = clap::value_parser!(u64).range(1..))]
+    batch_verify: Option<u64>,
+
+    /// Fold this many chains of the cubic circuit (`--steps` each) from
+    /// different initial states with Nova on BN254, sharing one preprocess,
+    /// and report the cost per chain with the preprocess amortized over them
+    #[arg(long, value_name = "K", conflicts_with_all = ["filter", "compare", "batch_verify"],
+          value_parser = clap::value_parser!(u64).range(1..))]
+    amortize: Option<u64>,
+
+    /// Report the augmented-circuit constraints of Nova, HyperNova and
+    /// ProtoGalaxy for the cubic circuit on BN254 instead
+    #[arg(long, conflicts_with_all = ["filter", "compare", "batch_verify"])]
+    overhead: bool,
+
+    /// Fold a selector over three circuits of 2^10, 2^11 and 2^12
+    /// constraints with Nova on BN254 instead, against a universal circuit of
+    /// the same size and each branch alone (see `nivc`)
+    #[arg(long, conflicts_with_all = ["filter", "compare", "batch_verify", "overhead"])]
+    nivc: bool,
+
+    /// Run the cubic circuit with Nova on BN254 in rayon pools of these
+    /// sizes instead, e.g. `1,2,4`, or `auto` for the powers of two up to
+    /// the number of cores, and report the speedup of every phase
+    #[arg(long, value_name = "N,...|auto",
+          conflicts_with_all = ["filter", "compare", "batch_verify", "overhead", "nivc"])]
+    threads: Option<ThreadCounts>,
+
+    /// Only fold `--steps` steps of the cubic circuit with Nova on BN254
+    /// instead, and save the folding state reached to this file (see
+    /// `snapshot`)
+    #[arg(long, value_name = "FILE",
+          conflicts_with_all = ["filter", "compare", "batch_verify", "overhead", "nivc", "threads"])]
+    snapshot_out: Option<PathBuf>,
+
+    /// Only fold the cubic circuit with Nova on BN254 instead, continuing
+    /// from the folding state saved to this file by `--snapshot-out`; the
+    /// params must be cached or seeded alike in both runs
+    #[arg(long, value_name = "FILE",
+          conflicts_with_all = ["filter", "compare", "batch_verify", "overhead", "nivc", "threads"])]
+    resume_from: Option<PathBuf>,
+
+    /// Fold the cubic and padding circuits with both sonobe and microsoft/Nova
+    /// (`nova-snark`) on BN254 instead and compare them (see `reference`)
+    #[arg(long, conflicts_with_all = ["filter", "compare", "batch_verify", "overhead"])]
+    nova_snark: bool,
+
+    /// Fold the toy zkVM over the execution trace in this JSON file (see
+    /// `trace`) with Nova on BN254 instead, reporting instructions per second
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["filter", "compare", "batch_verify", "overhead"])]
+    trace: Option<PathBuf>,
+
+    /// Rounds of each benchmark with `--compare`
+    #[arg(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    rounds: u64,
+
+    /// Only print summaries
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print every step instead of a progress bar (-vv: also the IVC state)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the campaign declared in a TOML file (see `campaign`) instead of
+    /// the flags' selection
+    Run {
+        #[arg(long)]
+        config: PathBuf,
+    },
+    /// Compare a run to a baseline and exit with 1 if any metric regressed
+    /// beyond its threshold, 2 if the reports cannot be read
+    Check {
+        /// CSV report (`--csv`) of the baseline run
+        #[arg(long)]
+        baseline: PathBuf,
+
+        /// CSV report of the run to check; without it, the `--filter`
+        /// selection is run now
+        #[arg(long)]
+        current: Option<PathBuf>,
+
+        /// Largest increase of a CSV column in percent, e.g. `avg_step_ns=5`,
+        /// replacing its default; may be repeated
+        #[arg(long, value_name = "COLUMN=PERCENT")]
+        threshold: Vec<Threshold>,
+    },
+    /// Serve an HTTP API to queue campaigns, follow their progress and
+    /// fetch their results (see `serve`); needs the `serve` feature
+    Serve {
+        /// Address to listen on; the API has no authentication
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+    },
+    /// Predict the step and decider times of a circuit from a cost model
+    /// fitted on the CSV reports of past runs, e.g. of a padding sweep (see
+    /// `estimate`)
+    Estimate {
+        /// CSV reports (`--csv`) to fit the model on
+        #[arg(required = true)]
+        reports: Vec<PathBuf>,
+
+        /// Constraints of the step circuit
+        #[arg(long)]
+        constraints: usize,
+
+        /// State length of the step circuit
+        #[arg(long, default_value_t = 1)]
+        state_width: usize,
+
+        /// Fit only the results of benchmarks whose name starts with this
+        #[arg(long, default_value = "nova/bn254/")]
+        prefix: String,
+    },
+    /// Run a workload small enough for a phone, printing only summaries and
+    /// writing the results as JSON (see `mobile`)
+    Mobile {
+        /// Where to write the JSON report
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Benchmarks to run, as with `--filter`
+        #[arg(long, default_value = mobile::DEFAULT_FILTER)]
+        filter: Vec<String>,
+
+        /// Folding steps per benchmark
+        #[arg(long, default_value_t = mobile::DEFAULT_STEPS)]
+        steps: usize,
+    },
+    /// Time the verification of a BN254 decider proof saved with
+    /// `--save-artifacts`, without folding or proving (see `artifacts`)
+    Verify {
+        /// Directory of one benchmark's `vp.bin`, `proof.bin` and `state.bin`
+        #[arg(long)]
+        dir: PathBuf,
+
+        /// Verifications to average over
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+        iterations: u64,
+    },
+    /// Summarize the CSV reports of past runs
+    Report {
+        #[command(subcommand)]
+        report: ReportCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ReportCommand {
+    /// Chart the per-step time, decider time and proof size of every
+    /// configuration across runs
+    Trends {
+        /// CSV reports (`--csv`), oldest first
+        #[arg(required = true)]
+        reports: Vec<PathBuf>,
+
+        /// Where to write the SVG chart
+        #[arg(long, default_value = "trends.svg")]
+        svg: PathBuf,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Some(Command::Report { report }) = &cli.command {
+        if let Err(e) = run_report(report) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Some(Command::Estimate {
+        reports,
+        constraints,
+        state_width,
+        prefix,
+    }) = &cli.command
+    {
+        if let Err(e) = run_estimate(reports, *constraints, *state_width, prefix, cli.poseidon) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Some(Command::Mobile { out, filter, steps }) = &cli.command {
+        match mobile::run_mobile(out, filter, *steps) {
+            Ok(report) if report.failed().is_empty() => return,
+            Ok(report) => {
+                println!("Did not complete: {}", report.failed().join(", "));
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(Command::Verify { dir, iterations }) = &cli.command {
+        #[cfg(feature = "bn254")]
+        {
+            let iterations = *iterations as usize;
+            if let Err(e) = mnt::artifacts::verify_artifacts_bn254("verify", dir, iterations) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            let _ = iterations;
+            eprintln!("verify {:?} needs the bn254 feature", dir);
+            std::process::exit(2);
+        }
+    }
+    config::set_n_steps(cli.steps);
+    config::set_poseidon(cli.poseidon);
+    config::set_timeout(cli.timeout.map(Duration::from_secs));
+    config::set_verify_every(cli.verify_every.map(|every| every as usize));
+    config::set_negative_tests(cli.negative_tests);
+    config::set_vp_round_trip(cli.vp_round_trip);
+    config::set_check_sat(cli.check_sat);
+    config::set_verbosity(match (cli.quiet, cli.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (false, 0) => Verbosity::Normal,
+        (false, 1) => Verbosity::Verbose,
+        (false, _) => Verbosity::Trace,
+    });
+    if let Some(seed) = cli.seed {
+        config::set_seed(seed);
+    }
+
+    let mut builder = BenchConfig::builder()
+        .steps(cli.steps)
+        .trials(cli.trials)
+        .median(cli.median)
+        .jobs(cli.jobs)
+        .phases(cli.phases)
+        .external_inputs(cli.inputs)
+        .dry_run(cli.dry_run)
+        .target(cli.target.map(Duration::from_secs))
+        .snarkjs_dir(cli.snarkjs)
+        .artifacts_dir(cli.save_artifacts)
+        .srs(cli.srs, cli.srs_powers)
+        .primitives(cli.primitives)
+        .pin_cores(cli.pin_cores.unwrap_or_default().0)
+        .no_work_stealing(cli.no_work_stealing)
+        .sinks(
+            std::iter::once(OutputSink::Terminal)
+                .chain(cli.json.map(OutputSink::Json))
+                .chain(cli.csv.map(OutputSink::Csv))
+                .chain(cli.markdown.map(OutputSink::Markdown))
+                .chain(cli.gbench_json.map(OutputSink::GoogleBenchmark))
+                .chain(cli.samples.map(OutputSink::Samples))
+                .chain(cli.latex.map(OutputSink::Latex)),
+        );
+    if let Some(seed) = cli.seed {
+        builder = builder.seed(seed);
+    }
+    if cli.no_cache {
+        builder = builder.cache_dir(None);
+    }
+    let bench_config = match builder.build() {
+        Ok(bench_config) => bench_config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(names) = cli.compare {
+        let mut entries = registry();
+        let mut take = |name: &str| match entries.iter().position(|e| e.name == name) {
+            Some(i) => entries.swap_remove(i),
+            None => {
+                eprintln!("No benchmark named {} (see --list)", name);
+                std::process::exit(2);
+            }
+        };
+        let (a, b) = (take(&names[0]), take(&names[1]));
+        match compare(&a, &b, &bench_config, cli.rounds as usize) {
+            Ok(comparison) => comparison.print(),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(k) = cli.batch_verify {
+        #[cfg(feature = "bn254")]
+        {
+            use ark_bn254::Fr;
+            use folding_schemes::frontend::FCircuit;
+            let f_circuit = mnt::CubicFCircuit::<Fr>::new(()).expect("cubic circuit");
+            match mnt::batch::bench_batch_verification("batch", f_circuit, k as usize, cli.steps) {
+                Ok(batch) => batch.print("batch"),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--batch-verify {} needs the bn254 feature", k);
+            std::process::exit(2);
+        }
+    }
+
+    if let Some(k) = cli.amortize {
+        #[cfg(feature = "bn254")]
+        {
+            use ark_bn254::{Fr, G1Projective as G1};
+            use ark_grumpkin::Projective as G2;
+            use folding_schemes::{folding::nova::PreprocessorParam, frontend::FCircuit};
+            use mnt::amortize::bench_amortized;
+            use mnt::solidity::{DeciderBn, NovaBn};
+            type Cubic = mnt::CubicFCircuit<Fr>;
+            let f_circuit = Cubic::new(()).expect("cubic circuit");
+            let poseidon = mnt::config::poseidon_config::<Fr>().expect("set from --poseidon");
+            let prep_param = PreprocessorParam::new(poseidon, f_circuit);
+            let amortized = bench_amortized::<G1, G2, _, NovaBn<Cubic>, DeciderBn<Cubic>>(
+                "amortized",
+                &prep_param,
+                f_circuit,
+                |chain| vec![Fr::from(3 + chain as u64)],
+                k as usize,
+                cli.steps,
+                |_, _| vec![],
+            );
+            match amortized {
+                Ok(amortized) => amortized.print("amortized"),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--amortize {} needs the bn254 feature", k);
+            std::process::exit(2);
+        }
+    }
+
+    if cli.overhead {
+        #[cfg(feature = "bn254")]
+        {
+            use ark_bn254::Fr;
+            use folding_schemes::frontend::FCircuit;
+            let f_circuit = mnt::CubicFCircuit::<Fr>::new(()).expect("cubic circuit");
+            let z_0 = vec![Fr::from(3_u32)];
+            if let Err(e) = mnt::overhead::compare_overhead_bn254("overhead", f_circuit, z_0) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--overhead needs the bn254 feature");
+            std::process::exit(2);
+        }
+    }
+
+    if cli.nivc {
+        #[cfg(feature = "bn254")]
+        {
+            let branches = vec![1 << 10, 1 << 11, 1 << 12];
+            if let Err(e) = mnt::nivc::compare_nivc_bn254("NIVC", branches, cli.steps) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--nivc needs the bn254 feature");
+            std::process::exit(2);
+        }
+    }
+
+    if let Some(threads) = cli.threads {
+        #[cfg(feature = "bn254")]
+        {
+            use ark_bn254::{Fr, G1Projective as G1};
+            use ark_grumpkin::Projective as G2;
+            use folding_schemes::{folding::nova::PreprocessorParam, frontend::FCircuit};
+            use mnt::solidity::{DeciderBn, NovaBn};
+            use mnt::sweep::thread_sweep;
+            type Cubic = mnt::CubicFCircuit<Fr>;
+            let f_circuit = Cubic::new(()).expect("cubic circuit");
+            let poseidon = mnt::config::poseidon_config::<Fr>().expect("set from --poseidon");
+            let prep_param = PreprocessorParam::new(poseidon, f_circuit);
+            if let Err(e) = thread_sweep::<G1, G2, _, NovaBn<Cubic>, DeciderBn<Cubic>>(
+                "threads",
+                &threads.0,
+                prep_param,
+                f_circuit,
+                vec![Fr::from(3_u32)],
+                cli.steps,
+                |_| vec![],
+            ) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--threads {:?} needs the bn254 feature", threads.0);
+            std::process::exit(2);
+        }
+    }
+
+    if cli.snapshot_out.is_some() || cli.resume_from.is_some() {
+        #[cfg(feature = "bn254")]
+        {
+            use ark_bn254::Fr;
+            use folding_schemes::frontend::FCircuit;
+            use mnt::snapshot::fold_resumable;
+            let f_circuit = mnt::CubicFCircuit::<Fr>::new(()).expect("cubic circuit");
+            if let Err(e) = fold_resumable(
+                "resumable",
+                f_circuit,
+                vec![Fr::from(3_u32)],
+                &bench_config,
+                cli.resume_from.as_deref(),
+                cli.snapshot_out.as_deref(),
+            ) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--snapshot-out and --resume-from need the bn254 feature");
+            std::process::exit(2);
+        }
+    }
+
+    if cli.nova_snark {
+        #[cfg(feature = "nova-snark")]
+        {
+            if let Err(e) = mnt::reference::compare_reference_bn254(cli.steps) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "nova-snark"))]
+        {
+            eprintln!("--nova-snark needs the nova-snark feature");
+            std::process::exit(2);
+        }
+    }
+
+    if let Some(path) = &cli.trace {
+        #[cfg(feature = "bn254")]
+        {
+            let result = mnt::trace::Trace::load(path)
+                .and_then(|trace| mnt::trace::run_trace_bench_bn254("trace", &trace));
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "bn254"))]
+        {
+            eprintln!("--trace {:?} needs the bn254 feature", path);
+            std::process::exit(2);
+        }
+    }
+
+    if let Some(Command::Serve { addr }) = &cli.command {
+        #[cfg(feature = "serve")]
+        {
+            if let Err(e) = mnt::serve::serve(addr) {
+                eprintln!("Cannot serve on {}: {}", addr, e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(not(feature = "serve"))]
+        {
+            eprintln!("serve on {} needs the serve feature", addr);
+            std::process::exit(2);
+        }
+    }
+
+    // the suites below still write what completed when interrupted
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(e) = interrupt::install() {
+        eprintln!("Cannot handle Ctrl-C: {}", e);
+    }
+
+    if let Some(Command::Run { config }) = &cli.command {
+        let campaign = match Campaign::load(config) {
+            Ok(campaign) => campaign,
+            Err(e) => {
+                eprintln!("Cannot load {:?}: {}", config, e);
+                std::process::exit(2);
+            }
+        };
+        let mut failed = Vec::new();
+        for run in &campaign.runs {
+            println!("\n{} steps", run.steps);
+            let report = run_suite(select(&campaign.filters), run);
+            write_manifest(run);
+            match report {
+                Ok(report) => failed.extend(
+                    report.failed().iter().map(|name| format!("{} ({} steps)", name, run.steps)),
+                ),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+            if interrupt::interrupted() {
+                std::process::exit(interrupt::EXIT_CODE);
+            }
+        }
+        if !failed.is_empty() {
+            println!("\n{} benchmark(s) did not complete: {}", failed.len(), failed.join(", "));
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Check {
+        baseline,
+        current,
+        threshold,
+    }) = &cli.command
+    {
+        let current = match current {
+            Some(path) => path.clone(),
+            None => {
+                let path = std::env::temp_dir()
+                    .join(format!("sonobe-bench-check-{}.csv", std::process::id()));
+                let mut config = bench_config.clone();
+                config.sinks.push(OutputSink::Csv(path.clone()));
+                if let Err(e) = run_suite(select(&cli.filter), &config) {
+                    eprintln!("{}", e);
+                    std::process::exit(2);
+                }
+                if interrupt::interrupted() {
+                    std::process::exit(interrupt::EXIT_CODE);
+                }
+                path
+            }
+        };
+        let runs = HistoryRun::load(baseline).and_then(|b| Ok((b, HistoryRun::load(&current)?)));
+        match runs {
+            Ok((baseline, current)) => {
+                let thresholds = with_overrides(default_thresholds(), threshold);
+                let report = check(&baseline, &current, &thresholds);
+                report.print();
+                if !report.passed() {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+        return;
+    }
+
+    // `--r1cs` alone runs only that circuit
+    #[allow(unused_mut)]
+    let mut entries = if cli.r1cs.is_some() && cli.filter.is_empty() {
+        Vec::new()
+    } else {
+        select(&cli.filter)
+    };
+    #[cfg(feature = "bn254")]
+    if let Some(path) = &cli.r1cs {
+        match mnt::registry::r1cs_entry(
+            path,
+            &cli.witness_cmd,
+            cli.state_len,
+            cli.external_inputs_len,
+        ) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                eprintln!("Cannot load {:?}: {}", path, e);
+                std::process::exit(2);
+            }
+        }
+    }
+    if cli.list {
+        entries.iter().for_each(|e| println!("{}", e.name));
+        return;
+    }
+    let report = run_suite(entries, &bench_config);
+    write_manifest(&bench_config);
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if interrupt::interrupted() {
+        std::process::exit(interrupt::EXIT_CODE);
+    }
+    let failed = report.failed();
+    if !failed.is_empty() {
+        println!("\n{} benchmark(s) did not complete: {}", failed.len(), failed.join(", "));
+        std::process::exit(1);
+    }
+}
+
+fn run_report(report: &ReportCommand) -> Result<(), mnt::error::BenchError> {
+    match report {
+        ReportCommand::Trends { reports, svg } => {
+            let runs = reports
+                .iter()
+                .map(|path| HistoryRun::load(path))
+                .collect::<Result<Vec<_>, _>>()?;
+            let trends = Trends::from_history(&runs);
+            trends.print();
+            trends.write_svg(svg)
+        }
+    }
+}
+
+fn run_estimate(
+    reports: &[PathBuf],
+    constraints: usize,
+    state_width: usize,
+    prefix: &str,
+    poseidon: PoseidonParams,
+) -> Result<(), mnt::error::BenchError> {
+    let runs = reports
+        .iter()
+        .map(|path| HistoryRun::load(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let model = CostModel::fit(&runs, prefix, poseidon)?;
+    model.print(prefix);
+    let estimate = model.predict(constraints, state_width);
+    println!(
+        "\nStep circuit of {} constraints, state of {}: ~{} augmented constraints, \
+         ~{} MSM points",
+        constraints, state_width, estimate.augmented_constraints, estimate.msm_points
+    );
+    println!("  step     {:>12.2?}", estimate.step);
+    match estimate.decider {
+        Some(decider) => println!("  decider  {:>12.2?}", decider),
+        None => println!("  decider  n/a (no decider in the reports)"),
+    }
+    Ok(())
+}
+
+/// Writes the reproducibility manifest next to the result files of `config`.
+fn write_manifest(config: &BenchConfig) {
+    if let Err(e) = Manifest::current(config.seed).write_beside(&config.sinks) {
+        eprintln!("Cannot write the manifest: {}", e);
+    }
+}
@@ -0,0 +1,172 @@
+//! Reproducibility manifest written next to every result file.
+//!
+//! It records what the report's `build` object leaves out: the compiler,
+//! profile and opt-level, every enabled feature, the version and source of
+//! every locked package, the seed and the command line. Rebuilding with that
+//! toolchain, `Cargo.lock` and features and rerunning the command reproduces
+//! the run, except that without a seed the params and inputs are drawn
+//! afresh.
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::build_info::BuildInfo;
+use crate::config::OutputSink;
+use crate::error::BenchError;
+use crate::report::json_string;
+
+/// `name version source` lines of `Cargo.lock`, see `build.rs`.
+const LOCKED_PACKAGES: &str = include_str!(concat!(env!("OUT_DIR"), "/locked_packages.txt"));
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: &'static str,
+    pub version: &'static str,
+    /// Registry or git URL, with the revision for git; empty for this crate.
+    pub source: &'static str,
+}
+
+/// The packages locked when the harness was built.
+pub fn locked_packages() -> Vec<LockedPackage> {
+    LOCKED_PACKAGES
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            Some(LockedPackage {
+                name: fields.next()?,
+                version: fields.next()?,
+                source: fields.next().unwrap_or(""),
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Manifest {
+    pub build: BuildInfo,
+    pub seed: Option<u64>,
+    /// The command line, program name included.
+    pub args: Vec<String>,
+    /// Seconds since the Unix epoch.
+    pub created: u64,
+}
+
+impl Manifest {
+    /// The manifest of this process run with `seed`.
+    pub fn current(seed: Option<u64>) -> Self {
+        Self {
+            build: BuildInfo::current(),
+            seed,
+            args: std::env::args().collect(),
+            created: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let packages: Vec<String> = locked_packages()
+            .iter()
+            .map(|p| {
+                format!(
+                    "    {{\"name\": {}, \"version\": {}, \"source\": {}}}",
+                    json_string(p.name),
+                    json_string(p.version),
+                    json_string(p.source)
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"created\": {},\n  \"args\": [{}],\n  \"seed\": {},\n  \"rustc\": {},\n  \
+             \"profile\": {},\n  \"opt_level\": {},\n  \"debug_assertions\": {},\n  \
+             \"features\": [{}],\n  \"sonobe_version\": {},\n  \"sonobe_revision\": {},\n  \
+             \"dependencies\": [\n{}\n  ]\n}}\n",
+            self.created,
+            json_strings(self.args.iter().map(String::as_str)),
+            self.seed.map_or("null".to_string(), |s| s.to_string()),
+            json_string(self.build.rustc),
+            json_string(self.build.profile),
+            json_string(self.build.opt_level),
+            self.build.debug_assertions,
+            json_strings(self.build.cargo_features.split(',').filter(|f| !f.is_empty())),
+            json_string(self.build.sonobe_version),
+            json_string(self.build.sonobe_revision),
+            packages.join(",\n")
+        )
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), BenchError> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(self.to_json().as_bytes())?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Writes the manifest next to the file of every sink of `sinks`, see
+    /// `manifest_path`, and returns where.
+    pub fn write_beside(&self, sinks: &[OutputSink]) -> Result<Vec<PathBuf>, BenchError> {
+        let mut written = Vec::new();
+        for sink in sinks {
+            let results = match sink {
+                OutputSink::Terminal => continue,
+                OutputSink::Json(path)
+                | OutputSink::Csv(path)
+                | OutputSink::Markdown(path)
+                | OutputSink::GoogleBenchmark(path)
+                | OutputSink::Samples(path)
+                | OutputSink::Latex(path) => path,
+            };
+            let path = manifest_path(results);
+            if written.contains(&path) {
+                continue;
+            }
+            self.write(&path)?;
+            println!("Wrote manifest to {:?}", path);
+            written.push(path);
+        }
+        if self.seed.is_none() && !written.is_empty() {
+            println!("No --seed was given, so the params and inputs of this run cannot be redrawn");
+        }
+        Ok(written)
+    }
+}
+
+fn json_strings<'a>(items: impl Iterator<Item = &'a str>) -> String {
+    items.map(json_string).collect::<Vec<_>>().join(", ")
+}
+
+/// `results.json` → `results.manifest.json`.
+pub fn manifest_path(results: &Path) -> PathBuf {
+    let stem = results.file_stem().unwrap_or_default().to_string_lossy();
+    results.with_file_name(format!("{}.manifest.json", stem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let packages = locked_packages();
+        assert!(packages.iter().any(|p| p.name == "folding-schemes"
+            && p.source.ends_with(BuildInfo::current().sonobe_revision)));
+        assert!(packages.iter().any(|p| p.name == "mnt" && p.source.is_empty()));
+
+        let manifest = Manifest {
+            build: BuildInfo::current(),
+            seed: Some(7),
+            args: ["sonobe-bench", "--filter", "nova/bn254"].map(String::from).to_vec(),
+            created: 1,
+        };
+        let json = manifest.to_json();
+        assert!(json.contains("\"args\": [\"sonobe-bench\", \"--filter\", \"nova/bn254\"]"));
+        assert!(json.contains("\"seed\": 7"));
+        assert!(json.contains(&format!("\"rustc\": {}", json_string(BuildInfo::current().rustc))));
+        assert!(json.contains("{\"name\": \"folding-schemes\", \"version\": "));
+        assert_eq!(json.contains("\"bn254\""), cfg!(feature = "bn254"));
+
+        assert_eq!(
+            manifest_path(Path::new("out/report.csv")),
+            Path::new("out/report.manifest.json")
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Memory-constrained runs: find the largest configuration that still
+//! completes under a memory cap, for sizing prover machines and containers.
+//!
+//! Each configuration runs in a child process of the current (test) binary,
+//! started through `sh -c 'ulimit -v ...'` so the cap is the address-space
+//! limit enforced by the kernel; an allocation failure aborts the child
+//! without taking the sweep down. The child finds out which configuration to
+//! run from `SONOBE_BENCH_MEM_CONFIG`. Unix only.
+use std::process::Command;
+
+use crate::error::BenchError;
+
+/// Set in the child processes to the index of the configuration to run.
+pub const CONFIG_ENV: &str = "SONOBE_BENCH_MEM_CONFIG";
+
+/// Index of the configuration this process was spawned to run, `None` in
+/// the parent.
+pub fn child_config() -> Result<Option<usize>, BenchError> {
+    std::env::var(CONFIG_ENV)
+        .ok()
+        .map(|i| {
+            i.parse().map_err(|_| {
+                BenchError::InvalidInput(format!("{} must be an index, got {:?}", CONFIG_ENV, i))
+            })
+        })
+        .transpose()
+}
+
+/// Runs the test `test_name` of the current binary once per configuration in
+/// `n_configs` (ordered from smallest to largest) with memory capped to
+/// `limit_mib`, stopping at the first failure. Returns the index of the
+/// largest configuration that completed, or an error if the children cannot
+/// be started at all.
+pub fn largest_completing(
+    label: &str,
+    test_name: &str,
+    n_configs: usize,
+    limit_mib: u64,
+) -> Result<Option<usize>, BenchError> {
+    let exe = std::env::current_exe()?;
+    let mut largest = None;
+    for i in 0..n_configs {
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!(
+                "ulimit -v {} && exec \"$0\" --exact \"$1\" --nocapture --test-threads 1",
+                limit_mib * 1024
+            ))
+            .arg(&exe)
+            .arg(test_name)
+            .env(CONFIG_ENV, i.to_string())
+            .status()?;
+        println!(
+            "{} config {} under {} MiB: {}",
+            label,
+            i,
+            limit_mib,
+            if status.success() { "completed" } else { "failed" }
+        );
+        if !status.success() {
+            break;
+        }
+        largest = Some(i);
+    }
+    Ok(largest)
+}
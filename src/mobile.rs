@@ -0,0 +1,91 @@
+//! Runs on Android and iOS dev devices, for mobile-prover feasibility
+//! studies: `sonobe-bench mobile --out results.json` from `adb shell` or a
+//! terminal app, or `sonobe_bench_mobile` from an app linking the `cdylib`.
+//!
+//! Build for the device without `asm`, which is x86-only, e.g.
+//!
+//! ```text
+//! cargo build --release --target aarch64-linux-android --no-default-features \
+//!     --features bn254,parallel,light-test
+//! ```
+//!
+//! Nothing assumes a terminal: no progress bar, only summaries on stdout,
+//! which an app may never show, and the results written as the `--json`
+//! report to a path the app can read back, e.g. in its files directory. The
+//! default workload, the cubic circuit on BN254 for `DEFAULT_STEPS` steps,
+//! fits a phone's memory and thermal budget; `mobile` takes larger ones.
+use std::ffi::{c_char, CStr};
+use std::path::Path;
+use std::sync::Once;
+
+use crate::config::{set_verbosity, BenchConfig, OutputSink, Verbosity};
+use crate::error::BenchError;
+use crate::registry::select;
+use crate::suite::{run_suite, SuiteReport};
+
+pub const DEFAULT_FILTER: &str = "nova/bn254/cubic";
+pub const DEFAULT_STEPS: usize = 3;
+
+/// A mobile run of `steps` steps writing its JSON report to `out`.
+pub fn mobile_config(out: &Path, steps: usize) -> Result<BenchConfig, BenchError> {
+    BenchConfig::builder()
+        .steps(steps)
+        .sinks([OutputSink::Json(out.to_path_buf())])
+        .build()
+}
+
+/// Runs the benchmarks selected by `filters` for `steps` steps each and
+/// writes the JSON report to `out`.
+pub fn run_mobile(out: &Path, filters: &[String], steps: usize) -> Result<SuiteReport, BenchError> {
+    static QUIET: Once = Once::new();
+    QUIET.call_once(|| set_verbosity(Verbosity::Quiet));
+    let entries = select(filters);
+    if entries.is_empty() {
+        return Err(BenchError::InvalidInput(format!(
+            "no benchmark matches {:?}",
+            filters
+        )));
+    }
+    run_suite(entries, &mobile_config(out, steps)?)
+}
+
+/// Runs the default workload from an app and writes the JSON report to
+/// `out`. Returns 0 if every benchmark completed, 1 if one did not, and 2 if
+/// `out` is not a UTF-8 path.
+///
+/// # Safety
+///
+/// `out` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sonobe_bench_mobile(out: *const c_char) -> i32 {
+    if out.is_null() {
+        return 2;
+    }
+    let Ok(out) = CStr::from_ptr(out).to_str() else {
+        return 2;
+    };
+    match run_mobile(Path::new(out), &[DEFAULT_FILTER.to_string()], DEFAULT_STEPS) {
+        Ok(report) if report.failed().is_empty() => 0,
+        Ok(_) => 1,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mobile_config() {
+        let config = mobile_config(Path::new("results.json"), DEFAULT_STEPS).unwrap();
+        assert_eq!(config.steps, DEFAULT_STEPS);
+        assert!(
+            matches!(&config.sinks[..], [OutputSink::Json(path)] if path.ends_with("results.json"))
+        );
+        assert!(mobile_config(Path::new("results.json"), 0).is_err());
+        assert_eq!(unsafe { sonobe_bench_mobile(std::ptr::null()) }, 2);
+    }
+}
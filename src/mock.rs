@@ -0,0 +1,192 @@
+//! Mock commitment scheme factoring the MSMs out of folding, enabled by the
+//! `mock-commitment` feature so that it never reaches a real build.
+//!
+//! `MockCommitment<CS>` commits to `v` as `G * (sum(v) + blind)`: one scalar
+//! multiplication whatever the length of `v`, yet still additively
+//! homomorphic, so Nova folds and `FoldingScheme::verify` accepts as with
+//! `CS`. It binds to nothing, and its openings are `CS`'s own, which do not
+//! match the mock commitments: fold with it, never prove a decider.
+//! `commitment_share_bn254` folds the same circuit with and without it and
+//! attributes the difference to the commitments.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_std::rand::RngCore;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use folding_schemes::{
+    commitment::CommitmentScheme, frontend::FCircuit, transcript::Transcript, Error, FoldingScheme,
+};
+
+use crate::bench::{fold_steps, no_external_inputs, IvcState};
+use crate::config::rng;
+use crate::error::BenchError;
+
+/// `CS` with near-free commitments, see the module docs.
+#[derive(Clone, Debug)]
+pub struct MockCommitment<CS>(PhantomData<CS>);
+
+impl<C: CurveGroup, CS: CommitmentScheme<C, H>, const H: bool> CommitmentScheme<C, H>
+    for MockCommitment<CS>
+{
+    type ProverParams = CS::ProverParams;
+    type VerifierParams = CS::VerifierParams;
+    type Proof = CS::Proof;
+    type ProverChallenge = CS::ProverChallenge;
+    type Challenge = CS::Challenge;
+
+    fn setup(
+        rng: impl RngCore,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        CS::setup(rng, len)
+    }
+
+    fn commit(
+        _params: &Self::ProverParams,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+    ) -> Result<C, Error> {
+        Ok(C::generator() * (v.iter().sum::<C::ScalarField>() + blind))
+    }
+
+    fn prove(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cm: &C,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        CS::prove(params, transcript, cm, v, blind, rng)
+    }
+
+    fn prove_with_challenge(
+        params: &Self::ProverParams,
+        challenge: Self::ProverChallenge,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        CS::prove_with_challenge(params, challenge, v, blind, rng)
+    }
+
+    fn verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cm: &C,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        CS::verify(params, transcript, cm, proof)
+    }
+
+    fn verify_with_challenge(
+        params: &Self::VerifierParams,
+        challenge: Self::Challenge,
+        cm: &C,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        CS::verify_with_challenge(params, challenge, cm, proof)
+    }
+}
+
+/// Mean folding step with real and with mock commitments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommitmentShare {
+    pub with_commitments: Duration,
+    pub without_commitments: Duration,
+}
+
+impl CommitmentShare {
+    /// Share of the step time spent committing.
+    pub fn share(&self) -> f64 {
+        let saved = self
+            .with_commitments
+            .saturating_sub(self.without_commitments);
+        saved.as_secs_f64() / self.with_commitments.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Preprocesses `FS`, folds `n_steps` from `z_0` and verifies the IVC,
+/// returning the mean step time.
+pub fn mean_fold_step<C1, C2, FC, FS>(
+    label: &str,
+    prep_param: &FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+) -> Result<Duration, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + IvcState<C1>,
+{
+    let fs_params = FS::preprocess(rng(), prep_param)?;
+    let mut fs = FS::init(&fs_params, f_circuit, z_0)?;
+    let steps = fold_steps::<C1, C2, FC, FS>(label, &mut fs, n_steps, no_external_inputs)?;
+    let (running, incoming, cyclefold) = fs.instances();
+    FS::verify(
+        fs_params.1,
+        fs.z_0(),
+        fs.z_i(),
+        fs.num_steps(),
+        running,
+        incoming,
+        cyclefold,
+    )?;
+    Ok(steps.iter().sum::<Duration>() / n_steps.max(1) as u32)
+}
+
+/// Folds `f_circuit` with Nova on BN254/Grumpkin, with KZG and Pedersen and
+/// then with their mocks, and prints the share of the commitments.
+#[cfg(feature = "bn254")]
+pub fn commitment_share_bn254<FC: FCircuit<ark_bn254::Fr>>(
+    label: &str,
+    f_circuit: FC,
+    z_0: Vec<ark_bn254::Fr>,
+    n_steps: usize,
+) -> Result<CommitmentShare, BenchError> {
+    use crate::config::poseidon_config;
+    use crate::solidity::NovaBn;
+    use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as G1};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+    use folding_schemes::{
+        commitment::{kzg::KZG, pedersen::Pedersen},
+        folding::nova::{Nova, PreprocessorParam},
+    };
+
+    type CS1 = MockCommitment<KZG<'static, Bn254>>;
+    type CS2 = MockCommitment<Pedersen<G2>>;
+    type Mocked<FC> = Nova<G1, GVar, G2, GVar2, FC, CS1, CS2, false>;
+
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+    let with_commitments = mean_fold_step::<G1, G2, FC, NovaBn<FC>>(
+        label,
+        &prep_param,
+        f_circuit.clone(),
+        z_0.clone(),
+        n_steps,
+    )?;
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+    let without_commitments = mean_fold_step::<G1, G2, FC, Mocked<FC>>(
+        &format!("{} mock", label),
+        &prep_param,
+        f_circuit,
+        z_0,
+        n_steps,
+    )?;
+    let share = CommitmentShare {
+        with_commitments,
+        without_commitments,
+    };
+    println!(
+        "{} Mean step: {:?} with commitments, {:?} without; commitments take {:.1}%",
+        label,
+        with_commitments,
+        without_commitments,
+        100.0 * share.share()
+    );
+    Ok(share)
+}
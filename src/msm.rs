@@ -0,0 +1,215 @@
+//! MSM cost attribution.
+//!
+//! `MsmCounter<CS>` wraps a commitment scheme and logs the size and time of
+//! every commitment and opening it computes, which is where sonobe performs
+//! its MSMs. `msm_attribution` then folds and proves with such schemes and
+//! reports how much of each phase went into MSMs, the share a GPU backend
+//! could take off.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_std::rand::RngCore;
+use std::any::type_name;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use folding_schemes::{
+    commitment::CommitmentScheme, frontend::FCircuit, transcript::Transcript, Decider, Error,
+    FoldingScheme,
+};
+
+use crate::bench::DeciderVerifierInputs;
+use crate::config::rng;
+use crate::error::BenchError;
+
+/// Curve, size and time of every logged MSM.
+static MSM_LOG: Mutex<Vec<(&'static str, usize, Duration)>> = Mutex::new(Vec::new());
+
+fn record<C, T>(size: usize, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    MSM_LOG
+        .lock()
+        .unwrap()
+        .push((type_name::<C>(), size, start.elapsed()));
+    result
+}
+
+/// Aggregate of the MSMs logged since the last `take_msm_stats`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MsmStats {
+    pub count: usize,
+    pub total_size: usize,
+    pub max_size: usize,
+    pub time: Duration,
+}
+
+impl MsmStats {
+    fn add(self, size: usize, time: Duration) -> Self {
+        Self {
+            count: self.count + 1,
+            total_size: self.total_size + size,
+            max_size: self.max_size.max(size),
+            time: self.time + time,
+        }
+    }
+}
+
+/// Drains the MSM log.
+pub fn take_msm_stats() -> MsmStats {
+    MSM_LOG
+        .lock()
+        .unwrap()
+        .drain(..)
+        .fold(MsmStats::default(), |acc, (_, size, time)| {
+            acc.add(size, time)
+        })
+}
+
+/// Drains the MSM log, split into the MSMs over `C` and the others.
+pub fn take_msm_stats_over<C: CurveGroup>() -> (MsmStats, MsmStats) {
+    MSM_LOG.lock().unwrap().drain(..).fold(
+        (MsmStats::default(), MsmStats::default()),
+        |(over, others), (curve, size, time)| {
+            if curve == type_name::<C>() {
+                (over.add(size, time), others)
+            } else {
+                (over, others.add(size, time))
+            }
+        },
+    )
+}
+
+/// `CS` with every commitment and opening logged, see the module docs.
+#[derive(Clone, Debug)]
+pub struct MsmCounter<CS>(PhantomData<CS>);
+
+impl<C: CurveGroup, CS: CommitmentScheme<C, H>, const H: bool> CommitmentScheme<C, H>
+    for MsmCounter<CS>
+{
+    type ProverParams = CS::ProverParams;
+    type VerifierParams = CS::VerifierParams;
+    type Proof = CS::Proof;
+    type ProverChallenge = CS::ProverChallenge;
+    type Challenge = CS::Challenge;
+
+    fn setup(
+        rng: impl RngCore,
+        len: usize,
+    ) -> Result<(Self::ProverParams, Self::VerifierParams), Error> {
+        CS::setup(rng, len)
+    }
+
+    fn commit(
+        params: &Self::ProverParams,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+    ) -> Result<C, Error> {
+        record::<C, _>(v.len(), || CS::commit(params, v, blind))
+    }
+
+    fn prove(
+        params: &Self::ProverParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cm: &C,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        record::<C, _>(v.len(), || CS::prove(params, transcript, cm, v, blind, rng))
+    }
+
+    fn prove_with_challenge(
+        params: &Self::ProverParams,
+        challenge: Self::ProverChallenge,
+        v: &[C::ScalarField],
+        blind: &C::ScalarField,
+        rng: Option<&mut dyn RngCore>,
+    ) -> Result<Self::Proof, Error> {
+        record::<C, _>(v.len(), || {
+            CS::prove_with_challenge(params, challenge, v, blind, rng)
+        })
+    }
+
+    fn verify(
+        params: &Self::VerifierParams,
+        transcript: &mut impl Transcript<C::ScalarField>,
+        cm: &C,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        CS::verify(params, transcript, cm, proof)
+    }
+
+    fn verify_with_challenge(
+        params: &Self::VerifierParams,
+        challenge: Self::Challenge,
+        cm: &C,
+        proof: &Self::Proof,
+    ) -> Result<(), Error> {
+        CS::verify_with_challenge(params, challenge, cm, proof)
+    }
+}
+
+fn print_share(label: &str, phase: &str, stats: MsmStats, total: Duration) {
+    println!(
+        "{} {}: {} MSMs (max size {}, total size {}), {:?} of {:?} ({:.1}%)",
+        label,
+        phase,
+        stats.count,
+        stats.max_size,
+        stats.total_size,
+        stats.time,
+        total,
+        100.0 * stats.time.as_secs_f64() / total.as_secs_f64()
+    );
+}
+
+/// Folds `n_steps` and proves, with `FS` and `D` built over `MsmCounter`
+/// commitment schemes, printing the MSM share of every step and of the
+/// decider proof. Returns the MSM share of the total prover time.
+pub fn msm_attribution<C1, C2, FC, FS, D>(
+    label: &str,
+    params: (FS::ProverParam, FS::VerifierParam, D::ProverParam, D::VerifierParam),
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    mut external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<f64, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS>,
+{
+    let (fs_pp, fs_vp, decider_pp, _) = params;
+    let mut fs = FS::init(&(fs_pp, fs_vp), f_circuit, z_0)?;
+    take_msm_stats();
+
+    let mut prover_time = Duration::ZERO;
+    let mut msm_time = Duration::ZERO;
+    for i in 0..n_steps {
+        let inputs = external_inputs(i);
+        let start = Instant::now();
+        fs.prove_step(rng(), inputs, None)?;
+        let elapsed = start.elapsed();
+        let stats = take_msm_stats();
+        print_share(label, &format!("prove_step {}", i), stats, elapsed);
+        prover_time += elapsed;
+        msm_time += stats.time;
+    }
+
+    let start = Instant::now();
+    D::prove(rng(), decider_pp, fs)?;
+    let elapsed = start.elapsed();
+    let stats = take_msm_stats();
+    print_share(label, "Decider prove", stats, elapsed);
+    prover_time += elapsed;
+    msm_time += stats.time;
+
+    let share = msm_time.as_secs_f64() / prover_time.as_secs_f64();
+    println!("{} MSM share of prover time: {:.1}%", label, 100.0 * share);
+    Ok(share)
+}
@@ -0,0 +1,185 @@
+//! Cost of selecting among several step circuits, the case non-uniform IVC
+//! (SuperNova-style) folding is designed for.
+//!
+//! sonobe's Nova only folds a single step circuit, so a program made of
+//! several circuits is folded through the selector circuit of
+//! `circuits::branches`, which synthesizes every branch at every step.
+//! `compare_nivc_bn254` folds it with Nova on BN254/Grumpkin next to
+//!
+//! - a universal circuit of the same size without any selection, the padding
+//!   circuit, to isolate the cost of the selection logic;
+//! - the padding circuit at the size of every branch, whose mean over the
+//!   round-robin schedule is what a non-uniform scheme paying only for the
+//!   selected circuit would approach, ignoring its extra running instances.
+use ark_bn254::{Fr, G1Projective as G1};
+use ark_grumpkin::Projective as G2;
+use std::time::Duration;
+
+use folding_schemes::{folding::nova::PreprocessorParam, frontend::FCircuit, FoldingScheme};
+
+use crate::bench::{fold_steps, no_external_inputs, CircuitSize};
+use crate::circuits::{branches::BranchesFCircuit, padding::PaddingFCircuit};
+use crate::config::{poseidon_config, rng};
+use crate::error::BenchError;
+use crate::overhead::step_constraints;
+use crate::solidity::NovaBn;
+
+/// Folding of one step circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepCost {
+    pub circuit: String,
+    /// The step circuit alone.
+    pub step_constraints: usize,
+    pub augmented_constraints: usize,
+    pub mean_step: Duration,
+}
+
+#[derive(Clone, Debug)]
+pub struct NivcComparison {
+    /// Every branch behind the selector.
+    pub selection: StepCost,
+    /// A circuit of the same size without selection.
+    pub universal: StepCost,
+    /// A circuit of the size of every branch, folded alone.
+    pub branches: Vec<StepCost>,
+}
+
+impl NivcComparison {
+    /// Step time of the selection over that of the universal circuit.
+    pub fn selection_overhead(&self) -> f64 {
+        ratio(self.selection.mean_step, self.universal.mean_step)
+    }
+
+    /// Mean step time of the branches folded alone, in round-robin order.
+    pub fn branch_mean_step(&self) -> Duration {
+        self.branches.iter().map(|b| b.mean_step).sum::<Duration>()
+            / self.branches.len().max(1) as u32
+    }
+
+    /// Step time of the selection over that of folding only the selected
+    /// branch at every step.
+    pub fn non_uniform_speedup(&self) -> f64 {
+        ratio(self.selection.mean_step, self.branch_mean_step())
+    }
+
+    pub fn print(&self, label: &str) {
+        println!(
+            "{} {:<16} {:>10} {:>10} {:>14}",
+            label, "circuit", "step", "augmented", "mean step"
+        );
+        for cost in [&self.selection, &self.universal]
+            .into_iter()
+            .chain(&self.branches)
+        {
+            println!(
+                "{} {:<16} {:>10} {:>10} {:>14.2?}",
+                label,
+                cost.circuit,
+                cost.step_constraints,
+                cost.augmented_constraints,
+                cost.mean_step
+            );
+        }
+        println!(
+            "{} Selection vs universal circuit: {:.2}x; vs the selected branch only: {:.2}x",
+            label,
+            self.selection_overhead(),
+            self.non_uniform_speedup()
+        );
+    }
+}
+
+fn ratio(a: Duration, b: Duration) -> f64 {
+    a.as_secs_f64() / b.as_secs_f64().max(f64::MIN_POSITIVE)
+}
+
+/// Folds `f_circuit` with Nova on BN254 for `n_steps` from `z_0`.
+fn step_cost<FC: FCircuit<Fr>>(
+    label: &str,
+    circuit: &str,
+    f_circuit: FC,
+    z_0: Vec<Fr>,
+    n_steps: usize,
+) -> Result<StepCost, BenchError> {
+    let step = step_constraints(&f_circuit)?;
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+    let fs_params = NovaBn::<FC>::preprocess(rng(), &prep_param)?;
+    let mut fs = NovaBn::<FC>::init(&fs_params, f_circuit, z_0)?;
+    let label = format!("{} {}", label, circuit);
+    let steps = fold_steps::<G1, G2, FC, _>(&label, &mut fs, n_steps, no_external_inputs)?;
+    Ok(StepCost {
+        circuit: circuit.to_string(),
+        step_constraints: step,
+        augmented_constraints: fs.augmented_constraints(),
+        mean_step: steps.iter().sum::<Duration>() / n_steps.max(1) as u32,
+    })
+}
+
+/// Folds the selector over branches of `branches` constraints, the
+/// universal circuit of its size and every branch alone, `n_steps` each.
+pub fn compare_nivc_bn254(
+    label: &str,
+    branches: Vec<usize>,
+    n_steps: usize,
+) -> Result<NivcComparison, BenchError> {
+    let three = Fr::from(3_u32);
+    let f_circuit = BranchesFCircuit::<Fr>::new(branches.clone())?;
+    let size = step_constraints(&f_circuit)?;
+    let selection = step_cost(
+        label,
+        "selection",
+        f_circuit,
+        vec![three, Fr::from(0_u32)],
+        n_steps,
+    )?;
+    let universal = step_cost(
+        label,
+        "universal",
+        PaddingFCircuit::<Fr>::new(size)?,
+        vec![three],
+        n_steps,
+    )?;
+    let branches = branches
+        .iter()
+        .enumerate()
+        .map(|(j, n)| {
+            step_cost(
+                label,
+                &format!("branch {}", j),
+                PaddingFCircuit::<Fr>::new(*n)?,
+                vec![three],
+                n_steps,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let comparison = NivcComparison {
+        selection,
+        universal,
+        branches,
+    };
+    comparison.print(label);
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nivc_ratios() {
+        let cost = |circuit: &str, ms| StepCost {
+            circuit: circuit.to_string(),
+            step_constraints: 0,
+            augmented_constraints: 0,
+            mean_step: Duration::from_millis(ms),
+        };
+        let comparison = NivcComparison {
+            selection: cost("selection", 120),
+            universal: cost("universal", 100),
+            branches: vec![cost("branch 0", 20), cost("branch 1", 40)],
+        };
+        assert!((comparison.selection_overhead() - 1.2).abs() < 1e-9);
+        assert_eq!(comparison.branch_mean_step(), Duration::from_millis(30));
+        assert!((comparison.non_uniform_speedup() - 4.0).abs() < 1e-9);
+    }
+}
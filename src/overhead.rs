@@ -0,0 +1,180 @@
+//! Recursion overhead of the folding schemes, in constraints.
+//!
+//! Every scheme wraps the same step circuit into its own augmented circuit,
+//! whose size over the step circuit's is what the scheme's recursion costs at
+//! every step, independently of the machine. `compare_overhead_bn254` reports
+//! it side by side for Nova, HyperNova and ProtoGalaxy on BN254/Grumpkin.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::AllocVar, fields::fp::FpVar, groups::GroupOpsBounds, prelude::CurveVar,
+    ToConstraintFieldGadget,
+};
+use ark_relations::r1cs::{ConstraintSystem, SynthesisError, SynthesisMode};
+
+use folding_schemes::{
+    commitment::CommitmentScheme,
+    folding::{circuits::CF2, protogalaxy::ProtoGalaxy},
+    frontend::FCircuit,
+    FoldingScheme,
+};
+
+use crate::bench::CircuitSize;
+use crate::config::rng;
+use crate::error::BenchError;
+
+impl<C1, GC1, C2, GC2, FC, CS1, CS2> CircuitSize for ProtoGalaxy<C1, GC1, C2, GC2, FC, CS1, CS2>
+where
+    C1: CurveGroup,
+    GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+    C2: CurveGroup,
+    GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+    FC: FCircuit<C1::ScalarField>,
+    CS1: CommitmentScheme<C1>,
+    CS2: CommitmentScheme<C2>,
+    for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+    for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+{
+    fn augmented_constraints(&self) -> usize {
+        self.r1cs.A.n_rows
+    }
+    fn cyclefold_constraints(&self) -> usize {
+        self.cf_r1cs.A.n_rows
+    }
+}
+
+/// Constraints of one scheme's circuits for a step circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemeOverhead {
+    pub scheme: String,
+    /// The step circuit alone.
+    pub step: usize,
+    /// The augmented circuit, step circuit included.
+    pub augmented: usize,
+    /// The CycleFold circuit, on the secondary curve.
+    pub cyclefold: usize,
+}
+
+impl SchemeOverhead {
+    /// Constraints the augmented circuit adds to the step circuit.
+    pub fn overhead(&self) -> usize {
+        self.augmented.saturating_sub(self.step)
+    }
+}
+
+/// Constraints of `f_circuit` synthesized on its own. Only the shape is
+/// built, so circuits whose witness would fail on dummy inputs count too.
+pub fn step_constraints<F: PrimeField, FC: FCircuit<F>>(
+    f_circuit: &FC,
+) -> Result<usize, BenchError> {
+    let synthesize = || {
+        let cs = ConstraintSystem::<F>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let z_i = Vec::<FpVar<F>>::new_witness(cs.clone(), || {
+            Ok(vec![F::zero(); f_circuit.state_len()])
+        })?;
+        let external_inputs = Vec::<FpVar<F>>::new_witness(cs.clone(), || {
+            Ok(vec![F::zero(); f_circuit.external_inputs_len()])
+        })?;
+        f_circuit.generate_step_constraints(cs.clone(), 0, z_i, external_inputs)?;
+        Ok::<_, SynthesisError>(cs.num_constraints())
+    };
+    Ok(synthesize().map_err(folding_schemes::Error::from)?)
+}
+
+/// Preprocesses `FS` for `f_circuit` and reads the sizes of its circuits.
+pub fn measure_overhead<C1, C2, FC, FS>(
+    scheme: &str,
+    prep_param: &FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+) -> Result<SchemeOverhead, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC> + CircuitSize,
+{
+    let step = step_constraints(&f_circuit)?;
+    let fs_params = FS::preprocess(rng(), prep_param)?;
+    let fs = FS::init(&fs_params, f_circuit, z_0)?;
+    Ok(SchemeOverhead {
+        scheme: scheme.to_string(),
+        step,
+        augmented: fs.augmented_constraints(),
+        cyclefold: fs.cyclefold_constraints(),
+    })
+}
+
+/// Prints `overheads` as a table, relative to the first scheme.
+pub fn print_overheads(label: &str, overheads: &[SchemeOverhead]) {
+    let Some(base) = overheads.first() else {
+        return;
+    };
+    println!(
+        "{} {:<12} {:>10} {:>10} {:>10} {:>10} {:>8}",
+        label, "scheme", "step", "augmented", "overhead", "cyclefold", "vs first"
+    );
+    for o in overheads {
+        println!(
+            "{} {:<12} {:>10} {:>10} {:>10} {:>10} {:>7.2}x",
+            label,
+            o.scheme,
+            o.step,
+            o.augmented,
+            o.overhead(),
+            o.cyclefold,
+            o.overhead() as f64 / base.overhead().max(1) as f64
+        );
+    }
+}
+
+/// Augmented circuits of Nova, HyperNova (`MU = NU = 1`) and ProtoGalaxy on
+/// BN254/Grumpkin for `f_circuit`, printed and returned in that order.
+#[cfg(feature = "bn254")]
+pub fn compare_overhead_bn254<FC: FCircuit<ark_bn254::Fr>>(
+    label: &str,
+    f_circuit: FC,
+    z_0: Vec<ark_bn254::Fr>,
+) -> Result<Vec<SchemeOverhead>, BenchError> {
+    use crate::config::poseidon_config;
+    use crate::solidity::NovaBn;
+    use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as G1};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+    use folding_schemes::{
+        commitment::{kzg::KZG, pedersen::Pedersen},
+        folding::{
+            hypernova::{self, HyperNova},
+            nova,
+        },
+    };
+
+    type HN<FC> =
+        HyperNova<G1, GVar, G2, GVar2, FC, KZG<'static, Bn254>, Pedersen<G2>, 1, 1, false>;
+    type PG<FC> = ProtoGalaxy<G1, GVar, G2, GVar2, FC, KZG<'static, Bn254>, Pedersen<G2>>;
+
+    let poseidon = poseidon_config::<Fr>()?;
+    let overheads = vec![
+        measure_overhead::<G1, G2, _, NovaBn<FC>>(
+            "Nova",
+            &nova::PreprocessorParam::new(poseidon.clone(), f_circuit.clone()),
+            f_circuit.clone(),
+            z_0.clone(),
+        )?,
+        measure_overhead::<G1, G2, _, HN<FC>>(
+            "HyperNova",
+            &hypernova::PreprocessorParam::new(poseidon.clone(), f_circuit.clone()),
+            f_circuit.clone(),
+            z_0.clone(),
+        )?,
+        measure_overhead::<G1, G2, _, PG<FC>>(
+            "ProtoGalaxy",
+            &(poseidon, f_circuit.clone()),
+            f_circuit,
+            z_0,
+        )?,
+    ];
+    print_overheads(label, &overheads);
+    Ok(overheads)
+}
@@ -0,0 +1,81 @@
+//! Hardware performance counters per phase, through `perf_event_open`,
+//! enabled by the `perf` feature (Linux only).
+//!
+//! Counters are opened for this process with `inherit`, so they also count
+//! threads spawned while they are enabled, but not threads that already
+//! existed (e.g. a warm rayon pool); set `RAYON_NUM_THREADS=1` for exact
+//! attribution. Opening them may need `kernel.perf_event_paranoid <= 2`.
+//! Like `energy`, `PerfSample::start` returns `None` when unavailable.
+#[cfg(all(feature = "perf", target_os = "linux"))]
+use perf_event::{events::Hardware, Builder, Counter};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PerfCounts {
+    pub instructions: u64,
+    pub cycles: u64,
+    pub cache_misses: u64,
+    pub branch_misses: u64,
+}
+
+pub struct PerfSample {
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    counters: [Counter; 4],
+}
+
+impl PerfSample {
+    pub fn start() -> Option<Self> {
+        #[cfg(all(feature = "perf", target_os = "linux"))]
+        {
+            let open = |kind: Hardware| -> Option<Counter> {
+                let mut counter = Builder::new().kind(kind).inherit(true).build().ok()?;
+                counter.enable().ok()?;
+                Some(counter)
+            };
+            Some(Self {
+                counters: [
+                    open(Hardware::INSTRUCTIONS)?,
+                    open(Hardware::CPU_CYCLES)?,
+                    open(Hardware::CACHE_MISSES)?,
+                    open(Hardware::BRANCH_MISSES)?,
+                ],
+            })
+        }
+        #[cfg(not(all(feature = "perf", target_os = "linux")))]
+        None
+    }
+
+    pub fn stop(self) -> PerfCounts {
+        #[cfg(all(feature = "perf", target_os = "linux"))]
+        {
+            let [instructions, cycles, cache_misses, branch_misses] =
+                self.counters.map(|mut c| {
+                    c.disable().unwrap();
+                    c.read().unwrap()
+                });
+            PerfCounts {
+                instructions,
+                cycles,
+                cache_misses,
+                branch_misses,
+            }
+        }
+        #[cfg(not(all(feature = "perf", target_os = "linux")))]
+        PerfCounts::default()
+    }
+}
+
+/// Prints the counters of `sample` for `phase`, if measured, and returns them.
+pub fn report_perf(label: &str, phase: &str, sample: Option<PerfSample>) -> Option<PerfCounts> {
+    let counts = sample?.stop();
+    println!(
+        "{} {} perf: {} instructions, {} cycles (IPC {:.2}), {} cache misses, {} branch misses",
+        label,
+        phase,
+        counts.instructions,
+        counts.cycles,
+        counts.instructions as f64 / counts.cycles as f64,
+        counts.cache_misses,
+        counts.branch_misses
+    );
+    Some(counts)
+}
@@ -0,0 +1,181 @@
+//! Microbenchmarks of the primitives folding and deciding are built on, per
+//! curve, so that differences between cycles (e.g. BN254 against MNT) can be
+//! traced to the speed of their MSMs, FFTs and Poseidon permutations.
+//!
+//! Both curves of every cycle are measured, as the CycleFold circuit commits
+//! on the secondary curve: MSMs over its group, FFTs over its scalar field
+//! (skipped where its two-adicity is too small for the domain) and Poseidon
+//! permutations of the transcript parameters over its scalar field. With
+//! `BenchConfig::primitives` the suite runs them for the cycles of its
+//! entries and reports them next to the benchmarks, see
+//! `SuiteReport::primitives`.
+use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{PrimeField, UniformRand};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::config::{rng, PoseidonParams};
+
+/// Log2 of the MSM and FFT sizes measured.
+pub const LOG_SIZES: [u32; 4] = [10, 12, 14, 16];
+/// Runs every MSM and FFT time is averaged over.
+pub const REPEATS: u32 = 3;
+/// Permutations the Poseidon time is averaged over.
+pub const PERMUTATIONS: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Msm,
+    Fft,
+    Poseidon,
+}
+
+impl fmt::Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Primitive::Msm => "MSM",
+            Primitive::Fft => "FFT",
+            Primitive::Poseidon => "Poseidon",
+        })
+    }
+}
+
+/// Mean time of one primitive on one curve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrimitiveTime {
+    /// The curve, e.g. `bn254` or `grumpkin`.
+    pub curve: String,
+    pub primitive: Primitive,
+    /// Points of the MSM, evaluations of the FFT, or the state width of the
+    /// Poseidon permutation.
+    pub size: usize,
+    pub time: Duration,
+}
+
+/// MSMs and FFTs of every size of `LOG_SIZES` and Poseidon permutations with
+/// `poseidon` on the curve `C`, named `curve`.
+pub fn bench_primitives<C: CurveGroup>(
+    curve: &str,
+    poseidon: PoseidonParams,
+) -> Vec<PrimitiveTime> {
+    let mut rng = rng();
+    let max = 1 << LOG_SIZES[LOG_SIZES.len() - 1];
+    let scalars: Vec<C::ScalarField> = (0..max).map(|_| C::ScalarField::rand(&mut rng)).collect();
+    // consecutive multiples of a random point: MSMs do not depend on how the
+    // bases relate, and these are much faster to draw than random points
+    let g = C::rand(&mut rng);
+    let bases = C::normalize_batch(
+        &std::iter::successors(Some(g), |p| Some(*p + g))
+            .take(max)
+            .collect::<Vec<_>>(),
+    );
+    let time = |primitive, size, time| PrimitiveTime {
+        curve: curve.to_string(),
+        primitive,
+        size,
+        time,
+    };
+
+    let mut times = Vec::new();
+    for size in LOG_SIZES.map(|log| 1_usize << log) {
+        let start = Instant::now();
+        for _ in 0..REPEATS {
+            black_box(C::msm_unchecked(&bases[..size], &scalars[..size]));
+        }
+        times.push(time(Primitive::Msm, size, start.elapsed() / REPEATS));
+    }
+    for size in LOG_SIZES.map(|log| 1_usize << log) {
+        let Some(domain) = GeneralEvaluationDomain::<C::ScalarField>::new(size) else {
+            continue;
+        };
+        let start = Instant::now();
+        for _ in 0..REPEATS {
+            black_box(domain.fft(&scalars[..size]));
+        }
+        times.push(time(Primitive::Fft, size, start.elapsed() / REPEATS));
+    }
+    times.push(bench_poseidon::<C::ScalarField>(curve, poseidon));
+    times
+}
+
+/// `PERMUTATIONS` permutations with `poseidon`: absorbing a full rate of
+/// elements and squeezing one permutes once.
+fn bench_poseidon<F: PrimeField>(curve: &str, poseidon: PoseidonParams) -> PrimitiveTime {
+    let config = poseidon.config::<F>();
+    let mut rng = rng();
+    let input: Vec<F> = (0..config.rate).map(|_| F::rand(&mut rng)).collect();
+    let start = Instant::now();
+    for _ in 0..PERMUTATIONS {
+        let mut sponge = PoseidonSponge::new(&config);
+        sponge.absorb(&input);
+        black_box(sponge.squeeze_field_elements::<F>(1));
+    }
+    PrimitiveTime {
+        curve: curve.to_string(),
+        primitive: Primitive::Poseidon,
+        size: config.rate + config.capacity,
+        time: start.elapsed() / PERMUTATIONS,
+    }
+}
+
+/// `bench_primitives` on both curves of every cycle of `cycles` that is
+/// enabled, given by the curve segment of the registry names (`bn254`,
+/// `mnt`, ...).
+pub fn run_primitives(cycles: &[&str], poseidon: PoseidonParams) -> Vec<PrimitiveTime> {
+    #[allow(unused_mut)]
+    let mut times = Vec::new();
+    for cycle in cycles {
+        match *cycle {
+            #[cfg(feature = "bn254")]
+            "bn254" => {
+                times.extend(bench_primitives::<ark_bn254::G1Projective>(
+                    "bn254", poseidon,
+                ));
+                times.extend(bench_primitives::<ark_grumpkin::Projective>(
+                    "grumpkin", poseidon,
+                ));
+            }
+            #[cfg(feature = "mnt")]
+            "mnt" => {
+                times.extend(bench_primitives::<ark_mnt4_298::G1Projective>(
+                    "mnt4", poseidon,
+                ));
+                times.extend(bench_primitives::<ark_mnt6_298::G1Projective>(
+                    "mnt6", poseidon,
+                ));
+            }
+            #[cfg(feature = "mnt753")]
+            "mnt753" => {
+                times.extend(bench_primitives::<ark_mnt4_753::G1Projective>(
+                    "mnt4-753", poseidon,
+                ));
+                times.extend(bench_primitives::<ark_mnt6_753::G1Projective>(
+                    "mnt6-753", poseidon,
+                ));
+            }
+            #[cfg(feature = "pasta")]
+            "pasta" => {
+                times.extend(bench_primitives::<ark_pallas::Projective>(
+                    "pallas", poseidon,
+                ));
+                times.extend(bench_primitives::<ark_vesta::Projective>("vesta", poseidon));
+            }
+            #[cfg(feature = "secp")]
+            "secp" => {
+                times.extend(bench_primitives::<ark_secp256k1::Projective>(
+                    "secp256k1",
+                    poseidon,
+                ));
+                times.extend(bench_primitives::<ark_secq256k1::Projective>(
+                    "secq256k1",
+                    poseidon,
+                ));
+            }
+            _ => {}
+        }
+    }
+    times
+}
@@ -0,0 +1,293 @@
+//! Head-to-head runs against microsoft/Nova (`nova-snark`), the reference
+//! Nova implementation, with the `nova-snark` feature.
+//!
+//! The cubic and padding step circuits are written again against
+//! `bellpepper` and folded by both stacks on BN254/Grumpkin with KZG
+//! commitments on the primary curve, from the same initial state; the final
+//! states are checked to match so that both did the same work. Both stacks
+//! fold without a decider: the table compares preprocessing (`setup` for
+//! `nova-snark`), the mean step and the IVC verification. The Poseidon
+//! chains are left out, the two stacks using different Poseidon parameters.
+//!
+//! `nova-snark` applies the first step when its `RecursiveSNARK` is created,
+//! so its first step time includes the creation.
+use ark_bn254::{Fr, G1Projective as G1};
+use ark_ff::{BigInteger, PrimeField};
+use ark_grumpkin::Projective as G2;
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::{Field, PrimeField as NovaPrimeField};
+use nova_snark::{
+    provider::{Bn256EngineKZG, GrumpkinEngine},
+    traits::{
+        circuit::{StepCircuit, TrivialCircuit},
+        snark::default_ck_hint,
+        Engine,
+    },
+    PublicParams, RecursiveSNARK,
+};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use folding_schemes::{folding::nova::PreprocessorParam, frontend::FCircuit, FoldingScheme};
+
+use crate::bench::{fold_steps, no_external_inputs, CircuitSize, IvcState};
+use crate::circuits::padding::PaddingFCircuit;
+use crate::clock::Stopwatch;
+use crate::config::{poseidon_config, rng};
+use crate::error::BenchError;
+use crate::solidity::NovaBn;
+use crate::CubicFCircuit;
+
+type E1 = Bn256EngineKZG;
+type E2 = GrumpkinEngine;
+
+/// `CubicFCircuit` for `nova-snark`: `y = x^3 + x + 5`, one constraint more
+/// than sonobe's since the output must be allocated.
+#[derive(Clone, Debug, Default)]
+pub struct CubicStepCircuit<F> {
+    _f: PhantomData<F>,
+}
+
+impl<F: NovaPrimeField> StepCircuit<F> for CubicStepCircuit<F> {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let x = &z[0];
+        let x_sq = x.square(cs.namespace(|| "x_sq"))?;
+        let x_cu = x_sq.mul(cs.namespace(|| "x_cu"), x)?;
+        let y = AllocatedNum::alloc(cs.namespace(|| "y"), || {
+            let x = x.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            let x_cu = x_cu.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(x_cu + x + F::from(5))
+        })?;
+        cs.enforce(
+            || "y = x^3 + x + 5",
+            |lc| lc + x_cu.get_variable() + x.get_variable() + (F::from(5), CS::one()),
+            |lc| lc + CS::one(),
+            |lc| lc + y.get_variable(),
+        );
+        Ok(vec![y])
+    }
+}
+
+/// `PaddingFCircuit` for `nova-snark`: `t = t^2 + z`, `n_constraints` times,
+/// one constraint each as in sonobe.
+#[derive(Clone, Debug)]
+pub struct PaddingStepCircuit<F> {
+    pub n_constraints: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F> PaddingStepCircuit<F> {
+    pub fn new(n_constraints: usize) -> Self {
+        Self {
+            n_constraints,
+            _f: PhantomData,
+        }
+    }
+}
+
+impl<F: NovaPrimeField> StepCircuit<F> for PaddingStepCircuit<F> {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        z: &[AllocatedNum<F>],
+    ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+        let z = &z[0];
+        let mut t = z.clone();
+        for i in 0..self.n_constraints {
+            let next = AllocatedNum::alloc(cs.namespace(|| format!("t_{}", i)), || {
+                let t = t.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+                let z = z.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+                Ok(t.square() + z)
+            })?;
+            cs.enforce(
+                || format!("t_{} = t^2 + z", i),
+                |lc| lc + t.get_variable(),
+                |lc| lc + t.get_variable(),
+                |lc| lc + next.get_variable() - z.get_variable(),
+            );
+            t = next;
+        }
+        Ok(vec![t])
+    }
+}
+
+/// One stack folding one circuit.
+#[derive(Clone, Debug)]
+pub struct StackRun {
+    pub stack: &'static str,
+    /// Constraints of the augmented circuit on the primary curve.
+    pub constraints: usize,
+    pub preprocess: Duration,
+    pub steps: Vec<Duration>,
+    pub verify: Duration,
+}
+
+impl StackRun {
+    pub fn mean_step(&self) -> Duration {
+        self.steps.iter().sum::<Duration>() / self.steps.len().max(1) as u32
+    }
+}
+
+fn run_sonobe<FC: FCircuit<Fr>>(
+    label: &str,
+    f_circuit: FC,
+    z_0: u64,
+    n_steps: usize,
+) -> Result<(StackRun, Fr), BenchError> {
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+    let start = Stopwatch::start();
+    let fs_params = NovaBn::<FC>::preprocess(rng(), &prep_param)?;
+    let preprocess = start.elapsed();
+    let mut fs = NovaBn::<FC>::init(&fs_params, f_circuit, vec![Fr::from(z_0)])?;
+    let steps = fold_steps::<G1, G2, FC, _>(label, &mut fs, n_steps, no_external_inputs)?;
+
+    let (running, incoming, cyclefold) = fs.instances();
+    let start = Stopwatch::start();
+    NovaBn::<FC>::verify(
+        fs_params.1.clone(),
+        fs.z_0(),
+        fs.z_i(),
+        fs.num_steps(),
+        running,
+        incoming,
+        cyclefold,
+    )?;
+    let verify = start.elapsed();
+    let run = StackRun {
+        stack: "sonobe",
+        constraints: fs.augmented_constraints(),
+        preprocess,
+        steps,
+        verify,
+    };
+    Ok((run, fs.z_i()[0]))
+}
+
+fn run_nova_snark<SC: StepCircuit<<E1 as Engine>::Scalar>>(
+    circuit: SC,
+    z_0: u64,
+    n_steps: usize,
+) -> Result<(StackRun, <E1 as Engine>::Scalar), BenchError> {
+    type F1 = <E1 as Engine>::Scalar;
+    type F2 = <E2 as Engine>::Scalar;
+    let secondary = TrivialCircuit::<F2>::default();
+    let start = Stopwatch::start();
+    let pp = PublicParams::<E1, E2, SC, TrivialCircuit<F2>>::setup(
+        &circuit,
+        &secondary,
+        &*default_ck_hint(),
+        &*default_ck_hint(),
+    )?;
+    let preprocess = start.elapsed();
+
+    let (z0, z0_secondary) = (vec![F1::from(z_0)], vec![F2::ZERO]);
+    let start = Stopwatch::start();
+    let mut snark = RecursiveSNARK::new(&pp, &circuit, &secondary, &z0, &z0_secondary)?;
+    let created = start.elapsed();
+    let mut steps = Vec::with_capacity(n_steps);
+    for _ in 0..n_steps {
+        let start = Stopwatch::start();
+        snark.prove_step(&pp, &circuit, &secondary)?;
+        steps.push(start.elapsed());
+    }
+    if let Some(first) = steps.first_mut() {
+        *first += created;
+    }
+
+    let start = Stopwatch::start();
+    let (z_n, _) = snark.verify(&pp, n_steps, &z0, &z0_secondary)?;
+    let verify = start.elapsed();
+    let run = StackRun {
+        stack: "nova-snark",
+        constraints: pp.num_constraints().0,
+        preprocess,
+        steps,
+        verify,
+    };
+    Ok((run, z_n[0]))
+}
+
+/// Folds `f_circuit` with sonobe and `circuit` with `nova-snark` for
+/// `n_steps` from `z_0`, checking that they reach the same state.
+pub fn compare_stacks<FC, SC>(
+    label: &str,
+    f_circuit: FC,
+    circuit: SC,
+    z_0: u64,
+    n_steps: usize,
+) -> Result<[StackRun; 2], BenchError>
+where
+    FC: FCircuit<Fr>,
+    SC: StepCircuit<<E1 as Engine>::Scalar>,
+{
+    println!("\nRunning {} on sonobe and nova-snark", label);
+    let (sonobe, z_sonobe) = run_sonobe(label, f_circuit, z_0, n_steps)?;
+    let (nova, z_nova) = run_nova_snark(circuit, z_0, n_steps)?;
+    // both fields are the BN254 scalar field, little-endian on both sides
+    if z_sonobe.into_bigint().to_bytes_le() != z_nova.to_repr().as_ref() {
+        return Err(BenchError::NativeMismatch(format!(
+            "{}: sonobe reached {}, nova-snark {:?}",
+            label, z_sonobe, z_nova
+        )));
+    }
+    let runs = [sonobe, nova];
+    print_stacks(label, &runs);
+    Ok(runs)
+}
+
+/// Prints `runs` as a table, relative to the first stack.
+pub fn print_stacks(label: &str, runs: &[StackRun]) {
+    let Some(base) = runs.first() else {
+        return;
+    };
+    println!(
+        "{} {:<12} {:>12} {:>14} {:>14} {:>14} {:>8}",
+        label, "stack", "constraints", "preprocess", "mean step", "verify", "vs first"
+    );
+    for run in runs {
+        println!(
+            "{} {:<12} {:>12} {:>14.2?} {:>14.2?} {:>14.2?} {:>7.2}x",
+            label,
+            run.stack,
+            run.constraints,
+            run.preprocess,
+            run.mean_step(),
+            run.verify,
+            run.mean_step().as_secs_f64() / base.mean_step().as_secs_f64().max(f64::MIN_POSITIVE)
+        );
+    }
+}
+
+/// `compare_stacks` for the cubic circuit and the padding circuit of the
+/// registry (`2^12` constraints).
+pub fn compare_reference_bn254(n_steps: usize) -> Result<Vec<[StackRun; 2]>, BenchError> {
+    let padding = 1 << 12;
+    Ok(vec![
+        compare_stacks(
+            "cubic",
+            CubicFCircuit::<Fr>::new(())?,
+            CubicStepCircuit::default(),
+            3,
+            n_steps,
+        )?,
+        compare_stacks(
+            "padding",
+            PaddingFCircuit::<Fr>::new(padding)?,
+            PaddingStepCircuit::new(padding),
+            3,
+            n_steps,
+        )?,
+    ])
+}
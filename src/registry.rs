@@ -0,0 +1,713 @@
+//! Named benchmark configurations, `scheme/curve/circuit`, selectable with
+//! `sonobe-bench --filter`.
+use std::sync::Arc;
+
+use crate::config::BenchConfig;
+use crate::error::BenchError;
+use crate::result::{BenchResult, DeciderKind};
+
+/// Runs one configuration and returns the measurements of every trial.
+pub type BenchFn = dyn Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync;
+
+pub struct BenchEntry {
+    /// `scheme/curve/circuit`, e.g. `nova/bn254/sha256`.
+    pub name: String,
+    pub run: Arc<BenchFn>,
+}
+
+impl BenchEntry {
+    fn new(
+        name: &str,
+        run: impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            run: Arc::new(run),
+        }
+    }
+
+    /// Whether `filter` selects this entry. Filters are `/`-separated like the
+    /// names, `*` matches any segment and a shorter filter matches every name
+    /// it is a prefix of, so `*/bn254` selects all BN254 benchmarks.
+    pub fn matches(&self, filter: &str) -> bool {
+        let mut name = self.name.split('/');
+        filter
+            .split('/')
+            .all(|f| name.next().is_some_and(|n| f == "*" || f == n))
+    }
+}
+
+/// Marks the results that ran a decider as proven by `kind`.
+#[cfg(any(
+    feature = "bn254",
+    feature = "mnt",
+    feature = "mnt753",
+    feature = "bls"
+))]
+fn with_decider(
+    results: Result<Vec<BenchResult>, BenchError>,
+    kind: DeciderKind,
+) -> Result<Vec<BenchResult>, BenchError> {
+    let mut results = results?;
+    for result in &mut results {
+        if !result.decider_prove.is_zero() {
+            result.decider = Some(kind);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(feature = "bn254")]
+fn nova_bn254<FC: folding_schemes::frontend::FCircuit<ark_bn254::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + std::hash::Hash + Send + Sync,
+{
+    use crate::artifacts::save_artifacts;
+    use crate::bench::{run_nova_bench_cached, BenchCase};
+    use crate::cache::ParamCache;
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::{poseidon_config, poseidon_params};
+    use crate::snarkjs::export_snarkjs;
+    use crate::solidity::{DeciderBn, NovaBn};
+    use crate::srs::load_srs;
+    use ark_bn254::{Fr, G1Projective as G1};
+    use ark_grumpkin::Projective as G2;
+    use folding_schemes::folding::nova::PreprocessorParam;
+    use std::time::Instant;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let mut prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        let mut srs_load = None;
+        if let Some(path) = &config.srs {
+            let start = Instant::now();
+            let (pk, vk) = load_srs(&label, path, config.srs_powers)?;
+            srs_load = Some(start.elapsed());
+            prep_param.cs_pp = Some(pk);
+            prep_param.cs_vp = Some(vk);
+        }
+        let case = BenchCase::new(&label, f_circuit, prep_param);
+        let inputs = (
+            &params,
+            poseidon_params()?,
+            config.seed,
+            &config.srs,
+            config.srs_powers,
+        );
+        let key = ParamCache::key(&label, "bn254", "nova", &inputs);
+        let results = run_nova_bench_cached::<G1, G2, _, NovaBn<Timed<FC>>, DeciderBn<Timed<FC>>>(
+            case,
+            &key,
+            config,
+            |nova, decider_vp, proof, result| {
+                let name = result.label.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+                if let Some(dir) = &config.snarkjs_dir {
+                    export_snarkjs(&result.label, dir.join(&name), nova, &decider_vp, &proof)?;
+                }
+                if let Some(dir) = &config.artifacts_dir {
+                    save_artifacts::<G1, G2, Timed<FC>, NovaBn<Timed<FC>>, DeciderBn<Timed<FC>>>(
+                        &result.label,
+                        dir.join(&name),
+                        nova,
+                        &decider_vp,
+                        &proof,
+                    )?;
+                }
+                #[cfg(feature = "evm")]
+                crate::solidity::record_verification_gas(nova, decider_vp, proof, result)?;
+                Ok(())
+            },
+        );
+        let mut results = with_decider(results, DeciderKind::OnChain)?;
+        for result in &mut results {
+            result.srs_load = srs_load;
+        }
+        Ok(results)
+    }
+}
+
+/// HyperNova folding one CCS instance into one running instance per step
+/// (`MU = NU = 1`). There is no decider for it yet, so only folding is timed.
+#[cfg(feature = "bn254")]
+fn hypernova_bn254<FC: folding_schemes::frontend::FCircuit<ark_bn254::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_folding_only_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as G1};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+    use folding_schemes::{
+        commitment::{kzg::KZG, pedersen::Pedersen},
+        folding::hypernova::{HyperNova, PreprocessorParam},
+    };
+
+    type HN<FC> = HyperNova<G1, GVar, G2, GVar2, FC, KZG<'static, Bn254>, Pedersen<G2>, 1, 1, false>;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        run_nova_folding_only_bench::<G1, G2, _, HN<Timed<FC>>>(
+            BenchCase::new(&label, f_circuit, prep_param),
+            config,
+        )
+    }
+}
+
+/// Nova on BN254 with `CS1` as the primary commitment scheme instead of KZG.
+/// Sonobe's deciders open the primary commitments with KZG, so only folding
+/// is timed.
+#[cfg(feature = "bn254")]
+fn nova_bn254_with<CS1, FC>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    CS1: folding_schemes::commitment::CommitmentScheme<ark_bn254::G1Projective, false> + 'static,
+    FC: folding_schemes::frontend::FCircuit<ark_bn254::Fr> + 'static,
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_folding_only_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use ark_bn254::{constraints::GVar, Fr, G1Projective as G1};
+    use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::nova::{Nova, PreprocessorParam},
+    };
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        run_nova_folding_only_bench::<
+            G1,
+            G2,
+            _,
+            Nova<G1, GVar, G2, GVar2, Timed<FC>, CS1, Pedersen<G2>, false>,
+        >(BenchCase::new(&label, f_circuit, prep_param), config)
+    }
+}
+
+#[cfg(feature = "bn254")]
+fn nova_bn254_pedersen<FC: folding_schemes::frontend::FCircuit<ark_bn254::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use folding_schemes::commitment::pedersen::Pedersen;
+    nova_bn254_with::<Pedersen<ark_bn254::G1Projective>, FC>(label, params)
+}
+
+#[cfg(feature = "bn254")]
+fn nova_bn254_ipa<FC: folding_schemes::frontend::FCircuit<ark_bn254::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use folding_schemes::commitment::ipa::IPA;
+    nova_bn254_with::<IPA<ark_bn254::G1Projective>, FC>(label, params)
+}
+
+#[cfg(feature = "mnt")]
+fn nova_mnt<FC: folding_schemes::frontend::FCircuit<ark_mnt4_298::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use crate::{GVar4, GVar6};
+    use ark_groth16::Groth16;
+    use ark_mnt4_298::{Fr, G1Projective as G1, MNT4_298};
+    use ark_mnt6_298::G1Projective as G2;
+    use folding_schemes::{
+        commitment::{kzg::KZG, pedersen::Pedersen},
+        folding::nova::{decider_eth::Decider as DeciderEth, Nova, PreprocessorParam},
+    };
+
+    type N<FC> = Nova<G1, GVar4, G2, GVar6, FC, KZG<'static, MNT4_298>, Pedersen<G2>, false>;
+    type D<FC> = DeciderEth<
+        G1,
+        GVar4,
+        G2,
+        GVar6,
+        FC,
+        KZG<'static, MNT4_298>,
+        Pedersen<G2>,
+        Groth16<MNT4_298>,
+        N<FC>,
+    >;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        let results = run_nova_bench::<G1, G2, _, N<Timed<FC>>, D<Timed<FC>>>(
+            BenchCase::new(&label, f_circuit, prep_param),
+            config,
+        );
+        with_decider(results, DeciderKind::OnChain)
+    }
+}
+
+/// Nova on the MNT4/MNT6-298 cycle with sonobe's off-chain decider, which
+/// opens both commitments with KZG and proves with one Groth16 per curve, to
+/// set against `nova_mnt`'s `DeciderEth`.
+#[cfg(feature = "mnt")]
+fn nova_mnt_offchain<FC: folding_schemes::frontend::FCircuit<ark_mnt4_298::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use crate::{GVar4, GVar6};
+    use ark_groth16::Groth16;
+    use ark_mnt4_298::{Fr, G1Projective as G1, MNT4_298};
+    use ark_mnt6_298::{G1Projective as G2, MNT6_298};
+    use folding_schemes::{
+        commitment::kzg::KZG,
+        folding::nova::{decider::Decider, Nova, PreprocessorParam},
+    };
+
+    type N<FC> = Nova<G1, GVar4, G2, GVar6, FC, KZG<'static, MNT4_298>, KZG<'static, MNT6_298>, false>;
+    type D<FC> = Decider<
+        G1,
+        GVar4,
+        G2,
+        GVar6,
+        FC,
+        KZG<'static, MNT4_298>,
+        KZG<'static, MNT6_298>,
+        Groth16<MNT4_298>,
+        Groth16<MNT6_298>,
+        N<FC>,
+    >;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        let results = run_nova_bench::<G1, G2, _, N<Timed<FC>>, D<Timed<FC>>>(
+            BenchCase::new(&label, f_circuit, prep_param),
+            config,
+        );
+        with_decider(results, DeciderKind::OffChain)
+    }
+}
+
+#[cfg(feature = "mnt753")]
+fn nova_mnt753<FC: folding_schemes::frontend::FCircuit<ark_mnt4_753::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use crate::{GVar4_753, GVar6_753};
+    use ark_groth16::Groth16;
+    use ark_mnt4_753::{Fr, G1Projective as G1, MNT4_753};
+    use ark_mnt6_753::G1Projective as G2;
+    use folding_schemes::{
+        commitment::{kzg::KZG, pedersen::Pedersen},
+        folding::nova::{decider_eth::Decider as DeciderEth, Nova, PreprocessorParam},
+    };
+
+    type N<FC> = Nova<G1, GVar4_753, G2, GVar6_753, FC, KZG<'static, MNT4_753>, Pedersen<G2>, false>;
+    type D<FC> = DeciderEth<
+        G1,
+        GVar4_753,
+        G2,
+        GVar6_753,
+        FC,
+        KZG<'static, MNT4_753>,
+        Pedersen<G2>,
+        Groth16<MNT4_753>,
+        N<FC>,
+    >;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        let results = run_nova_bench::<G1, G2, _, N<Timed<FC>>, D<Timed<FC>>>(
+            BenchCase::new(&label, f_circuit, prep_param),
+            config,
+        );
+        with_decider(results, DeciderKind::OnChain)
+    }
+}
+
+#[cfg(feature = "pasta")]
+fn nova_pasta<FC: folding_schemes::frontend::FCircuit<ark_pallas::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_folding_only_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use ark_pallas::{constraints::GVar as GVar1, Fr, Projective as G1};
+    use ark_vesta::{constraints::GVar as GVar2, Projective as G2};
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::nova::{Nova, PreprocessorParam},
+    };
+
+    type N<FC> = Nova<G1, GVar1, G2, GVar2, FC, Pedersen<G1>, Pedersen<G2>, false>;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        run_nova_folding_only_bench::<G1, G2, _, N<Timed<FC>>>(
+            BenchCase::new(&label, f_circuit, prep_param),
+            config,
+        )
+    }
+}
+
+#[cfg(feature = "secp")]
+fn nova_secp<FC: folding_schemes::frontend::FCircuit<ark_secp256k1::Fr> + 'static>(
+    label: &str,
+    params: FC::Params,
+) -> impl Fn(&BenchConfig) -> Result<Vec<BenchResult>, BenchError> + Send + Sync
+where
+    FC::Params: Clone + Send + Sync,
+{
+    use crate::bench::{run_nova_folding_only_bench, BenchCase};
+    use crate::circuits::timed::TimedFCircuit;
+    use crate::config::poseidon_config;
+    use crate::{GVarSecp, GVarSecq};
+    use ark_secp256k1::{Fr, Projective as G1};
+    use ark_secq256k1::Projective as G2;
+    use folding_schemes::{
+        commitment::pedersen::Pedersen,
+        folding::nova::{Nova, PreprocessorParam},
+    };
+
+    type N<FC> = Nova<G1, GVarSecp, G2, GVarSecq, FC, Pedersen<G1>, Pedersen<G2>, false>;
+
+    type Timed<FC> = TimedFCircuit<Fr, FC>;
+
+    let label = label.to_string();
+    move |config: &BenchConfig| {
+        let f_circuit = Timed::<FC>::new(params.clone())?;
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        run_nova_folding_only_bench::<G1, G2, _, N<Timed<FC>>>(
+            BenchCase::new(&label, f_circuit, prep_param),
+            config,
+        )
+    }
+}
+
+/// Registers every circuit of the matrix under one scheme and curve cycle:
+/// `$bench` is the cycle's `nova_*` or `hypernova_*` function, `$Fr` the
+/// scalar field of its first curve.
+///
+/// Entries fold from `z_0 = (3, ..., 3)` with random external inputs (see
+/// `BenchCase::new`), so the circuits whose steps only hold for consistent
+/// witnesses are left out: `merkle`, `sparse_merkle`, `mpt` and `rollup`
+/// (Merkle paths of the state's root), `schnorr` and `signature_batch`
+/// (signatures), `rlp` (encodings), `bitcoin` (header chains), `aes` (128-bit
+/// keys), `rsa` (exponent bits) and `branches` (a selector below the number of
+/// branches, benchmarked by `--nivc` instead). `closure`, `circom`, `r1cs_file`
+/// and `ed25519` take their circuit or witnesses from the user, see
+/// `r1cs_entry`. `nonnative` and `pedersen` are BN254-only, see
+/// `bn254_circuit_matrix`.
+#[allow(unused_macros)]
+macro_rules! circuit_matrix {
+    ($entries:ident, $scheme:literal, $curve:literal, $label:literal, $bench:ident, $Fr:ty) => {{
+        use crate::circuits::{
+            blake3::Blake3FCircuit,
+            conv::ConvFCircuit,
+            dense::DenseReluFCircuit,
+            external_inputs::ExternalInputsFCircuit,
+            keccak::KeccakFCircuit,
+            matmul::MatMulFCircuit,
+            multi_state::MultiStateFCircuit,
+            padding::PaddingFCircuit,
+            poseidon2::{Poseidon2FCircuit, PoseidonVersion},
+            sha256::Sha256FCircuit,
+            toy_vm::{fibonacci_program, ToyVmFCircuit},
+            uint64::UInt64FCircuit,
+        };
+        use crate::CubicFCircuit;
+
+        $entries.extend([
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/cubic"),
+                $bench::<CubicFCircuit<$Fr>>($label, ()),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/sha256"),
+                $bench::<Sha256FCircuit<$Fr>>(concat!($label, " SHA-256"), 1),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/keccak"),
+                $bench::<KeccakFCircuit<$Fr>>(concat!($label, " Keccak"), 1),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/blake3"),
+                $bench::<Blake3FCircuit<$Fr>>(concat!($label, " Blake3"), 1),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/uint64"),
+                $bench::<UInt64FCircuit<$Fr>>(concat!($label, " UInt64"), 1),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/external_inputs"),
+                $bench::<ExternalInputsFCircuit<$Fr>>(concat!($label, " external inputs"), 4),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/multi_state"),
+                $bench::<MultiStateFCircuit<$Fr>>(concat!($label, " multi-state"), 4),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/padding"),
+                $bench::<PaddingFCircuit<$Fr>>(concat!($label, " padding"), 1 << 12),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/matmul"),
+                $bench::<MatMulFCircuit<$Fr>>(concat!($label, " MatMul"), (3, 4, 8)),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/conv"),
+                $bench::<ConvFCircuit<$Fr>>(concat!($label, " Conv"), (5, 3, 8)),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/dense"),
+                $bench::<DenseReluFCircuit<$Fr>>(concat!($label, " dense ReLU"), (4, 8, 32)),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/toy_vm"),
+                $bench::<ToyVmFCircuit<$Fr>>(concat!($label, " toy VM"), fibonacci_program(10)),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/", $curve, "/poseidon2"),
+                $bench::<Poseidon2FCircuit<$Fr>>(
+                    concat!($label, " Poseidon2"),
+                    (PoseidonVersion::Poseidon2, 2),
+                ),
+            ),
+        ]);
+    }};
+}
+
+/// The circuits of the matrix tied to BN254: `nonnative` emulates its base
+/// field and `pedersen` hashes on the twisted Edwards curve over its scalar
+/// field, ed-on-BN254.
+#[cfg(feature = "bn254")]
+macro_rules! bn254_circuit_matrix {
+    ($entries:ident, $scheme:literal, $label:literal, $bench:ident) => {{
+        use crate::circuits::{nonnative::NonNativeFCircuit, pedersen::PedersenFCircuit};
+        use ark_bn254::{Fq, Fr};
+        use ark_ed_on_bn254::EdwardsConfig;
+
+        $entries.extend([
+            BenchEntry::new(
+                concat!($scheme, "/bn254/nonnative"),
+                $bench::<NonNativeFCircuit<Fr, Fq>>(concat!($label, " non-native"), 3),
+            ),
+            BenchEntry::new(
+                concat!($scheme, "/bn254/pedersen"),
+                $bench::<PedersenFCircuit<EdwardsConfig>>(concat!($label, " Pedersen hash"), 3),
+            ),
+        ]);
+    }};
+}
+
+/// Every configuration available with the enabled features: the circuits of
+/// `circuit_matrix` under each scheme on each enabled curve cycle. On BN254,
+/// `nova` commits with KZG and `nova-pedersen`, `nova-ipa` swap it for the
+/// named scheme, so `*/bn254/<circuit>` compares the three. `nova` on BN254
+/// reuses its params from the params cache, see `run_nova_bench_cached`, and
+/// loads its KZG keys from `BenchConfig::srs` when one is given.
+/// On MNT, `nova-offchain` proves with the off-chain decider instead of
+/// `DeciderEth`. `nova-bw6/bls12-377/cubic` folds the cubic circuit on the
+/// BLS12-377/BW6-761 chain and proves it with the recursive decider of `bw6`.
+pub fn registry() -> Vec<BenchEntry> {
+    #[allow(unused_mut)]
+    let mut entries = Vec::new();
+
+    #[cfg(feature = "bn254")]
+    circuit_matrix!(entries, "nova", "bn254", "BN254", nova_bn254, ark_bn254::Fr);
+    #[cfg(feature = "bn254")]
+    bn254_circuit_matrix!(entries, "nova", "BN254", nova_bn254);
+    #[cfg(feature = "bn254")]
+    circuit_matrix!(
+        entries,
+        "nova-pedersen",
+        "bn254",
+        "BN254 Pedersen",
+        nova_bn254_pedersen,
+        ark_bn254::Fr
+    );
+    #[cfg(feature = "bn254")]
+    bn254_circuit_matrix!(entries, "nova-pedersen", "BN254 Pedersen", nova_bn254_pedersen);
+    #[cfg(feature = "bn254")]
+    circuit_matrix!(entries, "nova-ipa", "bn254", "BN254 IPA", nova_bn254_ipa, ark_bn254::Fr);
+    #[cfg(feature = "bn254")]
+    bn254_circuit_matrix!(entries, "nova-ipa", "BN254 IPA", nova_bn254_ipa);
+    #[cfg(feature = "bn254")]
+    circuit_matrix!(
+        entries,
+        "hypernova",
+        "bn254",
+        "BN254 HyperNova",
+        hypernova_bn254,
+        ark_bn254::Fr
+    );
+    #[cfg(feature = "bn254")]
+    bn254_circuit_matrix!(entries, "hypernova", "BN254 HyperNova", hypernova_bn254);
+    #[cfg(feature = "mnt")]
+    circuit_matrix!(entries, "nova", "mnt", "MNT", nova_mnt, ark_mnt4_298::Fr);
+    #[cfg(feature = "mnt")]
+    circuit_matrix!(
+        entries,
+        "nova-offchain",
+        "mnt",
+        "MNT off-chain",
+        nova_mnt_offchain,
+        ark_mnt4_298::Fr
+    );
+    #[cfg(feature = "mnt753")]
+    circuit_matrix!(entries, "nova", "mnt753", "MNT-753", nova_mnt753, ark_mnt4_753::Fr);
+    #[cfg(feature = "pasta")]
+    circuit_matrix!(entries, "nova", "pasta", "Pasta", nova_pasta, ark_pallas::Fr);
+    #[cfg(feature = "secp")]
+    circuit_matrix!(entries, "nova", "secp", "secp", nova_secp, ark_secp256k1::Fr);
+    #[cfg(feature = "bls")]
+    entries.push(BenchEntry::new(
+        "nova-bw6/bls12-377/cubic",
+        |config: &BenchConfig| {
+            let results = crate::bw6::run_bw6_bench("BLS12-377/BW6-761", config);
+            with_decider(results, DeciderKind::Recursive)
+        },
+    ));
+
+    entries
+}
+
+/// A compiled `.r1cs` circuit folded with Nova on BN254, named
+/// `nova/bn254/r1cs:<file stem>`, with its witnesses computed by running
+/// `witness_cmd` (see `circuits::r1cs_file::command_witness`).
+#[cfg(feature = "bn254")]
+pub fn r1cs_entry(
+    path: &std::path::Path,
+    witness_cmd: &[String],
+    state_len: usize,
+    external_inputs_len: usize,
+) -> Result<BenchEntry, BenchError> {
+    use crate::circuits::r1cs_file::{command_witness, R1csFCircuit, R1csFile, R1csParams};
+
+    let (program, args) = witness_cmd
+        .split_first()
+        .ok_or_else(|| BenchError::InvalidInput("empty witness command".into()))?;
+    let params = R1csParams {
+        r1cs: Arc::new(R1csFile::read(path)?),
+        state_len,
+        external_inputs_len,
+        witness: command_witness(program, args.to_vec()),
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let label = format!("BN254 {}", stem);
+    Ok(BenchEntry::new(
+        &format!("nova/bn254/r1cs:{}", stem),
+        nova_bn254::<R1csFCircuit<ark_bn254::Fr>>(&label, params),
+    ))
+}
+
+/// Entries selected by any of `filters`, or all of them when there is none.
+pub fn select(filters: &[String]) -> Vec<BenchEntry> {
+    registry()
+        .into_iter()
+        .filter(|e| filters.is_empty() || filters.iter().any(|f| e.matches(f)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_matching() {
+        let entry = BenchEntry::new("nova/bn254/sha256", |_| Ok(vec![]));
+        assert!(entry.matches("nova/bn254/sha256"));
+        assert!(entry.matches("nova/bn254"));
+        assert!(entry.matches("nova/*/sha256"));
+        assert!(!entry.matches("nova/mnt"));
+        assert!(!entry.matches("nova/bn254/sha256/extra"));
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn test_commitment_variants() {
+        let names: Vec<String> =
+            select(&["*/bn254/cubic".to_string()]).into_iter().map(|e| e.name).collect();
+        for name in ["nova/bn254/cubic", "nova-pedersen/bn254/cubic", "nova-ipa/bn254/cubic"] {
+            assert!(names.iter().any(|n| n == name), "{} missing from {:?}", name, names);
+        }
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn test_library_circuits() {
+        let names: Vec<String> = select(&["nova/bn254".to_string()])
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        for circuit in [
+            "matmul",
+            "conv",
+            "dense",
+            "toy_vm",
+            "poseidon2",
+            "nonnative",
+            "pedersen",
+        ] {
+            let name = format!("nova/bn254/{}", circuit);
+            assert!(names.contains(&name), "{} missing from {:?}", name, names);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Regression gate of `sonobe-bench check`: compares a run to a baseline run
+//! metric by metric and fails when any grows by more than its threshold.
+//!
+//! Both runs are CSV reports (see `history`). Every metric checked is one
+//! where more is worse; a benchmark that completed in the baseline but not
+//! in the current run is a regression too, one missing from the current run
+//! is only listed.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::history::HistoryRun;
+
+/// Largest relative increase of a CSV report column that still passes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Threshold {
+    pub column: String,
+    /// E.g. 0.1 for 10%.
+    pub max_increase: f64,
+}
+
+/// Columns checked by default and their thresholds. Timings are noisy;
+/// sizes and constraint counts are deterministic.
+pub const DEFAULT_THRESHOLDS: [(&str, f64); 6] = [
+    ("avg_step_ns", 0.10),
+    ("decider_prove_ns", 0.10),
+    ("decider_verify_ns", 0.10),
+    ("preprocess_ns", 0.20),
+    ("proof_size", 0.0),
+    ("augmented_constraints", 0.0),
+];
+
+pub fn default_thresholds() -> Vec<Threshold> {
+    DEFAULT_THRESHOLDS
+        .iter()
+        .map(|&(column, max_increase)| Threshold {
+            column: column.to_string(),
+            max_increase,
+        })
+        .collect()
+}
+
+/// `column=percent`, e.g. `avg_step_ns=5`.
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (column, percent) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected column=percent, got {:?}", s))?;
+        let percent: f64 = percent
+            .parse()
+            .ok()
+            .filter(|p: &f64| *p >= 0.0)
+            .ok_or_else(|| format!("expected a non-negative percentage, got {:?}", percent))?;
+        Ok(Self {
+            column: column.to_string(),
+            max_increase: percent / 100.0,
+        })
+    }
+}
+
+/// `defaults` with the thresholds of `overrides` replacing or adding to them.
+pub fn with_overrides(mut defaults: Vec<Threshold>, overrides: &[Threshold]) -> Vec<Threshold> {
+    for t in overrides {
+        match defaults.iter_mut().find(|d| d.column == t.column) {
+            Some(d) => d.max_increase = t.max_increase,
+            None => defaults.push(t.clone()),
+        }
+    }
+    defaults
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Regression {
+    /// `column` of `name` grew from `baseline` to `current` beyond `threshold`.
+    Metric {
+        name: String,
+        column: String,
+        baseline: f64,
+        current: f64,
+        threshold: f64,
+    },
+    /// `name` completed in the baseline but not in the current run.
+    NotCompleted { name: String, status: String },
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Regression::Metric {
+                name,
+                column,
+                baseline,
+                current,
+                threshold,
+            } => write!(
+                f,
+                "{} {}: {} -> {} ({:+.1}%, threshold {:.1}%)",
+                name,
+                column,
+                baseline,
+                current,
+                (current - baseline) / baseline * 100.0,
+                threshold * 100.0
+            ),
+            Regression::NotCompleted { name, status } => {
+                write!(f, "{}: completed in the baseline, {} now", name, status)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CheckReport {
+    /// Benchmarks present in both runs.
+    pub compared: usize,
+    /// Benchmarks of the baseline absent from the current run.
+    pub missing: Vec<String>,
+    pub regressions: Vec<Regression>,
+}
+
+impl CheckReport {
+    pub fn passed(&self) -> bool {
+        self.regressions.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!("\nCompared {} benchmark(s) to the baseline", self.compared);
+        if !self.missing.is_empty() {
+            println!("Not in this run: {}", self.missing.join(", "));
+        }
+        if self.passed() {
+            println!("No regression");
+        } else {
+            println!("{} regression(s):", self.regressions.len());
+            for r in &self.regressions {
+                println!("  {}", r);
+            }
+        }
+    }
+}
+
+/// Compares the first trial of every benchmark of `current` to `baseline`.
+/// A column empty or zero in either run (e.g. a phase not run) is skipped.
+pub fn check(baseline: &HistoryRun, current: &HistoryRun, thresholds: &[Threshold]) -> CheckReport {
+    let current_first = current.first_trials();
+    let mut report = CheckReport::default();
+    let base_first = baseline.first_trials();
+    let mut names: Vec<&str> = base_first.keys().copied().collect();
+    names.sort_unstable();
+    for name in names {
+        let base = base_first[name];
+        let Some(now) = current_first.get(name) else {
+            match current.rows.iter().find(|r| r.name == name) {
+                Some(row) => report.regressions.push(Regression::NotCompleted {
+                    name: name.to_string(),
+                    status: row.status.clone(),
+                }),
+                None => report.missing.push(name.to_string()),
+            }
+            continue;
+        };
+        report.compared += 1;
+        for t in thresholds {
+            let (Some(b), Some(c)) = (base.metric(&t.column), now.metric(&t.column)) else {
+                continue;
+            };
+            if b > 0.0 && c > 0.0 && c > b * (1.0 + t.max_increase) {
+                report.regressions.push(Regression::Metric {
+                    name: name.to_string(),
+                    column: t.column.clone(),
+                    baseline: b,
+                    current: c,
+                    threshold: t.max_increase,
+                });
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::parse_csv;
+    use std::path::PathBuf;
+
+    fn run(csv: &str) -> HistoryRun {
+        HistoryRun {
+            path: PathBuf::from("run.csv"),
+            rows: parse_csv(&format!("name,status,trial,avg_step_ns,proof_size\n{}", csv)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let baseline = run("a,ok,0,100,500\nb,ok,0,100,500\nc,ok,0,100,500\nd,ok,0,100,\n");
+        let current = run("a,ok,0,109,500\nb,ok,0,100,501\nc,timeout,,,\nd,ok,0,200,\n");
+        let thresholds = with_overrides(default_thresholds(), &["avg_step_ns=50".parse().unwrap()]);
+        let report = check(&baseline, &current, &thresholds);
+        assert_eq!(report.compared, 3);
+        assert!(report.missing.is_empty());
+        assert_eq!(
+            report.regressions,
+            [
+                Regression::Metric {
+                    name: "b".to_string(),
+                    column: "proof_size".to_string(),
+                    baseline: 500.0,
+                    current: 501.0,
+                    threshold: 0.0,
+                },
+                Regression::NotCompleted {
+                    name: "c".to_string(),
+                    status: "timeout".to_string(),
+                },
+                Regression::Metric {
+                    name: "d".to_string(),
+                    column: "avg_step_ns".to_string(),
+                    baseline: 100.0,
+                    current: 200.0,
+                    threshold: 0.5,
+                },
+            ]
+        );
+        assert!(!report.passed());
+
+        let report = check(&baseline, &run("a,ok,0,109,500\n"), &default_thresholds());
+        assert!(report.passed());
+        assert_eq!(report.missing, ["b", "c", "d"]);
+
+        assert!("avg_step_ns".parse::<Threshold>().is_err());
+        assert!("avg_step_ns=-1".parse::<Threshold>().is_err());
+    }
+}
@@ -0,0 +1,985 @@
+//! Result sinks.
+//!
+//! A `Reporter` is told when each phase of a benchmark starts and ends and
+//! receives the whole `SuiteReport` at the end. The terminal, JSON, CSV,
+//! Markdown, Google Benchmark, samples and LaTeX sinks of `config::OutputSink`
+//! are implemented against it; other sinks (e.g. a dashboard client) are passed to
+//! `suite::run_suite_with`.
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::build_info::BuildInfo;
+use crate::ccs::CcsStats;
+use crate::config::OutputSink;
+use crate::error::BenchError;
+use crate::result::{
+    AugmentedBreakdown, BenchResult, ParamSizes, PreprocessBreakdown, StepSplit, VpRoundTrip,
+};
+use crate::stats::StepStats;
+use crate::suite::{verify_time, Status, SuiteReport};
+
+pub trait Reporter: Send {
+    /// `phase` of benchmark `bench` started: `preprocess`, `decider
+    /// preprocess`, `fold`, `prove` or `verify`.
+    fn on_phase_start(&mut self, _bench: &str, _phase: &str) {}
+
+    fn on_phase_end(&mut self, _bench: &str, _phase: &str, _elapsed: Duration) {}
+
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError>;
+}
+
+/// Reporters of the suite being run, see `run_suite_with`.
+static ACTIVE: Mutex<Vec<Box<dyn Reporter>>> = Mutex::new(Vec::new());
+
+/// Makes `reporters` receive the phase events until `deactivate`.
+pub(crate) fn activate(reporters: Vec<Box<dyn Reporter>>) {
+    *ACTIVE.lock().unwrap() = reporters;
+}
+
+pub(crate) fn deactivate() -> Vec<Box<dyn Reporter>> {
+    std::mem::take(&mut *ACTIVE.lock().unwrap())
+}
+
+pub(crate) fn phase_start(bench: &str, phase: &str) {
+    for reporter in ACTIVE.lock().unwrap().iter_mut() {
+        reporter.on_phase_start(bench, phase);
+    }
+}
+
+pub(crate) fn phase_end(bench: &str, phase: &str, elapsed: Duration) {
+    for reporter in ACTIVE.lock().unwrap().iter_mut() {
+        reporter.on_phase_end(bench, phase, elapsed);
+    }
+}
+
+/// The reporters writing to `sinks`.
+pub fn reporters(sinks: &[OutputSink]) -> Vec<Box<dyn Reporter>> {
+    sinks
+        .iter()
+        .map(|sink| -> Box<dyn Reporter> {
+            match sink {
+                OutputSink::Terminal => Box::new(TerminalReporter),
+                OutputSink::Json(path) => Box::new(JsonReporter { path: path.clone() }),
+                OutputSink::Csv(path) => Box::new(CsvReporter { path: path.clone() }),
+                OutputSink::Markdown(path) => Box::new(MarkdownReporter { path: path.clone() }),
+                OutputSink::GoogleBenchmark(path) => {
+                    Box::new(GoogleBenchmarkReporter { path: path.clone() })
+                }
+                OutputSink::Samples(path) => Box::new(SamplesReporter { path: path.clone() }),
+                OutputSink::Latex(path) => Box::new(LatexReporter { path: path.clone() }),
+            }
+        })
+        .collect()
+}
+
+/// The table of `SuiteReport::print`; the phases are already printed by the
+/// pipelines as they run.
+pub struct TerminalReporter;
+
+impl Reporter for TerminalReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        report.print();
+        Ok(())
+    }
+}
+
+/// An array with one object per benchmark, holding its status and the
+/// measurements of every trial. Durations are in nanoseconds.
+pub struct JsonReporter {
+    pub path: PathBuf,
+}
+
+impl Reporter for JsonReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        write_json(&mut out, report)?;
+        out.flush()?;
+        println!("Wrote JSON report to {:?}", self.path);
+        Ok(())
+    }
+}
+
+/// Writes `report` in the format of `JsonReporter`.
+pub fn write_json(out: &mut impl Write, report: &SuiteReport) -> Result<(), BenchError> {
+    let build = json_build(&BuildInfo::current());
+    writeln!(out, "[")?;
+    for (i, entry) in report.entries.iter().enumerate() {
+        let trials: Vec<String> = match &entry.status {
+            Status::Completed(results) => results.iter().map(json_result).collect(),
+            _ => Vec::new(),
+        };
+        write!(
+            out,
+            "  {{\"name\": {}, \"status\": \"{}\", \"error\": {}, \"build\": {}, \
+             \"trials\": [{}]}}",
+            json_string(&entry.name),
+            entry.status.label(),
+            entry.status.error().map_or("null".to_string(), |e| json_string(&e)),
+            build,
+            trials.join(", ")
+        )?;
+        writeln!(out, "{}", if i + 1 < report.entries.len() { "," } else { "" })?;
+    }
+    writeln!(out, "]")?;
+    Ok(())
+}
+
+fn json_build(build: &BuildInfo) -> String {
+    let features: Vec<String> = build.features().into_iter().map(json_string).collect();
+    format!(
+        "{{\"sonobe_version\": {}, \"sonobe_revision\": {}, \"features\": [{}], \
+         \"debug_assertions\": {}}}",
+        json_string(build.sonobe_version),
+        json_string(build.sonobe_revision),
+        features.join(", "),
+        build.debug_assertions
+    )
+}
+
+fn json_result(r: &BenchResult) -> String {
+    let steps: Vec<String> = r.steps.iter().map(|d| d.as_nanos().to_string()).collect();
+    format!(
+        "{{\"label\": {}, \"n_steps\": {}, \"preprocess_ns\": {}, \"params_cached\": {}, \
+         \"srs_load_ns\": {}, \"decider_preprocess_ns\": {}, \
+         \"decider_pk_size\": {}, \"decider_vk_size\": {}, \"steps_ns\": [{}], \"decider\": {}, \"decider_prove_ns\": {}, \"decider_verify_ns\": {}, \
+         \"proof_size\": {}, \"total_ns\": {}, \"augmented_constraints\": {}, \
+         \"cyclefold_constraints\": {}, \"augmented_breakdown\": {}, \"verify_gas\": {}, \
+         \"us_per_constraint\": {}, \
+         \"constraints_per_sec\": {}, \"proof_bytes_per_step\": {}, \"gas_per_step\": {}, \
+         \"step_stats\": {}, \"step_split\": {}, \"preprocess_breakdown\": {}, \"ccs\": {}, \
+         \"param_sizes\": {}, \"vp_round_trip\": {}, \"sat_check_ns\": {}}}",
+        json_string(&r.label),
+        r.n_steps,
+        r.preprocess.as_nanos(),
+        r.params_cached,
+        json_option(r.srs_load.map(|d| d.as_nanos())),
+        r.decider_preprocess.as_nanos(),
+        r.decider_pk_size,
+        r.decider_vk_size,
+        steps.join(", "),
+        r.decider.map_or("null".to_string(), |d| json_string(&d.to_string())),
+        r.decider_prove.as_nanos(),
+        r.decider_verify.as_nanos(),
+        r.proof_size,
+        r.total.as_nanos(),
+        r.augmented_constraints,
+        r.cyclefold_constraints,
+        r.augmented_breakdown.as_ref().map_or("null".to_string(), json_augmented),
+        json_option(r.verify_gas),
+        json_option(r.us_per_constraint()),
+        json_option(r.constraints_per_sec()),
+        json_option(r.proof_bytes_per_step()),
+        json_option(r.gas_per_step()),
+        r.step_stats().as_ref().map_or("null".to_string(), json_step_stats),
+        r.step_split().as_ref().map_or("null".to_string(), json_step_split),
+        r.preprocess_breakdown.as_ref().map_or("null".to_string(), json_breakdown),
+        r.ccs.as_ref().map_or("null".to_string(), json_ccs),
+        r.param_sizes.as_ref().map_or("null".to_string(), json_param_sizes),
+        r.vp_round_trip.as_ref().map_or("null".to_string(), json_vp_round_trip),
+        r.sat_check.as_nanos()
+    )
+}
+
+fn json_step_stats(stats: &StepStats) -> String {
+    format!(
+        "{{\"min_ns\": {}, \"max_ns\": {}, \"mean_ns\": {}, \"std_dev_ns\": {}, \"cv\": {}, \
+         \"drift\": {}}}",
+        stats.min.as_nanos(),
+        stats.max.as_nanos(),
+        stats.mean.as_nanos(),
+        stats.std_dev.as_nanos(),
+        stats.cv,
+        stats.drift
+    )
+}
+
+fn json_step_split(split: &StepSplit) -> String {
+    format!(
+        "{{\"witness_ns\": {}, \"constraints_ns\": {}, \"prover_ns\": {}}}",
+        split.witness.as_nanos(),
+        split.constraints.as_nanos(),
+        split.prover.as_nanos()
+    )
+}
+
+fn json_augmented(b: &AugmentedBreakdown) -> String {
+    format!(
+        "{{\"step\": {}, \"folding_verifier\": {}, \"transcript\": {}, \"cyclefold\": {}}}",
+        b.step, b.folding_verifier, b.transcript, b.cyclefold
+    )
+}
+
+fn json_breakdown(b: &PreprocessBreakdown) -> String {
+    format!(
+        "{{\"synthesis_ns\": {}, \"commitment_keys_ns\": {}, \"assembly_ns\": {}}}",
+        b.synthesis.as_nanos(),
+        b.commitment_keys.as_nanos(),
+        b.assembly.as_nanos()
+    )
+}
+
+fn json_param_sizes(s: &ParamSizes) -> String {
+    format!(
+        "{{\"commitment_pk\": {}, \"commitment_vk\": {}, \"cyclefold_pk\": {}, \
+         \"cyclefold_vk\": {}}}",
+        s.commitment_pk, s.commitment_vk, s.cyclefold_pk, s.cyclefold_vk
+    )
+}
+
+fn json_vp_round_trip(v: &VpRoundTrip) -> String {
+    format!(
+        "{{\"bytes\": {}, \"serialize_ns\": {}, \"deserialize_ns\": {}, \"verify_ns\": {}}}",
+        v.bytes,
+        v.serialize.as_nanos(),
+        v.deserialize.as_nanos(),
+        v.verify.as_nanos()
+    )
+}
+
+fn json_ccs(ccs: &CcsStats) -> String {
+    let nonzeros: Vec<String> = ccs.nonzeros.iter().map(usize::to_string).collect();
+    format!(
+        "{{\"rows\": {}, \"columns\": {}, \"public_inputs\": {}, \"matrices\": {}, \
+         \"multisets\": {}, \"degree\": {}, \"nonzeros\": [{}]}}",
+        ccs.rows,
+        ccs.columns,
+        ccs.public_inputs,
+        ccs.matrices,
+        ccs.multisets,
+        ccs.degree,
+        nonzeros.join(", ")
+    )
+}
+
+fn json_option(value: Option<impl ToString>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// One row per trial, durations in nanoseconds. Benchmarks that did not
+/// complete get a single row with their status and empty measurements.
+pub struct CsvReporter {
+    pub path: PathBuf,
+}
+
+impl Reporter for CsvReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        writeln!(
+            out,
+            "name,status,trial,n_steps,preprocess_ns,synthesis_ns,commitment_keys_ns,assembly_ns,\
+             decider_preprocess_ns,avg_step_ns,step_witness_ns,step_constraints_ns,step_prover_ns,\
+             decider_prove_ns,decider_verify_ns,proof_size,total_ns,augmented_constraints,\
+             step_circuit_constraints,folding_verifier_constraints,poseidon_constraints,\
+             cyclefold_check_constraints,commitment_pk_size,commitment_vk_size,\
+             cyclefold_pk_size,cyclefold_vk_size,decider_pk_size,decider_vk_size,\
+             us_per_constraint,constraints_per_sec,proof_bytes_per_step,gas_per_step,step_cv,\
+             step_drift,sonobe_revision,features"
+        )?;
+        let build = BuildInfo::current();
+        let build = format!("{},{}", build.sonobe_revision, build.features().join("+"));
+        let opt = |v: Option<f64>| v.map_or(String::new(), |v| v.to_string());
+        for entry in &report.entries {
+            match &entry.status {
+                Status::Completed(results) => {
+                    for (trial, r) in results.iter().enumerate() {
+                        let stage = |f: fn(&PreprocessBreakdown) -> Duration| {
+                            r.preprocess_breakdown
+                                .as_ref()
+                                .map_or(String::new(), |b| f(b).as_nanos().to_string())
+                        };
+                        let component = |f: fn(&AugmentedBreakdown) -> usize| {
+                            r.augmented_breakdown
+                                .as_ref()
+                                .map_or(String::new(), |b| f(b).to_string())
+                        };
+                        let size = |f: fn(&ParamSizes) -> usize| {
+                            r.param_sizes.as_ref().map_or(String::new(), |s| f(s).to_string())
+                        };
+                        let split = |f: fn(&StepSplit) -> Duration| {
+                            r.step_split().map_or(String::new(), |s| f(&s).as_nanos().to_string())
+                        };
+                        writeln!(
+                            out,
+                            "{},ok,{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},\
+                             {},{},{},{},{},{},{},{},{},{},{}",
+                            entry.name,
+                            trial,
+                            r.n_steps,
+                            r.preprocess.as_nanos(),
+                            stage(|b| b.synthesis),
+                            stage(|b| b.commitment_keys),
+                            stage(|b| b.assembly),
+                            r.decider_preprocess.as_nanos(),
+                            r.avg_step().as_nanos(),
+                            split(|s| s.witness),
+                            split(|s| s.constraints),
+                            split(|s| s.prover),
+                            r.decider_prove.as_nanos(),
+                            r.decider_verify.as_nanos(),
+                            r.proof_size,
+                            r.total.as_nanos(),
+                            r.augmented_constraints,
+                            component(|b| b.step),
+                            component(|b| b.folding_verifier),
+                            component(|b| b.transcript),
+                            component(|b| b.cyclefold),
+                            size(|s| s.commitment_pk),
+                            size(|s| s.commitment_vk),
+                            size(|s| s.cyclefold_pk),
+                            size(|s| s.cyclefold_vk),
+                            r.decider_pk_size,
+                            r.decider_vk_size,
+                            opt(r.us_per_constraint()),
+                            opt(r.constraints_per_sec()),
+                            opt(r.proof_bytes_per_step()),
+                            opt(r.gas_per_step()),
+                            opt(r.step_stats().map(|s| s.cv)),
+                            opt(r.step_stats().map(|s| s.drift)),
+                            build
+                        )?;
+                    }
+                }
+                status => writeln!(
+                    out,
+                    "{},{},,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,,{}",
+                    entry.name,
+                    status.label(),
+                    build
+                )?,
+            }
+        }
+        out.flush()?;
+        println!("Wrote CSV report to {:?}", self.path);
+        Ok(())
+    }
+}
+
+/// A table with one row per benchmark and its first trial, for pasting into
+/// PRs and issues.
+pub struct MarkdownReporter {
+    pub path: PathBuf,
+}
+
+impl Reporter for MarkdownReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        writeln!(
+            out,
+            "| benchmark | status | avg step | decider prove | decider verify | proof size (B) | total |"
+        )?;
+        writeln!(out, "|---|---|---:|---:|---:|---:|---:|")?;
+        for entry in &report.entries {
+            match &entry.status {
+                Status::Completed(results) => {
+                    let Some(r) = results.first() else { continue };
+                    // folding-only runs, e.g. commitment schemes without a decider
+                    let decider = |value: String| {
+                        if r.decider_prove.is_zero() {
+                            "n/a".to_string()
+                        } else {
+                            value
+                        }
+                    };
+                    writeln!(
+                        out,
+                        "| `{}` | ok | {:?} | {} | {} | {} | {:?} |",
+                        entry.name,
+                        r.avg_step(),
+                        decider(format!("{:?}", r.decider_prove)),
+                        decider(format!("{:?}", r.decider_verify)),
+                        decider(r.proof_size.to_string()),
+                        r.total
+                    )?;
+                }
+                status => writeln!(
+                    out,
+                    "| `{}` | {} | | | | | |",
+                    entry.name,
+                    status.label()
+                )?,
+            }
+        }
+        for (circuit, rows) in report.decider_comparison() {
+            writeln!(out, "\n### Deciders of `{}`\n", circuit)?;
+            writeln!(out, "| benchmark | decider | proof size (B) | verify | EVM gas |")?;
+            writeln!(out, "|---|---|---:|---:|---:|")?;
+            for row in rows {
+                writeln!(
+                    out,
+                    "| `{}` | {} | {} | {} | {} |",
+                    row.benchmark,
+                    row.kind,
+                    row.proof_size,
+                    verify_time(row.verify),
+                    row.gas.map_or("n/a".to_string(), |g| g.to_string())
+                )?;
+            }
+        }
+        if !report.primitives.is_empty() {
+            writeln!(out, "\n### Primitives\n")?;
+            writeln!(out, "| curve | primitive | size | time |")?;
+            writeln!(out, "|---|---|---:|---:|")?;
+            for p in &report.primitives {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | {:?} |",
+                    p.curve, p.primitive, p.size, p.time
+                )?;
+            }
+        }
+        writeln!(out, "\nBuild: {}", BuildInfo::current())?;
+        out.flush()?;
+        println!("Wrote Markdown report to {:?}", self.path);
+        Ok(())
+    }
+}
+
+/// The JSON of Google Benchmark's `--benchmark_format=json`, so its
+/// `compare.py` and the dashboards reading that format take our results as
+/// is. Every phase of a trial is a benchmark `<entry>/<phase>` of one
+/// repetition; the folding step runs `n_steps` iterations.
+pub struct GoogleBenchmarkReporter {
+    pub path: PathBuf,
+}
+
+impl Reporter for GoogleBenchmarkReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let build = BuildInfo::current();
+        let mut benchmarks = Vec::new();
+        for entry in &report.entries {
+            let Status::Completed(results) = &entry.status else { continue };
+            for (i, r) in results.iter().enumerate() {
+                let breakdown = r.preprocess_breakdown.unwrap_or_default();
+                let split = r.step_split().unwrap_or_default();
+                let phases = [
+                    ("preprocess", 1, r.preprocess),
+                    ("preprocess_synthesis", 1, breakdown.synthesis),
+                    ("preprocess_commitment_keys", 1, breakdown.commitment_keys),
+                    ("preprocess_assembly", 1, breakdown.assembly),
+                    ("decider_preprocess", 1, r.decider_preprocess),
+                    ("fold_step", r.steps.len(), r.avg_step()),
+                    ("fold_step_witness", r.steps.len(), split.witness),
+                    ("fold_step_constraints", r.steps.len(), split.constraints),
+                    ("fold_step_prover", r.steps.len(), split.prover),
+                    ("decider_prove", 1, r.decider_prove),
+                    ("decider_verify", 1, r.decider_verify),
+                ];
+                for (phase, iterations, time) in phases {
+                    // phases the run skipped, e.g. the decider of a folding-only cycle
+                    if iterations == 0 || time.is_zero() {
+                        continue;
+                    }
+                    let name = format!("{}/{}", entry.name, phase);
+                    benchmarks.push(format!(
+                        "    {{\"name\": {}, \"run_name\": {}, \"run_type\": \"iteration\", \
+                         \"repetitions\": {}, \"repetition_index\": {}, \"threads\": 1, \
+                         \"iterations\": {}, \"real_time\": {}, \"cpu_time\": {}, \
+                         \"time_unit\": \"ns\"}}",
+                        json_string(&name),
+                        json_string(&name),
+                        results.len(),
+                        i,
+                        iterations,
+                        time.as_nanos(),
+                        time.as_nanos()
+                    ));
+                }
+            }
+        }
+
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        writeln!(out, "{{")?;
+        writeln!(out, "  \"context\": {{")?;
+        writeln!(out, "    \"date\": \"{}\",", utc_now())?;
+        writeln!(
+            out,
+            "    \"executable\": {},",
+            json_string(&std::env::args().next().unwrap_or_default())
+        )?;
+        writeln!(
+            out,
+            "    \"num_cpus\": {},",
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        )?;
+        writeln!(
+            out,
+            "    \"library_build_type\": \"{}\",",
+            if build.debug_assertions { "debug" } else { "release" }
+        )?;
+        writeln!(out, "    \"sonobe_revision\": {},", json_string(build.sonobe_revision))?;
+        writeln!(out, "    \"build\": {}", json_string(&build.to_string()))?;
+        writeln!(out, "  }},")?;
+        writeln!(out, "  \"benchmarks\": [\n{}\n  ]", benchmarks.join(",\n"))?;
+        writeln!(out, "}}")?;
+        out.flush()?;
+        println!("Wrote Google Benchmark report to {:?}", self.path);
+        Ok(())
+    }
+}
+
+/// Every timing sample of every trial, one per row, for analyses of the raw
+/// data in R or pandas: `benchmark,trial,phase,index,ns`. Per-step phases
+/// (`fold_step`, and `fold_step_witness` and `fold_step_constraints` for
+/// timed step circuits) have one row per step, indexed from 0; `ivc_verify`
+/// one per verification, indexed by the steps folded at that point; the
+/// others a single row of index 0. Phases a run skipped have no rows.
+pub struct SamplesReporter {
+    pub path: PathBuf,
+}
+
+impl Reporter for SamplesReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        writeln!(out, "benchmark,trial,phase,index,ns")?;
+        for entry in &report.entries {
+            let Status::Completed(results) = &entry.status else { continue };
+            for (trial, r) in results.iter().enumerate() {
+                let mut row = |phase: &str, index: usize, time: Duration| {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{}",
+                        entry.name,
+                        trial,
+                        phase,
+                        index,
+                        time.as_nanos()
+                    )
+                };
+                let breakdown = r.preprocess_breakdown;
+                let single = [
+                    ("preprocess", Some(r.preprocess)),
+                    ("preprocess_synthesis", breakdown.map(|b| b.synthesis)),
+                    ("preprocess_commitment_keys", breakdown.map(|b| b.commitment_keys)),
+                    ("preprocess_assembly", breakdown.map(|b| b.assembly)),
+                    ("decider_preprocess", Some(r.decider_preprocess)),
+                ];
+                for (phase, time) in single {
+                    if let Some(time) = time.filter(|t| !t.is_zero()) {
+                        row(phase, 0, time)?;
+                    }
+                }
+                for (i, time) in r.steps.iter().enumerate() {
+                    row("fold_step", i, *time)?;
+                }
+                for (i, times) in r.step_circuit.iter().enumerate() {
+                    row("fold_step_witness", i, times.native)?;
+                    row("fold_step_constraints", i, times.constraints)?;
+                }
+                for verification in &r.ivc_verifications {
+                    row("ivc_verify", verification.step, verification.time)?;
+                }
+                let single = [
+                    ("decider_prove", r.decider_prove),
+                    ("decider_verify", r.decider_verify),
+                    ("total", r.total),
+                ];
+                for (phase, time) in single {
+                    if !time.is_zero() {
+                        row(phase, 0, time)?;
+                    }
+                }
+            }
+        }
+        out.flush()?;
+        println!("Wrote timing samples to {:?}", self.path);
+        Ok(())
+    }
+}
+
+/// booktabs tables for `\input` into a paper: one row per benchmark and its
+/// first trial, grouped by circuit so that the schemes and curves of a
+/// circuit sit together, then the decider comparison of the circuits proven
+/// both on-chain and off-chain and the primitive timings, if any. Times are
+/// in milliseconds, except decider proving in seconds, and sizes in bytes;
+/// `--` marks a phase that did not run. The document needs
+/// `\usepackage{booktabs}`.
+pub struct LatexReporter {
+    pub path: PathBuf,
+}
+
+impl Reporter for LatexReporter {
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+        write_latex(&mut out, report)?;
+        out.flush()?;
+        println!("Wrote LaTeX tables to {:?}", self.path);
+        Ok(())
+    }
+}
+
+/// Writes `report` in the format of `LatexReporter`.
+pub fn write_latex(out: &mut impl Write, report: &SuiteReport) -> Result<(), BenchError> {
+    // `scheme/curve/circuit`, stably sorted by circuit
+    let mut rows: Vec<_> = report
+        .entries
+        .iter()
+        .map(|entry| {
+            let mut parts = entry.name.splitn(3, '/');
+            let (scheme, curve, circuit) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(scheme), Some(curve), Some(circuit)) => (scheme, curve, circuit),
+                _ => ("", "", entry.name.as_str()),
+            };
+            (circuit, scheme, curve, &entry.status)
+        })
+        .collect();
+    rows.sort_by_key(|(circuit, ..)| *circuit);
+
+    writeln!(out, "% sonobe-bench results, {}", BuildInfo::current())?;
+    writeln!(out, "\\begin{{table}}[t]")?;
+    writeln!(out, "\\centering")?;
+    writeln!(
+        out,
+        "\\caption{{Folding and decider benchmarks: augmented circuit constraints, mean \
+         folding step, decider proving and verification, and decider proof size.}}"
+    )?;
+    writeln!(out, "\\label{{tab:sonobe-bench}}")?;
+    writeln!(out, "\\begin{{tabular}}{{lllrrrrrr}}")?;
+    writeln!(out, "\\toprule")?;
+    writeln!(
+        out,
+        "Circuit & Scheme & Curve & Steps & Constraints & Step (ms) & Prove (s) & \
+         Verify (ms) & Proof (B) \\\\"
+    )?;
+    let mut previous = None;
+    for (circuit, scheme, curve, status) in rows {
+        let first = previous != Some(circuit);
+        if first {
+            writeln!(out, "\\midrule")?;
+            previous = Some(circuit);
+        }
+        let circuit = if first {
+            latex_escape(circuit)
+        } else {
+            String::new()
+        };
+        let (scheme, curve) = (latex_escape(scheme), latex_escape(curve));
+        match status {
+            Status::Completed(results) => {
+                let Some(r) = results.first() else { continue };
+                // folding-only runs, e.g. commitment schemes without a decider
+                let (prove, proof_size) = if r.decider_prove.is_zero() {
+                    ("--".to_string(), "--".to_string())
+                } else {
+                    (
+                        format!("{:.2}", r.decider_prove.as_secs_f64()),
+                        r.proof_size.to_string(),
+                    )
+                };
+                writeln!(
+                    out,
+                    "{} & {} & {} & {} & {} & {} & {} & {} & {} \\\\",
+                    circuit,
+                    scheme,
+                    curve,
+                    r.n_steps,
+                    r.augmented_constraints,
+                    latex_ms(r.avg_step()),
+                    prove,
+                    latex_ms(r.decider_verify),
+                    proof_size
+                )?;
+            }
+            status => writeln!(
+                out,
+                "{} & {} & {} & \\multicolumn{{6}}{{c}}{{{}}} \\\\",
+                circuit,
+                scheme,
+                curve,
+                status.label()
+            )?,
+        }
+    }
+    writeln!(out, "\\bottomrule")?;
+    writeln!(out, "\\end{{tabular}}")?;
+    writeln!(out, "\\end{{table}}")?;
+    write_latex_deciders(out, report)?;
+    write_latex_primitives(out, report)
+}
+
+fn write_latex_deciders(out: &mut impl Write, report: &SuiteReport) -> Result<(), BenchError> {
+    let comparison = report.decider_comparison();
+    if comparison.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n\\begin{{table}}[t]")?;
+    writeln!(out, "\\centering")?;
+    writeln!(
+        out,
+        "\\caption{{Deciders of the same circuit: proof size, verification and EVM gas.}}"
+    )?;
+    writeln!(out, "\\label{{tab:sonobe-bench-deciders}}")?;
+    writeln!(out, "\\begin{{tabular}}{{lllrrr}}")?;
+    writeln!(out, "\\toprule")?;
+    writeln!(
+        out,
+        "Circuit & Benchmark & Decider & Proof (B) & Verify (ms) & Gas \\\\"
+    )?;
+    for (circuit, rows) in comparison {
+        writeln!(out, "\\midrule")?;
+        for (i, row) in rows.iter().enumerate() {
+            writeln!(
+                out,
+                "{} & {} & {} & {} & {} & {} \\\\",
+                if i == 0 {
+                    latex_escape(&circuit)
+                } else {
+                    String::new()
+                },
+                latex_escape(&row.benchmark),
+                latex_escape(&row.kind.to_string()),
+                row.proof_size,
+                latex_ms(row.verify),
+                row.gas.map_or("--".to_string(), |g| g.to_string())
+            )?;
+        }
+    }
+    writeln!(out, "\\bottomrule")?;
+    writeln!(out, "\\end{{tabular}}")?;
+    writeln!(out, "\\end{{table}}")?;
+    Ok(())
+}
+
+fn write_latex_primitives(out: &mut impl Write, report: &SuiteReport) -> Result<(), BenchError> {
+    if report.primitives.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "\n\\begin{{table}}[t]")?;
+    writeln!(out, "\\centering")?;
+    writeln!(
+        out,
+        "\\caption{{Primitives of every curve: MSMs by number of points, FFTs by domain \
+         size and Poseidon permutations by state width.}}"
+    )?;
+    writeln!(out, "\\label{{tab:sonobe-bench-primitives}}")?;
+    writeln!(out, "\\begin{{tabular}}{{llrr}}")?;
+    writeln!(out, "\\toprule")?;
+    writeln!(out, "Curve & Primitive & Size & Time ($\\mu$s) \\\\")?;
+    let mut previous = None;
+    for p in &report.primitives {
+        let first = previous != Some(&p.curve);
+        if first {
+            writeln!(out, "\\midrule")?;
+            previous = Some(&p.curve);
+        }
+        writeln!(
+            out,
+            "{} & {} & {} & {:.1} \\\\",
+            if first {
+                latex_escape(&p.curve)
+            } else {
+                String::new()
+            },
+            p.primitive,
+            p.size,
+            p.time.as_secs_f64() * 1e6
+        )?;
+    }
+    writeln!(out, "\\bottomrule")?;
+    writeln!(out, "\\end{{tabular}}")?;
+    writeln!(out, "\\end{{table}}")?;
+    Ok(())
+}
+
+/// `d` in milliseconds, `--` when the phase did not run.
+fn latex_ms(d: Duration) -> String {
+    if d.is_zero() {
+        "--".to_string()
+    } else {
+        format!("{:.2}", d.as_secs_f64() * 1e3)
+    }
+}
+
+/// `s` with the characters LaTeX treats specially escaped, e.g. the `_` of
+/// circuit names.
+fn latex_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The current UTC time as `YYYY-MM-DDTHH:MM:SS+00:00`.
+fn utc_now() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, secs) = (secs / 86400, secs % 86400);
+    // civil date of a day count, from Howard Hinnant's `civil_from_days`
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}+00:00",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::timed::StepCircuitTimes;
+    use crate::primitives::{Primitive, PrimitiveTime};
+    use crate::suite::SuiteEntry;
+
+    fn report() -> SuiteReport {
+        let mut result = BenchResult::new("BN254");
+        result.n_steps = 2;
+        result.preprocess = Duration::from_millis(4);
+        result.preprocess_breakdown = Some(PreprocessBreakdown {
+            synthesis: Duration::from_millis(1),
+            commitment_keys: Duration::from_millis(2),
+            assembly: Duration::from_millis(1),
+        });
+        result.steps = vec![Duration::from_millis(3), Duration::from_millis(5)];
+        result.step_circuit = vec![
+            StepCircuitTimes {
+                native: Duration::from_millis(1),
+                constraints: Duration::ZERO,
+            },
+            StepCircuitTimes {
+                native: Duration::from_millis(3),
+                constraints: Duration::ZERO,
+            },
+        ];
+        result.proof_size = 1184;
+        result.augmented_constraints = 10;
+        result.augmented_breakdown = Some(AugmentedBreakdown {
+            step: 1,
+            folding_verifier: 2,
+            transcript: 3,
+            cyclefold: 4,
+        });
+        result.param_sizes = Some(ParamSizes {
+            commitment_pk: 640,
+            commitment_vk: 128,
+            cyclefold_pk: 320,
+            cyclefold_vk: 320,
+        });
+        result.vp_round_trip = Some(VpRoundTrip {
+            bytes: 608,
+            serialize: Duration::from_micros(5),
+            deserialize: Duration::from_millis(2),
+            verify: Duration::from_millis(9),
+        });
+        SuiteReport {
+            entries: vec![
+                SuiteEntry {
+                    name: "nova/bn254/cubic".to_string(),
+                    status: Status::Completed(vec![result]),
+                },
+                SuiteEntry {
+                    name: "nova/mnt753/cubic".to_string(),
+                    status: Status::Failed("SRS \"too\" small".to_string()),
+                },
+            ],
+            primitives: vec![PrimitiveTime {
+                curve: "bn254".to_string(),
+                primitive: Primitive::Msm,
+                size: 1024,
+                time: Duration::from_micros(1500),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_file_sinks() {
+        let dir = std::env::temp_dir().join(format!("report-sinks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sinks = [
+            OutputSink::Json(dir.join("report.json")),
+            OutputSink::Csv(dir.join("report.csv")),
+            OutputSink::Markdown(dir.join("report.md")),
+            OutputSink::GoogleBenchmark(dir.join("gbench.json")),
+            OutputSink::Samples(dir.join("samples.csv")),
+            OutputSink::Latex(dir.join("report.tex")),
+        ];
+        for mut reporter in reporters(&sinks) {
+            reporter.on_suite_end(&report()).unwrap();
+        }
+
+        let json = std::fs::read_to_string(dir.join("report.json")).unwrap();
+        assert!(json.contains("\"steps_ns\": [3000000, 5000000]"));
+        assert!(json.contains("\"error\": \"SRS \\\"too\\\" small\""));
+        assert!(json.contains("\"commitment_keys_ns\": 2000000"));
+        assert!(json.contains("\"mean_ns\": 4000000"));
+        assert!(json.contains("\"augmented_breakdown\": {\"step\": 1, \"folding_verifier\": 2"));
+        assert!(json.contains("\"step_split\": {\"witness_ns\": 2000000, \"constraints_ns\": 0"));
+        assert!(json.contains("\"param_sizes\": {\"commitment_pk\": 640, \"commitment_vk\": 128"));
+        assert!(json.contains("\"vp_round_trip\": {\"bytes\": 608, \"serialize_ns\": 5000,"));
+        assert!(json.contains(&format!(
+            "\"sonobe_revision\": \"{}\"",
+            BuildInfo::current().sonobe_revision
+        )));
+        let csv = std::fs::read_to_string(dir.join("report.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        assert!(csv.contains("nova/bn254/cubic,ok,0,2,4000000,1000000,2000000,1000000,"));
+        assert!(csv.contains(",4000000,2000000,0,2000000,"));
+        assert!(csv.contains(",10,1,2,3,4,640,128,320,320,0,0,"));
+        let columns = csv.lines().next().unwrap().split(',').count();
+        assert!(csv.lines().all(|line| line.split(',').count() == columns));
+        let md = std::fs::read_to_string(dir.join("report.md")).unwrap();
+        assert!(md.contains("| `nova/mnt753/cubic` | failed |"));
+        assert!(md.contains("| `nova/bn254/cubic` | ok | 4ms | n/a | n/a | n/a |"));
+        assert!(md.contains("### Primitives\n\n| curve |"));
+        assert!(md.contains("| bn254 | MSM | 1024 | 1.5ms |"));
+        let gbench = std::fs::read_to_string(dir.join("gbench.json")).unwrap();
+        assert!(gbench.contains("\"name\": \"nova/bn254/cubic/fold_step\""));
+        assert!(gbench.contains("\"iterations\": 2, \"real_time\": 4000000"));
+        assert!(gbench.contains("\"name\": \"nova/bn254/cubic/preprocess_commitment_keys\""));
+        assert!(gbench.contains("\"name\": \"nova/bn254/cubic/fold_step_witness\""));
+        assert!(!gbench.contains("fold_step_constraints"));
+        assert!(!gbench.contains("decider_prove"));
+        let samples = std::fs::read_to_string(dir.join("samples.csv")).unwrap();
+        assert_eq!(samples.lines().count(), 11);
+        assert!(samples.contains("nova/bn254/cubic,0,preprocess_commitment_keys,0,2000000\n"));
+        assert!(samples.contains("nova/bn254/cubic,0,fold_step,1,5000000\n"));
+        assert!(samples.contains("nova/bn254/cubic,0,fold_step_witness,1,3000000\n"));
+        assert!(!samples.contains("mnt753"));
+        let latex = std::fs::read_to_string(dir.join("report.tex")).unwrap();
+        assert!(latex.contains("\\toprule\nCircuit & Scheme & Curve & Steps &"));
+        assert!(latex.contains("\\midrule\ncubic & nova & bn254 & 2 & 10 & 4.00 & -- & -- & --"));
+        assert!(latex.contains(" & nova & mnt753 & \\multicolumn{6}{c}{failed} \\\\\n"));
+        assert!(!latex.contains("tab:sonobe-bench-deciders"));
+        assert!(latex.contains("\\midrule\nbn254 & MSM & 1024 & 1500.0 \\\\\n"));
+        assert_eq!(latex_escape("merkle_update/50%"), "merkle\\_update/50\\%");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,452 @@
+//! Measurements of one benchmark trial, as returned by `bench::run_nova_bench`.
+use std::fmt;
+use std::time::Duration;
+
+use crate::bench::IvcVerification;
+use crate::ccs::CcsStats;
+use crate::circuits::timed::StepCircuitTimes;
+use crate::config::{verbosity, Verbosity};
+use crate::stats::{histogram, StepStats};
+
+#[derive(Clone, Debug, Default)]
+pub struct BenchResult {
+    pub label: String,
+    pub n_steps: usize,
+    /// Folding scheme preprocess.
+    pub preprocess: Duration,
+    /// Whether the params were read from the params cache, in which case
+    /// `preprocess` is the time it took to load them.
+    pub params_cached: bool,
+    /// Loading the KZG keys from `BenchConfig::srs`, `None` when they were
+    /// sampled in preprocess.
+    pub srs_load: Option<Duration>,
+    /// `preprocess` per stage, for schemes whose preprocess can be split.
+    pub preprocess_breakdown: Option<PreprocessBreakdown>,
+    /// Sizes of the folding scheme params, for schemes whose keys can be
+    /// told apart.
+    pub param_sizes: Option<ParamSizes>,
+    /// Decider preprocess, zero for folding-only runs.
+    pub decider_preprocess: Duration,
+    /// Compressed sizes of the decider proving and verifying keys (for
+    /// `DeciderEth`, the Groth16 keys with the KZG ones).
+    pub decider_pk_size: usize,
+    pub decider_vk_size: usize,
+    /// Time of every `prove_step`, in order.
+    pub steps: Vec<Duration>,
+    /// Time spent in the step circuit during every `prove_step`, empty unless
+    /// the circuit is a `TimedFCircuit`.
+    pub step_circuit: Vec<StepCircuitTimes>,
+    /// Re-synthesizing and checking every step with `config::check_sat`, zero
+    /// without.
+    pub sat_check: Duration,
+    /// IVC verifications run with `config::verify_every`.
+    pub ivc_verifications: Vec<IvcVerification>,
+    /// Which decider produced the proof, `None` for folding-only runs.
+    pub decider: Option<DeciderKind>,
+    pub decider_prove: Duration,
+    pub decider_verify: Duration,
+    /// Decider verifier params serialized, read back and verifying the proof,
+    /// with `config::vp_round_trip`.
+    pub vp_round_trip: Option<VpRoundTrip>,
+    /// Compressed size of the decider proof, zero for folding-only runs.
+    pub proof_size: usize,
+    /// Constraints of the augmented circuit folded at each step.
+    pub augmented_constraints: usize,
+    pub cyclefold_constraints: usize,
+    /// `augmented_constraints` per component, for schemes where they can be
+    /// told apart.
+    pub augmented_breakdown: Option<AugmentedBreakdown>,
+    /// Shape of the augmented circuit, for schemes folding CCS.
+    pub ccs: Option<CcsStats>,
+    /// Gas of verifying the decider proof on the EVM, with the `evm` feature.
+    pub verify_gas: Option<u64>,
+    /// Wall-clock time of the whole run.
+    pub total: Duration,
+}
+
+/// Where a decider proof is meant to be verified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeciderKind {
+    /// `DeciderEth`: a Groth16 and KZG proof for the Solidity verifier.
+    OnChain,
+    /// Sonobe's native decider, for cycles of pairing-friendly curves.
+    OffChain,
+    /// A Groth16 proof verified inside another one on a 2-chain, see `bw6`.
+    Recursive,
+}
+
+impl fmt::Display for DeciderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DeciderKind::OnChain => "on-chain",
+            DeciderKind::OffChain => "off-chain",
+            DeciderKind::Recursive => "recursive",
+        })
+    }
+}
+
+/// Stages of the folding scheme preprocess.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreprocessBreakdown {
+    /// Synthesis of the augmented and CycleFold circuits into R1CS.
+    pub synthesis: Duration,
+    /// Sampling of the commitment keys (the SRS for KZG), zero when they
+    /// were given, e.g. loaded with `srs::load_srs`.
+    pub commitment_keys: Duration,
+    /// Building the params from the circuits and keys.
+    pub assembly: Duration,
+}
+
+impl PreprocessBreakdown {
+    pub fn total(&self) -> Duration {
+        self.synthesis + self.commitment_keys + self.assembly
+    }
+}
+
+impl fmt::Display for PreprocessBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "synthesis: {:?}, commitment keys: {:?}, assembly: {:?}",
+            self.synthesis, self.commitment_keys, self.assembly
+        )
+    }
+}
+
+/// The decider verifier params through their bytes, as a verifier on
+/// another machine receives them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VpRoundTrip {
+    /// Compressed size of the params.
+    pub bytes: usize,
+    pub serialize: Duration,
+    /// Reading the params back, with validation.
+    pub deserialize: Duration,
+    /// Verifying the proof with the params read back.
+    pub verify: Duration,
+}
+
+impl fmt::Display for VpRoundTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes, serialize: {:?}, deserialize: {:?}, verify: {:?}",
+            self.bytes, self.serialize, self.deserialize, self.verify
+        )
+    }
+}
+
+/// Compressed sizes of the commitment keys in the folding scheme params,
+/// what a prover or verifier has to ship besides the decider keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParamSizes {
+    /// Primary commitment prover key: the KZG SRS, or the Pedersen
+    /// generators.
+    pub commitment_pk: usize,
+    pub commitment_vk: usize,
+    /// Pedersen generators on the secondary curve, for CycleFold.
+    pub cyclefold_pk: usize,
+    pub cyclefold_vk: usize,
+}
+
+impl ParamSizes {
+    /// The folding scheme prover params, as serialized.
+    pub fn prover_params(&self) -> usize {
+        self.commitment_pk + self.cyclefold_pk
+    }
+
+    /// The folding scheme verifier params, as serialized.
+    pub fn verifier_params(&self) -> usize {
+        self.commitment_vk + self.cyclefold_vk
+    }
+}
+
+impl fmt::Display for ParamSizes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "prover params: {} bytes (commitment key: {}, CycleFold key: {}), \
+             verifier params: {} bytes (commitment key: {}, CycleFold key: {})",
+            self.prover_params(),
+            self.commitment_pk,
+            self.cyclefold_pk,
+            self.verifier_params(),
+            self.commitment_vk,
+            self.cyclefold_vk
+        )
+    }
+}
+
+/// Constraints of the augmented circuit per component. Everything but `step`
+/// is the "folding tax" paid over the step circuit itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AugmentedBreakdown {
+    /// The user's step circuit.
+    pub step: usize,
+    /// The NIFS verifier folding the incoming instance into the running one,
+    /// with the checks of the instance hashes.
+    pub folding_verifier: usize,
+    /// Poseidon, for the transcript challenges and the instance hashes.
+    pub transcript: usize,
+    /// The in-circuit checks of the CycleFold instances (not the CycleFold
+    /// circuit itself, which is `cyclefold_constraints`).
+    pub cyclefold: usize,
+}
+
+impl AugmentedBreakdown {
+    pub fn total(&self) -> usize {
+        self.step + self.folding_verifier + self.transcript + self.cyclefold
+    }
+
+    /// Constraints of the augmented circuit per constraint of the step
+    /// circuit, `None` for an empty step circuit.
+    pub fn tax(&self) -> Option<f64> {
+        (self.step > 0).then(|| self.total() as f64 / self.step as f64)
+    }
+}
+
+impl fmt::Display for AugmentedBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().max(1) as f64;
+        let percent = |n: usize| n as f64 / total * 100.0;
+        write!(
+            f,
+            "step circuit: {} ({:.1}%), folding verifier: {} ({:.1}%), Poseidon: {} ({:.1}%), \
+             CycleFold checks: {} ({:.1}%)",
+            self.step,
+            percent(self.step),
+            self.folding_verifier,
+            percent(self.folding_verifier),
+            self.transcript,
+            percent(self.transcript),
+            self.cyclefold,
+            percent(self.cyclefold)
+        )
+    }
+}
+
+/// Mean split of a folding step between the step circuit and the prover.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StepSplit {
+    /// `step_native`, the witness computation.
+    pub witness: Duration,
+    /// `generate_step_constraints`, within the augmented circuit synthesis.
+    pub constraints: Duration,
+    /// The rest of the step: the augmented and CycleFold circuits around the
+    /// step circuit, and folding.
+    pub prover: Duration,
+}
+
+impl fmt::Display for StepSplit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = (self.witness + self.constraints + self.prover).as_secs_f64();
+        let percent = |d: Duration| {
+            if total > 0.0 {
+                d.as_secs_f64() / total * 100.0
+            } else {
+                0.0
+            }
+        };
+        write!(
+            f,
+            "witness: {:?} ({:.1}%), constraints: {:?} ({:.1}%), prover: {:?} ({:.1}%)",
+            self.witness,
+            percent(self.witness),
+            self.constraints,
+            percent(self.constraints),
+            self.prover,
+            percent(self.prover)
+        )
+    }
+}
+
+impl BenchResult {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn avg_step(&self) -> Duration {
+        match self.steps.len() {
+            0 => Duration::ZERO,
+            n => self.steps.iter().sum::<Duration>() / n as u32,
+        }
+    }
+
+    /// Mean split of the steps, when the step circuit was timed.
+    pub fn step_split(&self) -> Option<StepSplit> {
+        if self.step_circuit.is_empty() || self.step_circuit.len() != self.steps.len() {
+            return None;
+        }
+        let n = self.steps.len() as u32;
+        let witness = self.step_circuit.iter().map(|t| t.native).sum::<Duration>() / n;
+        let constraints = self.step_circuit.iter().map(|t| t.constraints).sum::<Duration>() / n;
+        Some(StepSplit {
+            witness,
+            constraints,
+            prover: self.avg_step().saturating_sub(witness + constraints),
+        })
+    }
+
+    /// Spread and drift of the step times, with at least two steps.
+    pub fn step_stats(&self) -> Option<StepStats> {
+        StepStats::new(&self.steps)
+    }
+
+    // The metrics below normalize the raw timings so that circuits of
+    // different sizes compare; they are `None` when an input was not measured.
+
+    /// Folding time per augmented-circuit constraint, in microseconds.
+    pub fn us_per_constraint(&self) -> Option<f64> {
+        (self.augmented_constraints > 0 && !self.steps.is_empty())
+            .then(|| self.avg_step().as_secs_f64() * 1e6 / self.augmented_constraints as f64)
+    }
+
+    /// Augmented-circuit constraints folded per second.
+    pub fn constraints_per_sec(&self) -> Option<f64> {
+        let step = self.avg_step().as_secs_f64();
+        (self.augmented_constraints > 0 && step > 0.0)
+            .then(|| self.augmented_constraints as f64 / step)
+    }
+
+    /// Decider proof bytes per folded step.
+    pub fn proof_bytes_per_step(&self) -> Option<f64> {
+        (self.proof_size > 0 && self.n_steps > 0)
+            .then(|| self.proof_size as f64 / self.n_steps as f64)
+    }
+
+    /// EVM verification gas per folded step.
+    pub fn gas_per_step(&self) -> Option<f64> {
+        self.verify_gas
+            .filter(|_| self.n_steps > 0)
+            .map(|gas| gas as f64 / self.n_steps as f64)
+    }
+
+    pub fn print_metrics(&self) {
+        if let (Some(us), Some(per_sec)) = (self.us_per_constraint(), self.constraints_per_sec()) {
+            println!(
+                "{} {} constraints: {:.4} µs/constraint, {:.0} constraints/s",
+                self.label, self.augmented_constraints, us, per_sec
+            );
+        }
+        if let Some(bytes) = self.proof_bytes_per_step() {
+            println!("{} Proof bytes per step: {:.1}", self.label, bytes);
+        }
+        if let Some(gas) = self.gas_per_step() {
+            println!("{} Gas per step: {:.0}", self.label, gas);
+        }
+        if let Some(stats) = self.step_stats() {
+            println!(
+                "{} Step time: {:?} ± {:?} (CV {:.1}%), min {:?}, max {:?}, last/first quarter {:.2}",
+                self.label,
+                stats.mean,
+                stats.std_dev,
+                stats.cv * 100.0,
+                stats.min,
+                stats.max,
+                stats.drift
+            );
+            if verbosity() >= Verbosity::Normal {
+                print!("{}", histogram(&self.steps, 10, 40));
+            }
+        }
+        if let Some(split) = self.step_split() {
+            println!("{} Step split: {}", self.label, split);
+        }
+    }
+}
+
+/// The median of `results` by total time and its index; the lower one of the
+/// two middle trials for an even count, so that it is a trial that ran.
+pub fn median_trial(results: Vec<BenchResult>) -> Option<(usize, BenchResult)> {
+    let mut order: Vec<usize> = (0..results.len()).collect();
+    order.sort_by_key(|&i| results[i].total);
+    let i = *order.get(order.len().checked_sub(1)? / 2)?;
+    results.into_iter().nth(i).map(|result| (i, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized_metrics() {
+        let mut result = BenchResult::new("test");
+        assert_eq!(result.us_per_constraint(), None);
+        assert_eq!(result.gas_per_step(), None);
+
+        result.n_steps = 4;
+        result.steps = vec![Duration::from_millis(2); 4];
+        result.augmented_constraints = 1000;
+        result.proof_size = 800;
+        result.verify_gas = Some(400_000);
+        assert!((result.us_per_constraint().unwrap() - 2.0).abs() < 1e-9);
+        assert!((result.constraints_per_sec().unwrap() - 500_000.0).abs() < 1e-3);
+        assert_eq!(result.proof_bytes_per_step(), Some(200.0));
+        assert_eq!(result.gas_per_step(), Some(100_000.0));
+    }
+
+    #[test]
+    fn test_augmented_breakdown() {
+        let breakdown = AugmentedBreakdown {
+            step: 1000,
+            folding_verifier: 2000,
+            transcript: 3000,
+            cyclefold: 4000,
+        };
+        assert_eq!(breakdown.total(), 10_000);
+        assert_eq!(breakdown.tax(), Some(10.0));
+        assert_eq!(AugmentedBreakdown::default().tax(), None);
+        assert_eq!(
+            breakdown.to_string(),
+            "step circuit: 1000 (10.0%), folding verifier: 2000 (20.0%), Poseidon: 3000 (30.0%), \
+             CycleFold checks: 4000 (40.0%)"
+        );
+    }
+
+    #[test]
+    fn test_step_split() {
+        let mut result = BenchResult::new("test");
+        result.steps = vec![Duration::from_millis(10), Duration::from_millis(20)];
+        assert_eq!(result.step_split(), None);
+
+        result.step_circuit = vec![
+            StepCircuitTimes {
+                native: Duration::from_millis(6),
+                constraints: Duration::from_millis(1),
+            },
+            StepCircuitTimes {
+                native: Duration::from_millis(8),
+                constraints: Duration::from_millis(3),
+            },
+        ];
+        let split = result.step_split().unwrap();
+        assert_eq!(split.witness, Duration::from_millis(7));
+        assert_eq!(split.constraints, Duration::from_millis(2));
+        assert_eq!(split.prover, Duration::from_millis(6));
+        assert_eq!(
+            split.to_string(),
+            "witness: 7ms (46.7%), constraints: 2ms (13.3%), prover: 6ms (40.0%)"
+        );
+    }
+
+    #[test]
+    fn test_median_trial() {
+        assert!(median_trial(vec![]).is_none());
+        let trials = |totals: &[u64]| -> Vec<BenchResult> {
+            totals
+                .iter()
+                .map(|&t| BenchResult {
+                    total: Duration::from_secs(t),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let (i, median) = median_trial(trials(&[9, 3, 5])).unwrap();
+        assert_eq!((i, median.total), (2, Duration::from_secs(5)));
+        let (i, _) = median_trial(trials(&[4, 8, 2, 6])).unwrap();
+        assert_eq!(i, 0);
+    }
+}
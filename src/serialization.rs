@@ -0,0 +1,72 @@
+//! (De)serialization cost of proofs and params, the overhead a networked
+//! prover pays on top of proving.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+use std::time::{Duration, Instant};
+
+use crate::error::BenchError;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SerializationStats {
+    pub compress: Compress,
+    pub size: usize,
+    pub serialize: Duration,
+    pub deserialize: Duration,
+}
+
+/// Serializes `value` and reads it back, printing the encoded size and both
+/// times.
+pub fn measure_serialization<T>(
+    label: &str,
+    name: &str,
+    value: &T,
+    compress: Compress,
+) -> Result<SerializationStats, BenchError>
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+{
+    let mut bytes = Vec::with_capacity(value.serialized_size(compress));
+    let start = Instant::now();
+    value.serialize_with_mode(&mut bytes, compress)?;
+    let serialize = start.elapsed();
+
+    let start = Instant::now();
+    T::deserialize_with_mode(&bytes[..], compress, Validate::Yes)?;
+    let deserialize = start.elapsed();
+
+    println!(
+        "{} {} ({:?}): {} bytes, serialize {:?}, deserialize {:?}",
+        label,
+        name,
+        compress,
+        bytes.len(),
+        serialize,
+        deserialize
+    );
+    Ok(SerializationStats {
+        compress,
+        size: bytes.len(),
+        serialize,
+        deserialize,
+    })
+}
+
+/// Measures `value` under both point encodings. Compressed points halve the
+/// size but deserializing them costs a square root per point.
+pub fn compare_compression<T>(
+    label: &str,
+    name: &str,
+    value: &T,
+) -> Result<(SerializationStats, SerializationStats), BenchError>
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+{
+    let compressed = measure_serialization(label, name, value, Compress::Yes)?;
+    let uncompressed = measure_serialization(label, name, value, Compress::No)?;
+    println!(
+        "{} {}: compressed is {:.2}x the uncompressed size",
+        label,
+        name,
+        compressed.size as f64 / uncompressed.size as f64
+    );
+    Ok((compressed, uncompressed))
+}
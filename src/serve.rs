@@ -0,0 +1,415 @@
+//! HTTP API to drive the harness remotely, served by `sonobe-bench serve`
+//! with the `serve` feature: the benchmarks run on a dedicated machine and
+//! are started and followed from elsewhere.
+//!
+//! - `POST /runs` with a campaign TOML body (see `campaign`) queues it and
+//!   answers `{"id": N}`;
+//! - `GET /runs` lists the runs and `GET /runs/N` gives the status of one:
+//!   `queued`, `running`, `done` or `failed`;
+//! - `GET /runs/N/events` streams its progress, one line per phase start and
+//!   end, until it finishes;
+//! - `GET /runs/N/result` gives its results once done, an array with the
+//!   step count and the `JsonReporter` array of every run of the campaign.
+//!
+//! Runs are executed one at a time in submission order, so that they do not
+//! disturb each other's timings. Campaigns with an `[output]` table are
+//! rejected: a client must not pick paths the server writes to, so results
+//! are only fetched through `GET /runs/N/result`. There is no
+//! authentication: keep the default loopback address and reach it through an
+//! SSH tunnel.
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::campaign::Campaign;
+use crate::config::OutputSink;
+use crate::error::BenchError;
+use crate::registry::select;
+use crate::report::{json_string, reporters, write_json, Reporter};
+use crate::suite::{run_suite_with, SuiteReport};
+
+/// Largest request body, well above any campaign file.
+const MAX_BODY: usize = 1 << 20;
+/// Largest request line and headers, in bytes and in lines.
+const MAX_HEADER: usize = 8 << 10;
+const MAX_HEADER_LINES: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl RunStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RunStatus::Queued => "queued",
+            RunStatus::Running => "running",
+            RunStatus::Done => "done",
+            RunStatus::Failed => "failed",
+        }
+    }
+
+    fn finished(&self) -> bool {
+        matches!(self, RunStatus::Done | RunStatus::Failed)
+    }
+}
+
+/// A submitted campaign.
+#[derive(Debug)]
+struct Run {
+    status: RunStatus,
+    events: Vec<String>,
+    /// The step count and JSON report of every finished run.
+    results: Vec<(usize, String)>,
+    error: Option<String>,
+}
+
+/// The runs, indexed by id, and a notification for every change.
+#[derive(Default)]
+struct Runs {
+    runs: Mutex<Vec<Run>>,
+    changed: Condvar,
+}
+
+impl Runs {
+    fn update(&self, id: usize, f: impl FnOnce(&mut Run)) {
+        f(&mut self.runs.lock().unwrap()[id]);
+        self.changed.notify_all();
+    }
+
+    fn status_json(&self, id: usize) -> Option<String> {
+        let runs = self.runs.lock().unwrap();
+        let run = runs.get(id)?;
+        Some(format!(
+            "{{\"id\": {}, \"status\": \"{}\", \"events\": {}, \"error\": {}}}",
+            id,
+            run.status.label(),
+            run.events.len(),
+            run.error.as_deref().map_or("null".to_string(), json_string)
+        ))
+    }
+}
+
+/// Records the progress of run `id`, for `GET /runs/N/events`.
+struct ProgressReporter {
+    runs: Arc<Runs>,
+    id: usize,
+    steps: usize,
+}
+
+impl Reporter for ProgressReporter {
+    fn on_phase_start(&mut self, bench: &str, phase: &str) {
+        let event = format!("{} {} started", bench, phase);
+        self.runs.update(self.id, |run| run.events.push(event));
+    }
+
+    fn on_phase_end(&mut self, bench: &str, phase: &str, elapsed: Duration) {
+        let event = format!("{} {} {:?}", bench, phase, elapsed);
+        self.runs.update(self.id, |run| run.events.push(event));
+    }
+
+    fn on_suite_end(&mut self, report: &SuiteReport) -> Result<(), BenchError> {
+        let mut json = Vec::new();
+        write_json(&mut json, report)?;
+        let json = String::from_utf8(json).expect("the JSON report is UTF-8");
+        let steps = self.steps;
+        self.runs
+            .update(self.id, |run| run.results.push((steps, json)));
+        Ok(())
+    }
+}
+
+fn execute(runs: &Arc<Runs>, id: usize, campaign: &Campaign) -> Result<(), BenchError> {
+    for config in &campaign.runs {
+        let event = format!("{} steps", config.steps);
+        runs.update(id, |run| run.events.push(event));
+        let mut sinks = reporters(&config.sinks);
+        sinks.push(Box::new(ProgressReporter {
+            runs: runs.clone(),
+            id,
+            steps: config.steps,
+        }));
+        run_suite_with(select(&campaign.filters), config, sinks)?;
+    }
+    Ok(())
+}
+
+/// Checks that `campaign` writes no files on the server.
+fn check_servable(campaign: &Campaign) -> Result<(), BenchError> {
+    let writes_files = campaign.runs.iter().any(|config| {
+        config
+            .sinks
+            .iter()
+            .any(|sink| *sink != OutputSink::Terminal)
+    });
+    if writes_files {
+        return Err(BenchError::InvalidInput(
+            "served campaigns cannot have an [output] table, fetch the results from \
+             GET /runs/N/result"
+                .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// An HTTP request, read up to its `Content-Length` body.
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+fn bad_request(msg: &str) -> BenchError {
+    BenchError::InvalidInput(format!("bad request: {}", msg))
+}
+
+/// Reads the next line of the request line and headers into `line`, failing
+/// once they exceed `MAX_HEADER` bytes in all, of which `left` remain.
+fn read_header_line(
+    reader: &mut impl BufRead,
+    line: &mut String,
+    left: &mut usize,
+) -> Result<usize, BenchError> {
+    line.clear();
+    let read = reader.take(*left as u64).read_line(line)?;
+    *left -= read;
+    if *left == 0 && !line.ends_with('\n') {
+        return Err(bad_request("headers too large"));
+    }
+    Ok(read)
+}
+
+fn read_request(stream: impl Read) -> Result<Request, BenchError> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    let mut left = MAX_HEADER;
+    read_header_line(&mut reader, &mut line, &mut left)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("no request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    for lines in 0.. {
+        if read_header_line(&mut reader, &mut line, &mut left)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if lines == MAX_HEADER_LINES {
+            return Err(bad_request("too many headers"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad_request("invalid Content-Length"))?;
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(bad_request("body too large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|_| bad_request("body is not UTF-8"))?;
+    Ok(Request { method, path, body })
+}
+
+fn respond(mut stream: &TcpStream, status: &str, body: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+fn error_json(msg: &str) -> String {
+    format!("{{\"error\": {}}}", json_string(msg))
+}
+
+/// Writes the events of run `id` as they come, until it finishes.
+fn stream_events(mut stream: &TcpStream, runs: &Runs, id: usize) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n"
+    )?;
+    let mut sent = 0;
+    let mut guard = runs.runs.lock().unwrap();
+    loop {
+        let run = &guard[id];
+        let (events, finished) = (run.events[sent..].to_vec(), run.status.finished());
+        sent += events.len();
+        // not holding the lock while the client reads
+        drop(guard);
+        for event in events {
+            writeln!(stream, "{}", event)?;
+        }
+        stream.flush()?;
+        if finished {
+            return Ok(());
+        }
+        guard = runs.runs.lock().unwrap();
+        while guard[id].events.len() == sent && !guard[id].status.finished() {
+            guard = runs.changed.wait(guard).unwrap();
+        }
+    }
+}
+
+fn handle(stream: TcpStream, runs: &Arc<Runs>, queue: &Sender<(usize, Campaign)>) {
+    let request = match read_request(&stream) {
+        Ok(request) => request,
+        Err(e) => {
+            let _ = respond(&stream, "400 Bad Request", &error_json(&e.to_string()));
+            return;
+        }
+    };
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let id = segments
+        .get(1)
+        .and_then(|id| id.parse::<usize>().ok())
+        .filter(|id| *id < runs.runs.lock().unwrap().len());
+    let result = match (request.method.as_str(), segments.as_slice(), id) {
+        ("POST", ["runs"], _) => match Campaign::parse(&request.body).and_then(|campaign| {
+            check_servable(&campaign)?;
+            Ok(campaign)
+        }) {
+            Ok(campaign) => {
+                let id = {
+                    let mut all = runs.runs.lock().unwrap();
+                    all.push(Run {
+                        status: RunStatus::Queued,
+                        events: Vec::new(),
+                        results: Vec::new(),
+                        error: None,
+                    });
+                    all.len() - 1
+                };
+                queue
+                    .send((id, campaign))
+                    .expect("the runner thread is alive");
+                respond(&stream, "201 Created", &format!("{{\"id\": {}}}", id))
+            }
+            Err(e) => respond(&stream, "400 Bad Request", &error_json(&e.to_string())),
+        },
+        ("GET", ["runs"], _) => {
+            let count = runs.runs.lock().unwrap().len();
+            let all: Vec<String> = (0..count).filter_map(|id| runs.status_json(id)).collect();
+            respond(&stream, "200 OK", &format!("[{}]", all.join(", ")))
+        }
+        ("GET", ["runs", _], Some(id)) => {
+            respond(&stream, "200 OK", &runs.status_json(id).unwrap_or_default())
+        }
+        ("GET", ["runs", _, "events"], Some(id)) => stream_events(&stream, runs, id),
+        ("GET", ["runs", _, "result"], Some(id)) => {
+            let all = runs.runs.lock().unwrap();
+            let run = &all[id];
+            if run.status.finished() {
+                let results: Vec<String> = run
+                    .results
+                    .iter()
+                    .map(|(steps, json)| format!("{{\"steps\": {}, \"report\": {}}}", steps, json))
+                    .collect();
+                let body = format!("[{}]", results.join(", "));
+                drop(all);
+                respond(&stream, "200 OK", &body)
+            } else {
+                let msg = format!("run {} is {}", id, run.status.label());
+                drop(all);
+                respond(&stream, "409 Conflict", &error_json(&msg))
+            }
+        }
+        _ => respond(&stream, "404 Not Found", &error_json("no such resource")),
+    };
+    if let Err(e) = result {
+        eprintln!("{} {}: {}", request.method, request.path, e);
+    }
+}
+
+/// Serves the API on `addr` (e.g. `127.0.0.1:8080`) until the process ends.
+pub fn serve(addr: &str) -> Result<(), BenchError> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Serving on http://{}", listener.local_addr()?);
+    let runs = Arc::new(Runs::default());
+
+    let (queue, submitted) = mpsc::channel::<(usize, Campaign)>();
+    let runner = runs.clone();
+    thread::spawn(move || {
+        for (id, campaign) in submitted {
+            runner.update(id, |run| run.status = RunStatus::Running);
+            let outcome = execute(&runner, id, &campaign);
+            runner.update(id, |run| match outcome {
+                Ok(()) => run.status = RunStatus::Done,
+                Err(e) => {
+                    run.status = RunStatus::Failed;
+                    run.error = Some(e.to_string());
+                }
+            });
+        }
+    });
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Cannot accept a connection: {}", e);
+                continue;
+            }
+        };
+        let (runs, queue) = (runs.clone(), queue.clone());
+        thread::spawn(move || handle(stream, &runs, &queue));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = "POST /runs HTTP/1.1\r\nHost: bench\r\ncontent-length: 11\r\n\r\nsteps = [5]";
+        let request = read_request(raw.as_bytes()).unwrap();
+        assert_eq!(
+            request,
+            Request {
+                method: "POST".into(),
+                path: "/runs".into(),
+                body: "steps = [5]".into(),
+            }
+        );
+        assert!(Campaign::parse(&request.body).is_ok());
+
+        assert!(read_request("\r\n".as_bytes()).is_err());
+        let truncated = "POST /runs HTTP/1.1\r\nContent-Length: 20\r\n\r\nsteps";
+        assert!(read_request(truncated.as_bytes()).is_err());
+        let long = format!(
+            "GET /runs HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER)
+        );
+        assert!(read_request(long.as_bytes()).is_err());
+        let many = format!(
+            "GET /runs HTTP/1.1\r\n{}\r\n",
+            "X: a\r\n".repeat(MAX_HEADER_LINES + 1)
+        );
+        assert!(read_request(many.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_output_rejected() {
+        let campaign = Campaign::parse("steps = [5]").unwrap();
+        assert!(check_servable(&campaign).is_ok());
+        let campaign = Campaign::parse("[output]\njson = \"/etc/passwd\"").unwrap();
+        assert!(check_servable(&campaign).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Snapshots of the Nova folding state, to stop a chain after some step and
+//! resume it later, possibly in another process.
+//!
+//! Only the state that changes between steps is stored, with the `pp_hash`
+//! of the params it was folded under; params and circuits are rebuilt by
+//! `FoldingScheme::init` (or loaded from the `cache`) before `restore`, which
+//! refuses a snapshot of other params. `fold_resumable` is what
+//! `sonobe-bench --snapshot-out` and `--resume-from` run.
+use ark_ec::CurveGroup;
+use ark_r1cs_std::{groups::GroupOpsBounds, prelude::CurveVar, ToConstraintFieldGadget};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use crate::error::BenchError;
+use folding_schemes::{
+    commitment::CommitmentScheme,
+    folding::{
+        circuits::CF2,
+        nova::{CommittedInstance, Nova, Witness},
+    },
+    frontend::FCircuit,
+};
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct NovaSnapshot<C1: CurveGroup, C2: CurveGroup> {
+    /// Hash of the params the state was folded under.
+    pub pp_hash: C1::ScalarField,
+    pub i: C1::ScalarField,
+    pub z_0: Vec<C1::ScalarField>,
+    pub z_i: Vec<C1::ScalarField>,
+    pub w_i: Witness<C1>,
+    pub u_i: CommittedInstance<C1>,
+    pub W_i: Witness<C1>,
+    pub U_i: CommittedInstance<C1>,
+    pub cf_W_i: Witness<C2>,
+    pub cf_U_i: CommittedInstance<C2>,
+}
+
+impl<C1: CurveGroup, C2: CurveGroup> NovaSnapshot<C1, C2> {
+    pub fn take<GC1, GC2, FC, CS1, CS2, const H: bool>(
+        nova: &Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>,
+    ) -> Self
+    where
+        GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+        GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, H>,
+        CS2: CommitmentScheme<C2, H>,
+        for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+        for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+    {
+        Self {
+            pp_hash: nova.pp_hash,
+            i: nova.i,
+            z_0: nova.z_0.clone(),
+            z_i: nova.z_i.clone(),
+            w_i: nova.w_i.clone(),
+            u_i: nova.u_i.clone(),
+            W_i: nova.W_i.clone(),
+            U_i: nova.U_i.clone(),
+            cf_W_i: nova.cf_W_i.clone(),
+            cf_U_i: nova.cf_U_i.clone(),
+        }
+    }
+
+    /// Overwrites the folding state of `nova`, which must have been
+    /// initialized with the same circuit as the snapshotted one. Fails if it
+    /// was initialized with other params.
+    pub fn restore<GC1, GC2, FC, CS1, CS2, const H: bool>(
+        self,
+        nova: &mut Nova<C1, GC1, C2, GC2, FC, CS1, CS2, H>,
+    ) -> Result<(), BenchError>
+    where
+        GC1: CurveVar<C1, CF2<C1>> + ToConstraintFieldGadget<CF2<C1>>,
+        GC2: CurveVar<C2, CF2<C2>> + ToConstraintFieldGadget<CF2<C2>>,
+        FC: FCircuit<C1::ScalarField>,
+        CS1: CommitmentScheme<C1, H>,
+        CS2: CommitmentScheme<C2, H>,
+        for<'a> &'a GC1: GroupOpsBounds<'a, C1, GC1>,
+        for<'a> &'a GC2: GroupOpsBounds<'a, C2, GC2>,
+    {
+        if self.pp_hash != nova.pp_hash {
+            return Err(BenchError::InvalidInput(
+                "snapshot was folded under other params".to_string(),
+            ));
+        }
+        nova.i = self.i;
+        nova.z_0 = self.z_0;
+        nova.z_i = self.z_i;
+        nova.w_i = self.w_i;
+        nova.u_i = self.u_i;
+        nova.W_i = self.W_i;
+        nova.U_i = self.U_i;
+        nova.cf_W_i = self.cf_W_i;
+        nova.cf_U_i = self.cf_U_i;
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BenchError> {
+        let file = File::create(path)?;
+        self.serialize_compressed(BufWriter::new(file))?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BenchError> {
+        let file = File::open(path)?;
+        Ok(Self::deserialize_compressed(BufReader::new(file))?)
+    }
+}
+
+/// Folds `config.steps` steps of `f_circuit` with Nova on BN254, first
+/// restoring the state saved at `resume_from`, and saves the state reached
+/// to `snapshot_out`, so that one chain can be folded over several runs.
+/// The params come from the params cache, or from a fixed `--seed`, so that
+/// every run folds under the same ones; fresh params make `restore` fail.
+#[cfg(feature = "bn254")]
+pub fn fold_resumable<FC: FCircuit<ark_bn254::Fr>>(
+    label: &str,
+    f_circuit: FC,
+    z_0: Vec<ark_bn254::Fr>,
+    config: &crate::config::BenchConfig,
+    resume_from: Option<&Path>,
+    snapshot_out: Option<&Path>,
+) -> Result<crate::solidity::NovaBn<FC>, BenchError> {
+    use crate::bench::{fold_steps, no_external_inputs};
+    use crate::cache::ParamCache;
+    use crate::config::{poseidon_config, poseidon_params, rng};
+    use crate::solidity::NovaBn;
+    use folding_schemes::{folding::nova::PreprocessorParam, FoldingScheme};
+
+    let prep_param = PreprocessorParam::new(poseidon_config()?, f_circuit.clone());
+    let inputs = (poseidon_params()?, config.seed);
+    let key = ParamCache::key(label, "bn254", "nova-fold", &inputs);
+    let (fs_params, _) = config.param_cache().get_or_insert_with(label, &key, || {
+        Ok(NovaBn::<FC>::preprocess(&mut rng(), &prep_param)?)
+    })?;
+    let mut nova = NovaBn::<FC>::init(&fs_params, f_circuit, z_0)?;
+    if let Some(path) = resume_from {
+        NovaSnapshot::load(path)?.restore(&mut nova)?;
+        println!("{} Resumed from {:?} at step {}", label, path, nova.i);
+    }
+    fold_steps(label, &mut nova, config.steps, no_external_inputs)?;
+    if let Some(path) = snapshot_out {
+        NovaSnapshot::take(&nova).save(path)?;
+        println!("{} Saved the state at step {} to {:?}", label, nova.i, path);
+    }
+    Ok(nova)
+}
@@ -0,0 +1,204 @@
+//! snarkjs export of the Groth16 proof inside the BN254 `DeciderEth` proofs,
+//! so that tooling built around snarkjs (`snarkjs groth16 verify`, its
+//! calldata and Solidity verifier generation) takes them as they are.
+//!
+//! `proof.json`, `public.json` and `verification_key.json` are written in
+//! snarkjs's layout: decimal strings, projective points with `z = 1`, and
+//! `Fq2` elements as `[c0, c1]`. Only the Groth16 part has a snarkjs
+//! counterpart: the KZG openings that `DeciderBn::verify` also checks are
+//! left out, so snarkjs only attests that the decider circuit is satisfied
+//! for the public inputs.
+use ark_bn254::{Bn254, Fq12, Fq2, Fr, G1Affine, G1Projective as G1, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_grumpkin::Projective as G2;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use folding_schemes::{frontend::FCircuit, Decider};
+
+use crate::batch::groth16_part;
+use crate::error::BenchError;
+use crate::solidity::{DeciderBn, NovaBn};
+
+type VerifierParam<FC> = <DeciderBn<FC> as Decider<G1, G2, FC, NovaBn<FC>>>::VerifierParam;
+type DeciderProof<FC> = <DeciderBn<FC> as Decider<G1, G2, FC, NovaBn<FC>>>::Proof;
+
+/// Files written by `write_snarkjs`.
+#[derive(Clone, Debug)]
+pub struct SnarkjsExport {
+    pub proof_path: PathBuf,
+    pub public_path: PathBuf,
+    pub vk_path: PathBuf,
+    pub public_inputs: usize,
+}
+
+fn decimal(x: impl PrimeField) -> String {
+    format!("\"{}\"", x.into_bigint())
+}
+
+fn json_fq2(x: &Fq2) -> String {
+    format!("[{}, {}]", decimal(x.c0), decimal(x.c1))
+}
+
+fn json_g1(p: &G1Affine) -> String {
+    if p.infinity {
+        return "[\"0\", \"1\", \"0\"]".to_string();
+    }
+    format!("[{}, {}, \"1\"]", decimal(p.x), decimal(p.y))
+}
+
+fn json_g2(p: &G2Affine) -> String {
+    if p.infinity {
+        return "[[\"0\", \"0\"], [\"1\", \"0\"], [\"0\", \"0\"]]".to_string();
+    }
+    format!("[{}, {}, [\"1\", \"0\"]]", json_fq2(&p.x), json_fq2(&p.y))
+}
+
+fn json_fq12(x: &Fq12) -> String {
+    let fq6 = |c: &ark_bn254::Fq6| {
+        format!(
+            "[{}, {}, {}]",
+            json_fq2(&c.c0),
+            json_fq2(&c.c1),
+            json_fq2(&c.c2)
+        )
+    };
+    format!("[{}, {}]", fq6(&x.c0), fq6(&x.c1))
+}
+
+/// `proof.json`.
+pub fn json_proof(proof: &Proof<Bn254>) -> String {
+    format!(
+        "{{\n  \"pi_a\": {},\n  \"pi_b\": {},\n  \"pi_c\": {},\n  \"protocol\": \"groth16\",\n  \
+         \"curve\": \"bn128\"\n}}\n",
+        json_g1(&proof.a),
+        json_g2(&proof.b),
+        json_g1(&proof.c)
+    )
+}
+
+/// `public.json`.
+pub fn json_public(inputs: &[Fr]) -> String {
+    let inputs: Vec<String> = inputs.iter().map(|x| decimal(*x)).collect();
+    format!("[{}]\n", inputs.join(", "))
+}
+
+/// `verification_key.json`.
+pub fn json_verifying_key(vk: &VerifyingKey<Bn254>) -> String {
+    let ic: Vec<String> = vk.gamma_abc_g1.iter().map(json_g1).collect();
+    let alphabeta = Bn254::pairing(vk.alpha_g1, vk.beta_g2).0;
+    format!(
+        "{{\n  \"protocol\": \"groth16\",\n  \"curve\": \"bn128\",\n  \"nPublic\": {},\n  \
+         \"vk_alpha_1\": {},\n  \"vk_beta_2\": {},\n  \"vk_gamma_2\": {},\n  \
+         \"vk_delta_2\": {},\n  \"vk_alphabeta_12\": {},\n  \"IC\": [{}]\n}}\n",
+        vk.gamma_abc_g1.len().saturating_sub(1),
+        json_g1(&vk.alpha_g1),
+        json_g2(&vk.beta_g2),
+        json_g2(&vk.gamma_g2),
+        json_g2(&vk.delta_g2),
+        json_fq12(&alphabeta),
+        ic.join(", ")
+    )
+}
+
+/// Writes the snarkjs files of a Groth16 `proof` of `inputs` under `vk` into
+/// `out_dir`.
+pub fn write_snarkjs(
+    out_dir: impl AsRef<Path>,
+    vk: &VerifyingKey<Bn254>,
+    inputs: &[Fr],
+    proof: &Proof<Bn254>,
+) -> Result<SnarkjsExport, BenchError> {
+    let out_dir = out_dir.as_ref();
+    fs::create_dir_all(out_dir)?;
+    let export = SnarkjsExport {
+        proof_path: out_dir.join("proof.json"),
+        public_path: out_dir.join("public.json"),
+        vk_path: out_dir.join("verification_key.json"),
+        public_inputs: inputs.len(),
+    };
+    fs::write(&export.proof_path, json_proof(proof))?;
+    fs::write(&export.public_path, json_public(inputs))?;
+    fs::write(&export.vk_path, json_verifying_key(vk))?;
+    Ok(export)
+}
+
+/// Exports the Groth16 part of `proof` over the state of `nova` with the
+/// Groth16 key of `decider_vp` into `out_dir`, once checked to verify.
+pub fn export_snarkjs<FC: FCircuit<Fr>>(
+    label: &str,
+    out_dir: impl AsRef<Path>,
+    nova: &NovaBn<FC>,
+    decider_vp: &VerifierParam<FC>,
+    proof: &DeciderProof<FC>,
+) -> Result<SnarkjsExport, BenchError> {
+    let (inputs, snark_proof) = groth16_part(nova, proof)?;
+    let vk = &decider_vp.1;
+    let pvk = Groth16::<Bn254>::process_vk(vk).map_err(folding_schemes::Error::from)?;
+    let verified = Groth16::<Bn254>::verify_proof(&pvk, &snark_proof, &inputs)
+        .map_err(folding_schemes::Error::from)?;
+    if !verified {
+        return Err(BenchError::Verification(format!(
+            "{} Groth16 proof rejected; its public inputs do not match the decider's",
+            label
+        )));
+    }
+    let export = write_snarkjs(out_dir, vk, &inputs, &snark_proof)?;
+    println!(
+        "{} Wrote snarkjs {:?}, {:?} ({} public inputs) and {:?}",
+        label, export.proof_path, export.public_path, export.public_inputs, export.vk_path
+    );
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::AffineRepr;
+
+    #[test]
+    fn test_snarkjs_json() {
+        let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+        assert_eq!(json_g1(&g1), "[\"1\", \"2\", \"1\"]");
+        assert_eq!(json_g1(&G1Affine::zero()), "[\"0\", \"1\", \"0\"]");
+        assert!(json_g2(&g2).starts_with(
+            "[[\"10857046999023057135944570762232829481370756359578518086990519993285655852781\", "
+        ));
+        assert!(json_g2(&g2).ends_with(", [\"1\", \"0\"]]"));
+        let minus_one =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616";
+        assert_eq!(
+            json_public(&[Fr::from(0_u32), -Fr::from(1_u32)]),
+            format!("[\"0\", \"{}\"]\n", minus_one)
+        );
+
+        let vk = VerifyingKey::<Bn254> {
+            alpha_g1: g1,
+            beta_g2: g2,
+            gamma_g2: g2,
+            delta_g2: g2,
+            gamma_abc_g1: vec![g1; 3],
+        };
+        let proof = Proof::<Bn254> {
+            a: g1,
+            b: g2,
+            c: g1,
+        };
+        let dir = std::env::temp_dir().join(format!("snarkjs-{}", std::process::id()));
+        let inputs = [Fr::from(7_u32), Fr::from(8_u32)];
+        let export = write_snarkjs(&dir, &vk, &inputs, &proof).unwrap();
+        let proof_json = fs::read_to_string(&export.proof_path).unwrap();
+        assert!(proof_json.contains("\"pi_a\": [\"1\", \"2\", \"1\"]"));
+        assert!(proof_json.contains("\"protocol\": \"groth16\""));
+        assert_eq!(
+            fs::read_to_string(&export.public_path).unwrap(),
+            "[\"7\", \"8\"]\n"
+        );
+        let vk_json = fs::read_to_string(&export.vk_path).unwrap();
+        assert!(vk_json.contains("\"nPublic\": 2"));
+        assert!(vk_json.contains("\"vk_alphabeta_12\": [[["));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
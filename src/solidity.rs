@@ -0,0 +1,357 @@
+//! Solidity verifier export for the BN254 `DeciderEth` proofs.
+//!
+//! Only BN254 has the precompiles the generated verifier relies on, so this
+//! stage exists for that cycle alone.
+//!
+//! With the `evm` feature the exported verifier can also be deployed into an
+//! in-process revm instance to measure the exact verification gas; this needs
+//! `solc` on the `PATH`. The `anvil` feature goes one step further and sends
+//! the deployment and the verification as transactions to a local anvil node.
+use ark_bn254::{constraints::GVar, Bn254, Fr, G1Projective as G1};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::Groth16;
+use ark_grumpkin::{constraints::GVar as GVar2, Projective as G2};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use folding_schemes::{
+    commitment::{kzg::KZG, pedersen::Pedersen},
+    folding::nova::{
+        decider_eth::{prepare_calldata, Decider as DeciderEth},
+        Nova,
+    },
+    frontend::FCircuit,
+    Decider,
+};
+#[cfg(feature = "evm")]
+use solidity_verifiers::evm::{compile_solidity, Evm};
+use solidity_verifiers::{
+    utils::get_function_selector_for_nova_cyclefold_verifier,
+    verifiers::nova_cyclefold::get_decider_template_for_cyclefold_decider,
+    NovaCycleFoldVerifierKey,
+};
+
+use crate::error::BenchError;
+#[cfg(feature = "evm")]
+use crate::result::BenchResult;
+
+pub type NovaBn<FC> = Nova<G1, GVar, G2, GVar2, FC, KZG<'static, Bn254>, Pedersen<G2>, false>;
+pub type DeciderBn<FC> = DeciderEth<
+    G1,
+    GVar,
+    G2,
+    GVar2,
+    FC,
+    KZG<'static, Bn254>,
+    Pedersen<G2>,
+    Groth16<Bn254>,
+    NovaBn<FC>,
+>;
+
+type VerifierParam<FC> = <DeciderBn<FC> as Decider<G1, G2, FC, NovaBn<FC>>>::VerifierParam;
+type Proof<FC> = <DeciderBn<FC> as Decider<G1, G2, FC, NovaBn<FC>>>::Proof;
+
+/// Name of the contract emitted by the decider template.
+pub const VERIFIER_CONTRACT: &str = "NovaDecider";
+
+/// Generated verifier contract and the calldata verifying one proof with it.
+#[derive(Clone, Debug)]
+pub struct SolidityVerifier {
+    pub source: String,
+    pub calldata: Vec<u8>,
+    /// Files the contract and calldata were written to.
+    pub source_path: PathBuf,
+    pub calldata_path: PathBuf,
+}
+
+/// ABI-encoded call: a 4-byte function selector followed by 32-byte words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiCalldata {
+    pub selector: [u8; 4],
+    pub words: Vec<[u8; 32]>,
+}
+
+impl AbiCalldata {
+    /// Splits `calldata` into selector and words, `None` if it is not
+    /// word-aligned.
+    pub fn decode(calldata: &[u8]) -> Option<Self> {
+        if calldata.len() < 4 || (calldata.len() - 4) % 32 != 0 {
+            return None;
+        }
+        Some(Self {
+            selector: calldata[..4].try_into().unwrap(),
+            words: calldata[4..]
+                .chunks(32)
+                .map(|w| w.try_into().unwrap())
+                .collect(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut calldata = self.selector.to_vec();
+        self.words.iter().for_each(|w| calldata.extend_from_slice(w));
+        calldata
+    }
+
+    /// `0x`-prefixed hex of the encoded call, as taken by `cast` and ethers.
+    pub fn to_hex(&self) -> String {
+        std::iter::once("0x".to_string())
+            .chain(self.encode().iter().map(|b| format!("{:02x}", b)))
+            .collect()
+    }
+}
+
+/// Big-endian `uint256` word of a field element.
+fn field_word(x: Fr) -> [u8; 32] {
+    x.into_bigint().to_bytes_be().try_into().unwrap()
+}
+
+/// Calldata verifying `proof` for the current state of `nova`. The encoding is
+/// decoded back and its leading words (`i`, `z_0`, `z_i`) are checked against
+/// the IVC state, failing with `BenchError::Verification` if they differ.
+pub fn verification_calldata<FC: FCircuit<Fr>>(
+    nova: &NovaBn<FC>,
+    proof: Proof<FC>,
+) -> Result<Vec<u8>, BenchError> {
+    let function_selector =
+        get_function_selector_for_nova_cyclefold_verifier(nova.z_0.len() * 2 + 1);
+    let calldata = prepare_calldata(
+        function_selector,
+        nova.i,
+        nova.z_0.clone(),
+        nova.z_i.clone(),
+        &nova.U_i,
+        &nova.u_i,
+        proof,
+    )?;
+
+    let invalid = |msg: &str| BenchError::Verification(format!("calldata {}", msg));
+    let decoded = AbiCalldata::decode(&calldata).ok_or_else(|| invalid("is not word-aligned"))?;
+    if decoded.encode() != calldata {
+        return Err(invalid("does not re-encode to itself"));
+    }
+    if decoded.selector != function_selector {
+        return Err(invalid("has the wrong function selector"));
+    }
+    let public_inputs = std::iter::once(nova.i)
+        .chain(nova.z_0.iter().copied())
+        .chain(nova.z_i.iter().copied())
+        .map(field_word);
+    if !public_inputs.eq(decoded.words.iter().copied().take(nova.z_0.len() * 2 + 1)) {
+        return Err(invalid("does not start with the IVC state"));
+    }
+    Ok(calldata)
+}
+
+/// Generates the Solidity verifier for `decider_vp` and the calldata for
+/// `proof` over the state of `nova`, and writes them into `out_dir` as
+/// `NovaDecider.sol`, `calldata.bin` and `calldata.hex`.
+pub fn export_decider_verifier<FC: FCircuit<Fr>>(
+    label: &str,
+    out_dir: impl AsRef<Path>,
+    nova: &NovaBn<FC>,
+    decider_vp: VerifierParam<FC>,
+    proof: Proof<FC>,
+) -> Result<SolidityVerifier, BenchError> {
+    let calldata = verification_calldata(nova, proof)?;
+    let vk = NovaCycleFoldVerifierKey::from((decider_vp, nova.z_0.len()));
+    let source = get_decider_template_for_cyclefold_decider(vk);
+
+    let out_dir = out_dir.as_ref();
+    fs::create_dir_all(out_dir)?;
+    let source_path = out_dir.join(format!("{}.sol", VERIFIER_CONTRACT));
+    let calldata_path = out_dir.join("calldata.bin");
+    fs::write(&source_path, &source)?;
+    fs::write(&calldata_path, &calldata)?;
+    fs::write(
+        out_dir.join("calldata.hex"),
+        AbiCalldata::decode(&calldata).unwrap().to_hex(),
+    )?;
+
+    println!("{} Solidity verifier: {} bytes", label, source.len());
+    println!("{} Calldata: {} bytes", label, calldata.len());
+    println!("{} Wrote {:?} and {:?}", label, source_path, calldata_path);
+
+    Ok(SolidityVerifier {
+        source,
+        calldata,
+        source_path,
+        calldata_path,
+    })
+}
+
+/// Gas used deploying and calling the verifier.
+#[cfg(feature = "evm")]
+#[derive(Clone, Copy, Debug)]
+pub struct VerificationGas {
+    pub bytecode_size: usize,
+    pub verify_gas: u64,
+}
+
+/// Compiles and deploys `verifier` into a fresh revm instance, then executes
+/// its calldata, failing if the proof is rejected.
+#[cfg(feature = "evm")]
+pub fn measure_verification_gas(
+    label: &str,
+    verifier: &SolidityVerifier,
+) -> Result<VerificationGas, BenchError> {
+    let bytecode = compile_solidity(&verifier.source, VERIFIER_CONTRACT);
+    let bytecode_size = bytecode.len();
+    let mut evm = Evm::default();
+    let address = evm.create(bytecode);
+    let (verify_gas, output) = evm.call(address, verifier.calldata.clone());
+    if output.last() != Some(&1) {
+        return Err(BenchError::Verification(format!(
+            "{} EVM rejected the decider proof",
+            label
+        )));
+    }
+
+    println!("{} Verifier bytecode: {} bytes", label, bytecode_size);
+    println!("{} EVM verification gas: {}", label, verify_gas);
+    Ok(VerificationGas {
+        bytecode_size,
+        verify_gas,
+    })
+}
+
+/// `after_decider` stage of `bench::run_nova_bench_with`: exports the
+/// verifier of the trial to `target/solidity/<label>` and records its EVM
+/// verification gas in `result`.
+#[cfg(feature = "evm")]
+pub fn record_verification_gas<FC: FCircuit<Fr>>(
+    nova: &NovaBn<FC>,
+    decider_vp: VerifierParam<FC>,
+    proof: Proof<FC>,
+    result: &mut BenchResult,
+) -> Result<(), BenchError> {
+    let label = result.label.clone();
+    let out_dir = Path::new("target/solidity")
+        .join(label.replace(|c: char| !c.is_ascii_alphanumeric(), "_"));
+    let verifier = export_decider_verifier(&label, out_dir, nova, decider_vp, proof)?;
+    result.verify_gas = Some(measure_verification_gas(&label, &verifier)?.verify_gas);
+    Ok(())
+}
+
+/// Deploys `verifier` to a freshly spawned anvil node (the `anvil` binary
+/// must be on the `PATH`) and submits the verification as a transaction,
+/// failing with `BenchError::Verification` if anvil cannot be reached or
+/// rejects it. Returns the gas used by the transaction.
+#[cfg(feature = "anvil")]
+pub async fn submit_to_anvil(label: &str, verifier: &SolidityVerifier) -> Result<u128, BenchError> {
+    use alloy::{
+        network::{EthereumWallet, TransactionBuilder},
+        node_bindings::Anvil,
+        providers::{Provider, ProviderBuilder},
+        rpc::types::TransactionRequest,
+        signers::local::PrivateKeySigner,
+    };
+
+    let failed = |what: &str, e: &dyn std::fmt::Display| {
+        BenchError::Verification(format!("{} anvil {}: {}", label, what, e))
+    };
+    let anvil = Anvil::new().try_spawn().map_err(|e| failed("spawn", &e))?;
+    let signer: PrivateKeySigner = anvil.keys()[0].clone().into();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(EthereumWallet::from(signer))
+        .on_http(anvil.endpoint_url());
+
+    let bytecode = compile_solidity(&verifier.source, VERIFIER_CONTRACT);
+    let deploy = TransactionRequest::default().with_deploy_code(bytecode);
+    let receipt = provider
+        .send_transaction(deploy)
+        .await
+        .map_err(|e| failed("deployment", &e))?
+        .get_receipt()
+        .await
+        .map_err(|e| failed("deployment", &e))?;
+    let address = receipt
+        .contract_address
+        .ok_or_else(|| failed("deployment", &"no contract address in the receipt"))?;
+    println!("{} Deployed {} at {}", label, VERIFIER_CONTRACT, address);
+
+    let verify = TransactionRequest::default()
+        .with_to(address)
+        .with_input(verifier.calldata.clone());
+    let output = provider
+        .call(&verify)
+        .await
+        .map_err(|e| failed("call", &e))?;
+    if output.last() != Some(&1) {
+        return Err(BenchError::Verification(format!(
+            "{} anvil rejected the decider proof",
+            label
+        )));
+    }
+    let receipt = provider
+        .send_transaction(verify)
+        .await
+        .map_err(|e| failed("verification", &e))?
+        .get_receipt()
+        .await
+        .map_err(|e| failed("verification", &e))?;
+    if !receipt.status() {
+        return Err(BenchError::Verification(format!(
+            "{} verification transaction reverted",
+            label
+        )));
+    }
+
+    println!("{} anvil verification tx gas: {}", label, receipt.gas_used);
+    Ok(receipt.gas_used)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abi_calldata_roundtrip() {
+        let mut calldata = vec![0xde, 0xad, 0xbe, 0xef];
+        calldata.extend(field_word(Fr::from(7_u32)));
+        calldata.extend([0xff; 32]);
+        let decoded = AbiCalldata::decode(&calldata).unwrap();
+        assert_eq!(decoded.words[0][31], 7);
+        assert_eq!(decoded.encode(), calldata);
+        assert!(decoded.to_hex().starts_with("0xdeadbeef"));
+        assert_eq!(decoded.to_hex().len(), 2 + 2 * calldata.len());
+
+        assert!(AbiCalldata::decode(&calldata[..40]).is_none());
+    }
+
+    // Needs `solc` and `anvil` installed, run with
+    // `cargo test --features anvil -- --ignored test_anvil_end_to_end`.
+    #[cfg(feature = "anvil")]
+    #[tokio::test]
+    #[ignore]
+    async fn test_anvil_end_to_end() {
+        use crate::bench::{no_external_inputs, run_folding_bench};
+        use crate::CubicFCircuit;
+        use folding_schemes::{
+            folding::nova::PreprocessorParam, transcript::poseidon::poseidon_canonical_config,
+        };
+
+        let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+        let poseidon_config = poseidon_canonical_config::<Fr>();
+        let (nova, decider_vp, proof) = run_folding_bench::<
+            G1,
+            G2,
+            _,
+            NovaBn<CubicFCircuit<Fr>>,
+            DeciderBn<CubicFCircuit<Fr>>,
+        >(
+            "BN254 anvil",
+            PreprocessorParam::new(poseidon_config, f_circuit),
+            f_circuit,
+            vec![Fr::from(3_u32)],
+            5,
+            no_external_inputs,
+        )
+        .unwrap();
+        let verifier =
+            export_decider_verifier("BN254 anvil", "target/anvil", &nova, decider_vp, proof)
+                .unwrap();
+        assert!(submit_to_anvil("BN254 anvil", &verifier).await.unwrap() > 0);
+    }
+}
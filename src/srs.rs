@@ -0,0 +1,312 @@
+//! Loading a pre-generated BN254 KZG SRS instead of sampling one per run.
+//!
+//! Two formats are read: snarkjs/Hermez Powers-of-Tau files (`.ptau`), and
+//! the arkworks compressed encoding of `(powers_of_g, vk)` written by
+//! `save_srs`, which loads much faster and is used for any other extension.
+//!
+//! `load_srs` reads and deserializes the whole file. With the `mmap-srs`
+//! feature, `load_srs_mmap` maps it instead and deserializes only the powers
+//! it is asked for, so that a circuit of `2^16` constraints pays for `2^16`
+//! powers whatever the size of the ceremony file, and the rest of it never
+//! becomes resident; `compare_srs_loading` reports both side by side.
+use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{BigInt, BigInteger, PrimeField};
+use ark_poly_commit::kzg10::VerifierKey;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use folding_schemes::commitment::kzg::ProverKey;
+
+use crate::error::BenchError;
+
+/// KZG prover and verifier keys, in the form `PreprocessorParam` takes them.
+pub type Srs = (ProverKey<'static, G1Projective>, VerifierKey<Bn254>);
+
+/// Loads at most `n_powers` powers of tau from `path`, printing the load time.
+pub fn load_srs(label: &str, path: impl AsRef<Path>, n_powers: usize) -> Result<Srs, BenchError> {
+    let path = path.as_ref();
+    let start = Instant::now();
+    let (mut powers_of_g, vk) = if path.extension().is_some_and(|e| e == "ptau") {
+        read_ptau(&fs::read(path)?, n_powers)?
+    } else {
+        let file = File::open(path)?;
+        <(Vec<G1Affine>, VerifierKey<Bn254>)>::deserialize_compressed_unchecked(BufReader::new(
+            file,
+        ))?
+    };
+    if powers_of_g.len() < n_powers {
+        return Err(BenchError::InvalidInput(format!(
+            "{:?} holds {} powers, {} needed",
+            path,
+            powers_of_g.len(),
+            n_powers
+        )));
+    }
+    powers_of_g.truncate(n_powers);
+    println!("{} SRS load ({} powers): {:?}", label, n_powers, start.elapsed());
+    Ok((
+        ProverKey {
+            powers_of_g: Cow::Owned(powers_of_g),
+        },
+        vk,
+    ))
+}
+
+/// `load_srs` from a memory map of `path`, deserializing only the first
+/// `n_powers` powers.
+#[cfg(feature = "mmap-srs")]
+pub fn load_srs_mmap(
+    label: &str,
+    path: impl AsRef<Path>,
+    n_powers: usize,
+) -> Result<Srs, BenchError> {
+    let path = path.as_ref();
+    let start = Instant::now();
+    let file = File::open(path)?;
+    // SAFETY: the file is only read, and is not expected to change while
+    // the benchmark runs
+    let data = unsafe { memmap2::Mmap::map(&file)? };
+    let (mut powers_of_g, vk) = if path.extension().is_some_and(|e| e == "ptau") {
+        read_ptau(&data, n_powers)?
+    } else {
+        read_arkworks_prefix(&data, n_powers)?
+    };
+    if powers_of_g.len() < n_powers {
+        return Err(BenchError::InvalidInput(format!(
+            "{:?} holds {} powers, {} needed",
+            path,
+            powers_of_g.len(),
+            n_powers
+        )));
+    }
+    powers_of_g.truncate(n_powers);
+    println!("{} SRS mmap load ({} powers): {:?}", label, n_powers, start.elapsed());
+    Ok((
+        ProverKey {
+            powers_of_g: Cow::Owned(powers_of_g),
+        },
+        vk,
+    ))
+}
+
+/// Time and resident memory of loading an SRS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SrsLoad {
+    pub time: Duration,
+    /// Growth of the resident set while the SRS is held, `None` off Linux.
+    pub rss_kib: Option<u64>,
+}
+
+/// Loads `n_powers` powers of `path` memory-mapped and then in memory,
+/// printing the load time and resident memory of each. The memory-mapped
+/// load runs first: memory freed by the other may stay with the allocator.
+#[cfg(feature = "mmap-srs")]
+pub fn compare_srs_loading(
+    label: &str,
+    path: impl AsRef<Path>,
+    n_powers: usize,
+) -> Result<[SrsLoad; 2], BenchError> {
+    use crate::sweep::rss_kib;
+
+    let path = path.as_ref();
+    let measure = |load: &dyn Fn() -> Result<Srs, BenchError>| -> Result<SrsLoad, BenchError> {
+        let before = rss_kib();
+        let start = Instant::now();
+        let srs = load()?;
+        let time = start.elapsed();
+        let resident = rss_kib().zip(before).map(|(after, before)| after.saturating_sub(before));
+        drop(srs);
+        Ok(SrsLoad {
+            time,
+            rss_kib: resident,
+        })
+    };
+    let mmap = measure(&|| load_srs_mmap(label, path, n_powers))?;
+    let full = measure(&|| load_srs(label, path, n_powers))?;
+    let rss = |load: &SrsLoad| load.rss_kib.map_or("n/a".to_string(), |k| format!("{} KiB", k));
+    println!(
+        "{} SRS load of {} powers: mmap {:?} ({} resident), in memory {:?} ({} resident)",
+        label,
+        n_powers,
+        mmap.time,
+        rss(&mmap),
+        full.time,
+        rss(&full)
+    );
+    Ok([mmap, full])
+}
+
+/// The first `n_powers` powers and the verifier key of the encoding written
+/// by `save_srs`, deserializing none of the other powers.
+#[cfg(feature = "mmap-srs")]
+fn read_arkworks_prefix(
+    data: &[u8],
+    n_powers: usize,
+) -> Result<(Vec<G1Affine>, VerifierKey<Bn254>), BenchError> {
+    // a `Vec` is its length as a `u64`, then its elements
+    let len = u64::deserialize_compressed(data)? as usize;
+    let point = G1Affine::generator().compressed_size();
+    let vk_start = len
+        .checked_mul(point)
+        .and_then(|n| n.checked_add(8))
+        .filter(|&start| start <= data.len())
+        .ok_or_else(|| BenchError::InvalidInput("truncated SRS file".to_string()))?;
+    let powers_of_g = data[8..vk_start]
+        .chunks(point)
+        .take(n_powers)
+        .map(G1Affine::deserialize_compressed_unchecked)
+        .collect::<Result<Vec<_>, _>>()?;
+    let vk = VerifierKey::deserialize_compressed_unchecked(&data[vk_start..])?;
+    Ok((powers_of_g, vk))
+}
+
+/// Writes `srs` in the arkworks encoding read back by `load_srs`.
+pub fn save_srs(path: impl AsRef<Path>, srs: &Srs) -> Result<(), BenchError> {
+    let file = File::create(path)?;
+    (srs.0.powers_of_g.to_vec(), srs.1.clone()).serialize_compressed(BufWriter::new(file))?;
+    Ok(())
+}
+
+/// `len` bytes of `data` at `offset`, or an error if the file ends first.
+fn read_bytes(data: &[u8], offset: usize, len: usize) -> Result<&[u8], BenchError> {
+    offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or_else(|| BenchError::InvalidInput("truncated ptau file".to_string()))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, BenchError> {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(read_bytes(data, offset, 4)?);
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, BenchError> {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(read_bytes(data, offset, 8)?);
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Base field element stored little-endian in Montgomery form, as ptau does.
+fn read_fq(data: &[u8], offset: usize) -> Result<Fq, BenchError> {
+    let mut limbs = [0; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = read_u64(data, offset + 8 * i)?;
+    }
+    Ok(Fq::new_unchecked(BigInt::new(limbs)))
+}
+
+fn read_g1(data: &[u8], offset: usize) -> Result<G1Affine, BenchError> {
+    Ok(G1Affine::new_unchecked(read_fq(data, offset)?, read_fq(data, offset + 32)?))
+}
+
+fn read_g2(data: &[u8], offset: usize) -> Result<G2Affine, BenchError> {
+    Ok(G2Affine::new_unchecked(
+        Fq2::new(read_fq(data, offset)?, read_fq(data, offset + 32)?),
+        Fq2::new(read_fq(data, offset + 64)?, read_fq(data, offset + 96)?),
+    ))
+}
+
+/// Parses the first `n_powers` `tauG1` powers (section 2) and the `tauG2`
+/// powers (section 3) of a ptau file. At least one power is read, for the
+/// verifier key.
+fn read_ptau(
+    data: &[u8],
+    n_powers: usize,
+) -> Result<(Vec<G1Affine>, VerifierKey<Bn254>), BenchError> {
+    let invalid = |msg: &str| BenchError::InvalidInput(msg.to_string());
+    if !data.starts_with(b"ptau") {
+        return Err(invalid("not a Powers-of-Tau file"));
+    }
+    let n_sections = read_u32(data, 8)? as usize;
+    let mut sections = HashMap::new();
+    let mut offset = 12;
+    for _ in 0..n_sections {
+        let id = read_u32(data, offset)?;
+        let size = read_u64(data, offset + 4)? as usize;
+        sections.insert(id, (offset + 12, size));
+        offset = size
+            .checked_add(offset + 12)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| invalid("truncated ptau file"))?;
+    }
+
+    let section = |id, min_size: usize| match sections.get(&id) {
+        Some(&(start, size)) if size >= min_size => Ok(start),
+        Some(_) => Err(BenchError::InvalidInput(format!("ptau section {} is truncated", id))),
+        None => Err(BenchError::InvalidInput(format!("ptau file lacks section {}", id))),
+    };
+    let header = section(1, 4)?;
+    let n8 = read_u32(data, header)? as usize;
+    if n8 != 32 || read_bytes(data, header + 4, n8)? != &Fq::MODULUS.to_bytes_le()[..] {
+        return Err(invalid("ptau file is not over BN254"));
+    }
+    let power = read_u32(data, header + 4 + n8)?;
+
+    let n_powers = n_powers.max(1).min(1_usize.checked_shl(power).unwrap_or(usize::MAX));
+    let tau_g1 = section(2, n_powers.saturating_mul(64))?;
+    let powers_of_g = (0..n_powers)
+        .map(|i| read_g1(data, tau_g1 + 64 * i))
+        .collect::<Result<Vec<_>, _>>()?;
+    let tau_g2 = section(3, 256)?;
+    let h = read_g2(data, tau_g2)?;
+    let beta_h = read_g2(data, tau_g2 + 128)?;
+    let g = powers_of_g[0];
+    if g != G1Affine::generator() || h != G2Affine::generator() {
+        return Err(invalid("ptau powers do not start at the generators"));
+    }
+
+    let vk = VerifierKey {
+        g,
+        gamma_g: G1Affine::generator(),
+        h,
+        beta_h,
+        prepared_h: <Bn254 as Pairing>::G2Prepared::from(h),
+        prepared_beta_h: <Bn254 as Pairing>::G2Prepared::from(beta_h),
+    };
+    Ok((powers_of_g, vk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_truncated_ptau() {
+        assert!(read_ptau(b"ptau\x01\0\0\0", 1).is_err());
+        // one section claiming more bytes than the file holds
+        let mut data = b"ptau\x01\0\0\0\x01\0\0\0\x01\0\0\0".to_vec();
+        data.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(read_ptau(&data, 1).is_err());
+        // a header section too short for its field size
+        let mut data = b"ptau\x01\0\0\0\x01\0\0\0\x01\0\0\0".to_vec();
+        data.extend_from_slice(&4_u64.to_le_bytes());
+        data.extend_from_slice(&32_u32.to_le_bytes());
+        assert!(read_ptau(&data, 0).is_err());
+    }
+
+    #[cfg(feature = "mmap-srs")]
+    #[test]
+    fn test_load_srs_mmap() {
+        use crate::config::rng;
+        use folding_schemes::commitment::{kzg::KZG, CommitmentScheme};
+
+        let srs = KZG::<'static, Bn254>::setup(rng(), 16).unwrap();
+        let path = std::env::temp_dir().join(format!("srs-mmap-{}.bin", std::process::id()));
+        save_srs(&path, &srs).unwrap();
+
+        let (pk, vk) = load_srs_mmap("mmap", &path, 8).unwrap();
+        assert_eq!(pk.powers_of_g[..], srs.0.powers_of_g[..8]);
+        assert_eq!(vk.beta_h, srs.1.beta_h);
+        let (full, _) = load_srs("full", &path, 8).unwrap();
+        assert_eq!(pk.powers_of_g, full.powers_of_g);
+        assert!(load_srs_mmap("mmap", &path, 1 << 10).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+//! Distribution of the per-step folding times.
+//!
+//! The average step time hides whether steps are stable or slow down as the
+//! running instance accumulates; the spread, the drift between the first and
+//! last steps and a histogram show it. `paired_test` tells whether the steps
+//! of two configurations differ by more than noise.
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StepStats {
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    /// Sample standard deviation.
+    pub std_dev: Duration,
+    /// Coefficient of variation, `std_dev / mean`.
+    pub cv: f64,
+    /// Mean of the last quarter of the steps over the mean of the first
+    /// quarter; above 1 when steps slow down along the chain.
+    pub drift: f64,
+}
+
+impl StepStats {
+    /// `None` for fewer than two steps, where there is no spread.
+    pub fn new(steps: &[Duration]) -> Option<Self> {
+        if steps.len() < 2 {
+            return None;
+        }
+        let secs: Vec<f64> = steps.iter().map(Duration::as_secs_f64).collect();
+        let avg = mean(&secs);
+        let variance =
+            secs.iter().map(|s| (s - avg).powi(2)).sum::<f64>() / (secs.len() - 1) as f64;
+        let std_dev = variance.sqrt();
+        let quarter = (secs.len() / 4).max(1);
+        let first = mean(&secs[..quarter]);
+        let last = mean(&secs[secs.len() - quarter..]);
+        Some(Self {
+            min: *steps.iter().min()?,
+            max: *steps.iter().max()?,
+            mean: steps.iter().sum::<Duration>() / steps.len() as u32,
+            std_dev: Duration::from_secs_f64(std_dev),
+            cv: if avg > 0.0 { std_dev / avg } else { 0.0 },
+            drift: if first > 0.0 { last / first } else { 1.0 },
+        })
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Text histogram of `steps` in `buckets` equal-width bins between the
+/// fastest and slowest step, one line per bin with a bar of at most `width`
+/// characters.
+pub fn histogram(steps: &[Duration], buckets: usize, width: usize) -> String {
+    let (Some(min), Some(max)) = (steps.iter().min(), steps.iter().max()) else {
+        return String::new();
+    };
+    let buckets = buckets.max(1);
+    let span = (*max - *min).as_secs_f64();
+    let mut counts = vec![0usize; buckets];
+    for step in steps {
+        let offset = (*step - *min).as_secs_f64();
+        let bucket = if span > 0.0 {
+            ((offset / span * buckets as f64) as usize).min(buckets - 1)
+        } else {
+            0
+        };
+        counts[bucket] += 1;
+    }
+    let largest = counts.iter().copied().max().unwrap_or(1);
+    let bucket_width = span / buckets as f64;
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &count)| {
+            let low = *min + Duration::from_secs_f64(bucket_width * i as f64);
+            format!(
+                "{:>12?} | {:<width$} {}\n",
+                low,
+                "#".repeat(count * width / largest),
+                count,
+                width = width
+            )
+        })
+        .collect()
+}
+
+/// Paired t-test and Wilcoxon signed-rank test of the differences `a - b`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PairedTest {
+    pub pairs: usize,
+    /// Mean of `a - b`.
+    pub mean_diff: f64,
+    pub t: f64,
+    /// Two-sided p-value of the t-test.
+    pub t_p_value: f64,
+    /// Signed-rank statistic, normalized.
+    pub wilcoxon_z: f64,
+    /// Two-sided p-value of the Wilcoxon test, from its normal approximation.
+    pub wilcoxon_p_value: f64,
+}
+
+impl PairedTest {
+    /// Whether both tests reject equal step times at level `alpha`; the
+    /// t-test assumes normal differences, the Wilcoxon test does not.
+    pub fn significant(&self, alpha: f64) -> bool {
+        self.t_p_value < alpha && self.wilcoxon_p_value < alpha
+    }
+}
+
+/// Tests whether the paired samples `a[i]`, `b[i]` differ. `None` with fewer
+/// than two pairs.
+pub fn paired_test(a: &[Duration], b: &[Duration]) -> Option<PairedTest> {
+    let diffs: Vec<f64> =
+        a.iter().zip(b).map(|(a, b)| a.as_secs_f64() - b.as_secs_f64()).collect();
+    let n = diffs.len();
+    if n < 2 {
+        return None;
+    }
+    let mean_diff = mean(&diffs);
+    let variance = diffs.iter().map(|d| (d - mean_diff).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let df = (n - 1) as f64;
+    let (t, t_p_value) = if variance > 0.0 {
+        let t = mean_diff / (variance / n as f64).sqrt();
+        (t, incomplete_beta(df / (df + t * t), df / 2.0, 0.5))
+    } else if mean_diff == 0.0 {
+        (0.0, 1.0)
+    } else {
+        // every pair differs by the same amount
+        (mean_diff.signum() * f64::INFINITY, 0.0)
+    };
+    let (wilcoxon_z, wilcoxon_p_value) = wilcoxon(&diffs);
+    Some(PairedTest {
+        pairs: n,
+        mean_diff,
+        t,
+        t_p_value,
+        wilcoxon_z,
+        wilcoxon_p_value,
+    })
+}
+
+/// Signed-rank test without the zero differences, with tied ranks averaged.
+fn wilcoxon(diffs: &[f64]) -> (f64, f64) {
+    let mut nonzero: Vec<f64> = diffs.iter().copied().filter(|d| *d != 0.0).collect();
+    let n = nonzero.len();
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+    nonzero.sort_by(|x, y| x.abs().total_cmp(&y.abs()));
+    let mut w_plus = 0.0;
+    let mut ties = 0.0;
+    let mut i = 0;
+    while i < n {
+        let mut j = i;
+        while j + 1 < n && nonzero[j + 1].abs() == nonzero[i].abs() {
+            j += 1;
+        }
+        // ranks i + 1 ..= j + 1 share their average
+        let rank = (i + j + 2) as f64 / 2.0;
+        let count = (j - i + 1) as f64;
+        w_plus += rank * nonzero[i..=j].iter().filter(|d| **d > 0.0).count() as f64;
+        ties += count * count * count - count;
+        i = j + 1;
+    }
+    let n = n as f64;
+    let expected = n * (n + 1.0) / 4.0;
+    let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - ties / 48.0;
+    if variance <= 0.0 {
+        return (0.0, 1.0);
+    }
+    let z = (w_plus - expected) / variance.sqrt();
+    (z, erfc(z.abs() / std::f64::consts::SQRT_2))
+}
+
+/// Complementary error function, with fractional error below 1.2e-7
+/// (Numerical Recipes' `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`, by its continued
+/// fraction.
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln())
+        .exp();
+    // the fraction converges fast on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_fraction(1.0 - x, b, a) / b
+    }
+}
+
+fn beta_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    d = 1.0 / if d.abs() < TINY { TINY } else { d };
+    let mut h = d;
+    for m in 1..200 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            d = 1.0 / if d.abs() < TINY { TINY } else { d };
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+/// `ln Γ(x)` for `x > 0`, Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |acc, (i, c)| acc + c / (x + 1.0 + i as f64));
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|&v| Duration::from_millis(v)).collect()
+    }
+
+    #[test]
+    fn test_step_stats() {
+        assert_eq!(StepStats::new(&ms(&[5])), None);
+
+        let stats = StepStats::new(&ms(&[2, 4, 4, 4, 5, 5, 7, 9])).unwrap();
+        assert_eq!(stats.min, Duration::from_millis(2));
+        assert_eq!(stats.max, Duration::from_millis(9));
+        assert_eq!(stats.mean, Duration::from_millis(5));
+        assert!((stats.cv - 0.4276).abs() < 1e-3);
+        // (7 + 9) / (2 + 4)
+        assert!((stats.drift - 16.0 / 6.0).abs() < 1e-9);
+
+        let flat = StepStats::new(&ms(&[3, 3, 3, 3])).unwrap();
+        assert!(flat.cv < 1e-9);
+        assert_eq!(flat.drift, 1.0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let text = histogram(&ms(&[1, 1, 1, 2, 3]), 2, 6);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("###### 3"));
+        assert!(lines[1].ends_with("####   2"));
+        assert_eq!(histogram(&[], 4, 10), "");
+        let flat = histogram(&ms(&[2, 2]), 3, 4);
+        assert_eq!(flat.lines().next().unwrap().trim_start(), "2ms | #### 2");
+    }
+
+    #[test]
+    fn test_paired_test() {
+        assert_eq!(paired_test(&ms(&[1]), &ms(&[2])), None);
+
+        // differences 1..=5 ms: t = 4.243 on 4 degrees of freedom
+        let test = paired_test(&ms(&[11, 12, 13, 14, 15]), &ms(&[10; 5])).unwrap();
+        assert_eq!(test.pairs, 5);
+        assert!((test.mean_diff - 0.003).abs() < 1e-12);
+        assert!((test.t - 4.2426).abs() < 1e-3);
+        assert!((test.t_p_value - 0.01324).abs() < 1e-4);
+        assert!((test.wilcoxon_z - 2.0226).abs() < 1e-3);
+        assert!((test.wilcoxon_p_value - 0.04311).abs() < 1e-4);
+        assert!(test.significant(0.05));
+        assert!(!test.significant(0.01));
+
+        let same = paired_test(&ms(&[3, 4, 5]), &ms(&[3, 4, 5])).unwrap();
+        assert_eq!(same.t_p_value, 1.0);
+        assert_eq!(same.wilcoxon_p_value, 1.0);
+    }
+}
@@ -0,0 +1,356 @@
+//! Runs a selection of the registry, e.g. the whole circuit × scheme × curve
+//! matrix, and gathers every outcome into one report.
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::affinity::isolated;
+use crate::calibrate::calibrate;
+use crate::config::{poseidon_params, timeout, BenchConfig};
+use crate::error::BenchError;
+use crate::heap::{self, HeapProfile};
+use crate::interrupt::interrupted;
+use crate::primitives::{run_primitives, PrimitiveTime};
+use crate::registry::BenchEntry;
+use crate::report::{self, Reporter};
+use crate::result::{BenchResult, DeciderKind};
+use crate::timeout::{with_timeout, Outcome};
+
+#[derive(Debug)]
+pub enum Status {
+    Completed(Vec<BenchResult>),
+    Failed(String),
+    TimedOut(Duration),
+    Panicked(String),
+    /// Running or not started yet when the suite was interrupted.
+    Aborted,
+}
+
+impl Status {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Status::Completed(_) => "ok",
+            Status::Failed(_) => "failed",
+            Status::TimedOut(_) => "timeout",
+            Status::Panicked(_) => "panicked",
+            Status::Aborted => "aborted",
+        }
+    }
+
+    /// Why the benchmark did not complete.
+    pub fn error(&self) -> Option<String> {
+        match self {
+            Status::Completed(_) => None,
+            Status::Failed(e) => Some(e.clone()),
+            Status::TimedOut(t) => Some(format!("timed out after {:?}", t)),
+            Status::Panicked(msg) => Some(msg.clone()),
+            Status::Aborted => Some("interrupted".to_string()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SuiteEntry {
+    pub name: String,
+    pub status: Status,
+}
+
+/// A decider proof of one entry, a row of `SuiteReport::decider_comparison`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeciderRow {
+    pub benchmark: String,
+    pub kind: DeciderKind,
+    pub proof_size: usize,
+    /// Zero when the verify phase did not run.
+    pub verify: Duration,
+    /// EVM verification gas, measured with the `evm` feature.
+    pub gas: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct SuiteReport {
+    pub entries: Vec<SuiteEntry>,
+    /// Primitive timings of the curves of the entries, with
+    /// `BenchConfig::primitives`.
+    pub primitives: Vec<PrimitiveTime>,
+}
+
+impl SuiteReport {
+    /// Names of the entries that did not complete.
+    pub fn failed(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| !matches!(e.status, Status::Completed(_)))
+            .map(|e| e.name.as_str())
+            .collect()
+    }
+
+    /// Prints one row per entry, with the timings of its first trial.
+    pub fn print(&self) {
+        println!(
+            "\n{:<32} {:>10} {:>14} {:>14} {:>14} {:>10} {:>14}",
+            "benchmark", "status", "avg step", "prove", "verify", "proof (B)", "total"
+        );
+        for entry in &self.entries {
+            match &entry.status {
+                Status::Completed(results) => {
+                    let Some(r) = results.first() else { continue };
+                    println!(
+                        "{:<32} {:>10} {:>14?} {:>14?} {:>14?} {:>10} {:>14?}",
+                        entry.name,
+                        "ok",
+                        r.avg_step(),
+                        r.decider_prove,
+                        r.decider_verify,
+                        r.proof_size,
+                        r.total
+                    );
+                }
+                status => println!(
+                    "{:<32} {:>10} {}",
+                    entry.name,
+                    status.label(),
+                    status.error().unwrap_or_default()
+                ),
+            }
+        }
+        self.print_decider_comparison();
+        self.print_primitives();
+    }
+
+    /// The deciders of every circuit that was proven by more than one kind of
+    /// decider (on-chain, off-chain, recursive), from the first trial of each
+    /// entry, grouped by circuit (the last segment of the entry names) in the
+    /// order they were run.
+    pub fn decider_comparison(&self) -> Vec<(String, Vec<DeciderRow>)> {
+        let mut circuits: Vec<(String, Vec<DeciderRow>)> = Vec::new();
+        for entry in &self.entries {
+            let Status::Completed(results) = &entry.status else { continue };
+            let Some(r) = results.first() else { continue };
+            let Some(kind) = r.decider else { continue };
+            let row = DeciderRow {
+                benchmark: entry.name.clone(),
+                kind,
+                proof_size: r.proof_size,
+                verify: r.decider_verify,
+                gas: r.verify_gas,
+            };
+            let circuit = entry.name.rsplit('/').next().unwrap_or_default();
+            match circuits.iter_mut().find(|(c, _)| c == circuit) {
+                Some((_, rows)) => rows.push(row),
+                None => circuits.push((circuit.to_string(), vec![row])),
+            }
+        }
+        circuits.retain(|(_, rows)| rows.iter().any(|r| r.kind != rows[0].kind));
+        circuits
+    }
+
+    fn print_primitives(&self) {
+        if self.primitives.is_empty() {
+            return;
+        }
+        println!("\nPrimitives:");
+        println!("{:<12} {:>10} {:>10} {:>14}", "curve", "primitive", "size", "time");
+        for p in &self.primitives {
+            println!(
+                "{:<12} {:>10} {:>10} {:>14?}",
+                p.curve,
+                p.primitive.to_string(),
+                p.size,
+                p.time
+            );
+        }
+    }
+
+    fn print_decider_comparison(&self) {
+        for (circuit, rows) in self.decider_comparison() {
+            println!("\nDeciders of {}:", circuit);
+            println!(
+                "{:<32} {:>10} {:>10} {:>14} {:>12}",
+                "benchmark", "decider", "proof (B)", "verify", "EVM gas"
+            );
+            for row in rows {
+                println!(
+                    "{:<32} {:>10} {:>10} {:>14} {:>12}",
+                    row.benchmark,
+                    row.kind.to_string(),
+                    row.proof_size,
+                    verify_time(row.verify),
+                    row.gas.map_or("n/a".to_string(), |g| g.to_string())
+                );
+            }
+        }
+    }
+}
+
+/// `verify` for the comparison tables, `n/a` when the phase did not run.
+pub(crate) fn verify_time(verify: Duration) -> String {
+    if verify.is_zero() {
+        "n/a".to_string()
+    } else {
+        format!("{:?}", verify)
+    }
+}
+
+/// Runs `entries`, `config.jobs` at a time and each within
+/// `config::timeout()`, and reports to the sinks of `config`. A failing entry
+/// is recorded and the others still run. On SIGINT the running and remaining
+/// entries are recorded as aborted and the sinks still get the report (see
+/// `interrupt`).
+pub fn run_suite(entries: Vec<BenchEntry>, config: &BenchConfig) -> Result<SuiteReport, BenchError> {
+    run_suite_with(entries, config, report::reporters(&config.sinks))
+}
+
+/// `run_suite` reporting to `reporters` instead of the sinks of `config`.
+pub fn run_suite_with(
+    entries: Vec<BenchEntry>,
+    config: &BenchConfig,
+    reporters: Vec<Box<dyn Reporter>>,
+) -> Result<SuiteReport, BenchError> {
+    let primitives = if config.primitives {
+        // the cycles of the entries, as the `curve` segment of their names
+        let mut cycles: Vec<&str> = Vec::new();
+        for cycle in entries.iter().filter_map(|e| e.name.split('/').nth(1)) {
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+        }
+        let poseidon = poseidon_params()?;
+        isolated("primitives", config, || run_primitives(&cycles, poseidon))?
+    } else {
+        Vec::new()
+    };
+    report::activate(reporters.into_iter().chain(heap::reporter()).collect());
+    let config = Arc::new(config.clone());
+    let count = entries.len();
+    let queue = Mutex::new(entries.into_iter().enumerate());
+    let done = Mutex::new(Vec::with_capacity(count));
+    thread::scope(|scope| {
+        for _ in 0..config.jobs.min(count) {
+            scope.spawn(|| loop {
+                if interrupted() {
+                    break;
+                }
+                let Some((i, entry)) = queue.lock().unwrap().next() else { break };
+                let entry = run_entry(entry, config.clone());
+                done.lock().unwrap().push((i, entry));
+            });
+        }
+    });
+    // merged in registry order whatever order they finished in
+    let mut entries = done.into_inner().unwrap();
+    // not started when interrupted
+    entries.extend(queue.into_inner().unwrap().map(|(i, entry)| {
+        let entry = SuiteEntry {
+            name: entry.name,
+            status: Status::Aborted,
+        };
+        (i, entry)
+    }));
+    entries.sort_by_key(|(i, _)| *i);
+    let entries = entries.into_iter().map(|(_, entry)| entry).collect();
+    let report = SuiteReport {
+        entries,
+        primitives,
+    };
+
+    // every sink gets the report even if an earlier one fails
+    let mut first_error = None;
+    for mut reporter in report::deactivate() {
+        if let Err(e) = reporter.on_suite_end(&report) {
+            println!("Failed to write report: {}", e);
+            first_error.get_or_insert(e);
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(report),
+    }
+}
+
+fn run_entry(entry: BenchEntry, config: Arc<BenchConfig>) -> SuiteEntry {
+    let run = entry.run.clone();
+    let name = entry.name.clone();
+    // written when dropped, at the end of the entry
+    let _profile = HeapProfile::start(&entry.name);
+    let status = match with_timeout(&entry.name, timeout(), move || {
+        isolated(&name, &config, || run(&calibrate(&name, &*run, &config)?))?
+    }) {
+        Outcome::Completed(Ok(results)) => Status::Completed(results),
+        Outcome::Completed(Err(e)) => {
+            println!("{} failed: {}", entry.name, e);
+            Status::Failed(e.to_string())
+        }
+        Outcome::TimedOut(t) => Status::TimedOut(t),
+        Outcome::Interrupted => Status::Aborted,
+        Outcome::Panicked(msg) => Status::Panicked(msg),
+    };
+    SuiteEntry {
+        name: entry.name,
+        status,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parallel_suite_keeps_order() {
+        let entries: Vec<BenchEntry> = [30, 10, 20]
+            .into_iter()
+            .map(|ms| BenchEntry {
+                name: format!("sleep/{}", ms),
+                run: Arc::new(move |_: &BenchConfig| {
+                    thread::sleep(Duration::from_millis(ms));
+                    if ms == 10 {
+                        return Err(BenchError::InvalidInput("too short".into()));
+                    }
+                    Ok(vec![BenchResult::new("sleep")])
+                }),
+            })
+            .collect();
+        let config = BenchConfig::builder().jobs(2).build().unwrap();
+        let report = run_suite_with(entries, &config, vec![]).unwrap();
+        let names: Vec<&str> = report.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["sleep/30", "sleep/10", "sleep/20"]);
+        assert_eq!(report.failed(), ["sleep/10"]);
+    }
+
+    #[test]
+    fn test_decider_comparison() {
+        let entry = |name: &str, decider, proof_size| {
+            let mut result = BenchResult::new(name);
+            result.decider = decider;
+            result.proof_size = proof_size;
+            SuiteEntry {
+                name: name.to_string(),
+                status: Status::Completed(vec![result]),
+            }
+        };
+        let report = SuiteReport {
+            entries: vec![
+                entry("nova/mnt/cubic", Some(DeciderKind::OnChain), 900),
+                entry("nova/mnt/sha256", Some(DeciderKind::OnChain), 900),
+                entry("nova-pedersen/bn254/cubic", None, 0),
+                entry("nova-offchain/mnt/cubic", Some(DeciderKind::OffChain), 1500),
+                entry("nova-bw6/bls12-377/cubic", Some(DeciderKind::Recursive), 1200),
+            ],
+            primitives: Vec::new(),
+        };
+        let comparison = report.decider_comparison();
+        assert_eq!(comparison.len(), 1);
+        let (circuit, rows) = &comparison[0];
+        assert_eq!(circuit, "cubic");
+        let sizes: Vec<(DeciderKind, usize)> =
+            rows.iter().map(|r| (r.kind, r.proof_size)).collect();
+        assert_eq!(
+            sizes,
+            [
+                (DeciderKind::OnChain, 900),
+                (DeciderKind::OffChain, 1500),
+                (DeciderKind::Recursive, 1200)
+            ]
+        );
+    }
+}
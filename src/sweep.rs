@@ -0,0 +1,463 @@
+//! Sweeps re-running the pipeline over one varying parameter.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use folding_schemes::{frontend::FCircuit, Decider, FoldingScheme};
+
+use crate::bench::{
+    fold_steps, preprocess, CircuitSize, DeciderVerifierInputs, PreprocessStages,
+};
+use crate::config::rng;
+use crate::error::BenchError;
+
+/// Wall-clock time of each pipeline phase in one run.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PhaseTimes {
+    pub preprocess: Duration,
+    pub fold: Duration,
+    pub prove: Duration,
+    pub verify: Duration,
+}
+
+impl PhaseTimes {
+    const NAMES: [&'static str; 4] = ["preprocess", "fold", "prove", "verify"];
+
+    fn as_array(&self) -> [Duration; 4] {
+        [self.preprocess, self.fold, self.prove, self.verify]
+    }
+}
+
+/// Powers of two up to the number of available cores, plus that number when
+/// it is not a power of two.
+pub fn default_thread_counts() -> Vec<usize> {
+    let max = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut counts: Vec<usize> = (0..)
+        .map(|i| 1 << i)
+        .take_while(|&n| n <= max)
+        .collect();
+    if *counts.last().unwrap() != max {
+        counts.push(max);
+    }
+    counts
+}
+
+/// Pool sizes of a thread sweep: a comma-separated list, e.g. `1,2,4`, or
+/// `auto` for `default_thread_counts`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadCounts(pub Vec<usize>);
+
+impl FromStr for ThreadCounts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "auto" {
+            return Ok(Self(default_thread_counts()));
+        }
+        s.split(',')
+            .map(|n| match n.trim().parse::<usize>() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(format!("invalid thread count {:?} in {:?}", n, s)),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Runs the whole pipeline once, timing each phase. Returns the folded
+/// scheme and the decider proof along with the times.
+pub fn run_phases<C1, C2, FC, FS, D>(
+    label: &str,
+    prep_param: &FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<(PhaseTimes, FS, D::Proof), BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: CanonicalSerialize,
+{
+    let mut rng = rng();
+    let mut times = PhaseTimes::default();
+
+    let start = Instant::now();
+    let (fs_pp, fs_vp, decider_pp, decider_vp) =
+        preprocess::<C1, C2, FC, FS, D>(label, prep_param, f_circuit.clone(), z_0.clone())?;
+    times.preprocess = start.elapsed();
+
+    let mut fs = FS::init(&(fs_pp, fs_vp), f_circuit, z_0)?;
+    let start = Instant::now();
+    fold_steps(label, &mut fs, n_steps, external_inputs)?;
+    times.fold = start.elapsed();
+
+    let start = Instant::now();
+    let proof = D::prove(&mut rng, decider_pp, fs.clone())?;
+    times.prove = start.elapsed();
+
+    let start = Instant::now();
+    let verified = D::verify(
+        decider_vp,
+        fs.num_steps(),
+        fs.z_0(),
+        fs.z_i(),
+        fs.running_instance(),
+        fs.incoming_instance(),
+        &proof,
+    )?;
+    times.verify = start.elapsed();
+    if !verified {
+        return Err(BenchError::Verification(format!("{} decider proof rejected", label)));
+    }
+    Ok((times, fs, proof))
+}
+
+/// Runs the whole pipeline in a rayon pool of each size in `thread_counts`
+/// and prints the per-phase speedup over the first pool. Fails on an empty
+/// `thread_counts`.
+pub fn thread_sweep<C1, C2, FC, FS, D>(
+    label: &str,
+    thread_counts: &[usize],
+    prep_param: FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_steps: usize,
+    mut external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<Vec<(usize, PhaseTimes)>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: CanonicalSerialize,
+{
+    if thread_counts.is_empty() {
+        return Err(BenchError::InvalidInput(format!("{} sweeps no thread counts", label)));
+    }
+    let mut results = Vec::new();
+    for &n_threads in thread_counts {
+        let run_label = format!("{} [{} threads]", label, n_threads);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(n_threads)
+            .build()
+            .map_err(|e| BenchError::InvalidInput(format!("{} thread pool: {}", run_label, e)))?;
+        let (times, _, _) = pool.install(|| {
+            run_phases::<C1, C2, FC, FS, D>(
+                &run_label,
+                &prep_param,
+                f_circuit.clone(),
+                z_0.clone(),
+                n_steps,
+                &mut external_inputs,
+            )
+        })?;
+        results.push((n_threads, times));
+    }
+
+    let base = results[0].1.as_array();
+    for (n_threads, times) in &results {
+        let speedups: Vec<String> = PhaseTimes::NAMES
+            .iter()
+            .zip(base.iter().zip(times.as_array()))
+            .map(|(name, (b, t))| format!("{} {:.2}x", name, b.as_secs_f64() / t.as_secs_f64()))
+            .collect();
+        println!("{} {} threads: {}", label, n_threads, speedups.join(", "));
+    }
+    Ok(results)
+}
+
+/// Resets the peak resident set size reported by `peak_rss_kib` (Linux only).
+fn reset_peak_rss() {
+    #[cfg(target_os = "linux")]
+    let _ = std::fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Peak resident set size since the last `reset_peak_rss`, in KiB.
+fn peak_rss_kib() -> Option<u64> {
+    proc_status_kib("VmHWM:")
+}
+
+/// Current resident set size, in KiB (Linux only).
+pub(crate) fn rss_kib() -> Option<u64> {
+    proc_status_kib("VmRSS:")
+}
+
+fn proc_status_kib(field: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with(field))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Measurements of one run of a chain-length sweep.
+#[derive(Clone, Copy, Debug)]
+pub struct StepSweepPoint {
+    pub n_steps: usize,
+    pub times: PhaseTimes,
+    pub avg_step: Duration,
+    pub peak_rss_kib: Option<u64>,
+    /// Compressed size of the decider proof.
+    pub proof_size: usize,
+}
+
+/// Runs the pipeline at each chain length in `step_counts` and prints how the
+/// average step time, peak memory and decider cost evolve with it. Fails if
+/// the decider proof size is not the same at every length.
+pub fn step_sweep<C1, C2, FC, FS, D>(
+    label: &str,
+    step_counts: &[usize],
+    prep_param: FS::PreprocessorParam,
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    mut external_inputs: impl FnMut(usize) -> Vec<C1::ScalarField>,
+) -> Result<Vec<StepSweepPoint>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: CanonicalSerialize,
+    D::Proof: CanonicalSerialize,
+{
+    let mut results = Vec::new();
+    for &n_steps in step_counts {
+        reset_peak_rss();
+        let (times, _, proof) = run_phases::<C1, C2, FC, FS, D>(
+            &format!("{} [{} steps]", label, n_steps),
+            &prep_param,
+            f_circuit.clone(),
+            z_0.clone(),
+            n_steps,
+            &mut external_inputs,
+        )?;
+        results.push(StepSweepPoint {
+            n_steps,
+            times,
+            avg_step: times.fold / n_steps as u32,
+            peak_rss_kib: peak_rss_kib(),
+            proof_size: proof.compressed_size(),
+        });
+    }
+
+    for point in &results {
+        println!(
+            "{} {} steps: avg step {:?}, decider prove {:?}, verify {:?}, proof {} bytes, peak RSS {}",
+            label,
+            point.n_steps,
+            point.avg_step,
+            point.times.prove,
+            point.times.verify,
+            point.proof_size,
+            point
+                .peak_rss_kib
+                .map_or("n/a".to_string(), |kib| format!("{} MiB", kib / 1024))
+        );
+    }
+    check_constant_proof_size(label, &results)?;
+    Ok(results)
+}
+
+/// Checks that the decider proof has the same size at every chain length of
+/// a step sweep, i.e. that the final proof really is constant-size.
+pub fn check_constant_proof_size(label: &str, points: &[StepSweepPoint]) -> Result<(), BenchError> {
+    if points.windows(2).any(|w| w[0].proof_size != w[1].proof_size) {
+        let sizes: Vec<String> = points
+            .iter()
+            .map(|p| format!("{} steps: {} bytes", p.n_steps, p.proof_size))
+            .collect();
+        return Err(BenchError::ProofSizeVaries(format!("{} {}", label, sizes.join(", "))));
+    }
+    if let Some(point) = points.first() {
+        println!(
+            "{} Decider proof size constant at {} bytes over {} chain lengths",
+            label,
+            point.proof_size,
+            points.len()
+        );
+    }
+    Ok(())
+}
+
+/// Measurements of one run of a circuit parameter sweep.
+#[derive(Clone, Copy, Debug)]
+pub struct ParamSweepPoint {
+    pub value: usize,
+    pub augmented_constraints: usize,
+    pub times: PhaseTimes,
+    pub avg_step: Duration,
+}
+
+/// Runs the pipeline once per `values`, with the circuit, preprocessor params
+/// and initial state built by `setup(value)` and step `i` fed with
+/// `external_inputs(value, i)`. Prints how the augmented circuit size, step
+/// time and decider cost grow with the swept parameter.
+pub fn param_sweep<C1, C2, FC, FS, D>(
+    label: &str,
+    param_name: &str,
+    values: &[usize],
+    n_steps: usize,
+    setup: impl Fn(usize) -> (FC, FS::PreprocessorParam, Vec<C1::ScalarField>),
+    external_inputs: impl Fn(usize, usize) -> Vec<C1::ScalarField>,
+) -> Result<Vec<ParamSweepPoint>, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField>,
+    FS: FoldingScheme<C1, C2, FC>
+        + PreprocessStages<C1, C2, FC>
+        + DeciderVerifierInputs<C1, CommittedInstance = D::CommittedInstance>
+        + CircuitSize,
+    D: Decider<C1, C2, FC, FS, PreprocessorParam = (FS::ProverParam, FS::VerifierParam)>,
+    D::ProverParam: CanonicalSerialize,
+    D::VerifierParam: CanonicalSerialize,
+{
+    let mut results = Vec::new();
+    for &value in values {
+        let (f_circuit, prep_param, z_0) = setup(value);
+        let (times, fs, _) = run_phases::<C1, C2, FC, FS, D>(
+            &format!("{} [{} = {}]", label, param_name, value),
+            &prep_param,
+            f_circuit,
+            z_0,
+            n_steps,
+            |i| external_inputs(value, i),
+        )?;
+        results.push(ParamSweepPoint {
+            value,
+            augmented_constraints: fs.augmented_constraints(),
+            times,
+            avg_step: times.fold / n_steps as u32,
+        });
+    }
+
+    for point in &results {
+        println!(
+            "{} {} = {}: {} augmented constraints, avg step {:?}, decider prove {:?}, verify {:?}",
+            label,
+            param_name,
+            point.value,
+            point.augmented_constraints,
+            point.avg_step,
+            point.times.prove,
+            point.times.verify
+        );
+    }
+    Ok(results)
+}
+
+/// Least-squares fit of `y = slope * x + intercept`.
+pub fn linear_fit(points: &[(f64, f64)]) -> (f64, f64) {
+    let n = points.len() as f64;
+    let (sx, sy) = points
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mx, my) = (sx / n, sy / n);
+    let (sxy, sxx) = points.iter().fold((0.0, 0.0), |(sxy, sxx), (x, y)| {
+        (sxy + (x - mx) * (y - my), sxx + (x - mx) * (x - mx))
+    });
+    let slope = sxy / sxx;
+    (slope, my - slope * mx)
+}
+
+/// Marginal cost of one more unit of the swept parameter, fitted over
+/// `points`: (constraints per unit, step time per unit).
+pub fn marginal_cost(label: &str, param_name: &str, points: &[ParamSweepPoint]) -> (f64, Duration) {
+    let (constraints, _) = linear_fit(
+        &points
+            .iter()
+            .map(|p| (p.value as f64, p.augmented_constraints as f64))
+            .collect::<Vec<_>>(),
+    );
+    let (secs, _) = linear_fit(
+        &points
+            .iter()
+            .map(|p| (p.value as f64, p.avg_step.as_secs_f64()))
+            .collect::<Vec<_>>(),
+    );
+    let step_time = Duration::from_secs_f64(secs.max(0.0));
+    println!(
+        "{} marginal cost per {}: {:.1} constraints, {:?} per step",
+        label, param_name, constraints, step_time
+    );
+    (constraints, step_time)
+}
+
+/// Scaling of the prover with the step circuit size.
+#[derive(Clone, Copy, Debug)]
+pub struct ScalingFit {
+    /// `b` in `step_time ~ n^b`, from a log-log fit.
+    pub exponent: f64,
+    /// Step time extrapolated to an empty step circuit.
+    pub overhead_time: Duration,
+    /// Constraints the augmented circuit adds on top of the step circuit.
+    pub overhead_constraints: f64,
+    pub time_per_constraint: Duration,
+}
+
+/// Fits how the step time scales with the step circuit size over `points`,
+/// whose values are step circuit constraint counts.
+pub fn scaling_fit(label: &str, points: &[ParamSweepPoint]) -> ScalingFit {
+    let (exponent, _) = linear_fit(
+        &points
+            .iter()
+            .map(|p| ((p.value as f64).ln(), p.avg_step.as_secs_f64().ln()))
+            .collect::<Vec<_>>(),
+    );
+    let (per_constraint, overhead) = linear_fit(
+        &points
+            .iter()
+            .map(|p| (p.value as f64, p.avg_step.as_secs_f64()))
+            .collect::<Vec<_>>(),
+    );
+    let (_, overhead_constraints) = linear_fit(
+        &points
+            .iter()
+            .map(|p| (p.value as f64, p.augmented_constraints as f64))
+            .collect::<Vec<_>>(),
+    );
+    let fit = ScalingFit {
+        exponent,
+        overhead_time: Duration::from_secs_f64(overhead.max(0.0)),
+        overhead_constraints,
+        time_per_constraint: Duration::from_secs_f64(per_constraint.max(0.0)),
+    };
+    println!(
+        "{} step time ~ n^{:.3}; {:?} per constraint, fixed overhead {:?} and {:.0} constraints",
+        label, fit.exponent, fit.time_per_constraint, fit.overhead_time, fit.overhead_constraints
+    );
+    fit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_counts() {
+        assert_eq!("1,2, 4".parse(), Ok(ThreadCounts(vec![1, 2, 4])));
+        assert_eq!("auto".parse(), Ok(ThreadCounts(default_thread_counts())));
+        assert!("0,2".parse::<ThreadCounts>().is_err());
+        assert!("".parse::<ThreadCounts>().is_err());
+        assert!("2,x".parse::<ThreadCounts>().is_err());
+    }
+}
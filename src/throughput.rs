@@ -0,0 +1,83 @@
+//! Throughput mode: many independent IVC chains folded concurrently from the
+//! same params, as a prover service handling many streams would.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use std::time::{Duration, Instant};
+
+use folding_schemes::{frontend::FCircuit, FoldingScheme};
+
+use crate::config::rng;
+use crate::error::BenchError;
+
+#[derive(Clone, Debug)]
+pub struct ThroughputStats {
+    /// Wall-clock time of each chain, from `init` to its last step.
+    pub chain_latencies: Vec<Duration>,
+    pub total_time: Duration,
+    pub steps_per_second: f64,
+}
+
+/// Folds `n_chains` chains of `n_steps` each on their own threads, sharing
+/// `fs_params`. `external_inputs(chain, step)` feeds every step.
+pub fn run_throughput_bench<C1, C2, FC, FS>(
+    label: &str,
+    fs_params: &(FS::ProverParam, FS::VerifierParam),
+    f_circuit: FC,
+    z_0: Vec<C1::ScalarField>,
+    n_chains: usize,
+    n_steps: usize,
+    external_inputs: impl Fn(usize, usize) -> Vec<C1::ScalarField> + Sync,
+) -> Result<ThroughputStats, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FC: FCircuit<C1::ScalarField> + Sync,
+    FS: FoldingScheme<C1, C2, FC>,
+    FS::ProverParam: Sync,
+    FS::VerifierParam: Sync,
+{
+    println!(
+        "\nRunning {} throughput benchmark: {} chains x {} steps",
+        label, n_chains, n_steps
+    );
+    let total_start = Instant::now();
+    let chain_latencies = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..n_chains)
+            .map(|chain| {
+                let (f_circuit, z_0, external_inputs) = (&f_circuit, &z_0, &external_inputs);
+                let mut rng = rng();
+                s.spawn(move || -> Result<Duration, BenchError> {
+                    let start = Instant::now();
+                    let mut fs = FS::init(fs_params, f_circuit.clone(), z_0.clone())?;
+                    for i in 0..n_steps {
+                        fs.prove_step(&mut rng, external_inputs(chain, i), None)?;
+                    }
+                    Ok(start.elapsed())
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    let total_time = total_start.elapsed();
+    let steps_per_second = (n_chains * n_steps) as f64 / total_time.as_secs_f64();
+
+    for (chain, latency) in chain_latencies.iter().enumerate() {
+        println!("{} chain {}: {:?}", label, chain, latency);
+    }
+    println!(
+        "{} Aggregate: {:.2} steps/s over {:?}, mean chain latency {:?}",
+        label,
+        steps_per_second,
+        total_time,
+        chain_latencies.iter().sum::<Duration>() / n_chains as u32
+    );
+    Ok(ThroughputStats {
+        chain_latencies,
+        total_time,
+        steps_per_second,
+    })
+}
@@ -0,0 +1,91 @@
+//! Per-benchmark timeout guard.
+//!
+//! A configuration runs on its own thread and is abandoned when it exceeds
+//! its time budget, so one runaway configuration (MNT4-753, a huge sweep
+//! point) gets recorded as timed out instead of hanging the whole run. The
+//! abandoned thread keeps its CPU until the process exits. The same goes
+//! for a configuration running when the process is interrupted.
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::interrupt::interrupted;
+
+/// How often a wait checks for an interruption.
+const POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+pub enum Outcome<T> {
+    Completed(T),
+    TimedOut(Duration),
+    /// Abandoned on SIGINT, see `interrupt`.
+    Interrupted,
+    /// The benchmark panicked; the payload message if it was a string.
+    Panicked(String),
+}
+
+impl<T> Outcome<T> {
+    pub fn completed(self) -> Option<T> {
+        match self {
+            Outcome::Completed(t) => Some(t),
+            _ => None,
+        }
+    }
+}
+
+/// Why a wait for a benchmark ended without its result.
+enum Stop {
+    TimedOut(Duration),
+    Interrupted,
+    Panicked,
+}
+
+/// Runs `f`, giving up after `timeout` (never when `None`) or on SIGINT.
+pub fn with_timeout<T, F>(label: &str, timeout: Option<Duration>, f: F) -> Outcome<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        // the receiver is gone once the benchmark timed out
+        let _ = tx.send(f());
+    });
+    let start = Instant::now();
+    let received = loop {
+        match rx.recv_timeout(POLL) {
+            Ok(t) => break Ok(t),
+            Err(mpsc::RecvTimeoutError::Disconnected) => break Err(Stop::Panicked),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if interrupted() {
+                    break Err(Stop::Interrupted);
+                }
+                if let Some(timeout) = timeout.filter(|&t| start.elapsed() >= t) {
+                    break Err(Stop::TimedOut(timeout));
+                }
+            }
+        }
+    };
+    let outcome = match received {
+        Ok(t) => Outcome::Completed(t),
+        Err(Stop::TimedOut(timeout)) => Outcome::TimedOut(timeout),
+        Err(Stop::Interrupted) => Outcome::Interrupted,
+        Err(Stop::Panicked) => {
+            let payload = handle.join().unwrap_err();
+            Outcome::Panicked(
+                payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default(),
+            )
+        }
+    };
+    match &outcome {
+        Outcome::Completed(_) => {}
+        Outcome::TimedOut(timeout) => println!("{} timed out after {:?}", label, timeout),
+        Outcome::Interrupted => println!("{} aborted", label),
+        Outcome::Panicked(msg) => println!("{} failed: {}", label, msg),
+    }
+    outcome
+}
@@ -0,0 +1,313 @@
+//! Trace-driven zkVM benchmark: folds the toy zkVM (`circuits::toy_vm`) over
+//! a recorded execution trace and reports proving throughput in executed
+//! instructions per second, the headline metric of zkVM comparisons.
+//!
+//! A trace is JSON: the initial registers and every executed instruction with
+//! the `pc` it was fetched from,
+//!
+//! ```json
+//! {"registers": [0, 0, 0, 0],
+//!  "steps": [{"pc": 0, "op": "li", "rd": 3, "imm": 1},
+//!            {"pc": 1, "op": "add", "rd": 0, "rs1": 0, "rs2": 1},
+//!            {"pc": 2, "op": "jnz", "rs": 2, "target": 1}]}
+//! ```
+//!
+//! The program ROM is rebuilt from the `pc`s, and the trace is replayed
+//! natively before folding to check that the machine does execute it in
+//! that order. `record_trace` writes such traces from a program.
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+
+use folding_schemes::{frontend::FCircuit, FoldingScheme};
+
+use crate::bench::{check_native_state, fold_steps, no_external_inputs, IvcState};
+use crate::circuits::toy_vm::{Instruction, ToyVmFCircuit, N_REGS};
+use crate::error::BenchError;
+
+/// An execution of the toy zkVM.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    pub registers: [i64; N_REGS],
+    /// Every executed instruction, with the `pc` it was fetched from.
+    pub steps: Vec<(usize, Instruction)>,
+}
+
+fn invalid(msg: String) -> BenchError {
+    BenchError::InvalidInput(msg)
+}
+
+fn instruction_from_json(step: &Value) -> Result<Instruction, BenchError> {
+    let field = |name: &str| {
+        step.get(name)
+            .and_then(Value::as_i64)
+            .ok_or_else(|| invalid(format!("{} has no integer {:?}", step, name)))
+    };
+    let reg = |name: &str| {
+        let r = field(name)?;
+        (0..N_REGS as i64)
+            .contains(&r)
+            .then_some(r as usize)
+            .ok_or_else(|| invalid(format!("{} has no register {}", step, r)))
+    };
+    Ok(match step.get("op").and_then(Value::as_str) {
+        Some("add") => Instruction::Add {
+            rd: reg("rd")?,
+            rs1: reg("rs1")?,
+            rs2: reg("rs2")?,
+        },
+        Some("sub") => Instruction::Sub {
+            rd: reg("rd")?,
+            rs1: reg("rs1")?,
+            rs2: reg("rs2")?,
+        },
+        Some("mul") => Instruction::Mul {
+            rd: reg("rd")?,
+            rs1: reg("rs1")?,
+            rs2: reg("rs2")?,
+        },
+        Some("li") => Instruction::Li {
+            rd: reg("rd")?,
+            imm: field("imm")?,
+        },
+        Some("jnz") => Instruction::Jnz {
+            rs: reg("rs")?,
+            target: usize::try_from(field("target")?)
+                .map_err(|_| invalid(format!("{} jumps to a negative pc", step)))?,
+        },
+        _ => return Err(invalid(format!("{} has no known op", step))),
+    })
+}
+
+fn instruction_to_json(pc: usize, instruction: &Instruction) -> Value {
+    match *instruction {
+        Instruction::Add { rd, rs1, rs2 } => {
+            json!({"pc": pc, "op": "add", "rd": rd, "rs1": rs1, "rs2": rs2})
+        }
+        Instruction::Sub { rd, rs1, rs2 } => {
+            json!({"pc": pc, "op": "sub", "rd": rd, "rs1": rs1, "rs2": rs2})
+        }
+        Instruction::Mul { rd, rs1, rs2 } => {
+            json!({"pc": pc, "op": "mul", "rd": rd, "rs1": rs1, "rs2": rs2})
+        }
+        Instruction::Li { rd, imm } => json!({"pc": pc, "op": "li", "rd": rd, "imm": imm}),
+        Instruction::Jnz { rs, target } => {
+            json!({"pc": pc, "op": "jnz", "rs": rs, "target": target})
+        }
+    }
+}
+
+impl Trace {
+    pub fn from_json(text: &str) -> Result<Self, BenchError> {
+        let json: Value =
+            serde_json::from_str(text).map_err(|e| invalid(format!("invalid JSON: {}", e)))?;
+        let registers = json
+            .get("registers")
+            .and_then(Value::as_array)
+            .filter(|regs| regs.len() == N_REGS)
+            .and_then(|regs| regs.iter().map(Value::as_i64).collect::<Option<Vec<_>>>())
+            .ok_or_else(|| invalid(format!("expected {} integer registers", N_REGS)))?;
+        let steps = json
+            .get("steps")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("expected an array of steps".into()))?
+            .iter()
+            .map(|step| {
+                let pc = step
+                    .get("pc")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| invalid(format!("{} has no pc", step)))?;
+                Ok((pc as usize, instruction_from_json(step)?))
+            })
+            .collect::<Result<_, BenchError>>()?;
+        Ok(Self {
+            registers: registers.try_into().unwrap(),
+            steps,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        let steps: Vec<Value> = self
+            .steps
+            .iter()
+            .map(|(pc, instruction)| instruction_to_json(*pc, instruction))
+            .collect();
+        json!({"registers": self.registers, "steps": steps}).to_string()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, BenchError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+            .map_err(|e| invalid(format!("{}: {}", path.display(), e)))
+    }
+
+    /// The program ROM, from the instructions at every `pc`. Fails if two
+    /// steps fetch different instructions from the same `pc`, and fills the
+    /// `pc`s never executed with no-ops.
+    pub fn program(&self) -> Result<Vec<Instruction>, BenchError> {
+        let len = self.steps.iter().map(|(pc, _)| pc + 1).max().unwrap_or(0);
+        let mut program: Vec<Option<Instruction>> = vec![None; len];
+        for (pc, instruction) in &self.steps {
+            match program[*pc] {
+                Some(other) if other != *instruction => {
+                    return Err(invalid(format!(
+                        "pc {} holds both {:?} and {:?}",
+                        pc, other, instruction
+                    )))
+                }
+                _ => program[*pc] = Some(*instruction),
+            }
+        }
+        let nop = Instruction::Add {
+            rd: 0,
+            rs1: 0,
+            rs2: 0,
+        };
+        Ok(program.into_iter().map(|ins| ins.unwrap_or(nop)).collect())
+    }
+
+    /// The initial state `[pc = 0, registers]`.
+    pub fn z_0<F: PrimeField>(&self) -> Vec<F> {
+        std::iter::once(F::zero())
+            .chain(self.registers.iter().map(|r| F::from(*r)))
+            .collect()
+    }
+
+    /// Replays the trace on `ToyVmFCircuit::step_native`, checking that every
+    /// step starts at the recorded `pc`.
+    pub fn check<F: PrimeField>(&self, circuit: &ToyVmFCircuit<F>) -> Result<(), BenchError> {
+        let mut z = self.z_0::<F>();
+        for (i, (pc, _)) in self.steps.iter().enumerate() {
+            if z[0] != F::from(*pc as u64) {
+                return Err(invalid(format!(
+                    "step {} is recorded at pc {}, the machine is at {}",
+                    i, pc, z[0]
+                )));
+            }
+            z = circuit.step_native(i, z, vec![])?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `program` from `registers` for up to `max_steps` instructions, or
+/// until it halts, and records the trace.
+pub fn record_trace(program: &[Instruction], registers: [i64; N_REGS], max_steps: usize) -> Trace {
+    let mut regs = registers;
+    let mut pc = 0;
+    let mut steps = Vec::new();
+    while pc < program.len() && steps.len() < max_steps {
+        let instruction = program[pc];
+        steps.push((pc, instruction));
+        pc += 1;
+        match instruction {
+            Instruction::Add { rd, rs1, rs2 } => regs[rd] = regs[rs1].wrapping_add(regs[rs2]),
+            Instruction::Sub { rd, rs1, rs2 } => regs[rd] = regs[rs1].wrapping_sub(regs[rs2]),
+            Instruction::Mul { rd, rs1, rs2 } => regs[rd] = regs[rs1].wrapping_mul(regs[rs2]),
+            Instruction::Li { rd, imm } => regs[rd] = imm,
+            Instruction::Jnz { rs, target } => {
+                if regs[rs] != 0 {
+                    pc = target;
+                }
+            }
+        }
+    }
+    Trace { registers, steps }
+}
+
+#[derive(Clone, Debug)]
+pub struct TraceStats {
+    pub instructions: usize,
+    /// Time of every step, one instruction each.
+    pub steps: Vec<Duration>,
+    pub instructions_per_second: f64,
+}
+
+/// Folds the toy zkVM over `trace`, one instruction per step, with `FS`
+/// preprocessed by `preprocess` for the circuit of the trace's program.
+pub fn run_trace_bench<C1, C2, FS>(
+    label: &str,
+    trace: &Trace,
+    preprocess: impl FnOnce(
+        &ToyVmFCircuit<C1::ScalarField>,
+    ) -> Result<(FS::ProverParam, FS::VerifierParam), BenchError>,
+) -> Result<TraceStats, BenchError>
+where
+    C1: CurveGroup<BaseField = C2::ScalarField, ScalarField = C2::BaseField>,
+    C2: CurveGroup,
+    C2::BaseField: PrimeField,
+    FS: FoldingScheme<C1, C2, ToyVmFCircuit<C1::ScalarField>> + IvcState<C1>,
+{
+    let instructions = trace.steps.len();
+    if instructions == 0 {
+        return Err(invalid("the trace executes no instruction".into()));
+    }
+    let f_circuit = ToyVmFCircuit::new(trace.program()?)?;
+    trace.check(&f_circuit)?;
+    println!(
+        "\nRunning {} trace benchmark: {} instructions, {}-instruction program",
+        label,
+        instructions,
+        f_circuit.program.len()
+    );
+
+    let fs_params = preprocess(&f_circuit)?;
+    let mut fs = FS::init(&fs_params, f_circuit.clone(), trace.z_0())?;
+    let steps = fold_steps(label, &mut fs, instructions, no_external_inputs)?;
+    check_native_state(label, &f_circuit, &fs, &vec![vec![]; instructions])?;
+
+    let instructions_per_second =
+        instructions as f64 / steps.iter().sum::<Duration>().as_secs_f64();
+    println!(
+        "{} Proving throughput: {:.2} instructions/s",
+        label, instructions_per_second
+    );
+    Ok(TraceStats {
+        instructions,
+        steps,
+        instructions_per_second,
+    })
+}
+
+/// `run_trace_bench` with Nova on BN254/Grumpkin.
+#[cfg(feature = "bn254")]
+pub fn run_trace_bench_bn254(label: &str, trace: &Trace) -> Result<TraceStats, BenchError> {
+    use crate::config::{poseidon_config, rng};
+    use crate::solidity::NovaBn;
+    use ark_bn254::{Fr, G1Projective as G1};
+    use ark_grumpkin::Projective as G2;
+    use folding_schemes::folding::nova::PreprocessorParam;
+
+    type N = NovaBn<ToyVmFCircuit<Fr>>;
+    run_trace_bench::<G1, G2, N>(label, trace, |f_circuit| {
+        let prep_param = PreprocessorParam::new(poseidon_config::<Fr>()?, f_circuit.clone());
+        Ok(N::preprocess(rng(), &prep_param)?)
+    })
+}
+
+#[cfg(all(test, feature = "bn254"))]
+mod tests {
+    use super::*;
+    use crate::circuits::toy_vm::fibonacci_program;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_trace_round_trip() {
+        let trace = record_trace(&fibonacci_program(5), [0; N_REGS], 100);
+        // 3 setup instructions and 4 per loop iteration
+        assert_eq!(trace.steps.len(), 3 + 4 * 5);
+        let read = Trace::from_json(&trace.to_json()).unwrap();
+        assert_eq!(read, trace);
+        assert_eq!(read.program().unwrap(), fibonacci_program(5));
+        let circuit = ToyVmFCircuit::<Fr>::new(read.program().unwrap()).unwrap();
+        read.check(&circuit).unwrap();
+
+        let mut skipped = trace.clone();
+        skipped.steps.remove(4);
+        assert!(skipped.check(&circuit).is_err());
+        let bad_register = r#"{"registers": [0, 0, 0, 0],
+            "steps": [{"pc": 0, "op": "li", "rd": 9, "imm": 1}]}"#;
+        assert!(Trace::from_json(bad_register).is_err());
+    }
+}
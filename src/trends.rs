@@ -0,0 +1,292 @@
+//! Time series of the per-step time, decider proving time and proof size of
+//! every configuration across the runs of the `history`, for `sonobe-bench
+//! report trends`. They are printed as first-to-last changes and drawn as an
+//! SVG chart with one panel per metric and one line per configuration.
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::error::BenchError;
+use crate::history::HistoryRun;
+
+/// A CSV report column charted over time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrendMetric {
+    pub column: &'static str,
+    pub title: &'static str,
+    pub unit: &'static str,
+    /// Column units per `unit`.
+    pub divisor: f64,
+}
+
+pub const TREND_METRICS: [TrendMetric; 3] = [
+    TrendMetric { column: "avg_step_ns", title: "Folding step", unit: "ms", divisor: 1e6 },
+    TrendMetric { column: "decider_prove_ns", title: "Decider proof", unit: "ms", divisor: 1e6 },
+    TrendMetric { column: "proof_size", title: "Proof size", unit: "B", divisor: 1.0 },
+];
+
+/// One configuration's values of one metric, one per run; `None` where the
+/// run lacks it (not run, failed, or a phase that was skipped).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub metric: TrendMetric,
+    pub values: Vec<Option<f64>>,
+}
+
+impl Series {
+    /// Relative change from the first to the last value.
+    pub fn change(&self) -> Option<f64> {
+        let mut present = self.values.iter().flatten();
+        let first = *present.next()?;
+        let last = *present.last()?;
+        (first > 0.0).then(|| (last - first) / first)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Trends {
+    /// Labels of the runs, oldest first.
+    pub runs: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+impl Trends {
+    /// The trends of `runs`, given oldest first, using the first trial of
+    /// every benchmark.
+    pub fn from_history(runs: &[HistoryRun]) -> Self {
+        let first_trials: Vec<_> = runs.iter().map(HistoryRun::first_trials).collect();
+        let mut names: Vec<&str> = first_trials.iter().flat_map(|t| t.keys().copied()).collect();
+        names.sort_unstable();
+        names.dedup();
+        let mut series = Vec::new();
+        for metric in TREND_METRICS {
+            for &name in &names {
+                let values: Vec<Option<f64>> = first_trials
+                    .iter()
+                    .map(|t| {
+                        t.get(name)
+                            .and_then(|r| r.metric(metric.column))
+                            .filter(|&v| v > 0.0)
+                            .map(|v| v / metric.divisor)
+                    })
+                    .collect();
+                if values.iter().any(Option::is_some) {
+                    series.push(Series {
+                        name: name.to_string(),
+                        metric,
+                        values,
+                    });
+                }
+            }
+        }
+        Self {
+            runs: runs.iter().map(HistoryRun::label).collect(),
+            series,
+        }
+    }
+
+    pub fn print(&self) {
+        println!("\nTrends over {} runs, {} to {}", self.runs.len(), self.first(), self.last());
+        for metric in TREND_METRICS {
+            println!("\n{} ({})", metric.title, metric.unit);
+            for s in self.series.iter().filter(|s| s.metric == metric) {
+                let present: Vec<f64> = s.values.iter().flatten().copied().collect();
+                println!(
+                    "  {:<32} {:>12.3} -> {:>12.3}  {}",
+                    s.name,
+                    present[0],
+                    present[present.len() - 1],
+                    s.change().map_or("n/a".to_string(), |c| format!("{:+.1}%", c * 100.0))
+                );
+            }
+        }
+    }
+
+    fn first(&self) -> &str {
+        self.runs.first().map_or("", String::as_str)
+    }
+
+    fn last(&self) -> &str {
+        self.runs.last().map_or("", String::as_str)
+    }
+
+    /// The chart as a standalone SVG document.
+    pub fn to_svg(&self) -> String {
+        const WIDTH: f64 = 960.0;
+        const PANEL: f64 = 280.0;
+        const LEFT: f64 = 70.0;
+        const PLOT_WIDTH: f64 = 600.0;
+        const TOP: f64 = 30.0;
+        const PLOT_HEIGHT: f64 = 180.0;
+        const COLORS: [&str; 8] = [
+            "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+        ];
+
+        let mut svg = String::new();
+        let height = PANEL * TREND_METRICS.len() as f64;
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             font-family=\"sans-serif\" font-size=\"11\">",
+            WIDTH, height
+        );
+        let x = |i: usize| {
+            LEFT + if self.runs.len() > 1 {
+                PLOT_WIDTH * i as f64 / (self.runs.len() - 1) as f64
+            } else {
+                PLOT_WIDTH / 2.0
+            }
+        };
+        for (p, metric) in TREND_METRICS.iter().enumerate() {
+            let top = PANEL * p as f64 + TOP;
+            let bottom = top + PLOT_HEIGHT;
+            let series: Vec<&Series> = self.series.iter().filter(|s| s.metric == *metric).collect();
+            let max = series
+                .iter()
+                .flat_map(|s| s.values.iter().flatten())
+                .fold(0.0_f64, |m, &v| m.max(v));
+            let max = if max > 0.0 { max * 1.1 } else { 1.0 };
+            let y = |v: f64| bottom - PLOT_HEIGHT * v / max;
+
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"14\" font-weight=\"bold\">{} ({})</text>",
+                LEFT,
+                top - 10.0,
+                metric.title,
+                metric.unit
+            );
+            let _ = writeln!(
+                svg,
+                "<path d=\"M{l} {t} V{b} H{r}\" fill=\"none\" stroke=\"#444\"/>",
+                l = LEFT,
+                t = top,
+                b = bottom,
+                r = LEFT + PLOT_WIDTH
+            );
+            for tick in 0..=4 {
+                let v = max * tick as f64 / 4.0;
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{:.3}</text>",
+                    LEFT - 6.0,
+                    y(v) + 4.0,
+                    v
+                );
+            }
+            for (i, run) in self.runs.iter().enumerate() {
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+                    x(i),
+                    bottom + 16.0,
+                    xml_escape(run)
+                );
+            }
+            for (k, s) in series.iter().enumerate() {
+                let color = COLORS[k % COLORS.len()];
+                // a gap where a run lacks the value
+                let mut d = String::new();
+                let mut pen_down = false;
+                for (i, v) in s.values.iter().enumerate() {
+                    match v {
+                        Some(v) => {
+                            let _ = write!(
+                                d,
+                                "{}{:.1} {:.1} ",
+                                if pen_down { "L" } else { "M" },
+                                x(i),
+                                y(*v)
+                            );
+                            pen_down = true;
+                            let _ = writeln!(
+                                svg,
+                                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\">\
+                                 <title>{}: {:.3} {}</title></circle>",
+                                x(i),
+                                y(*v),
+                                color,
+                                xml_escape(&s.name),
+                                v,
+                                metric.unit
+                            );
+                        }
+                        None => pen_down = false,
+                    }
+                }
+                let _ = writeln!(
+                    svg,
+                    "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+                    d.trim_end(),
+                    color
+                );
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+                    LEFT + PLOT_WIDTH + 20.0,
+                    top + 12.0 + 14.0 * k as f64,
+                    color,
+                    xml_escape(&s.name)
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    pub fn write_svg(&self, path: &Path) -> Result<(), BenchError> {
+        std::fs::write(path, self.to_svg())?;
+        println!("Wrote trend chart to {:?}", path);
+        Ok(())
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::parse_csv;
+    use std::path::PathBuf;
+
+    fn run(file: &str, csv: &str) -> HistoryRun {
+        HistoryRun {
+            path: PathBuf::from(file),
+            rows: parse_csv(csv).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_trends() {
+        let header = "name,status,trial,avg_step_ns,decider_prove_ns,proof_size,sonobe_revision\n";
+        let runs = [
+            run("jan.csv", &format!("{}a/b/c,ok,0,4000000,0,0,aaaaaaaaaa\n", header)),
+            run("feb.csv", &format!("{}a/b/c,failed,,,,,bbbbbbbbbb\n", header)),
+            run(
+                "mar.csv",
+                &format!(
+                    "{}a/b/c,ok,0,5000000,0,0,cccccccccc\nx/y/z,ok,0,1000000,7000000,900,cc\n",
+                    header
+                ),
+            ),
+        ];
+        let trends = Trends::from_history(&runs);
+        assert_eq!(trends.runs, ["jan (aaaaaaaa)", "feb (bbbbbbbb)", "mar (cccccccc)"]);
+        assert_eq!(trends.series.len(), 4);
+        let step = &trends.series[0];
+        assert_eq!(step.name, "a/b/c");
+        assert_eq!(step.values, [Some(4.0), None, Some(5.0)]);
+        assert!((step.change().unwrap() - 0.25).abs() < 1e-9);
+        let proof_size = trends.series.last().unwrap();
+        assert_eq!(proof_size.values, [None, None, Some(900.0)]);
+        assert_eq!(proof_size.change(), None);
+
+        let svg = trends.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Folding step (ms)"));
+        assert!(svg.contains("M70.0 "));
+        assert_eq!(svg.matches("<circle").count(), 5);
+    }
+}
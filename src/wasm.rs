@@ -0,0 +1,77 @@
+//! Browser entry points timing BN254 Nova folding and decider verification,
+//! exported through wasm-bindgen (`wasm` feature, wasm32 only).
+//!
+//! Build without the default features, as rayon cannot spawn threads in the
+//! browser and `asm` is x86-only:
+//!
+//! ```text
+//! wasm-pack build --target web -- --no-default-features --features bn254,wasm
+//! ```
+//!
+//! Times are wall-clock milliseconds from `Date.now()`.
+use ark_bn254::{Fr, G1Projective as G1};
+use ark_grumpkin::Projective as G2;
+use wasm_bindgen::prelude::*;
+
+use folding_schemes::{
+    folding::nova::PreprocessorParam, frontend::FCircuit, Decider, FoldingScheme,
+};
+
+use crate::bench::preprocess;
+use crate::config::{poseidon_config, rng};
+use crate::solidity::{DeciderBn, NovaBn};
+use crate::CubicFCircuit;
+
+type N = NovaBn<CubicFCircuit<Fr>>;
+type D = DeciderBn<CubicFCircuit<Fr>>;
+
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// Time of each of `n_steps` `prove_step` calls.
+#[wasm_bindgen]
+pub fn prove_step_times(n_steps: usize) -> Vec<f64> {
+    let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>().unwrap(), f_circuit);
+    let fs_params = N::preprocess(&mut rng(), &prep_param).unwrap();
+    let mut nova = N::init(&fs_params, f_circuit, vec![Fr::from(3_u32)]).unwrap();
+    (0..n_steps)
+        .map(|_| {
+            let start = now_ms();
+            nova.prove_step(rng(), vec![], None).unwrap();
+            now_ms() - start
+        })
+        .collect()
+}
+
+/// Time of verifying a decider proof of `n_steps` steps. Preprocessing and
+/// proving also run in the browser, so expect this to take a while.
+#[wasm_bindgen]
+pub fn decider_verify_time(n_steps: usize) -> f64 {
+    let f_circuit = CubicFCircuit::<Fr>::new(()).unwrap();
+    let z_0 = vec![Fr::from(3_u32)];
+    let prep_param = PreprocessorParam::new(poseidon_config::<Fr>().unwrap(), f_circuit);
+    let (fs_pp, fs_vp, decider_pp, decider_vp) =
+        preprocess::<G1, G2, _, N, D>("wasm", &prep_param, f_circuit, z_0.clone()).unwrap();
+    let mut nova = N::init(&(fs_pp, fs_vp), f_circuit, z_0).unwrap();
+    for _ in 0..n_steps {
+        nova.prove_step(rng(), vec![], None).unwrap();
+    }
+    let proof = D::prove(rng(), decider_pp, nova.clone()).unwrap();
+
+    let start = now_ms();
+    let verified = D::verify(
+        decider_vp,
+        nova.i,
+        nova.z_0.clone(),
+        nova.z_i.clone(),
+        &nova.U_i,
+        &nova.u_i,
+        &proof,
+    )
+    .unwrap();
+    let elapsed = now_ms() - start;
+    assert!(verified);
+    elapsed
+}
@@ -0,0 +1,42 @@
+//! Groth16 verification of BN254 `DeciderEth` proofs with a preloaded key,
+//! for verifiers on devices or in kernels.
+//!
+//! sonobe's `Decider::verify` needs `std`, so what runs here is the Groth16
+//! part of the decider proofs: one pairing check against a verifying key
+//! prepared once at load time, the bulk of the decider verification. The KZG
+//! openings and the folding of the last instances, which `DeciderBn::verify`
+//! also checks, still need sonobe. The crate is `no_std` and builds for bare
+//! metal targets, e.g.
+//! `cargo build -p sonobe-bench-verifier --target thumbv7em-none-eabi`;
+//! `mnt::embedded` times it on the host next to the whole decider
+//! verification.
+#![no_std]
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, SerializationError};
+use ark_std::vec::Vec;
+
+/// A Groth16 verifier with its key prepared once, taking public inputs and
+/// proofs as compressed bytes, e.g. `include_bytes!`d or read from flash.
+pub struct PreloadedVerifier {
+    pvk: PreparedVerifyingKey<Bn254>,
+}
+
+impl PreloadedVerifier {
+    /// From the compressed bytes of a verifying key.
+    pub fn new(vk: &[u8]) -> Result<Self, SerializationError> {
+        let vk = VerifyingKey::<Bn254>::deserialize_compressed(vk)?;
+        Ok(Self {
+            pvk: prepare_verifying_key(&vk),
+        })
+    }
+
+    /// Whether `proof` verifies for `inputs`, both compressed. Inputs of the
+    /// wrong length are rejected.
+    pub fn verify(&self, inputs: &[u8], proof: &[u8]) -> Result<bool, SerializationError> {
+        let inputs = Vec::<Fr>::deserialize_compressed(inputs)?;
+        let proof = Proof::<Bn254>::deserialize_compressed(proof)?;
+        Ok(Groth16::<Bn254>::verify_proof(&self.pvk, &proof, &inputs).unwrap_or(false))
+    }
+}